@@ -0,0 +1,13 @@
+//! Prints a file written through `PicoLogAdapter` to stdout as CSV.
+//!
+//! Run with `cargo run --example dump --features log-facade -- app.wal`.
+
+use picologger::{Csv, LogRecord, Logger};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).ok_or("usage: dump <log file>")?;
+    let logger = Logger::<LogRecord>::new().with_read_config(path);
+    let count = logger.export(std::io::stdout().lock(), Csv::new())?;
+    eprintln!("exported {} records", count);
+    Ok(())
+}
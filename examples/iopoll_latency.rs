@@ -0,0 +1,60 @@
+//! Compares how long entries take to become durable with and without `UringOptions::iopoll`.
+//!
+//! Run with `cargo run --release --example iopoll_latency -- /mnt/nvme/bench.wal [entries]`
+//! on a file whose device has poll queues; elsewhere the polled run fails to start.
+
+use picologger::{BufferFullPolicy, LoggerConfig, UringOptions};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+struct Sample {
+    id: u64,
+    value: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .ok_or("usage: iopoll_latency <log file> [entries]")?;
+    let entries: u64 = match args.next() {
+        Some(count) => count.parse()?,
+        None => 200_000,
+    };
+
+    println!("mode       count      p50      p99    p99.9      max");
+    for iopoll in [false, true] {
+        let _ = std::fs::remove_file(&path);
+        let mut logger = LoggerConfig::new()
+            .path(&path)
+            .flush_interval(Duration::from_micros(100))
+            .preallocate((entries * 64).next_multiple_of(1024 * 1024))
+            .build::<Sample>()?
+            .with_uring_options(UringOptions {
+                iopoll,
+                ..UringOptions::default()
+            })
+            .with_buffer_full_policy(BufferFullPolicy::Block)
+            .with_latency_tracking(true);
+        logger.start()?;
+        for id in 0..entries {
+            logger.try_log(Sample { id, value: id * 3 })?;
+        }
+        logger.shutdown()?;
+        let report = logger.latency_report().expect("tracking is enabled");
+
+        let durability = report.durability;
+        println!(
+            "{:<8} {:>7} {:>8.1?} {:>8.1?} {:>8.1?} {:>8.1?}",
+            if iopoll { "iopoll" } else { "irq" },
+            durability.count,
+            durability.p50,
+            durability.p99,
+            durability.p999,
+            durability.max,
+        );
+    }
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Installs `PicoLayer`, emits a million events and reads them back.
+//!
+//! Run with `cargo run --release --example tracing_layer --features tracing`.
+
+use picologger::{Logger, LoggerConfig, PicoLayer, TraceRecord};
+use std::time::{Duration, Instant};
+use tracing_subscriber::layer::SubscriberExt;
+
+const EVENTS: u64 = 1_000_000;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "tracing_example.wal";
+    let _ = std::fs::remove_file(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TraceRecord>()?;
+    logger.start()?;
+
+    // The subscriber holds the only clone of the layer, so dropping it at the end of the
+    // scope drops the logger, which writes out the last page.
+    let subscriber = tracing_subscriber::registry().with(PicoLayer::new(logger));
+    let start = Instant::now();
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("session", venue = "XNAS");
+        let _guard = span.enter();
+        for i in 0..EVENTS {
+            tracing::info!(order_id = i, qty = 100, "order filled");
+        }
+    });
+    let elapsed = start.elapsed();
+    println!(
+        "logged {} events in {:?} ({:.0} ns/event)",
+        EVENTS,
+        elapsed,
+        elapsed.as_nanos() as f64 / EVENTS as f64
+    );
+
+    let reader = Logger::<TraceRecord>::new().with_read_config(path.to_string());
+    let mut events = 0;
+    let mut last = None;
+    for entry in reader.read_iter()? {
+        let entry = entry?;
+        if !entry.data.is_span() {
+            events += 1;
+        }
+        last = Some(entry);
+    }
+    println!("read back {} events", events);
+    if let Some(entry) = last {
+        println!(
+            "seq_id {} at {}ns: {}",
+            entry.seq_id, entry.ts_nanos, entry.data
+        );
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
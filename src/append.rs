@@ -0,0 +1,230 @@
+//! Resuming an existing log file instead of starting it over.
+//!
+//! The writer lays pages out back to back from the end of the header block, so the file is
+//! walked page by page from there. Walking stops at the first page that doesn't check out:
+//! zeros past the end of what was written, or a page a crash left half-written. Everything
+//! from there on is cut off and new pages are written in its place, so a torn page never
+//! ends up in the middle of the file.
+
+use crate::codec::{PageCodec, stored_page_len};
+use crate::format::{ENTRY_ALIGN, EntryFormat, Parsed, parse_entry};
+use crate::header::{FileHeader, LEVEL_VERSION, PAGE_META_VERSION, Schema};
+use crate::page::{PAGE_FLAG_ENCRYPTED, PAGE_META_LEN, PageHeader, PageMeta};
+use crate::util;
+use crate::{Logger, PicoError};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::mem::size_of;
+use std::path::Path;
+
+/// Where appending to an existing file picks up.
+pub(crate) struct AppendPoint {
+    /// Offset right after the last intact page, where the next page goes.
+    pub end: u64,
+    /// Page id of the last intact page, if there is one.
+    pub last_page_id: Option<u32>,
+    /// Highest seq_id among the intact pages' entries; 0 if there are none.
+    pub last_seq: u64,
+    /// `(first seq_id, offset)` of every intact page, for rebuilding the index.
+    pub pages: Vec<(u64, u64)>,
+}
+
+/// The layout a file must have been written with to be appended to.
+pub(crate) struct AppendLayout<'a> {
+    pub schema: &'a Schema,
+    pub version: u32,
+    pub block_size: usize,
+    pub page_size: usize,
+    pub codec: &'a PageCodec,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Appends to the log file if it already exists, instead of writing it anew
+    /// (default `false`).
+    ///
+    /// On `start()`, the file is walked page by page from the start. The first page that is
+    /// not intact, such as one a crash left half-written, marks the end: it and anything
+    /// after it are truncated away and new pages are written from there. Seq_ids continue
+    /// after the highest one found, and the original file header, metadata included, is
+    /// kept.
+    ///
+    /// The file must have been written in the same format version, with the same payload
+    /// type, block size, page size and page transforms, from format version 3 on, whose
+    /// pages are checksummed; `start()` fails with [`PicoError::AppendMismatch`] otherwise.
+    /// A file that doesn't exist or is empty is written anew.
+    pub fn with_append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+}
+
+/// Walks the file at `path` for the point to append at, or returns `None` if it holds no
+/// file header yet and is to be written anew.
+pub(crate) fn find_append_point(
+    path: &Path,
+    layout: &AppendLayout,
+) -> Result<Option<AppendPoint>, PicoError> {
+    let mut file = File::open(path)?;
+    let file_len = util::file_len(&file)?;
+    let mut block = vec![0u8; size_of::<FileHeader>()];
+    match file.read_exact(&mut block) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof && file_len == 0 => return Ok(None),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Err(mismatch("too short")),
+        Err(e) => return Err(e.into()),
+    }
+    let header = FileHeader::read_from(&block).ok_or(mismatch("no file header"))?;
+    check_layout(&header, layout)?;
+
+    let mut point = AppendPoint {
+        end: header.data_offset as u64,
+        last_page_id: None,
+        last_seq: 0,
+        pages: Vec::new(),
+    };
+    let mut page = Vec::new();
+    let mut scratch = Vec::new();
+    let levels = header.version >= LEVEL_VERSION;
+    loop {
+        let offset = point.end;
+        page.resize(size_of::<PageHeader>(), 0);
+        if read_at(&mut file, offset, &mut page)? < page.len() {
+            break;
+        }
+        let len = match stored_page_len(&page) {
+            Some(stored) => stored.next_multiple_of(layout.block_size),
+            None => layout.page_size,
+        };
+        if offset + len as u64 > file_len {
+            break;
+        }
+        page.resize(len, 0);
+        read_at(&mut file, offset, &mut page)?;
+        let first = point.pages.is_empty();
+        let meta = match check_page(&mut page, &mut scratch, layout.codec, levels) {
+            PageCheck::Intact(meta) => meta,
+            PageCheck::Damaged => break,
+            // Rather than cutting off a whole file it cannot read, e.g. for want of the
+            // right key, refuse to append to it.
+            PageCheck::Unreadable(reason) if first => return Err(mismatch(reason)),
+            PageCheck::Unreadable(_) => break,
+        };
+        point.pages.push((meta.first_seq, offset));
+        point.last_page_id = Some(meta.page_id);
+        point.last_seq = point.last_seq.max(meta.last_seq);
+        point.end = offset + len as u64;
+    }
+    Ok(Some(point))
+}
+
+fn check_layout(header: &FileHeader, layout: &AppendLayout) -> Result<(), PicoError> {
+    if header.version < PAGE_META_VERSION {
+        return Err(mismatch("its format version has no page checksums"));
+    }
+    if header.version != layout.version {
+        return Err(mismatch("it was written in another format version"));
+    }
+    let found = header.schema();
+    if found != *layout.schema {
+        return Err(mismatch(
+            "it was written with another payload type or schema tag",
+        ));
+    }
+    if header.data_offset as usize != layout.block_size {
+        return Err(mismatch("it was written with another block size"));
+    }
+    if header.page_size as usize != layout.page_size {
+        return Err(mismatch("it was written with another page size"));
+    }
+    if !header.byte_order_supported() {
+        return Err(PicoError::ByteOrderMismatch {
+            found: header.byte_order,
+        });
+    }
+    Ok(())
+}
+
+enum PageCheck {
+    Intact(PageMeta),
+    /// Not a whole page: never written, or torn.
+    Damaged,
+    /// Possibly whole, but not readable with this logger's page transforms.
+    Unreadable(&'static str),
+}
+
+/// Decodes the on-disk page in `page` and returns its page meta if the page is whole: the
+/// meta's checksum holds and its entries are all there and well-formed.
+fn check_page(
+    page: &mut Vec<u8>,
+    scratch: &mut Vec<u8>,
+    codec: &PageCodec,
+    levels: bool,
+) -> PageCheck {
+    let Some(header) = PageHeader::read(page) else {
+        return match codec.is_identity() {
+            true => check_entries(page, levels),
+            false => PageCheck::Unreadable("its pages were written without a page transform"),
+        };
+    };
+    if codec.is_identity() {
+        return PageCheck::Unreadable("its pages were written with a page transform");
+    }
+    match codec.open(page, scratch) {
+        Ok(entries) => check_entries(&page[entries], levels),
+        Err(_) if header.flags & PAGE_FLAG_ENCRYPTED != 0 => {
+            PageCheck::Unreadable("its pages cannot be decrypted with this key")
+        }
+        Err(e) if e.kind() == ErrorKind::InvalidData => PageCheck::Damaged,
+        Err(_) => PageCheck::Unreadable("its pages cannot be decoded by this build"),
+    }
+}
+
+/// Checks the decoded entries of a page, starting with its page meta.
+fn check_entries(bytes: &[u8], levels: bool) -> PageCheck {
+    match intact_meta(bytes, levels) {
+        Some(meta) => PageCheck::Intact(meta),
+        None => PageCheck::Damaged,
+    }
+}
+
+fn intact_meta(bytes: &[u8], levels: bool) -> Option<PageMeta> {
+    let meta = PageMeta::read(bytes).filter(PageMeta::is_intact)?;
+    let used = PAGE_META_LEN + meta.used_bytes as usize;
+    if meta.entry_count == 0 || used > bytes.len() {
+        return None;
+    }
+
+    let format = EntryFormat {
+        levels,
+        ..EntryFormat::CURRENT
+    };
+    let mut at = PAGE_META_LEN;
+    let mut count = 0;
+    while at < used {
+        let Parsed::Entry(header, payload) = parse_entry(bytes, at..used, format) else {
+            return None;
+        };
+        if header.seq_id < meta.first_seq || header.seq_id > meta.last_seq {
+            return None;
+        }
+        count += 1;
+        at = payload.end.next_multiple_of(ENTRY_ALIGN);
+    }
+    (count == meta.entry_count && at == used).then_some(meta)
+}
+
+fn read_at(file: &mut File, offset: u64, buf: &mut [u8]) -> Result<usize, PicoError> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn mismatch(reason: &'static str) -> PicoError {
+    PicoError::AppendMismatch { reason }
+}
@@ -0,0 +1,376 @@
+//! A compact, self-describing stream of a log's entries for moving them off the machine,
+//! independent of the block size and page layout of the file they came from.
+//!
+//! An archive starts with [`ARCHIVE_MAGIC`], the archive version and the payload schema,
+//! followed by the source file's metadata block, all little-endian:
+//!
+//! ```text
+//! magic [8] | version u32 | flags u32 | size u32 | align u32 | tag_len u16 | tag
+//!           | metadata_len u32 | metadata
+//! ```
+//!
+//! `tag_len` is `u16::MAX` for a schema without a tag. The low byte of `flags` names the
+//! codec of compressed chunks: 0 for LZ4, 1 for zstd. Entries follow in chunks of
+//! `raw_len u32 | stored_len u32 | bytes`, compressed when `stored_len` is less than
+//! `raw_len`, and a chunk with a `raw_len` of 0 ends the archive. Each decompressed chunk
+//! holds whole records of `seq_id u64 | ts_nanos u64 | level u8 | payload_len u32 | payload`.
+
+use crate::Logger;
+use crate::compression::{self, Compression};
+use crate::header::Schema;
+use crate::level::Level;
+use crate::metadata::FileMetadata;
+use crate::reader::{LogEntry, LogReader};
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::mem::size_of;
+
+/// Bytes every archive starts with.
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"PICOARCH";
+
+/// Version of the archive layout written by [`Logger::export_archive`].
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Codec of an archive's compressed chunks, in the low byte of its header flags.
+const CODEC_LZ4: u32 = 0;
+const CODEC_ZSTD: u32 = 1;
+
+/// Raw record bytes gathered into a chunk before it is compressed and written.
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// Bytes of a record before its payload.
+const RECORD_HEADER_LEN: usize = 8 + 8 + 1 + 4;
+
+/// What a [`Logger::export_archive`] wrote.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveStats {
+    /// Entries written to the archive.
+    pub entries: u64,
+    /// Length of the archive in bytes, header included.
+    pub bytes: u64,
+    /// Sequence ID of the last entry written, or `None` if there was none.
+    pub last_seq_id: Option<u64>,
+}
+
+/// How [`Logger::export_archive_with`] compresses the chunks of an archive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArchiveCompression {
+    /// Chunks are stored as-is.
+    #[default]
+    None,
+    /// Chunks are LZ4 block-compressed, as pages are with [`Compression::Lz4`].
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Chunks are zstd-compressed at `level`, from 1 to 22, with 0 picking zstd's default.
+    /// Slower than LZ4 but smaller, for archives kept a long time.
+    #[cfg(feature = "zstd")]
+    Zstd { level: i32 },
+}
+
+impl ArchiveCompression {
+    /// The archive compression matching a logger's page compression.
+    fn of_pages(compression: Compression) -> Self {
+        match compression {
+            Compression::None => ArchiveCompression::None,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => ArchiveCompression::Lz4,
+        }
+    }
+
+    fn codec(self) -> u32 {
+        match self {
+            #[cfg(feature = "zstd")]
+            ArchiveCompression::Zstd { .. } => CODEC_ZSTD,
+            _ => CODEC_LZ4,
+        }
+    }
+
+    /// Compresses `input` into `scratch`, returning the compressed length, or `None` if the
+    /// data would not shrink.
+    #[cfg_attr(
+        not(any(feature = "lz4", feature = "zstd")),
+        allow(unused_variables, clippy::ptr_arg)
+    )]
+    fn compress(self, input: &[u8], scratch: &mut Vec<u8>) -> Option<usize> {
+        match self {
+            ArchiveCompression::None => None,
+            #[cfg(feature = "lz4")]
+            ArchiveCompression::Lz4 => compression::compress(Compression::Lz4, input, scratch),
+            #[cfg(feature = "zstd")]
+            ArchiveCompression::Zstd { level } => {
+                scratch.resize(zstd::compress_bound(input.len()), 0);
+                let compressed_len = zstd::bulk::compress_to_buffer(input, scratch, level).ok()?;
+                (compressed_len < input.len()).then_some(compressed_len)
+            }
+        }
+    }
+}
+
+/// Decompresses a chunk of `raw_len` bytes stored with `codec` into `scratch`.
+fn decompress(
+    codec: u32,
+    stored: &[u8],
+    raw_len: usize,
+    scratch: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let len = match codec {
+        CODEC_LZ4 => compression::decompress(stored, raw_len, scratch)?,
+        #[cfg(feature = "zstd")]
+        _ => {
+            scratch.resize(raw_len, 0);
+            zstd::bulk::decompress_to_buffer(stored, scratch)
+                .map_err(|_| invalid("damaged zstd chunk"))?
+        }
+        #[cfg(not(feature = "zstd"))]
+        _ => {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Archive is zstd-compressed: enable the `zstd` feature to read it",
+            ));
+        }
+    };
+    match len == raw_len {
+        true => Ok(()),
+        false => Err(invalid("chunk shorter than recorded")),
+    }
+}
+
+/// Counts the bytes passed through to `out`.
+struct Counting<W> {
+    out: W,
+    written: u64,
+}
+
+impl<W: Write> Counting<W> {
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.out.write_all(bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Writes `chunk` as the next chunk, compressed if that makes it smaller.
+    fn put_chunk(
+        &mut self,
+        chunk: &[u8],
+        compression: ArchiveCompression,
+        scratch: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let stored = match compression.compress(chunk, scratch) {
+            Some(len) => &scratch[..len],
+            None => chunk,
+        };
+        self.put(&(chunk.len() as u32).to_le_bytes())?;
+        self.put(&(stored.len() as u32).to_le_bytes())?;
+        self.put(stored)
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Invalid archive: {message}"),
+    )
+}
+
+/// Fills `buf` from `input`, an archive ending early counting as damaged.
+fn fill(input: &mut impl Read, buf: &mut [u8]) -> Result<(), Error> {
+    input.read_exact(buf).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => invalid("truncated"),
+        _ => e,
+    })
+}
+
+fn get_u16(input: &mut impl Read) -> Result<u16, Error> {
+    let mut bytes = [0; 2];
+    fill(input, &mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn get_u32(input: &mut impl Read) -> Result<u32, Error> {
+    let mut bytes = [0; 4];
+    fill(input, &mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn get_bytes(input: &mut impl Read, len: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![0; len];
+    fill(input, &mut bytes)?;
+    Ok(bytes)
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Writes every entry of the configured log file to `out` as an archive: the entries
+    /// back to back without page padding, framed explicitly in little-endian, after a header
+    /// recording the payload schema and the file's metadata block.
+    ///
+    /// Entries are compressed in chunks with the logger's [`Compression`], if any; see
+    /// [`Logger::export_archive_with`] to pick another, such as zstd. `out` is buffered
+    /// internally, so a bare `File` is fine. The layout is versioned separately from log
+    /// files, as [`ARCHIVE_VERSION`], and stays readable by later versions however the log
+    /// file format changes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ArchiveStats, Error>` - What was written, or the first read or write error.
+    pub fn export_archive(&self, out: impl Write) -> Result<ArchiveStats, Error> {
+        self.export_archive_with(out, ArchiveCompression::of_pages(self.compression))
+    }
+
+    /// Writes an archive like [`Logger::export_archive`], compressing its chunks with
+    /// `compression` whatever the logger's page compression is.
+    ///
+    /// Reading a zstd-compressed archive back takes the `zstd` feature as well.
+    pub fn export_archive_with(
+        &self,
+        out: impl Write,
+        compression: ArchiveCompression,
+    ) -> Result<ArchiveStats, Error> {
+        let raw = self.raw_reader()?;
+        let schema = raw.schema().cloned().unwrap_or_else(|| self.schema());
+        let metadata = match Self::read_metadata(self.log_path()?) {
+            Ok(metadata) => metadata.encode(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut out = Counting {
+            out: BufWriter::new(out),
+            written: 0,
+        };
+        out.put(&ARCHIVE_MAGIC)?;
+        out.put(&ARCHIVE_VERSION.to_le_bytes())?;
+        out.put(&compression.codec().to_le_bytes())?;
+        out.put(&(schema.size as u32).to_le_bytes())?;
+        out.put(&(schema.align as u32).to_le_bytes())?;
+        match &schema.tag {
+            Some(tag) => {
+                let tag = &tag.as_bytes()[..tag.len().min(u16::MAX as usize - 1)];
+                out.put(&(tag.len() as u16).to_le_bytes())?;
+                out.put(tag)?;
+            }
+            None => out.put(&u16::MAX.to_le_bytes())?,
+        }
+        out.put(&(metadata.len() as u32).to_le_bytes())?;
+        out.put(&metadata)?;
+
+        let mut stats = ArchiveStats::default();
+        let mut chunk = Vec::with_capacity(CHUNK_LEN);
+        let mut scratch = Vec::new();
+        for entry in LogReader::new(raw, self.payload_codec.decode) {
+            let entry = entry?;
+            let payload = (self.payload_codec.encode)(&entry.data);
+            chunk.extend_from_slice(&entry.seq_id.to_le_bytes());
+            chunk.extend_from_slice(&entry.ts_nanos.to_le_bytes());
+            chunk.push(entry.level as u8);
+            chunk.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            chunk.extend_from_slice(payload);
+            if chunk.len() >= CHUNK_LEN {
+                out.put_chunk(&chunk, compression, &mut scratch)?;
+                chunk.clear();
+            }
+            stats.entries += 1;
+            stats.last_seq_id = Some(entry.seq_id);
+        }
+        if !chunk.is_empty() {
+            out.put_chunk(&chunk, compression, &mut scratch)?;
+        }
+        out.put(&[0; 8])?;
+        out.out.flush()?;
+        stats.bytes = out.written;
+        Ok(stats)
+    }
+
+    /// Reads back the entries of an archive written by [`Logger::export_archive`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LogEntry<T>>, Error>` - The entries in the order they were archived,
+    ///   [`PicoError::SchemaMismatch`](crate::PicoError::SchemaMismatch) if they were
+    ///   archived with a payload type this logger can't read, or an `InvalidData` error for
+    ///   a damaged or truncated archive.
+    pub fn import_archive(&self, input: impl Read) -> Result<Vec<LogEntry<T>>, Error> {
+        let mut input = BufReader::new(input);
+        let magic = get_bytes(&mut input, ARCHIVE_MAGIC.len())?;
+        if magic != ARCHIVE_MAGIC {
+            return Err(invalid("bad magic"));
+        }
+        let version = get_u32(&mut input)?;
+        if version > ARCHIVE_VERSION {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Archive version {version} is newer than {ARCHIVE_VERSION}"),
+            ));
+        }
+        let codec = get_u32(&mut input)? & 0xff;
+        if codec != CODEC_LZ4 && codec != CODEC_ZSTD {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Archive chunks use unknown codec {codec}"),
+            ));
+        }
+        let size = get_u32(&mut input)? as usize;
+        let align = get_u32(&mut input)? as usize;
+        let tag = match get_u16(&mut input)? {
+            u16::MAX => None,
+            len => Some(
+                String::from_utf8(get_bytes(&mut input, len as usize)?)
+                    .map_err(|_| invalid("schema tag is not UTF-8"))?,
+            ),
+        };
+        self.check_schema(Some(&Schema { size, align, tag }))?;
+        let metadata_len = get_u32(&mut input)? as usize;
+        let metadata = get_bytes(&mut input, metadata_len)?;
+        if metadata_len > 0 && FileMetadata::decode(&metadata).is_none() {
+            return Err(invalid("damaged metadata block"));
+        }
+
+        let mut entries = Vec::new();
+        let mut scratch = Vec::new();
+        loop {
+            let raw_len = get_u32(&mut input)? as usize;
+            let stored_len = get_u32(&mut input)? as usize;
+            if raw_len == 0 {
+                break;
+            }
+            if stored_len > raw_len {
+                return Err(invalid("chunk longer than its contents"));
+            }
+            let stored = get_bytes(&mut input, stored_len)?;
+            let mut chunk = match stored_len < raw_len {
+                true => {
+                    decompress(codec, &stored, raw_len, &mut scratch)?;
+                    &scratch[..raw_len]
+                }
+                false => &stored[..],
+            };
+            while !chunk.is_empty() {
+                entries.push(take_record(&mut chunk, self.payload_codec.decode)?);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Splits the record at the start of `chunk` off it, turning its payload into a `T` with
+/// `decode`.
+fn take_record<T: Copy>(chunk: &mut &[u8], decode: fn(&[u8]) -> T) -> Result<LogEntry<T>, Error> {
+    if chunk.len() < RECORD_HEADER_LEN {
+        return Err(invalid("truncated record"));
+    }
+    let (header, rest) = chunk.split_at(RECORD_HEADER_LEN);
+    let seq_id = u64::from_le_bytes(header[0..8].try_into().unwrap());
+    let ts_nanos = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    let level = Level::from_byte(header[16]).ok_or_else(|| invalid("unknown level"))?;
+    let len = u32::from_le_bytes(header[17..21].try_into().unwrap()) as usize;
+    if len != size_of::<T>() || rest.len() < len {
+        return Err(invalid("payload of the wrong size"));
+    }
+    let (payload, rest) = rest.split_at(len);
+    *chunk = rest;
+    Ok(LogEntry {
+        seq_id,
+        ts_nanos,
+        level,
+        data: decode(payload),
+    })
+}
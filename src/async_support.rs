@@ -0,0 +1,132 @@
+//! Optional executor-agnostic futures that resolve once entries are durable.
+//!
+//! Futures register their waker together with the seq_id they wait for; the worker wakes
+//! every waiter covered by its logger's watermark each time the watermark moves. Only `std::task` is
+//! used, so any executor can drive them.
+
+use crate::stats::SharedStats;
+use crate::{Logger, PicoError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Wakers waiting for the ack watermark to reach their seq_id.
+#[derive(Default)]
+pub(crate) struct AckWaiters {
+    waiting: Mutex<Vec<(u64, Waker)>>,
+    /// Set once the worker has exited, after which nothing more will be acknowledged.
+    closed: AtomicBool,
+}
+
+impl AckWaiters {
+    /// Wakes every waiter whose seq_id is covered by `ack`.
+    pub fn wake_covered(&self, ack: u64) {
+        let mut waiting = self.waiting.lock().unwrap_or_else(|e| e.into_inner());
+        waiting.retain(|(seq_id, waker)| {
+            if *seq_id <= ack {
+                waker.wake_by_ref();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Marks the worker as gone and wakes everyone still waiting.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        let mut waiting = self.waiting.lock().unwrap_or_else(|e| e.into_inner());
+        for (_, waker) in waiting.drain(..) {
+            waker.wake();
+        }
+    }
+
+    fn register(&self, seq_id: u64, waker: &Waker) {
+        let mut waiting = self.waiting.lock().unwrap_or_else(|e| e.into_inner());
+        match waiting
+            .iter_mut()
+            .find(|(id, registered)| *id == seq_id && registered.will_wake(waker))
+        {
+            Some(_) => {}
+            None => waiting.push((seq_id, waker.clone())),
+        }
+    }
+}
+
+/// Future returned by [`Logger::log_durable`] and [`Logger::flush_async`], resolving once
+/// the logger's own watermark, [`LoggerStats::flushed_seq_id`], covers `seq_id`.
+///
+/// [`LoggerStats::flushed_seq_id`]: crate::LoggerStats::flushed_seq_id
+struct Durable {
+    seq_id: Option<u64>,
+    waiters: Arc<AckWaiters>,
+    stats: Arc<SharedStats>,
+}
+
+impl Future for Durable {
+    type Output = Result<u64, PicoError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(seq_id) = self.seq_id else {
+            return Poll::Ready(Ok(self.stats.flushed_seq_id()));
+        };
+        if self.stats.flushed_seq_id() >= seq_id {
+            return Poll::Ready(Ok(seq_id));
+        }
+        if self.waiters.closed.load(Ordering::Acquire) {
+            return Poll::Ready(Err(PicoError::WorkerStopped));
+        }
+        self.waiters.register(seq_id, cx.waker());
+        // The worker may have acknowledged the entry between the check and registering.
+        if self.stats.flushed_seq_id() >= seq_id {
+            return Poll::Ready(Ok(seq_id));
+        }
+        Poll::Pending
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Adds `data` to the log and returns a future that resolves with its sequence ID once
+    /// the entry is durable, as reported by this logger's [`Logger::stats`].
+    ///
+    /// The entry is handed to the worker before this returns, so dropping the future does
+    /// not cancel it. Resolves with [`PicoError::WorkerStopped`] if the worker exits before
+    /// the entry is acknowledged.
+    pub fn log_durable(
+        &mut self,
+        data: T,
+    ) -> impl Future<Output = Result<u64, PicoError>> + Send + 'static {
+        let logged = self.try_log(data);
+        let waiters = self.ack_waiters.clone();
+        let stats = self.stats.clone();
+        async move {
+            let seq_id = logged?;
+            Durable {
+                seq_id: Some(seq_id),
+                waiters,
+                stats,
+            }
+            .await
+        }
+    }
+
+    /// Flushes the active page and returns a future that resolves once every entry logged
+    /// through this logger so far is durable.
+    pub fn flush_async(&mut self) -> impl Future<Output = Result<(), PicoError>> + Send + 'static {
+        let flushed = self.flush().map(|()| self.last_seq_id);
+        let waiters = self.ack_waiters.clone();
+        let stats = self.stats.clone();
+        async move {
+            let seq_id = flushed?;
+            Durable {
+                seq_id,
+                waiters,
+                stats,
+            }
+            .await
+            .map(|_| ())
+        }
+    }
+}
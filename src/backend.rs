@@ -0,0 +1,379 @@
+//! The worker's write path, abstracted over io_uring and a synchronous `pwrite` fallback.
+//!
+//! The worker only ever hands a backend page writes and reads back `(user_data, result)`
+//! completions, so page accounting, retries and acks work the same whichever one is active.
+
+use crate::PageManager;
+use crate::errors::PicoError;
+use crate::probe::Capabilities;
+use crate::testing::FaultInjector;
+use crate::uring::UringOptions;
+#[cfg(target_os = "linux")]
+use crate::uring::{UringBackend, setup_ring};
+use crate::wakeup::Wakeup;
+use std::fs::File;
+use std::io::Error;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Set in the user_data of a page's fdatasync to tell its completion apart from the write's.
+pub(crate) const SYNC_COMPLETION: u64 = 1 << 63;
+
+/// Set in the user_data of the completion of a file extension queued with
+/// [`IoBackend::submit_allocate`], whose low bits hold the index of the file.
+pub(crate) const ALLOCATE_COMPLETION: u64 = 1 << 62;
+
+/// Set in the user_data of the completion of a range sync queued with
+/// [`IoBackend::submit_range_sync`], whose low bits hold the index of the file.
+pub(crate) const RANGE_SYNC_COMPLETION: u64 = 1 << 61;
+
+/// Set in the user_data of the completion of a full sync queued with
+/// [`IoBackend::submit_full_sync`], whose low bits hold the index of the file.
+pub(crate) const FULL_SYNC_COMPLETION: u64 = 1 << 60;
+
+/// The bits that tell the completions of file operations other than page writes apart.
+pub(crate) const FILE_OP_COMPLETIONS: u64 =
+    ALLOCATE_COMPLETION | RANGE_SYNC_COMPLETION | FULL_SYNC_COMPLETION;
+
+/// Which write path the worker uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Use io_uring, falling back to [`Backend::Sync`] if the kernel or sandbox refuses to
+    /// create a ring. Always [`Backend::Sync`] outside Linux.
+    #[default]
+    Auto,
+    /// Use io_uring; `start()` fails if no ring can be created, and always outside Linux.
+    IoUring,
+    /// Write each page with `pwrite(2)` on the worker thread, followed by `fdatasync(2)` in
+    /// [`Durability::Fdatasync`](crate::Durability::Fdatasync) mode.
+    Sync,
+}
+
+/// A page write handed to a backend.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(crate) struct PageOp {
+    pub user_data: u64,
+    pub buf: *const u8,
+    pub len: usize,
+    pub offset: u64,
+    /// Index of the file to write to, among those the backend was opened with.
+    pub file: usize,
+    /// Index of the page, which is also its registered buffer index unless the page was
+    /// added after the buffers were registered.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub buf_index: u16,
+    /// Follow the write with an fdatasync that only runs once the write has landed. Its
+    /// completion carries `user_data | SYNC_COMPLETION`.
+    pub sync: bool,
+}
+
+pub(crate) trait IoBackend: Send {
+    /// Returns which backend this is; never [`Backend::Auto`].
+    fn kind(&self) -> Backend;
+
+    /// Queues `op`. A backend may carry it out before returning.
+    fn submit_page(&mut self, op: &PageOp) -> Result<(), PicoError>;
+
+    /// Queues allocating `len` bytes of file `file` starting at `offset`, extending it if
+    /// needed. Its completion carries `ALLOCATE_COMPLETION | file` and a result of 0 or a
+    /// negative errno.
+    fn submit_allocate(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError>;
+
+    /// Queues starting writeback of `len` bytes of file `file` from `offset`, with
+    /// `sync_file_range(SYNC_FILE_RANGE_WRITE)`. Completes with
+    /// `RANGE_SYNC_COMPLETION | file`.
+    fn submit_range_sync(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError>;
+
+    /// Queues an `fdatasync` of file `file`. Completes with `FULL_SYNC_COMPLETION | file`.
+    fn submit_full_sync(&mut self, file: usize) -> Result<(), PicoError>;
+
+    /// Hands queued writes to the kernel and, if `wait` is set, blocks until at least one
+    /// completion is available.
+    fn submit(&mut self, wait: bool) -> Result<(), PicoError>;
+
+    /// Like `submit(true)`, but returns once `timeout` has passed without a completion.
+    /// Backends that cannot bound the wait block as `submit(true)` does.
+    fn submit_timeout(&mut self, timeout: Duration) -> Result<(), PicoError> {
+        let _ = timeout;
+        self.submit(true)
+    }
+
+    /// Moves every available completion into `out` as `(user_data, result)` pairs.
+    fn poll_completions(&mut self, out: &mut Vec<(u64, i32)>);
+
+    /// Replaces a failed instance with a fresh one, abandoning whatever was in flight.
+    fn rebuild(&mut self) -> Result<(), PicoError>;
+
+    /// Releases kernel resources before the worker exits.
+    fn drain(&mut self) {}
+
+    /// What this backend uses of the kernel's io_uring support.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            backend: self.kind(),
+            ..Capabilities::default()
+        }
+    }
+
+    /// Whether completions only turn up when [`IoBackend::poll_completions`] asks the
+    /// device for them, so the worker must keep polling while writes are in flight.
+    fn polls_for_completions(&self) -> bool {
+        false
+    }
+}
+
+/// Creates the backend `requested` for writing `files` from the buffers of `pages`. An
+/// io_uring backend signals `wakeup` for every completion, where the kernel allows it.
+///
+/// Whatever the kernel can't do is downgraded rather than failing later, with a note
+/// pushed to `downgrades` for each requested option that couldn't be honored.
+#[cfg(unix)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn open<T>(
+    requested: Backend,
+    options: &UringOptions,
+    ring_depth: u32,
+    pages: &mut PageManager<T>,
+    files: Vec<File>,
+    wakeup: &Arc<Wakeup>,
+    fault_injector: Option<Arc<dyn FaultInjector>>,
+    downgrades: &mut Vec<String>,
+) -> Result<Box<dyn IoBackend>, Error> {
+    match requested {
+        Backend::Sync => Ok(Box::new(SyncBackend::new(files))),
+        #[cfg(target_os = "linux")]
+        Backend::Auto | Backend::IoUring => {
+            let (ring, sqpoll_refused) = match setup_ring(options, ring_depth) {
+                Ok(setup) => setup,
+                Err(e) if requested == Backend::Auto => {
+                    downgrades.push(format!("io_uring unavailable ({e}), writing with pwrite"));
+                    return Ok(Box::new(SyncBackend::new(files)));
+                }
+                Err(e) => return Err(e),
+            };
+            let ops = crate::probe::opcodes(&ring);
+            if !ops.write {
+                if requested == Backend::IoUring {
+                    return Err(PicoError::UnsupportedPlatform(
+                        "this kernel's io_uring cannot write pages (IORING_OP_WRITE needs Linux 5.6)",
+                    )
+                    .into());
+                }
+                downgrades.push("io_uring lacks IORING_OP_WRITE, writing with pwrite".to_string());
+                return Ok(Box::new(SyncBackend::new(files)));
+            }
+            if let Some(e) = sqpoll_refused {
+                downgrades.push(format!(
+                    "SQPOLL unavailable ({e}), submitting from the worker"
+                ));
+            }
+            Ok(Box::new(UringBackend::new(
+                ring,
+                *options,
+                ring_depth,
+                ops,
+                pages.iovecs(),
+                files,
+                wakeup.clone(),
+                fault_injector,
+            )))
+        }
+        #[cfg(not(target_os = "linux"))]
+        Backend::Auto => {
+            let _ = (
+                options,
+                ring_depth,
+                pages,
+                wakeup,
+                fault_injector,
+                downgrades,
+            );
+            Ok(Box::new(SyncBackend::new(files)))
+        }
+        #[cfg(not(target_os = "linux"))]
+        Backend::IoUring => {
+            Err(PicoError::UnsupportedPlatform("io_uring is only available on Linux").into())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn open<T>(
+    _requested: Backend,
+    _options: &UringOptions,
+    _ring_depth: u32,
+    _pages: &mut PageManager<T>,
+    _files: Vec<File>,
+    _wakeup: &Arc<Wakeup>,
+    _fault_injector: Option<Arc<dyn FaultInjector>>,
+    _downgrades: &mut Vec<String>,
+) -> Result<Box<dyn IoBackend>, Error> {
+    Err(PicoError::UnsupportedPlatform("no write backend").into())
+}
+
+#[cfg(unix)]
+/// Writes pages inline with `pwrite(2)`, for platforms, kernels or sandboxes without
+/// io_uring.
+///
+/// Every write finishes inside [`IoBackend::submit_page`], so the worker stalls on the disk
+/// instead of overlapping writes, but producers are still decoupled by the ring buffer.
+pub(crate) struct SyncBackend {
+    files: Vec<File>,
+    completed: Vec<(u64, i32)>,
+}
+
+#[cfg(unix)]
+impl SyncBackend {
+    pub fn new(files: Vec<File>) -> Self {
+        Self {
+            files,
+            completed: Vec::new(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn errno_result(ret: isize) -> i32 {
+    if ret < 0 {
+        -Error::last_os_error().raw_os_error().unwrap_or(libc::EIO)
+    } else {
+        ret as i32
+    }
+}
+
+/// Allocates `len` bytes of `file` from `offset` on the calling thread, returning 0 or a
+/// negative errno like the completion of [`IoBackend::submit_allocate`].
+#[cfg(unix)]
+pub(crate) fn allocate_now(file: &File, offset: u64, len: u64) -> i32 {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let result = match crate::util::preallocate(file, offset + len) {
+        Ok(()) => 0,
+        Err(e) => -e.raw_os_error().unwrap_or(libc::EIO),
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let result = {
+        let _ = (file, offset, len);
+        -libc::EOPNOTSUPP
+    };
+    result
+}
+
+/// Starts writeback of `len` bytes of `file` from `offset` on the calling thread, returning
+/// 0 or a negative errno like the completion of [`IoBackend::submit_range_sync`].
+#[cfg(unix)]
+pub(crate) fn range_sync_now(file: &File, offset: u64, len: u64) -> i32 {
+    #[cfg(target_os = "linux")]
+    let result = {
+        let ret = unsafe {
+            libc::sync_file_range(
+                file.as_raw_fd(),
+                offset as libc::off64_t,
+                len as libc::off64_t,
+                libc::SYNC_FILE_RANGE_WRITE,
+            )
+        };
+        errno_result(ret as isize)
+    };
+    #[cfg(not(target_os = "linux"))]
+    let result = {
+        let _ = (file, offset, len);
+        -libc::EOPNOTSUPP
+    };
+    result
+}
+
+/// Runs `fdatasync` on `file` on the calling thread, returning 0 or a negative errno like
+/// the completion of [`IoBackend::submit_full_sync`].
+#[cfg(unix)]
+pub(crate) fn full_sync_now(file: &File) -> i32 {
+    match file.sync_data() {
+        Ok(()) => 0,
+        Err(e) => -e.raw_os_error().unwrap_or(libc::EIO),
+    }
+}
+
+#[cfg(unix)]
+impl IoBackend for SyncBackend {
+    fn kind(&self) -> Backend {
+        Backend::Sync
+    }
+
+    fn submit_page(&mut self, op: &PageOp) -> Result<(), PicoError> {
+        let file = &self.files[op.file];
+        let fd = file.as_raw_fd();
+        let result = loop {
+            let ret = unsafe {
+                libc::pwrite(
+                    fd,
+                    op.buf as *const libc::c_void,
+                    op.len,
+                    op.offset as libc::off_t,
+                )
+            };
+            let result = errno_result(ret);
+            if result != -libc::EINTR {
+                break result;
+            }
+        };
+        self.completed.push((op.user_data, result));
+
+        if op.sync {
+            // Mirrors a linked io_uring fsync, which is cancelled when its write falls short.
+            let sync_result = if result < 0 || result as usize != op.len {
+                -libc::ECANCELED
+            } else {
+                match file.sync_data() {
+                    Ok(()) => 0,
+                    Err(e) => -e.raw_os_error().unwrap_or(libc::EIO),
+                }
+            };
+            self.completed
+                .push((op.user_data | SYNC_COMPLETION, sync_result));
+        }
+        Ok(())
+    }
+
+    fn submit_allocate(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError> {
+        let result = allocate_now(&self.files[file], offset, len);
+        self.completed
+            .push((ALLOCATE_COMPLETION | file as u64, result));
+        Ok(())
+    }
+
+    fn submit_range_sync(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError> {
+        let result = range_sync_now(&self.files[file], offset, len);
+        self.completed
+            .push((RANGE_SYNC_COMPLETION | file as u64, result));
+        Ok(())
+    }
+
+    fn submit_full_sync(&mut self, file: usize) -> Result<(), PicoError> {
+        let result = full_sync_now(&self.files[file]);
+        self.completed
+            .push((FULL_SYNC_COMPLETION | file as u64, result));
+        Ok(())
+    }
+
+    fn submit(&mut self, _wait: bool) -> Result<(), PicoError> {
+        Ok(())
+    }
+
+    fn submit_timeout(&mut self, timeout: Duration) -> Result<(), PicoError> {
+        // Writes complete inline, so none can arrive while waiting; just avoid spinning.
+        if self.completed.is_empty() {
+            std::thread::sleep(timeout.min(Duration::from_millis(1)));
+        }
+        Ok(())
+    }
+
+    fn poll_completions(&mut self, out: &mut Vec<(u64, i32)>) {
+        out.append(&mut self.completed);
+    }
+
+    fn rebuild(&mut self) -> Result<(), PicoError> {
+        self.completed.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Source of the timestamps recorded in each entry header, and of the time the worker
+/// measures its flush interval against.
+pub trait Clock: Send + Sync {
+    /// Current time in nanoseconds since the Unix epoch.
+    fn now_nanos(&self) -> u64;
+
+    /// Nanoseconds since an arbitrary origin, on a clock that never steps backwards. The
+    /// worker reads it to decide when its flush interval has elapsed.
+    ///
+    /// Defaults to the process's monotonic clock, so a clock that only changes timestamps
+    /// leaves flushing on real time. Override it along with `now_nanos` to drive both, as
+    /// [`MockClock`](crate::testing::MockClock) does.
+    fn monotonic_nanos(&self) -> u64 {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+}
+
+/// The default clock: a raw monotonic clock anchored to the wall clock once at creation.
+///
+/// Reading the monotonic clock is a vDSO call with no epoch conversion, so it is much
+/// cheaper than `SystemTime::now()`, and timestamps never step backwards when the wall
+/// clock is adjusted. The trade-off is that they slowly drift from wall-clock time over a
+/// long-running process, since NTP corrections are not applied.
+#[derive(Clone, Copy, Debug)]
+pub struct MonotonicClock {
+    epoch_offset: u64,
+    /// Platforms without `clock_gettime` measure from this instant instead.
+    #[cfg(not(unix))]
+    anchor: std::time::Instant,
+}
+
+impl MonotonicClock {
+    #[cfg(unix)]
+    pub fn new() -> Self {
+        let realtime = clock_nanos(libc::CLOCK_REALTIME);
+        let monotonic = clock_nanos(MONOTONIC_CLOCK_ID);
+        Self {
+            epoch_offset: realtime.wrapping_sub(monotonic),
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> Self {
+        let realtime = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self {
+            epoch_offset: realtime,
+            anchor: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    #[inline]
+    #[cfg(unix)]
+    fn now_nanos(&self) -> u64 {
+        clock_nanos(MONOTONIC_CLOCK_ID).wrapping_add(self.epoch_offset)
+    }
+
+    #[cfg(not(unix))]
+    fn now_nanos(&self) -> u64 {
+        (self.anchor.elapsed().as_nanos() as u64).wrapping_add(self.epoch_offset)
+    }
+}
+
+#[cfg(target_os = "linux")]
+const MONOTONIC_CLOCK_ID: libc::clockid_t = libc::CLOCK_MONOTONIC_RAW;
+#[cfg(all(unix, not(target_os = "linux")))]
+const MONOTONIC_CLOCK_ID: libc::clockid_t = libc::CLOCK_MONOTONIC;
+
+#[cfg(unix)]
+#[inline]
+fn clock_nanos(clock_id: libc::clockid_t) -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(clock_id, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
@@ -0,0 +1,175 @@
+//! Page transforms (compression, encryption) applied between a page buffer and the disk.
+//!
+//! Pages written without any transform are stored exactly as the page buffer holds them.
+//! Otherwise the page starts with a [`PageHeader`] that records how to undo the transforms.
+
+use crate::compression::{self, Compression};
+#[cfg(feature = "encryption")]
+use crate::encryption::PageCipher;
+use crate::page::{PAGE_FLAG_ENCRYPTED, PAGE_FLAG_LZ4, PAGE_MAGIC, Page, PageHeader};
+use std::io::{Error, ErrorKind};
+use std::mem::size_of;
+use std::ops::Range;
+
+pub(crate) struct PageCodec {
+    compression: Compression,
+    #[cfg(feature = "encryption")]
+    cipher: Option<PageCipher>,
+}
+
+impl PageCodec {
+    pub fn new(compression: Compression) -> Self {
+        Self {
+            compression,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn with_key(mut self, key: Option<&[u8; 32]>) -> Self {
+        self.cipher = key.map(PageCipher::new);
+        self
+    }
+
+    /// Whether pages pass through untouched, without a page header.
+    pub fn is_identity(&self) -> bool {
+        #[cfg(feature = "encryption")]
+        if self.cipher.is_some() {
+            return false;
+        }
+        self.compression == Compression::None
+    }
+
+    /// Number of blocks buffered per in-memory page.
+    pub fn page_blocks(&self) -> usize {
+        self.compression.page_blocks()
+    }
+
+    /// Bytes each page must keep free for its page header.
+    pub fn header_len(&self) -> usize {
+        if self.is_identity() {
+            0
+        } else {
+            size_of::<PageHeader>()
+        }
+    }
+
+    /// Rewrites the page in place as a page header followed by the transformed entries,
+    /// returning the number of meaningful bytes at the start of the page.
+    ///
+    /// The page must have been created with at least [`PageCodec::header_len`] reserved
+    /// bytes; any reserved past those are transformed along with the entries.
+    pub fn seal<T>(&mut self, page: &mut Page<T>, scratch: &mut Vec<u8>) -> usize {
+        if self.is_identity() {
+            return page.get_page_content().len();
+        }
+
+        let header_len = self.header_len();
+        let raw_len = page.used() - header_len;
+        let mut header = PageHeader {
+            magic: PAGE_MAGIC,
+            flags: 0,
+            stored_len: raw_len as u32,
+            raw_len: raw_len as u32,
+            _pad: [0; 4],
+            nonce: [0; 24],
+            tag: [0; 16],
+        };
+
+        if let Some(compressed_len) = compression::compress(
+            self.compression,
+            &page.get_page_content()[header_len..page.used()],
+            scratch,
+        ) {
+            let content = page.get_page_content_mut();
+            content[header_len..header_len + compressed_len]
+                .copy_from_slice(&scratch[..compressed_len]);
+            // Clear what is left of the raw entries so only the compressed form hits disk.
+            content[header_len + compressed_len..header_len + raw_len].fill(0);
+            header.flags |= PAGE_FLAG_LZ4;
+            header.stored_len = compressed_len as u32;
+        }
+
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = self.cipher.as_mut() {
+            header.flags |= PAGE_FLAG_ENCRYPTED;
+            let stored_len = header.stored_len as usize;
+            let content = page.get_page_content_mut();
+            let (nonce, tag) = cipher.encrypt(
+                &header.authenticated_bytes(),
+                &mut content[header_len..header_len + stored_len],
+            );
+            header.nonce = nonce;
+            header.tag = tag;
+        }
+
+        page.get_page_content_mut()[..size_of::<PageHeader>()].copy_from_slice(&header.to_bytes());
+        header_len + header.stored_len as usize
+    }
+
+    /// Decodes the on-disk page held in `page`, returning the range of `page` that holds its
+    /// entries. Decompressed entries are produced in `scratch`, which is then swapped into
+    /// `page`.
+    ///
+    /// Pages without a [`PageHeader`] are returned unchanged.
+    pub fn open(&self, page: &mut Vec<u8>, scratch: &mut Vec<u8>) -> Result<Range<usize>, Error> {
+        let Some(header) = PageHeader::read(page) else {
+            return Ok(0..page.len());
+        };
+        let header_len = size_of::<PageHeader>();
+        let stored = header_len..header_len + header.stored_len as usize;
+        if stored.end > page.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Page header claims more bytes than the page holds",
+            ));
+        }
+
+        if header.flags & PAGE_FLAG_ENCRYPTED != 0 {
+            self.decrypt(&header, &mut page[stored.clone()])?;
+        }
+
+        if header.flags & PAGE_FLAG_LZ4 == 0 {
+            return Ok(stored);
+        }
+        let len = compression::decompress(&page[stored], header.raw_len as usize, scratch)?;
+        std::mem::swap(page, scratch);
+        Ok(0..len)
+    }
+
+    #[cfg(feature = "encryption")]
+    fn decrypt(&self, header: &PageHeader, stored: &mut [u8]) -> Result<(), Error> {
+        let cipher = self.cipher.as_ref().ok_or(Error::new(
+            ErrorKind::InvalidInput,
+            "Page is encrypted: configure the key with with_encryption()",
+        ))?;
+        cipher
+            .decrypt(
+                &header.nonce,
+                &header.tag,
+                &header.authenticated_bytes(),
+                stored,
+            )
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn decrypt(&self, _: &PageHeader, _: &mut [u8]) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Page is encrypted: enable the `encryption` feature to read it",
+        ))
+    }
+}
+
+/// Upper bound on the on-disk length of a single page; larger claims indicate corruption.
+pub(crate) const MAX_STORED_PAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// Returns how many bytes the page starting with `block` occupies on disk, not counting the
+/// padding up to the writer's block size, or `None` if the page has no [`PageHeader`].
+///
+/// `block` only needs to hold the page header.
+pub(crate) fn stored_page_len(block: &[u8]) -> Option<usize> {
+    PageHeader::read(block).map(|header| size_of::<PageHeader>() + header.stored_len as usize)
+}
@@ -0,0 +1,66 @@
+use std::io::Error;
+#[cfg(not(feature = "lz4"))]
+use std::io::ErrorKind;
+
+/// Compression applied to each page before it is written to disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Pages are written as-is.
+    #[default]
+    None,
+    /// Pages are LZ4 block-compressed. Pages that would not shrink are stored raw.
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+impl Compression {
+    /// Number of blocks of raw entries buffered per page in this mode.
+    ///
+    /// Compressed pages buffer several blocks so the compressed result can be written in
+    /// fewer blocks than the raw entries would take.
+    pub(crate) fn page_blocks(self) -> usize {
+        if self == Compression::None { 1 } else { 4 }
+    }
+}
+
+/// Compresses `input` into `scratch`, returning the compressed length, or `None` if the
+/// data would not shrink.
+#[cfg(feature = "lz4")]
+pub(crate) fn compress(
+    compression: Compression,
+    input: &[u8],
+    scratch: &mut Vec<u8>,
+) -> Option<usize> {
+    if compression != Compression::Lz4 {
+        return None;
+    }
+
+    scratch.resize(lz4_flex::block::get_maximum_output_size(input.len()), 0);
+    let compressed_len = lz4_flex::block::compress_into(input, scratch).ok()?;
+    (compressed_len < input.len()).then_some(compressed_len)
+}
+
+#[cfg(not(feature = "lz4"))]
+pub(crate) fn compress(_: Compression, _: &[u8], _: &mut Vec<u8>) -> Option<usize> {
+    None
+}
+
+/// Decompresses an LZ4 page payload into `scratch`, returning the decompressed length.
+#[cfg(feature = "lz4")]
+pub(crate) fn decompress(
+    input: &[u8],
+    raw_len: usize,
+    scratch: &mut Vec<u8>,
+) -> Result<usize, Error> {
+    scratch.resize(raw_len, 0);
+    lz4_flex::block::decompress_into(input, scratch)
+        .map_err(|e| Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(not(feature = "lz4"))]
+pub(crate) fn decompress(_: &[u8], _: usize, _: &mut Vec<u8>) -> Result<usize, Error> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "Page is LZ4-compressed: enable the `lz4` feature to read it",
+    ))
+}
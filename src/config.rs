@@ -0,0 +1,156 @@
+use crate::shard::{MAX_SHARDS, shard_path};
+use crate::{DEFAULT_PAGE_COUNT, Logger, PicoError, ShardedLogger};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_CAPACITY: usize = 4096;
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_micros(100);
+
+/// Write-side configuration for a [`Logger`], assembled with chained setters.
+///
+/// ```no_run
+/// # use picologger::LoggerConfig;
+/// # use std::time::Duration;
+/// # #[derive(Clone, Copy, Default)] struct Trade { id: u64 }
+/// let mut logger = LoggerConfig::new()
+///     .path("trades.wal")
+///     .capacity(4096)
+///     .flush_interval(Duration::from_millis(1))
+///     .poll_interval(Duration::from_micros(10))
+///     .preallocate(1024 * 1024 * 1024)
+///     .build::<Trade>()?;
+/// logger.start()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Only the path is required. The returned logger can still be tuned with the `with_*`
+/// methods before it is started.
+#[derive(Clone, Debug)]
+pub struct LoggerConfig {
+    path: Option<PathBuf>,
+    capacity: usize,
+    flush_interval: Duration,
+    poll_interval: Duration,
+    page_count: usize,
+    pre_alloc_size: u64,
+    create_dirs: bool,
+}
+
+impl LoggerConfig {
+    /// Creates a configuration with no path, a 4096-slot ring buffer, a 10ms flush interval,
+    /// a 100µs poll interval, the default page pool and no preallocation.
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            capacity: DEFAULT_CAPACITY,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            page_count: DEFAULT_PAGE_COUNT,
+            pre_alloc_size: 0,
+            create_dirs: false,
+        }
+    }
+
+    /// Sets the log file to write to, which may also be a block device written to directly
+    /// (see [`PicoError::DeviceFull`]).
+    pub fn path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the number of slots in the ring buffer between producers and the worker.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets how long a partially filled page may wait before it is flushed.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Sets the longest the idle worker sleeps before checking on its timers and writes.
+    ///
+    /// New entries and messages always wake the worker, and so do completed writes where
+    /// [`Logger::wakes_on_completion`] holds, so this is only a fallback for everything else.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the number of page buffers in the pool (see [`Logger::with_pages`]).
+    pub fn pages(mut self, page_count: usize) -> Self {
+        self.page_count = page_count;
+        self
+    }
+
+    /// Sets how many bytes of disk space to reserve for the log file up front (see
+    /// [`Logger::with_preallocation`]).
+    pub fn preallocate(mut self, bytes: u64) -> Self {
+        self.pre_alloc_size = bytes;
+        self
+    }
+
+    /// Creates the directories missing above the log file on `start()` (see
+    /// [`Logger::with_create_dirs`]).
+    pub fn create_dirs(mut self, enabled: bool) -> Self {
+        self.create_dirs = enabled;
+        self
+    }
+
+    /// Validates the configuration and returns a logger ready to be started.
+    pub fn build<T: Send + Sync + Default + Copy + 'static>(self) -> Result<Logger<T>, PicoError> {
+        let path = self.path.ok_or(PicoError::EmptyPath)?;
+        let flush_interval = duration_nanos(self.flush_interval);
+        let poll_interval = duration_nanos(self.poll_interval);
+
+        let mut logger = Logger::new();
+        logger.capacity = self.capacity;
+        logger.page_count = self.page_count;
+        logger.create_dirs = self.create_dirs;
+        logger.validate_config(&path, flush_interval, poll_interval)?;
+
+        logger.logpath = Some(path);
+        logger.flush_interval = Some(flush_interval);
+        logger.poll_interval = Some(poll_interval);
+        logger.pre_alloc_size = Some(self.pre_alloc_size);
+        Ok(logger)
+    }
+
+    /// Validates the configuration and returns a [`ShardedLogger`] of `shards` loggers, each
+    /// with this configuration and its own file: `<path>.0`, `<path>.1` and so on.
+    ///
+    /// Every shard gets a ring buffer of the configured capacity, a page pool of the
+    /// configured size and its own worker thread. Fails with [`PicoError::InvalidShardCount`]
+    /// unless `shards` is between 1 and [`MAX_SHARDS`].
+    pub fn build_sharded<T: Send + Sync + Default + Copy + 'static>(
+        self,
+        shards: usize,
+    ) -> Result<ShardedLogger<T>, PicoError> {
+        if !(1..=MAX_SHARDS).contains(&shards) {
+            return Err(PicoError::InvalidShardCount {
+                count: shards,
+                max: MAX_SHARDS,
+            });
+        }
+        let path = self.path.clone().ok_or(PicoError::EmptyPath)?;
+        let paths: Vec<_> = (0..shards).map(|i| shard_path(&path, i)).collect();
+        let loggers = paths
+            .iter()
+            .map(|shard| self.clone().path(shard).build())
+            .collect::<Result<_, _>>()?;
+        Ok(ShardedLogger::new(loggers, paths))
+    }
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn duration_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
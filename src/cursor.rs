@@ -0,0 +1,238 @@
+//! Resumable consumer position over a log file, persisted in a small sidecar file between
+//! runs.
+//!
+//! A cursor remembers the seq_id it resumes from and the page holding the entry before it,
+//! by offset and page id, so restoring one does not scan the file from the start. Pages keep
+//! their id when the logger restarts and appends to the file, so a saved cursor still finds
+//! its page then; if the page at the saved offset is not the one saved, the cursor seeks to
+//! its seq_id instead.
+
+use crate::errors::PicoError;
+use crate::reader::{LogEntry, RawReader};
+use crate::stats::SharedStats;
+use crate::util::sync_parent_dir;
+use crate::{Logger, LoggerState};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::Arc;
+
+/// Marks a saved cursor file.
+const CURSOR_MAGIC: u64 = u64::from_le_bytes(*b"PICOCURS");
+
+const CURSOR_FILE_LEN: usize = 32;
+
+/// Stands in for the page id of a page without a page meta in a saved cursor.
+const NO_PAGE_ID: u64 = u64::MAX;
+
+/// A consumer's position in a log file, handing out entries in batches.
+///
+/// Created with [`Logger::cursor_from`] or restored with [`Cursor::load`]. Each call to
+/// [`Cursor::next_batch`] picks up entries appended since the last one, so a consumer can
+/// poll a file that is still being written.
+pub struct Cursor<T> {
+    raw: RawReader,
+    /// Seq_id the next entry returned must reach; one past the last entry returned.
+    position: u64,
+    /// File offset at or before the entry holding `position`: the start of the page the
+    /// last entry returned was in, where the file has page metas.
+    offset: u64,
+    /// Page id of the page at `offset`, or `None` if it has no page meta.
+    page_id: Option<u32>,
+    /// The running logger's stats, whose durable watermark caps the entries handed out.
+    durable: Option<Arc<SharedStats>>,
+    clamped: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Opens a cursor over the configured log file that returns entries from `seq_id` on.
+    ///
+    /// If `seq_id` is older than the first entry in the file, the cursor starts from the
+    /// earliest entry there is and [`Cursor::clamped`] returns `true`.
+    ///
+    /// On a logger that is running, the cursor only returns entries up to this logger's
+    /// [`LoggerStats::flushed_seq_id`](crate::LoggerStats::flushed_seq_id), so a consumer
+    /// never sees an entry a crash could still take away. A cursor opened on a logger that
+    /// only reads the file has no watermark to go by, and returns whatever has been written.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Cursor<T>, PicoError>` - The cursor, or an error if the file cannot be
+    ///   opened or was written with a different payload type.
+    pub fn cursor_from(&self, seq_id: u64) -> Result<Cursor<T>, PicoError> {
+        let mut raw = self.raw_reader()?;
+        let clamped = raw.peek()?.is_some_and(|first| seq_id < first.seq_id);
+        raw.seek(seq_id)?;
+        let mut cursor = self.cursor_at(raw, seq_id, clamped);
+        cursor.remember_page();
+        Ok(cursor)
+    }
+
+    fn cursor_at(&self, raw: RawReader, position: u64, clamped: bool) -> Cursor<T> {
+        Cursor {
+            offset: raw.data_start(),
+            page_id: None,
+            raw,
+            position,
+            durable: (self.state == LoggerState::Running).then(|| self.stats.clone()),
+            clamped,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy> Cursor<T> {
+    /// Returns up to `max` entries following the last batch, or an empty batch if nothing
+    /// new has been written (or, on a live file, acknowledged) yet.
+    ///
+    /// Entries whose size differs from `T` are skipped, or fail the batch with
+    /// [`PicoError::EntrySizeMismatch`] if the logger was configured with
+    /// [`Logger::with_strict_reads`].
+    pub fn next_batch(&mut self, max: usize) -> Result<Vec<LogEntry<T>>, PicoError> {
+        let durable = self.durable.as_ref().map(|stats| stats.flushed_seq_id());
+        // Re-read from the last known good spot: the tail of a page that looked like padding
+        // may have been filled in since.
+        self.raw.refresh();
+        self.resume(false)?;
+
+        let mut batch = Vec::new();
+        while batch.len() < max {
+            let Some(header) = self.raw.peek()? else {
+                break;
+            };
+            if header.seq_id < self.position {
+                self.raw.next_entry()?;
+                continue;
+            }
+            if durable.is_some_and(|durable| header.seq_id > durable) {
+                break;
+            }
+            let strict = self.raw.is_strict();
+            let Some((header, payload)) = self.raw.next_entry()? else {
+                break;
+            };
+            let seq_id = header.seq_id;
+            if payload.len() == size_of::<T>() {
+                let data = unsafe { ptr::read_unaligned(payload.as_ptr() as *const T) };
+                batch.push(LogEntry {
+                    seq_id,
+                    ts_nanos: header.ts_nanos,
+                    level: header.level(),
+                    data,
+                });
+            } else if strict {
+                return Err(PicoError::EntrySizeMismatch {
+                    seq_id,
+                    len: payload.len(),
+                    expected: size_of::<T>(),
+                });
+            }
+            self.position = seq_id + 1;
+            self.remember_page();
+        }
+        Ok(batch)
+    }
+
+    /// Returns the seq_id the cursor resumes from: every entry returned so far has a lower
+    /// one.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns `true` if the position the cursor was opened at was older than anything in
+    /// the file, so it started from the earliest entry still there instead.
+    pub fn clamped(&self) -> bool {
+        self.clamped
+    }
+
+    /// Persists the cursor's position to `path`, replacing any earlier save atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PicoError> {
+        let path = path.as_ref();
+        let mut bytes = [0u8; CURSOR_FILE_LEN];
+        bytes[..8].copy_from_slice(&CURSOR_MAGIC.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.position.to_le_bytes());
+        let page_id = self.page_id.map_or(NO_PAGE_ID, u64::from);
+        bytes[24..].copy_from_slice(&page_id.to_le_bytes());
+
+        let tmp = tmp_path(path);
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(sync_parent_dir(path)?)
+    }
+
+    /// Restores a cursor saved with [`Cursor::save`], reading `logger`'s log file, which
+    /// may have been reopened and appended to since.
+    ///
+    /// The saved offset is used as long as the page there is still the one saved, or, for a
+    /// file without page metas, the entry there precedes the saved position; otherwise the
+    /// cursor seeks to the position as [`Logger::cursor_from`] does.
+    ///
+    /// Fails with [`PicoError::InvalidCursor`] if `path` holds no saved cursor.
+    pub fn load(path: impl AsRef<Path>, logger: &Logger<T>) -> Result<Self, PicoError>
+    where
+        T: Send + Sync + Default + 'static,
+    {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        if bytes.len() != CURSOR_FILE_LEN
+            || u64::from_le_bytes(bytes[..8].try_into().unwrap()) != CURSOR_MAGIC
+        {
+            return Err(PicoError::InvalidCursor {
+                path: path.to_path_buf(),
+            });
+        }
+        let offset = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        let position = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+        let page_id = u64::from_le_bytes(bytes[24..].try_into().unwrap());
+
+        let raw = logger.raw_reader()?;
+        let mut cursor = logger.cursor_at(raw, position, false);
+        if offset >= cursor.raw.data_start() {
+            cursor.offset = offset;
+            cursor.page_id = u32::try_from(page_id).ok();
+        }
+        cursor.resume(true)?;
+        cursor.remember_page();
+        Ok(cursor)
+    }
+
+    /// Positions the reader at the saved offset, or, if the page there is not the one the
+    /// cursor was last in, at the first entry from `position` on. Without a page id to go
+    /// by, the offset is trusted unless `check_seq` is set and the entry there is already
+    /// past `position`.
+    fn resume(&mut self, check_seq: bool) -> Result<(), PicoError> {
+        self.raw.seek_offset(self.offset);
+        let first = self.raw.peek()?;
+        let moved = match self.page_id {
+            Some(page_id) => self.raw.page_id() != Some(page_id),
+            None => check_seq && first.is_some_and(|header| header.seq_id > self.position),
+        };
+        if moved {
+            self.raw.seek(self.position)?;
+        }
+        Ok(())
+    }
+
+    /// Records the page holding the entry at the reader as the one to resume from.
+    fn remember_page(&mut self) {
+        self.page_id = self.raw.page_id();
+        self.offset = match self.page_id {
+            Some(_) => self.raw.page_start(),
+            None => self.raw.resume_offset(),
+        };
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+/// How strongly a page must be persisted before its entries are acknowledged through
+/// [`Logger::get_last_flushed_entry`](crate::Logger::get_last_flushed_entry).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// Entries are acknowledged once their page write completes. With `O_DIRECT` the data
+    /// has reached the device, but may still sit in its volatile write cache.
+    #[default]
+    None,
+    /// Every page write is followed by a linked `fdatasync`, and entries are acknowledged
+    /// only once the sync completes.
+    Fdatasync,
+    /// The log file is opened with `O_DSYNC`, so each page write completes only after the
+    /// data is on stable storage.
+    Odsync,
+}
+
+/// Background syncs that harden written pages a batch at a time, for a middle ground between
+/// [`Durability::None`] and an `fdatasync` after every page. Set with
+/// [`Logger::with_sync_policy`](crate::Logger::with_sync_policy).
+///
+/// Entries are still acknowledged as soon as their page write completes. Range syncs only
+/// start writeback of the pages completed since the last one, spreading the device's work
+/// out; a full sync advances the hardened watermark,
+/// [`Logger::get_last_hardened_entry`](crate::Logger::get_last_hardened_entry), to every
+/// entry acknowledged before it was issued.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncPolicy {
+    /// Issue a `sync_file_range(SYNC_FILE_RANGE_WRITE)` once this many pages have completed
+    /// since the last one; 0 leaves it to `range_interval`.
+    pub range_every_pages: u32,
+    /// Also issue one once this long has passed since the last, if a page has completed
+    /// since; `None` leaves it to `range_every_pages`.
+    pub range_interval: Option<Duration>,
+    /// Time between full `fdatasync`s. One is only issued if entries were acknowledged
+    /// since the last, and a final one when the logger shuts down.
+    pub full_sync_interval: Duration,
+}
+
+impl Default for SyncPolicy {
+    fn default() -> Self {
+        Self {
+            range_every_pages: 16,
+            range_interval: Some(Duration::from_millis(10)),
+            full_sync_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The worker's progress through a [`SyncPolicy`].
+pub(crate) struct BackgroundSync {
+    /// File range written since the last range sync; for a striped logger, the union of
+    /// the ranges written to each of its files.
+    pub range_start: u64,
+    pub range_end: u64,
+    /// Pages completed since the last range sync.
+    pub pages: u32,
+    /// [`Clock::monotonic_nanos`](crate::Clock::monotonic_nanos) readings at the last
+    /// range and full syncs.
+    pub last_range: u64,
+    pub last_full: u64,
+    /// Range syncs in flight, one per file.
+    pub ranges_in_flight: usize,
+    /// Seq_id the full sync in flight hardens once it completes.
+    pub full_in_flight: Option<u64>,
+    /// Files whose `fdatasync` is part of the full sync in flight and hasn't completed yet.
+    pub full_pending: usize,
+    /// errno of the first of them that failed, if any.
+    pub full_errno: Option<i32>,
+    /// Seq_id up to which the last full sync hardened this logger's entries.
+    pub hardened: u64,
+    /// Cleared once the file turns out not to support range syncs.
+    pub ranges_supported: bool,
+}
+
+impl BackgroundSync {
+    /// Starts both intervals at `now`, hardening nothing beyond `hardened` yet.
+    pub fn new(now: u64, hardened: u64) -> Self {
+        Self {
+            range_start: 0,
+            range_end: 0,
+            pages: 0,
+            last_range: now,
+            last_full: now,
+            ranges_in_flight: 0,
+            full_in_flight: None,
+            full_pending: 0,
+            full_errno: None,
+            hardened,
+            ranges_supported: true,
+        }
+    }
+
+    /// Adds the completed page write of `len` bytes at `offset` to the next range sync.
+    pub fn page_written(&mut self, offset: u64, len: usize) {
+        let end = offset + len as u64;
+        if self.pages == 0 {
+            (self.range_start, self.range_end) = (offset, end);
+        } else {
+            self.range_start = self.range_start.min(offset);
+            self.range_end = self.range_end.max(end);
+        }
+        self.pages += 1;
+    }
+
+    pub fn in_flight(&self) -> bool {
+        self.ranges_in_flight > 0 || self.full_in_flight.is_some()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::errors::PicoError;
+use chacha20poly1305::{
+    XChaCha20Poly1305, XNonce,
+    aead::{AeadInPlace, KeyInit, OsRng, rand_core::RngCore},
+};
+
+pub const NONCE_LEN: usize = 24;
+pub const TAG_LEN: usize = 16;
+
+/// XChaCha20-Poly1305 page cipher.
+///
+/// Nonces are a random per-logger prefix followed by a page counter, so they never repeat
+/// within a run and collide across runs only with negligible probability.
+pub(crate) struct PageCipher {
+    aead: XChaCha20Poly1305,
+    nonce_prefix: [u8; NONCE_LEN - 8],
+    counter: u64,
+}
+
+impl PageCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut nonce_prefix = [0u8; NONCE_LEN - 8];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        Self {
+            aead: XChaCha20Poly1305::new(key.into()),
+            nonce_prefix,
+            counter: 0,
+        }
+    }
+
+    /// Encrypts `buf` in place, returning the nonce and authentication tag to store with it.
+    pub fn encrypt(&mut self, aad: &[u8], buf: &mut [u8]) -> ([u8; NONCE_LEN], [u8; TAG_LEN]) {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_LEN - 8].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_LEN - 8..].copy_from_slice(&self.counter.to_le_bytes());
+        self.counter += 1;
+
+        let tag = self
+            .aead
+            .encrypt_in_place_detached(XNonce::from_slice(&nonce), aad, buf)
+            .expect("page length within XChaCha20-Poly1305 limits");
+        (nonce, tag.into())
+    }
+
+    /// Decrypts `buf` in place, failing with [`PicoError::AuthFailed`] if the key is wrong or
+    /// the page was tampered with.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8; NONCE_LEN],
+        tag: &[u8; TAG_LEN],
+        aad: &[u8],
+        buf: &mut [u8],
+    ) -> Result<(), PicoError> {
+        self.aead
+            .decrypt_in_place_detached(XNonce::from_slice(nonce), aad, buf, tag.into())
+            .map_err(|_| PicoError::AuthFailed)
+    }
+}
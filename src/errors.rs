@@ -1,9 +1,457 @@
+use crate::Level;
+use crate::header::Schema;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Custom error type for the library.
+///
+/// Converts to and from [`std::io::Error`], so it can be propagated with `?` from functions
+/// returning either; wrapping a `PicoError` in an `io::Error` and back yields the original.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum PicoError {
     /// Indicates that the current page is full and cannot accept more data.
     #[error("Page Full: Cannot write here. Move to the next page")]
     PageFull {},
+
+    /// The logger was used before `start()` was called.
+    #[error("Logger not started: call start() before logging")]
+    NotStarted,
+
+    /// `start()` was called on a logger without a log path and intervals to write with.
+    #[error("Logger not configured for writing: use LoggerConfig or with_write_config")]
+    NotConfigured,
+
+    /// `start()` was called on a logger that has already been started.
+    #[error("Logger already started: start() can only be called once")]
+    AlreadyStarted,
+
+    /// No log path was configured, or it is empty.
+    #[error("Invalid log path: a non-empty log file path is required")]
+    EmptyPath,
+
+    /// The directory the log file would be created in does not exist, and
+    /// [`Logger::with_create_dirs`](crate::Logger::with_create_dirs) is off.
+    #[error("Log directory {} does not exist: create it, or enable create_dirs, before calling start()", path.display())]
+    MissingDirectory { path: PathBuf },
+
+    /// Another logger, in this process or another one, is already writing to the log file.
+    #[error("Log file {} is locked by another writer", path.display())]
+    FileLocked { path: PathBuf },
+
+    /// The configured ring buffer capacity is unusable.
+    #[error("Invalid capacity {capacity}: the ring buffer needs at least one slot")]
+    InvalidCapacity { capacity: usize },
+
+    /// A flush or poll interval is zero, which would spin the worker.
+    #[error("Invalid {name} of {nanos}ns: intervals are in nanoseconds and must be non-zero")]
+    InvalidInterval { name: &'static str, nanos: u64 },
+
+    /// The page count passed to `with_pages` is out of range.
+    #[error("Invalid page count {count}: must be between {min} and {max}")]
+    InvalidPageCount {
+        count: usize,
+        min: usize,
+        max: usize,
+    },
+
+    /// The ring depth passed to `with_ring_depth` is not usable.
+    #[error("Invalid ring depth {depth}: must be a power of two no larger than {max}")]
+    InvalidRingDepth { depth: u32, max: u32 },
+
+    /// The fraction passed to `with_flush_watermark` is out of range.
+    #[error("Invalid flush watermark {fraction}: must be in (0, 1]")]
+    InvalidWatermark { fraction: f32 },
+
+    /// The fill level passed to `with_partial_rewrites` is out of range.
+    #[error("Invalid fill level {fraction}: must be in (0, 1]")]
+    InvalidFillLevel { fraction: f32 },
+
+    /// The tag passed to `with_schema_tag` does not fit in the file header.
+    #[error("Schema tag too long: {len} bytes exceeds the maximum of {max} bytes")]
+    SchemaTagTooLong { len: usize, max: usize },
+
+    /// The size passed to `with_page_size` is not usable with the file's block size.
+    #[error(
+        "Invalid page size {size}: must be a multiple of the {block_size}-byte block size between {min} and {max} bytes"
+    )]
+    InvalidPageSize {
+        size: usize,
+        block_size: usize,
+        min: usize,
+        max: usize,
+    },
+
+    /// The size passed to `with_block_size` is not a power of two of at least 512 bytes.
+    #[error("Invalid block size {size}: must be a power of two of at least 512 bytes")]
+    InvalidBlockSize { size: usize },
+
+    /// The block size is not a multiple of the alignment direct I/O on the log file needs,
+    /// its logical sector size.
+    #[error(
+        "Block size {block_size} is not a multiple of the {sector_size}-byte logical sector size of {}",
+        path.display()
+    )]
+    MisalignedBlockSize {
+        path: PathBuf,
+        block_size: usize,
+        sector_size: usize,
+    },
+
+    /// A direct write of one page of zeros, made by `start()` where the first page goes to
+    /// check the block size, was rejected with `EINVAL`.
+    #[error(
+        "A direct write of a {page_size}-byte page to {} was rejected (block size {block_size}, logical sector size {}): set one with with_block_size",
+        path.display(),
+        sector_size.map_or("unknown".to_string(), |size| size.to_string())
+    )]
+    DirectWriteRejected {
+        path: PathBuf,
+        page_size: usize,
+        block_size: usize,
+        /// The alignment the kernel reported for direct I/O on the file, if it did.
+        sector_size: Option<usize>,
+    },
+
+    /// [`UringOptions::iopoll`](crate::UringOptions::iopoll) was set together with a setting
+    /// a polled ring cannot serve.
+    #[error("IOPOLL cannot be combined with {setting}")]
+    IopollConflict { setting: &'static str },
+
+    /// A polled read made by `start()` failed, so the file's device or filesystem does not
+    /// support [`UringOptions::iopoll`](crate::UringOptions::iopoll) and no write to it would
+    /// ever complete.
+    #[error(
+        "{} does not support polled I/O ({})",
+        path.display(),
+        io::Error::from_raw_os_error(*errno)
+    )]
+    IopollUnavailable { path: PathBuf, errno: i32 },
+
+    /// The size passed to `with_preallocation` is not a whole number of the file's blocks.
+    #[error(
+        "Invalid preallocation of {bytes} bytes: must be a multiple of the {block_size}-byte block size"
+    )]
+    InvalidPreallocation { bytes: u64, block_size: usize },
+
+    /// The file `with_append` was to append to was written in a way this logger cannot
+    /// continue.
+    #[error("Cannot append to the log file: {reason}")]
+    AppendMismatch { reason: &'static str },
+
+    /// The entry's level is below the one set with `with_min_level`, so it was dropped.
+    #[error("Entry at level {level} dropped: below the minimum level {min}")]
+    LevelFiltered { level: Level, min: Level },
+
+    /// The worker could not be pinned to the CPU core passed to `with_worker_affinity`.
+    #[error("Cannot pin the worker to CPU core {core}: {source}")]
+    AffinityFailed {
+        core: usize,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The shard count passed to `build_sharded` is out of range.
+    #[error("Invalid shard count {count}: must be between 1 and {max}")]
+    InvalidShardCount { count: usize, max: usize },
+
+    /// The stripe paths passed to `with_stripe_paths` are too many.
+    #[error("Invalid stripe count {count}: must be at most {max}")]
+    InvalidStripeCount { count: usize, max: usize },
+
+    /// Striping was combined with a setting that only supports a single file.
+    #[error("Striped logs do not support {setting}")]
+    StripeConflict { setting: &'static str },
+
+    /// [`Logger::start_inline`](crate::Logger::start_inline) was combined with a setting, or
+    /// an inline logger asked for an operation, that needs the ring buffer.
+    #[error("Inline mode does not support {setting}")]
+    InlineConflict { setting: &'static str },
+
+    /// A page lent in inline mode came back to the worker ahead of one lent before it.
+    #[error("Inline page {returned} handed back while page {expected} was still lent")]
+    InlinePageOutOfOrder { expected: usize, returned: usize },
+
+    /// The metadata block, with the tags set by `with_metadata`, does not fit the file header
+    /// block.
+    #[error("Metadata too large: {len} bytes exceeds the maximum of {max} bytes")]
+    MetadataTooLarge { len: usize, max: usize },
+
+    /// The version passed to `with_format_version` cannot be written.
+    #[error("Invalid format version {version}: must be between {min} and {max}")]
+    InvalidFormatVersion { version: u32, min: u32, max: u32 },
+
+    /// The file was written with a different payload type than the one it is read as.
+    #[error("Schema mismatch: reading {expected} but the file holds {found}")]
+    SchemaMismatch { expected: Schema, found: Schema },
+
+    /// A single entry does not fit in one page.
+    #[error("Entry too large: {size} bytes exceeds the per-page maximum of {max} bytes")]
+    EntryTooLarge { size: usize, max: usize },
+
+    /// An entry has an empty payload, whose zero length would read back as the end of the
+    /// page's entries.
+    #[error("Empty entry: a payload must hold at least one byte")]
+    EmptyEntry,
+
+    /// The ring buffer has no free slot and the entry was not accepted.
+    #[error("Buffer full: no free slot in the ring buffer")]
+    BufferFull,
+
+    /// A batch passed to `log_batch` has more entries than the ring buffer has slots.
+    #[error("Batch too large: {len} entries exceeds the ring buffer capacity of {capacity}")]
+    BatchTooLarge { len: usize, capacity: usize },
+
+    /// A page's stored checksum does not match its contents.
+    #[error("Checksum mismatch for the page at offset {offset}")]
+    ChecksumMismatch { offset: u64 },
+
+    /// An entry header in a page parsed with [`format::parse_page`](crate::format::parse_page)
+    /// can't have been written by the logger.
+    #[error("Corrupt entry at byte {offset} of the page")]
+    CorruptEntry { offset: usize },
+
+    /// A strict read found an entry that can't have been written by the logger, in the page
+    /// or unpaged entry starting at byte `offset` of the file.
+    #[error("Damaged entry in the log at offset {offset}")]
+    DamagedEntry { offset: u64 },
+
+    /// An entry's payload is not the size of the type it is read as, either shorter or, in
+    /// a strict read, of any other size.
+    #[error(
+        "Entry size mismatch: seq_id {seq_id} holds {len} bytes where {expected} were expected"
+    )]
+    EntrySizeMismatch {
+        seq_id: u64,
+        len: usize,
+        expected: usize,
+    },
+
+    /// A page header at byte `offset` of the file claims a length no page can have.
+    #[error("Damaged page header at offset {offset}: claims {len} bytes")]
+    DamagedPage { offset: u64, len: usize },
+
+    /// The file a [`Cursor`](crate::Cursor) was to be loaded from holds no saved cursor.
+    #[error("Not a saved cursor: {path}")]
+    InvalidCursor { path: PathBuf },
+
+    /// The file uses a format version this build cannot read.
+    #[error("Format mismatch: file format version {found} is newer than the supported {supported}")]
+    FormatMismatch { found: u32, supported: u32 },
+
+    /// The file's header records a byte order this build cannot read.
+    #[error(
+        "Byte order mismatch: file framing has byte order {found}, only little-endian is supported"
+    )]
+    ByteOrderMismatch { found: u8 },
+
+    /// An encrypted page failed authentication: the key is wrong or the page was modified.
+    #[error("Authentication failed: wrong encryption key or tampered page")]
+    AuthFailed,
+
+    /// A page write failed and the entries in it were not persisted.
+    #[error(
+        "Write failed with errno {errno} for the page at offset {offset} (seq_ids {first_seq_id}..={last_seq_id})"
+    )]
+    WriteFailed {
+        /// errno reported by the kernel, e.g. `libc::ENOSPC`.
+        errno: i32,
+        /// File offset the page was being written to.
+        offset: u64,
+        /// Sequence ID of the first entry in the lost page.
+        first_seq_id: u64,
+        /// Sequence ID of the last entry in the lost page.
+        last_seq_id: u64,
+    },
+
+    /// A page write to one of the files of a striped logger failed. The stripe takes no
+    /// further pages: this page and later ones go to the remaining stripes.
+    #[error(
+        "Stripe {} failed with errno {errno} at offset {offset}; writing to the remaining stripes",
+        path.display()
+    )]
+    StripeFailed {
+        /// The stripe file that failed.
+        path: PathBuf,
+        /// errno reported by the kernel, e.g. `libc::EIO`.
+        errno: i32,
+        /// File offset the page was being written to.
+        offset: u64,
+    },
+
+    /// The log is on a block device and the next page would run past its end. The worker
+    /// stops; entries not yet written are lost.
+    #[error("Block device {} is full: the log reached its end at {len} bytes", path.display())]
+    DeviceFull {
+        /// The device that filled up.
+        path: PathBuf,
+        /// Size of the device in bytes.
+        len: u64,
+    },
+
+    /// A background `fdatasync` issued under a [`SyncPolicy`](crate::SyncPolicy) failed, so
+    /// the hardened watermark did not advance.
+    #[error(
+        "Background sync failed with errno {errno}; entries up to seq_id {seq_id} not hardened"
+    )]
+    SyncFailed {
+        /// errno reported by the kernel, e.g. `libc::EIO`.
+        errno: i32,
+        /// Sequence ID the sync would have hardened.
+        seq_id: u64,
+    },
+
+    /// Extending a pre-allocated log file ahead of the writes failed. The worker carries on,
+    /// letting the file grow as pages are written past its end.
+    #[error("Extending the log file failed with errno {errno}; it now grows as pages are written")]
+    GrowthFailed {
+        /// errno reported by the kernel, e.g. `libc::ENOSPC`.
+        errno: i32,
+    },
+
+    /// Writing the sidecar index failed. The index is disabled for the rest of the run; the
+    /// log itself is unaffected.
+    #[error("Index write failed, index disabled: {0}")]
+    IndexFailed(#[source] io::Error),
+
+    /// Options requested through [`UringOptions`](crate::UringOptions) or the backend are
+    /// unavailable on this kernel, so the logger started without them.
+    #[error("Requested options unavailable on this kernel: {}", downgrades.join("; "))]
+    OptionsDowngraded {
+        /// What was given up, one description per option.
+        downgrades: Vec<String>,
+    },
+
+    /// Entries were discarded under [`OverflowPolicy::DropNewest`](crate::OverflowPolicy)
+    /// while every page was still being written.
+    #[error("Dropped {count} entries (seq_ids {first_seq_id}..={last_seq_id}): no free page")]
+    EntriesDropped {
+        first_seq_id: u64,
+        last_seq_id: u64,
+        count: u64,
+    },
+
+    /// The worker stopped after an unrecoverable error and no longer accepts entries.
+    #[error("Log worker stopped: see the error channel for the cause")]
+    WorkerStopped,
+
+    /// The logger has been shut down and accepts no further entries.
+    #[error("Logger shut down")]
+    ShutDown,
+
+    /// Shutdown gave up waiting for page writes to complete. Entries from `lost_from_seq`
+    /// on may not have reached the disk.
+    #[error("Shutdown timed out; entries from seq_id {lost_from_seq} on may be lost")]
+    ShutdownTimedOut { lost_from_seq: u64 },
+
+    /// The worker did not answer a snapshot request in time, for example because it is
+    /// blocked on a stalled disk.
+    #[error("Snapshot timed out after {timeout:?}")]
+    SnapshotTimedOut { timeout: Duration },
+
+    /// Writing logs is not supported on this platform; reading them is.
+    #[error("Writing is not supported on this platform: {0}")]
+    UnsupportedPlatform(&'static str),
+
+    /// An I/O operation failed, such as opening the log file or the worker's io_uring
+    /// instance.
+    #[error("I/O error: {0}")]
+    Io(#[source] io::Error),
+
+    /// A value could not be encoded for the serde entry path.
+    #[cfg(feature = "serde")]
+    #[error("Serialization failed: {0}")]
+    Serialize(#[from] postcard::Error),
+}
+
+impl PicoError {
+    /// The [`std::io::ErrorKind`] this error maps to when converted into an `io::Error`.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            PicoError::Io(e)
+            | PicoError::IndexFailed(e)
+            | PicoError::AffinityFailed { source: e, .. } => e.kind(),
+            PicoError::MissingDirectory { .. } => ErrorKind::NotFound,
+            PicoError::NotConfigured
+            | PicoError::EmptyPath
+            | PicoError::InvalidCapacity { .. }
+            | PicoError::InvalidInterval { .. }
+            | PicoError::InvalidPageCount { .. }
+            | PicoError::InvalidRingDepth { .. }
+            | PicoError::InvalidWatermark { .. }
+            | PicoError::InvalidFillLevel { .. }
+            | PicoError::SchemaTagTooLong { .. }
+            | PicoError::MetadataTooLarge { .. }
+            | PicoError::InvalidFormatVersion { .. }
+            | PicoError::InvalidPageSize { .. }
+            | PicoError::InvalidPreallocation { .. }
+            | PicoError::InvalidBlockSize { .. }
+            | PicoError::MisalignedBlockSize { .. }
+            | PicoError::DirectWriteRejected { .. }
+            | PicoError::IopollConflict { .. }
+            | PicoError::AppendMismatch { .. }
+            | PicoError::InvalidShardCount { .. }
+            | PicoError::InvalidStripeCount { .. }
+            | PicoError::StripeConflict { .. }
+            | PicoError::InlineConflict { .. }
+            | PicoError::EntryTooLarge { .. }
+            | PicoError::EmptyEntry
+            | PicoError::BatchTooLarge { .. } => ErrorKind::InvalidInput,
+            PicoError::SchemaMismatch { .. }
+            | PicoError::ChecksumMismatch { .. }
+            | PicoError::CorruptEntry { .. }
+            | PicoError::DamagedEntry { .. }
+            | PicoError::EntrySizeMismatch { .. }
+            | PicoError::DamagedPage { .. }
+            | PicoError::InvalidCursor { .. }
+            | PicoError::AuthFailed => ErrorKind::InvalidData,
+            #[cfg(feature = "serde")]
+            PicoError::Serialize(_) => ErrorKind::InvalidData,
+            PicoError::FormatMismatch { .. }
+            | PicoError::ByteOrderMismatch { .. }
+            | PicoError::IopollUnavailable { .. }
+            | PicoError::OptionsDowngraded { .. }
+            | PicoError::UnsupportedPlatform(_) => ErrorKind::Unsupported,
+            PicoError::BufferFull => ErrorKind::WouldBlock,
+            PicoError::FileLocked { .. } => ErrorKind::ResourceBusy,
+            PicoError::DeviceFull { .. } => ErrorKind::StorageFull,
+            PicoError::ShutdownTimedOut { .. } | PicoError::SnapshotTimedOut { .. } => {
+                ErrorKind::TimedOut
+            }
+            PicoError::WriteFailed { errno, .. }
+            | PicoError::StripeFailed { errno, .. }
+            | PicoError::GrowthFailed { errno }
+            | PicoError::SyncFailed { errno, .. } => io::Error::from_raw_os_error(*errno).kind(),
+            PicoError::PageFull {}
+            | PicoError::NotStarted
+            | PicoError::AlreadyStarted
+            | PicoError::WorkerStopped
+            | PicoError::InlinePageOutOfOrder { .. }
+            | PicoError::EntriesDropped { .. }
+            | PicoError::LevelFiltered { .. }
+            | PicoError::ShutDown => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<io::Error> for PicoError {
+    /// Unwraps a `PicoError` carried inside the `io::Error`, and wraps anything else in
+    /// [`PicoError::Io`].
+    fn from(err: io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<PicoError>()) {
+            return *err.into_inner().unwrap().downcast::<PicoError>().unwrap();
+        }
+        PicoError::Io(err)
+    }
+}
+
+impl From<PicoError> for io::Error {
+    fn from(err: PicoError) -> Self {
+        match err {
+            PicoError::Io(e) => e,
+            other => io::Error::new(other.kind(), other),
+        }
+    }
 }
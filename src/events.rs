@@ -0,0 +1,48 @@
+use crossbeam_channel::{Sender, TrySendError};
+use std::time::Duration;
+
+/// Describes a page that has just become durable, passed to the callback registered with
+/// [`Logger::with_flush_callback`](crate::Logger::with_flush_callback).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlushEvent {
+    /// Sequence ID of the first entry in the page.
+    pub first_seq_id: u64,
+    /// Sequence ID of the last entry in the page.
+    pub last_seq_id: u64,
+    /// Bytes written for the page, including padding to the block size.
+    pub bytes: usize,
+    /// Time from queueing the page write until it completed, including any retries.
+    pub latency: Duration,
+}
+
+pub(crate) type FlushCallback = Box<dyn Fn(FlushEvent) + Send + Sync>;
+
+/// A channel registered with [`Logger::subscribe`](crate::Logger::subscribe).
+pub(crate) struct Subscriber {
+    sender: Sender<u64>,
+    /// The last watermark the channel accepted.
+    sent: u64,
+}
+
+impl Subscriber {
+    pub fn new(sender: Sender<u64>) -> Self {
+        Self { sender, sent: 0 }
+    }
+
+    /// Offers `watermark` if it is newer than what was last sent, returning `false` once the
+    /// receiver is gone. A value still unread keeps its place, and `watermark` is offered
+    /// again on the next call.
+    pub fn notify(&mut self, watermark: u64) -> bool {
+        if watermark <= self.sent {
+            return true;
+        }
+        match self.sender.try_send(watermark) {
+            Ok(()) => {
+                self.sent = watermark;
+                true
+            }
+            Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
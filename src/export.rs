@@ -0,0 +1,157 @@
+//! Dumping a log file into formats other tools can load.
+//!
+//! Every exported record starts with the entry's `seq_id` and `ts_nanos`, followed by the
+//! payload: its [`ToRow`] fields for [`Csv`], or its serde representation for `JsonLines`.
+
+use crate::Logger;
+use std::fmt::{Display, Write as _};
+use std::io::{BufWriter, Error, Write};
+
+/// A payload that can be written as the columns of a CSV row.
+pub trait ToRow {
+    /// Names of the columns written by [`ToRow::write_row`], excluding the leading `seq_id`
+    /// and `ts_nanos` columns every export starts with.
+    fn columns() -> &'static [&'static str];
+
+    /// Writes one field per entry of [`ToRow::columns`], in the same order.
+    fn write_row(&self, row: &mut RowWriter<'_>) -> Result<(), Error>;
+}
+
+/// Writes the fields of one CSV row, quoting them as needed.
+pub struct RowWriter<'a> {
+    out: &'a mut dyn Write,
+    scratch: &'a mut String,
+}
+
+impl RowWriter<'_> {
+    /// Appends `value` as the next field of the row.
+    ///
+    /// Fields containing a comma, a quote or a line break are quoted, with quotes doubled.
+    pub fn field(&mut self, value: impl Display) -> Result<(), Error> {
+        self.scratch.clear();
+        let _ = write!(self.scratch, "{}", value);
+        self.out.write_all(b",")?;
+        if self.scratch.contains([',', '"', '\n', '\r']) {
+            self.out.write_all(b"\"")?;
+            self.out
+                .write_all(self.scratch.replace('"', "\"\"").as_bytes())?;
+            self.out.write_all(b"\"")
+        } else {
+            self.out.write_all(self.scratch.as_bytes())
+        }
+    }
+}
+
+/// An output format for [`Logger::export`], implemented for payloads it can represent.
+pub trait ExportFormat<T> {
+    /// Writes whatever comes before the first record, such as a header row.
+    fn begin(&mut self, out: &mut dyn Write) -> Result<(), Error>;
+
+    /// Writes a single record, including its trailing line break.
+    fn record(
+        &mut self,
+        out: &mut dyn Write,
+        seq_id: u64,
+        ts_nanos: u64,
+        data: &T,
+    ) -> Result<(), Error>;
+}
+
+/// Comma-separated values with a header row of `seq_id,ts_nanos` followed by
+/// [`ToRow::columns`].
+#[derive(Clone, Debug, Default)]
+pub struct Csv {
+    scratch: String,
+}
+
+impl Csv {
+    /// Creates a CSV format; equivalent to `Csv::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: ToRow> ExportFormat<T> for Csv {
+    fn begin(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        out.write_all(b"seq_id,ts_nanos")?;
+        for column in T::columns() {
+            RowWriter {
+                out: &mut *out,
+                scratch: &mut self.scratch,
+            }
+            .field(column)?;
+        }
+        out.write_all(b"\n")
+    }
+
+    fn record(
+        &mut self,
+        out: &mut dyn Write,
+        seq_id: u64,
+        ts_nanos: u64,
+        data: &T,
+    ) -> Result<(), Error> {
+        write!(out, "{},{}", seq_id, ts_nanos)?;
+        data.write_row(&mut RowWriter {
+            out: &mut *out,
+            scratch: &mut self.scratch,
+        })?;
+        out.write_all(b"\n")
+    }
+}
+
+/// One JSON object per line: `{"seq_id":…,"ts_nanos":…,"data":…}`, with `data` serialized
+/// through serde.
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonLines;
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> ExportFormat<T> for JsonLines {
+    fn begin(&mut self, _out: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        out: &mut dyn Write,
+        seq_id: u64,
+        ts_nanos: u64,
+        data: &T,
+    ) -> Result<(), Error> {
+        write!(
+            out,
+            "{{\"seq_id\":{},\"ts_nanos\":{},\"data\":",
+            seq_id, ts_nanos
+        )?;
+        serde_json::to_writer(&mut *out, data)?;
+        out.write_all(b"}\n")
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Writes every entry of the configured log file to `out` in `format`.
+    ///
+    /// `out` is buffered internally, so a bare `File` or `Stdout` is fine.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, Error>` - The number of records exported, or the first read or write
+    ///   error.
+    pub fn export<W: Write, F: ExportFormat<T>>(
+        &self,
+        out: W,
+        mut format: F,
+    ) -> Result<u64, Error> {
+        let mut out = BufWriter::new(out);
+        format.begin(&mut out)?;
+        let mut count = 0;
+        for entry in self.read_iter()? {
+            let entry = entry?;
+            format.record(&mut out, entry.seq_id, entry.ts_nanos, &entry.data)?;
+            count += 1;
+        }
+        out.flush()?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,265 @@
+//! The on-disk layout of entries, shared by the writer and every reader.
+//!
+//! This module only moves bytes between slices: it has no I/O and nothing specific to a
+//! platform, so tools that read or check log files can use it without a [`Logger`] and it
+//! runs under Miri. [`encode_entry`] is what pages are filled with, and [`parse_page`]
+//! reads them back, so the two can't disagree about the layout.
+//!
+//! An entry is an [`EntryHeader`] of [`ENTRY_HEADER_LEN`] bytes followed by its payload,
+//! zero-padded to a multiple of [`ENTRY_ALIGN`]. A page's entries end at the first header
+//! with a zero length, which the zeroed rest of the page provides.
+//!
+//! [`Logger`]: crate::Logger
+
+use crate::errors::PicoError;
+use crate::header::{LEVEL_VERSION, PAGE_META_VERSION, WIDE_LEN_VERSION, le_u32, le_u64};
+use crate::level::Level;
+use crate::page::{PAGE_META_LEN, PageMeta};
+use std::mem::size_of;
+use std::ops::Range;
+
+/// Bytes an [`EntryHeader`] takes ahead of each payload.
+pub const ENTRY_HEADER_LEN: usize = size_of::<EntryHeader>();
+/// Every entry, header and payload together, is padded to a multiple of this.
+pub const ENTRY_ALIGN: usize = 8;
+/// Offset of the little-endian `u64` seq_id within an entry header.
+pub const ENTRY_SEQ_ID_OFFSET: usize = 0;
+/// Offset of the little-endian `u64` timestamp within an entry header.
+pub const ENTRY_TS_OFFSET: usize = 8;
+/// Offset of the little-endian `u32` payload length within an entry header.
+pub const ENTRY_LEN_OFFSET: usize = 16;
+/// Offset of the level byte within an entry header.
+pub const ENTRY_LEVEL_OFFSET: usize = 20;
+
+/// Largest payload a single entry may carry. Keeps every page within the length readers
+/// accept, since a page must hold at least one whole entry.
+pub const MAX_ENTRY_LEN: usize = 16 * 1024 * 1024;
+
+/// The header written ahead of each entry's payload.
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub seq_id: u64,
+    pub ts_nanos: u64,
+    /// Payload length. Format version 1 stored it as a `u16` followed by padding.
+    pub len: u32,
+    /// The entry's [`Level`] as a byte; 0 before format version 4.
+    pub level: u8,
+    pub _pad: [u8; 3],
+}
+
+impl EntryHeader {
+    /// Reads the header at the start of `bytes`, which must hold a whole header. `wide_len`
+    /// is `false` for files whose format version stored the length as a `u16`.
+    pub fn read(bytes: &[u8], wide_len: bool) -> Self {
+        assert!(bytes.len() >= size_of::<Self>());
+        Self {
+            seq_id: le_u64(bytes, ENTRY_SEQ_ID_OFFSET),
+            ts_nanos: le_u64(bytes, ENTRY_TS_OFFSET),
+            len: if wide_len {
+                le_u32(bytes, ENTRY_LEN_OFFSET)
+            } else {
+                u16::from_le_bytes([bytes[ENTRY_LEN_OFFSET], bytes[ENTRY_LEN_OFFSET + 1]]) as u32
+            },
+            level: bytes[ENTRY_LEVEL_OFFSET],
+            _pad: bytes[ENTRY_LEVEL_OFFSET + 1..size_of::<Self>()]
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// Writes the header to the start of `bytes`, which must have room for a whole header.
+    fn write_to(&self, bytes: &mut [u8]) {
+        let bytes = &mut bytes[..size_of::<Self>()];
+        bytes[ENTRY_SEQ_ID_OFFSET..][..8].copy_from_slice(&{ self.seq_id }.to_le_bytes());
+        bytes[ENTRY_TS_OFFSET..][..8].copy_from_slice(&{ self.ts_nanos }.to_le_bytes());
+        bytes[ENTRY_LEN_OFFSET..][..4].copy_from_slice(&{ self.len }.to_le_bytes());
+        bytes[ENTRY_LEVEL_OFFSET] = self.level;
+        bytes[ENTRY_LEVEL_OFFSET + 1..].copy_from_slice(&self._pad);
+    }
+
+    /// The entry's level; [`Level::Info`] for a byte that holds none, including every entry
+    /// of a format before version 4.
+    pub fn level(&self) -> Level {
+        Level::from_byte(self.level).unwrap_or_default()
+    }
+}
+
+/// Which revision of the entry layout a file uses, as implied by its format version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntryFormat {
+    /// Entry headers carry a `u32` length rather than a `u16` one.
+    pub wide_len: bool,
+    /// Entry headers record a level after the length.
+    pub levels: bool,
+    /// Each page's entries are preceded by a page meta.
+    pub page_meta: bool,
+}
+
+impl EntryFormat {
+    /// The layout of files written in the current format version.
+    pub const CURRENT: Self = Self {
+        wide_len: true,
+        levels: true,
+        page_meta: true,
+    };
+
+    /// The layout of files written in format `version`; 0 for files without a file header.
+    pub fn of_version(version: u32) -> Self {
+        Self {
+            wide_len: version >= WIDE_LEN_VERSION,
+            levels: version >= LEVEL_VERSION,
+            page_meta: version >= PAGE_META_VERSION,
+        }
+    }
+}
+
+/// Writes an entry of `payload` to the start of `dest`, zero-padded to [`ENTRY_ALIGN`], and
+/// returns the bytes it takes. `level` is a [`Level`] as `u8`, or 0 for none.
+///
+/// Fails with [`PicoError::EmptyEntry`] if the payload is empty, since a zero length marks
+/// the end of a page's entries, and with [`PicoError::PageFull`] if the payload is over
+/// [`MAX_ENTRY_LEN`] or the entry doesn't fit in `dest`. The padding is cut short to fit at
+/// the end of `dest`.
+pub fn encode_entry(
+    dest: &mut [u8],
+    seq_id: u64,
+    ts_nanos: u64,
+    level: u8,
+    payload: &[u8],
+) -> Result<usize, PicoError> {
+    if payload.is_empty() {
+        return Err(PicoError::EmptyEntry);
+    }
+    let total = ENTRY_HEADER_LEN + payload.len();
+    if payload.len() > MAX_ENTRY_LEN || total > dest.len() {
+        return Err(PicoError::PageFull {});
+    }
+    let header = EntryHeader {
+        seq_id,
+        ts_nanos,
+        len: payload.len() as u32,
+        level,
+        _pad: [0; 3],
+    };
+    header.write_to(dest);
+    dest[ENTRY_HEADER_LEN..total].copy_from_slice(payload);
+    let aligned = total.next_multiple_of(ENTRY_ALIGN);
+    let padded = aligned.min(dest.len());
+    dest[total..padded].fill(0);
+    Ok(aligned)
+}
+
+/// Checks the parts of a non-empty entry header that every entry the writer produces has:
+/// a non-zero seq_id, zeroed padding, a known level if the format records one, and a
+/// length within [`MAX_ENTRY_LEN`]. `raw` holds the header as stored.
+pub(crate) fn is_plausible(header: &EntryHeader, raw: &[u8], format: EntryFormat) -> bool {
+    let padding = match (format.wide_len, format.levels) {
+        (_, true) => &raw[ENTRY_LEVEL_OFFSET + 1..ENTRY_HEADER_LEN],
+        (true, false) => &raw[ENTRY_LEVEL_OFFSET..ENTRY_HEADER_LEN],
+        (false, false) => &raw[ENTRY_LEN_OFFSET + 2..ENTRY_HEADER_LEN],
+    };
+    header.seq_id != 0
+        && padding.iter().all(|&b| b == 0)
+        && (!format.levels || Level::from_byte(header.level).is_some())
+        && header.len as usize <= MAX_ENTRY_LEN
+}
+
+/// What [`parse_entry`] found at the start of a page's remaining entries.
+pub(crate) enum Parsed {
+    /// An entry, with the range of its payload.
+    Entry(EntryHeader, Range<usize>),
+    /// The page holds no further entries.
+    End,
+    /// The entry here is damaged.
+    Corrupt,
+}
+
+/// Parses the entry at the start of `entries` in `page`.
+pub(crate) fn parse_entry(page: &[u8], entries: Range<usize>, format: EntryFormat) -> Parsed {
+    let cursor = entries.start;
+    if cursor + ENTRY_HEADER_LEN > entries.end {
+        return Parsed::End;
+    }
+
+    let raw = &page[cursor..cursor + ENTRY_HEADER_LEN];
+    let header = EntryHeader::read(raw, format.wide_len);
+    if header.len == 0 {
+        return Parsed::End;
+    }
+    if !is_plausible(&header, raw, format) {
+        return Parsed::Corrupt;
+    }
+
+    let payload = cursor + ENTRY_HEADER_LEN..cursor + ENTRY_HEADER_LEN + header.len as usize;
+    if payload.end > entries.end {
+        return Parsed::Corrupt;
+    }
+    Parsed::Entry(header, payload)
+}
+
+/// An entry borrowed from a page by [`PageEntries`].
+pub struct PageEntry<'a> {
+    pub header: EntryHeader,
+    pub payload: &'a [u8],
+}
+
+/// Iterator over the entries of one page, returned by [`parse_page`].
+///
+/// Ends at the page's zero padding, or after yielding [`PicoError::CorruptEntry`] for an
+/// entry whose header can't be right.
+pub struct PageEntries<'a> {
+    page: &'a [u8],
+    entries: Range<usize>,
+    format: EntryFormat,
+}
+
+impl<'a> PageEntries<'a> {
+    /// Reads the entries of `page` as laid out in `format`, stepping over a leading page
+    /// meta if the format has one.
+    pub fn with_format(page: &'a [u8], format: EntryFormat) -> Self {
+        let start = match format.page_meta && PageMeta::read(page).is_some() {
+            true => PAGE_META_LEN,
+            false => 0,
+        };
+        Self {
+            page,
+            entries: start..page.len(),
+            format,
+        }
+    }
+
+    /// Byte offset in the page of the next entry to be read.
+    pub fn offset(&self) -> usize {
+        self.entries.start
+    }
+}
+
+impl<'a> Iterator for PageEntries<'a> {
+    type Item = Result<PageEntry<'a>, PicoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parsed = parse_entry(self.page, self.entries.clone(), self.format);
+        let offset = self.entries.start;
+        self.entries.start = self.entries.end;
+        match parsed {
+            Parsed::Entry(header, payload) => {
+                self.entries.start = payload
+                    .end
+                    .next_multiple_of(ENTRY_ALIGN)
+                    .min(self.page.len());
+                Some(Ok(PageEntry {
+                    header,
+                    payload: &self.page[payload],
+                }))
+            }
+            Parsed::End => None,
+            Parsed::Corrupt => Some(Err(PicoError::CorruptEntry { offset })),
+        }
+    }
+}
+
+/// Reads the entries of a page of a file in the current format: a page as it is written,
+/// or, for pages written with a page transform, the decoded bytes after the page header.
+pub fn parse_page(page: &[u8]) -> PageEntries<'_> {
+    PageEntries::with_format(page, EntryFormat::CURRENT)
+}
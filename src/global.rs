@@ -1,13 +1,29 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 
-static SEQUENCE_ID: AtomicU64 = AtomicU64::new(0);
+/// Sequence IDs start at 1, so an ack number of 0 means nothing has been flushed yet.
+static SEQUENCE_ID: AtomicU64 = AtomicU64::new(1);
 static ACK_NUMBER: AtomicU64 = AtomicU64::new(0);
-static PAGE_ID: AtomicU64 = AtomicU64::new(0);
+/// Like the ack number, but only advanced once entries are known to be on stable storage.
+static HARDENED_NUMBER: AtomicU64 = AtomicU64::new(0);
 
 pub fn next_seq_id() -> u64 {
     SEQUENCE_ID.fetch_add(1, Ordering::Relaxed)
 }
 
+/// The seq_id the next entry will get, a lower bound on any seq_id not handed out yet.
+pub fn current_seq_id() -> u64 {
+    SEQUENCE_ID.load(Ordering::Relaxed)
+}
+
+/// Reserves `count` consecutive seq_ids, returning the first.
+pub fn next_seq_ids(count: u64) -> u64 {
+    SEQUENCE_ID.fetch_add(count, Ordering::Relaxed)
+}
+
+/// Makes the next seq_id handed out at least `next`.
+pub fn advance_seq_id(next: u64) {
+    SEQUENCE_ID.fetch_max(next, Ordering::Relaxed);
+}
 
 pub fn get_ack_number() -> u64 {
     ACK_NUMBER.load(Ordering::Acquire)
@@ -17,6 +33,10 @@ pub fn set_ack_number(val: u64) {
     ACK_NUMBER.fetch_max(val, Ordering::Release);
 }
 
-pub fn next_page_id() -> u64 {
-    PAGE_ID.fetch_add(1, Ordering::Relaxed)
+pub fn get_hardened_number() -> u64 {
+    HARDENED_NUMBER.load(Ordering::Acquire)
+}
+
+pub fn set_hardened_number(val: u64) {
+    HARDENED_NUMBER.fetch_max(val, Ordering::Release);
 }
@@ -0,0 +1,304 @@
+//! The block at the start of every log file, describing the payload type it was written with.
+//!
+//! Files written before the header existed start directly with a page; readers tell the two
+//! apart by the header's magic and skip the schema check for legacy files.
+//!
+//! Every integer in the file's framing (this header, page headers, page metas and entry
+//! headers) is little-endian, whatever the writing machine's byte order. Payloads are stored
+//! as the raw bytes of `T`.
+
+use crate::Logger;
+use crate::errors::PicoError;
+use crate::format::EntryFormat;
+use std::fmt;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read};
+use std::mem::{align_of, size_of};
+use std::path::Path;
+
+/// Marks a file that starts with a [`FileHeader`]. Like [`PAGE_MAGIC`], a legacy file's
+/// leading seq_id never reaches this value in practice.
+///
+/// [`PAGE_MAGIC`]: crate::page::PAGE_MAGIC
+pub const FILE_MAGIC: u64 = u64::from_le_bytes(*b"PICOFILE");
+
+/// Version of the on-disk layout written by this build.
+///
+/// * 1 - Entry lengths are `u16`. Also assumed for files without a header.
+/// * 2 - Entry lengths are `u32`, allowing payloads larger than 64 KiB.
+/// * 3 - Every page's entries are preceded by a [`PageMeta`].
+/// * 4 - Entry headers record a [`Level`] in the byte after the length.
+///
+/// [`PageMeta`]: crate::page::PageMeta
+/// [`Level`]: crate::Level
+pub const FORMAT_VERSION: u32 = 4;
+
+/// First format version whose entry headers store the length as a `u32`. Also the oldest
+/// version this build can write.
+pub const WIDE_LEN_VERSION: u32 = 2;
+
+/// First format version whose pages start with a [`PageMeta`](crate::page::PageMeta).
+pub const PAGE_META_VERSION: u32 = 3;
+
+/// First format version whose entry headers record the entry's level.
+pub const LEVEL_VERSION: u32 = 4;
+
+/// The on-disk layout of a log file, as found by [`Logger::detect_format`].
+///
+/// Every reader handles all of them; only files newer than this build are refused. The
+/// default is [`FormatVersion::CURRENT`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum FormatVersion {
+    /// Written before the file header existed: pages start at offset 0, entry lengths are
+    /// `u16`, and nothing records the payload type or checksums a page.
+    Legacy,
+    /// A file header, with `u16` entry lengths.
+    V1,
+    /// Entry lengths are `u32`.
+    V2,
+    /// Every page's entries are preceded by a checksummed page meta.
+    V3,
+    /// Entry headers record a level.
+    #[default]
+    V4,
+}
+
+impl FormatVersion {
+    /// The version this build writes by default.
+    pub const CURRENT: Self = Self::V4;
+
+    /// The version number recorded in the file header, or `None` for a legacy file.
+    pub fn number(self) -> Option<u32> {
+        match self {
+            Self::Legacy => None,
+            Self::V1 => Some(1),
+            Self::V2 => Some(2),
+            Self::V3 => Some(3),
+            Self::V4 => Some(4),
+        }
+    }
+
+    /// Whether the file starts with a file header.
+    pub fn has_header(self) -> bool {
+        self != Self::Legacy
+    }
+
+    /// How entries are laid out in files of this version.
+    pub fn entry_format(self) -> EntryFormat {
+        EntryFormat::of_version(self.number().unwrap_or(0))
+    }
+
+    /// The version of a file starting with `header`, or with none. Fails for a version or a
+    /// byte order this build can't read.
+    pub(crate) fn of_header(header: Option<&FileHeader>) -> Result<Self, PicoError> {
+        let Some(header) = header else {
+            return Ok(Self::Legacy);
+        };
+        let version = match header.version {
+            0 | 1 => Self::V1,
+            2 => Self::V2,
+            3 => Self::V3,
+            4 => Self::V4,
+            found => {
+                return Err(PicoError::FormatMismatch {
+                    found,
+                    supported: FORMAT_VERSION,
+                });
+            }
+        };
+        if !header.byte_order_supported() {
+            return Err(PicoError::ByteOrderMismatch {
+                found: header.byte_order,
+            });
+        }
+        Ok(version)
+    }
+}
+
+impl fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.number() {
+            Some(number) => write!(f, "format version {}", number),
+            None => f.write_str("legacy format"),
+        }
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Tells whether the log file at `path` starts with a file header, and which format
+    /// version it records, without reading any entries.
+    ///
+    /// A file too short to hold a header, including an empty one, is legacy.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FormatVersion, Error>` - The file's format, or an error if it cannot be
+    ///   opened or was written in a version or byte order this build cannot read.
+    pub fn detect_format(path: impl AsRef<Path>) -> Result<FormatVersion, Error> {
+        let mut file = File::open(path.as_ref())?;
+        let mut block = vec![0u8; size_of::<FileHeader>()];
+        let header = match file.read_exact(&mut block) {
+            Ok(()) => FileHeader::read_from(&block),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
+            Err(e) => return Err(e),
+        };
+        Ok(FormatVersion::of_header(header.as_ref())?)
+    }
+}
+
+/// Byte order recorded in the headers of files written by this build: the framing is
+/// little-endian.
+pub const BYTE_ORDER_LITTLE: u8 = 1;
+
+/// Longest tag accepted by [`Logger::with_schema_tag`](crate::Logger::with_schema_tag).
+pub const MAX_SCHEMA_TAG_LEN: usize = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FileHeader {
+    pub magic: u64,
+    pub version: u32,
+    /// Offset of the first page, which is the writer's block size.
+    pub data_offset: u32,
+    pub payload_size: u32,
+    pub payload_align: u32,
+    pub tag_len: u16,
+    /// Byte order of the framing integers; [`BYTE_ORDER_LITTLE`], or 0 in files written
+    /// before it was recorded, which are read as little-endian too.
+    pub byte_order: u8,
+    pub _pad: [u8; 5],
+    pub tag: [u8; MAX_SCHEMA_TAG_LEN],
+    /// Size of every page the writer filled, before any compression; 0 in files written
+    /// before it was recorded.
+    pub page_size: u32,
+    /// Length of the [`FileMetadata`](crate::FileMetadata) block right after the header; 0
+    /// in files written without one.
+    pub metadata_len: u32,
+}
+
+impl FileHeader {
+    pub fn new(schema: &Schema, data_offset: usize, page_size: usize, version: u32) -> Self {
+        let tag = schema.tag.as_deref().unwrap_or("").as_bytes();
+        let mut header = Self {
+            magic: FILE_MAGIC,
+            version,
+            data_offset: data_offset as u32,
+            payload_size: schema.size as u32,
+            payload_align: schema.align as u32,
+            tag_len: tag.len() as u16,
+            byte_order: BYTE_ORDER_LITTLE,
+            _pad: [0; 5],
+            tag: [0; MAX_SCHEMA_TAG_LEN],
+            page_size: page_size as u32,
+            metadata_len: 0,
+        };
+        header.tag[..tag.len()].copy_from_slice(tag);
+        header
+    }
+
+    /// Writes the header to the start of `block`, which must hold at least
+    /// `size_of::<FileHeader>()` bytes.
+    pub fn write_to(&self, block: &mut [u8]) {
+        let bytes = &mut block[..size_of::<Self>()];
+        bytes[..8].copy_from_slice(&self.magic.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.version.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.data_offset.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.payload_size.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self.payload_align.to_le_bytes());
+        bytes[24..26].copy_from_slice(&self.tag_len.to_le_bytes());
+        bytes[26] = self.byte_order;
+        bytes[27..32].copy_from_slice(&self._pad);
+        bytes[32..96].copy_from_slice(&self.tag);
+        bytes[96..100].copy_from_slice(&self.page_size.to_le_bytes());
+        bytes[100..104].copy_from_slice(&self.metadata_len.to_le_bytes());
+    }
+
+    /// Parses the header at the start of `block`, or returns `None` for a legacy file.
+    ///
+    /// The byte order is returned as recorded; callers reject ones they cannot read.
+    pub fn read_from(block: &[u8]) -> Option<Self> {
+        if block.len() < size_of::<Self>() || le_u64(block, 0) != FILE_MAGIC {
+            return None;
+        }
+        Some(Self {
+            magic: FILE_MAGIC,
+            version: le_u32(block, 8),
+            data_offset: le_u32(block, 12),
+            payload_size: le_u32(block, 16),
+            payload_align: le_u32(block, 20),
+            tag_len: u16::from_le_bytes([block[24], block[25]]),
+            byte_order: block[26],
+            _pad: block[27..32].try_into().unwrap(),
+            tag: block[32..96].try_into().unwrap(),
+            page_size: le_u32(block, 96),
+            metadata_len: le_u32(block, 100),
+        })
+    }
+
+    /// Returns `true` if the framing is in a byte order this build reads.
+    pub fn byte_order_supported(&self) -> bool {
+        matches!(self.byte_order, 0 | BYTE_ORDER_LITTLE)
+    }
+
+    pub fn schema(&self) -> Schema {
+        let tag_len = (self.tag_len as usize).min(MAX_SCHEMA_TAG_LEN);
+        let tag = (tag_len > 0).then(|| String::from_utf8_lossy(&self.tag[..tag_len]).into_owned());
+        Schema {
+            size: self.payload_size as usize,
+            align: self.payload_align as usize,
+            tag,
+        }
+    }
+}
+
+/// Reads the little-endian `u32` at `at` in `bytes`.
+pub(crate) fn le_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// Reads the little-endian `u64` at `at` in `bytes`.
+pub(crate) fn le_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+/// The payload type a file was written with, as recorded in its header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// `size_of` the payload type.
+    pub size: usize,
+    /// `align_of` the payload type.
+    pub align: usize,
+    /// The tag set with [`Logger::with_schema_tag`](crate::Logger::with_schema_tag), if any.
+    pub tag: Option<String>,
+}
+
+impl Schema {
+    /// Describes `T`, optionally tagged with a user-chosen schema name.
+    pub fn of<T>(tag: Option<&str>) -> Self {
+        Self {
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            tag: tag.map(str::to_owned),
+        }
+    }
+
+    /// Returns `true` if a reader expecting `self` can read a file written as `found`.
+    ///
+    /// The tag is only compared when the reader asks for one.
+    pub fn accepts(&self, found: &Schema) -> bool {
+        self.size == found.size
+            && self.align == found.align
+            && (self.tag.is_none() || self.tag == found.tag)
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes aligned to {}", self.size, self.align)?;
+        if let Some(tag) = &self.tag {
+            write!(f, ", tagged {:?}", tag)?;
+        }
+        Ok(())
+    }
+}
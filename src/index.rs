@@ -0,0 +1,78 @@
+//! Sidecar sparse index (`<logpath>.idx`) mapping each page's first seq_id to its file offset.
+//!
+//! Records are appended with plain buffered IO once a page write completes, so the index may
+//! lag the data file but never points at a page that was not written. Readers validate every
+//! record they use and fall back to a linear scan otherwise.
+
+use crate::util::sync_parent_dir;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Error, Read, Write};
+use std::path::{Path, PathBuf};
+
+const RECORD_LEN: usize = 16;
+
+pub(crate) struct IndexWriter {
+    out: BufWriter<File>,
+    dirty: bool,
+}
+
+impl IndexWriter {
+    /// Creates (or truncates) the index file belonging to `log_path`.
+    pub fn create(log_path: &Path) -> Result<Self, Error> {
+        let path = index_path(log_path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        sync_parent_dir(&path)?;
+        Ok(Self {
+            out: BufWriter::new(file),
+            dirty: false,
+        })
+    }
+
+    pub fn append(&mut self, first_seq_id: u64, offset: u64) -> Result<(), Error> {
+        self.out.write_all(&first_seq_id.to_le_bytes())?;
+        self.out.write_all(&offset.to_le_bytes())?;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.dirty {
+            self.out.flush()?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn index_path(log_path: &Path) -> PathBuf {
+    let mut name = OsString::from(log_path.as_os_str());
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+/// Loads `(first_seq_id, offset)` records sorted by seq_id, or `None` if there is no index.
+pub(crate) fn load_index(log_path: &Path) -> Option<Vec<(u64, u64)>> {
+    let mut bytes = Vec::new();
+    File::open(index_path(log_path))
+        .ok()?
+        .read_to_end(&mut bytes)
+        .ok()?;
+
+    let mut records: Vec<(u64, u64)> = bytes
+        .chunks_exact(RECORD_LEN)
+        .map(|r| {
+            (
+                u64::from_le_bytes(r[..8].try_into().unwrap()),
+                u64::from_le_bytes(r[8..].try_into().unwrap()),
+            )
+        })
+        .collect();
+    // Completions can arrive out of order, so records are not necessarily sorted on disk.
+    records.sort_unstable();
+    Some(records)
+}
@@ -0,0 +1,434 @@
+//! Inline mode, started with [`Logger::start_inline`]: `log()` appends straight into a page
+//! of the worker's pool on the caller's thread, skipping the ring buffer and the copy out of
+//! it, and the worker only seals, submits and acknowledges page writes.
+//!
+//! The worker lends the caller up to [`LEND_AHEAD`] free pages, in pool order, over one
+//! small bounded channel, and the caller hands each back over another once it is full,
+//! flushed or due for the flush interval. A page is only ever written by one side at a
+//! time: the caller until it hands the page back, the worker and the kernel after.
+
+use crate::errors::PicoError;
+use crate::overflow::{BufferFullPolicy, OverflowPolicy};
+use crate::page::Page;
+use crate::stats::{FlushReason, SharedStats};
+use crate::wakeup::Wakeup;
+use crate::worker::LogWorker;
+use crate::{Clock, Logger, LoggerState, PageManager, SLOT_WAIT_SLEEP, global};
+use crossbeam::utils::Backoff;
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+/// Most pages lent to the caller at once, which covers a burst for as long as it takes the
+/// worker to take a full page back and lend a free one.
+pub(crate) const LEND_AHEAD: usize = 8;
+
+/// Page `idx` of the worker's pool, as lent to the caller and handed back.
+pub(crate) struct LentPage<T> {
+    idx: usize,
+    /// A view of the pool page that does not own its memory.
+    page: Page<T>,
+    /// Why the caller handed the page back.
+    reason: FlushReason,
+}
+
+/// The worker's side of inline mode.
+pub(crate) struct InlinePool<T> {
+    lend: Sender<LentPage<T>>,
+    /// The other end of `lend`, for taking back pages the caller never picked up.
+    unclaimed: Receiver<LentPage<T>>,
+    returned: Receiver<LentPage<T>>,
+    /// Index of the next page to lend.
+    next: usize,
+    /// Pages lent and not taken back yet.
+    lent: usize,
+}
+
+/// The caller's side of inline mode.
+pub(crate) struct InlineWriter<T> {
+    /// The page being filled, once one has been taken.
+    current: Option<LentPage<T>>,
+    free: Receiver<LentPage<T>>,
+    full: Sender<LentPage<T>>,
+    wakeup: Arc<Wakeup>,
+    clock: Arc<dyn Clock>,
+    /// Nanoseconds a page may hold entries before it is handed back.
+    flush_interval: u64,
+    flush_watermark: Option<usize>,
+    /// [`Clock::monotonic_nanos`] reading when the first entry went into the current page.
+    opened_at: u64,
+}
+
+/// Builds both sides of inline mode for `pages`, lending the first pages right away.
+pub(crate) fn pair<T>(
+    pages: &PageManager<T>,
+    wakeup: Arc<Wakeup>,
+    clock: Arc<dyn Clock>,
+    flush_interval: u64,
+    flush_watermark: Option<usize>,
+) -> (InlinePool<T>, InlineWriter<T>) {
+    let (lend, free) = crossbeam_channel::bounded(LEND_AHEAD);
+    let (full, returned) = crossbeam_channel::bounded(LEND_AHEAD);
+    let mut pool = InlinePool {
+        lend,
+        unclaimed: free.clone(),
+        returned,
+        next: 0,
+        lent: 0,
+    };
+    pool.lend(pages);
+    let writer = InlineWriter {
+        current: None,
+        free,
+        full,
+        wakeup,
+        clock,
+        flush_interval,
+        flush_watermark,
+        opened_at: 0,
+    };
+    (pool, writer)
+}
+
+impl<T> InlinePool<T> {
+    /// Lends the next pages in pool order, as long as their previous writes have completed.
+    fn lend(&mut self, pages: &PageManager<T>) {
+        let ahead = LEND_AHEAD.min(pages.pages.len());
+        while self.lent < ahead && !pages.pending_status[self.next] {
+            // SAFETY: the worker doesn't free the pool while a page is lent; see
+            // `LogWorker::reclaim_inline_pages`.
+            let page = unsafe { pages.pages[self.next].view() };
+            let lent = LentPage {
+                idx: self.next,
+                page,
+                reason: FlushReason::PageFull,
+            };
+            if self.lend.try_send(lent).is_err() {
+                break;
+            }
+            self.lent += 1;
+            self.next = (self.next + 1) % pages.pages.len();
+        }
+    }
+}
+
+impl<T> InlineWriter<T> {
+    /// The page to append to, taking the next one lent if there is none. Without one, waits
+    /// for it under [`BufferFullPolicy::Block`] and fails with [`PicoError::BufferFull`]
+    /// otherwise.
+    fn page(
+        &mut self,
+        policy: BufferFullPolicy,
+        healthy: &AtomicBool,
+    ) -> Result<&mut Page<T>, PicoError> {
+        let lent = match self.current.take() {
+            Some(lent) => lent,
+            None => self.take_free(policy, healthy)?,
+        };
+        Ok(&mut self.current.insert(lent).page)
+    }
+
+    fn take_free(
+        &self,
+        policy: BufferFullPolicy,
+        healthy: &AtomicBool,
+    ) -> Result<LentPage<T>, PicoError> {
+        let backoff = Backoff::new();
+        loop {
+            match self.free.try_recv() {
+                Ok(lent) => return Ok(lent),
+                Err(TryRecvError::Disconnected) => return Err(PicoError::WorkerStopped),
+                Err(TryRecvError::Empty) if policy != BufferFullPolicy::Block => {
+                    return Err(PicoError::BufferFull);
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+            if !healthy.load(Ordering::Relaxed) {
+                return Err(PicoError::WorkerStopped);
+            }
+            if backoff.is_completed() {
+                thread::sleep(SLOT_WAIT_SLEEP);
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Runs `append` against the page being filled, handing it back and retrying on the
+    /// next one if it is full, then hands the page back if it reached the flush watermark
+    /// or has held entries for the flush interval.
+    fn append_with(
+        &mut self,
+        policy: BufferFullPolicy,
+        healthy: &AtomicBool,
+        mut append: impl FnMut(&mut Page<T>, u64) -> Result<(), PicoError>,
+    ) -> Result<(), PicoError> {
+        let now = self.clock.now_nanos();
+        let page = self.page(policy, healthy)?;
+        let mut appended = append(page, now);
+        if let Err(PicoError::PageFull {}) = appended
+            && !page.is_empty()
+        {
+            self.hand_over(FlushReason::PageFull)?;
+            appended = append(self.page(policy, healthy)?, now);
+        }
+        appended?;
+
+        let Some(lent) = &self.current else {
+            return Ok(());
+        };
+        let now = self.clock.monotonic_nanos();
+        if lent.page.entry_count() == 1 {
+            self.opened_at = now;
+        }
+        if self
+            .flush_watermark
+            .is_some_and(|watermark| lent.page.used() >= watermark)
+        {
+            self.hand_over(FlushReason::Watermark)?;
+        } else if now.saturating_sub(self.opened_at) >= self.flush_interval {
+            self.hand_over(FlushReason::Timer)?;
+        }
+        Ok(())
+    }
+
+    /// Hands the page being filled back to the worker to be written, unless it is empty.
+    fn hand_over(&mut self, reason: FlushReason) -> Result<(), PicoError> {
+        let Some(mut lent) = self.current.take_if(|lent| !lent.page.is_empty()) else {
+            return Ok(());
+        };
+        lent.reason = reason;
+        // Never blocks: no more pages are handed back than were lent.
+        self.full.send(lent).map_err(|_| PicoError::WorkerStopped)?;
+        self.wakeup.notify();
+        Ok(())
+    }
+
+    /// Entries in the page being filled, which the worker has not seen yet.
+    fn filling(&self) -> usize {
+        self.current
+            .as_ref()
+            .map_or(0, |lent| lent.page.entry_count())
+    }
+
+    /// Hands every page back before the worker is asked to stop: the one being filled to be
+    /// written out if `drain` is set, or emptied with its entries counted as dropped
+    /// otherwise, and the ones never used as they are.
+    fn release(mut self, drain: bool, stats: &SharedStats) {
+        if let Some(lent) = &mut self.current
+            && !drain
+        {
+            stats.record_dropped_n(lent.page.entry_count());
+            lent.page.reset();
+        }
+        let current = self.current.take();
+        for mut lent in current.into_iter().chain(self.free.try_iter()) {
+            lent.reason = FlushReason::Shutdown;
+            let _ = self.full.send(lent);
+        }
+        self.wakeup.notify();
+    }
+}
+
+impl<T> LogWorker<T> {
+    /// Writes out the pages handed back by the caller in inline mode, in the order they
+    /// were lent, then lends the pages that are free again. Returns whether any came back.
+    pub(crate) fn take_inline_pages(&mut self) -> Result<bool, PicoError> {
+        let mut taken = false;
+        loop {
+            let Some(pool) = &mut self.inline else {
+                return Ok(false);
+            };
+            let Ok(lent) = pool.returned.try_recv() else {
+                break;
+            };
+            pool.lent -= 1;
+            taken = true;
+            // Pages handed back unused at shutdown are just taken back.
+            if lent.page.is_empty() {
+                continue;
+            }
+            if lent.idx != self.pages.active_idx {
+                return Err(PicoError::InlinePageOutOfOrder {
+                    expected: self.pages.active_idx,
+                    returned: lent.idx,
+                });
+            }
+            self.claim_active_page()?;
+            self.pages.get_active_page().adopt_entries(&lent.page);
+            self.stats.record_appended_n(lent.page.entry_count());
+            self.newest_seq = self.newest_seq.max(lent.page.get_last_entry());
+            self.flush_current_page(lent.reason)?;
+        }
+        self.lend_inline_pages()?;
+        Ok(taken)
+    }
+
+    /// Lends the pages that are free. If none is, the caller has nothing to append to, so
+    /// this waits for a write to complete instead of leaving it to the flush timer.
+    fn lend_inline_pages(&mut self) -> Result<(), PicoError> {
+        let Some(pool) = &mut self.inline else {
+            return Ok(());
+        };
+        pool.lend(&self.pages);
+        while self.inline.as_ref().is_some_and(|pool| pool.lent == 0) && self.pending_writes > 0 {
+            self.submit_and_wait()?;
+            if let Some(pool) = &mut self.inline {
+                pool.lend(&self.pages);
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes back the pages lent and never used as the worker exits, and leaks the pool if
+    /// the caller may still hold one, since it could go on writing to it.
+    pub(crate) fn reclaim_inline_pages(&mut self) {
+        let Some(pool) = &mut self.inline else {
+            return;
+        };
+        while pool.unclaimed.try_recv().is_ok() || pool.returned.try_recv().is_ok() {
+            pool.lent -= 1;
+        }
+        if pool.lent > 0 {
+            self.pages.leak();
+        }
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Configures and starts the logger in inline mode, the single-producer alternative to
+    /// [`Logger::start`].
+    ///
+    /// `log()` appends each entry straight into a page of the worker's pool on the calling
+    /// thread, skipping the ring buffer and the copy the worker makes out of it, and the
+    /// worker is left with sealing, submitting and acknowledging page writes. It suits a
+    /// single thread logging on a latency-sensitive path; a logger is `&mut` to log with
+    /// either way, so nothing is lost to other threads.
+    ///
+    /// A page is handed to the worker once it is full, on [`Logger::flush`], or when an
+    /// entry is logged into a page that has held entries for the flush interval. Nothing
+    /// looks at a page between calls, so a producer that may go quiet should call
+    /// [`Logger::flush`]; [`Logger::pending_snapshot`] and a
+    /// [panic flush](Logger::install_panic_flush) don't see the page being filled either.
+    /// When no page is free, `log()` waits for one under [`BufferFullPolicy::Block`] and
+    /// fails with [`PicoError::BufferFull`] otherwise, since there is no older entry to
+    /// overwrite.
+    ///
+    /// Fails like [`Logger::start`], and with [`PicoError::InlineConflict`] combined with
+    /// [`Logger::with_partial_rewrites`] or an [`OverflowPolicy`] other than
+    /// [`OverflowPolicy::Block`]. Entries can't be reserved in inline mode, so
+    /// [`Logger::reserve`] returns `None` and [`Logger::log_with_seq`] fails with
+    /// [`PicoError::InlineConflict`].
+    pub fn start_inline(&mut self) -> Result<(), PicoError> {
+        if self.state != LoggerState::Configured {
+            return Err(PicoError::AlreadyStarted);
+        }
+        if self.partial_rewrites.is_some() {
+            return Err(PicoError::InlineConflict {
+                setting: "with_partial_rewrites",
+            });
+        }
+        match self.overflow_policy {
+            OverflowPolicy::Block => {}
+            OverflowPolicy::DropNewest => {
+                return Err(PicoError::InlineConflict {
+                    setting: "OverflowPolicy::DropNewest",
+                });
+            }
+            OverflowPolicy::Grow { .. } => {
+                return Err(PicoError::InlineConflict {
+                    setting: "OverflowPolicy::Grow",
+                });
+            }
+        }
+        self.launch(true)
+    }
+
+    /// Whether the logger was started with [`Logger::start_inline`].
+    pub fn is_inline(&self) -> bool {
+        self.inline.is_some()
+    }
+
+    /// Logs `count` entries with consecutive seq_ids straight into the page being filled,
+    /// the `i`th appended by `append(page, i, seq_id, now)`, on behalf of a call that
+    /// started at `started` when tracking latency.
+    ///
+    /// The seq_ids are taken up front, so if an append fails after some entries were
+    /// logged the batch is cut short: the range of those entries is returned and the
+    /// seq_ids taken for the rest are never used. Fails only if no entry was logged.
+    pub(crate) fn log_inline(
+        &mut self,
+        count: usize,
+        started: Option<u64>,
+        mut append: impl FnMut(&mut Page<T>, usize, u64, u64) -> Result<(), PicoError>,
+    ) -> Result<Range<u64>, PicoError> {
+        let policy = self.buffer_full_policy;
+        let Some(writer) = &mut self.inline else {
+            return Err(PicoError::NotStarted);
+        };
+        // Waiting for a page before taking seq_ids leaves no gap when none is free.
+        if let Err(e) = writer.page(policy, &self.healthy) {
+            if let PicoError::WorkerStopped = e {
+                self.state = LoggerState::Stopped;
+            }
+            return Err(e);
+        }
+        let first_seq_id = global::next_seq_ids(count as u64);
+        let mut logged = 0;
+        let mut result = Ok(());
+        while result.is_ok() && logged < count {
+            let seq_id = first_seq_id + logged as u64;
+            result = writer.append_with(policy, &self.healthy, |page, now| {
+                append(page, logged, seq_id, now)
+            });
+            if result.is_ok() {
+                logged += 1;
+            }
+        }
+        self.stats.record_logged_n(logged);
+        if let Err(e) = result {
+            self.stats.record_dropped_n(count - logged);
+            if let PicoError::WorkerStopped = e {
+                self.state = LoggerState::Stopped;
+            }
+            if logged == 0 {
+                return Err(e);
+            }
+        }
+        let seq_ids = first_seq_id..first_seq_id + logged as u64;
+        self.record_latency(started, seq_ids.end - 1, logged as u64);
+        #[cfg(feature = "async")]
+        {
+            self.last_seq_id = self.last_seq_id.max(Some(seq_ids.end - 1));
+        }
+        Ok(seq_ids)
+    }
+
+    /// Hands the page being filled in inline mode to the worker, for [`Logger::flush`].
+    pub(crate) fn flush_inline(&mut self) -> Result<(), PicoError> {
+        let Some(writer) = &mut self.inline else {
+            return Err(PicoError::NotStarted);
+        };
+        let handed = writer.hand_over(FlushReason::Explicit);
+        if let Err(PicoError::WorkerStopped) = handed {
+            self.state = LoggerState::Stopped;
+        }
+        handed
+    }
+}
+
+impl<T> Logger<T> {
+    /// Entries in the page being filled in inline mode, which the worker has not seen yet.
+    pub(crate) fn inline_filling(&self) -> usize {
+        self.inline.as_ref().map_or(0, InlineWriter::filling)
+    }
+
+    /// Hands every page lent in inline mode back to the worker ahead of stopping it.
+    pub(crate) fn release_inline_pages(&mut self, drain: bool) {
+        if let Some(writer) = self.inline.take() {
+            writer.release(drain, &self.stats);
+        }
+    }
+}
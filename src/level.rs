@@ -0,0 +1,47 @@
+//! Severity recorded alongside each entry, in a byte of the entry header that earlier format
+//! versions left as padding.
+
+use std::fmt;
+
+/// Severity of an entry, set with [`Logger::log_with_level`](crate::Logger::log_with_level).
+///
+/// Entries logged without one are [`Level::Info`], as are all entries of files written in a
+/// format version before 4.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace = 1,
+    Debug = 2,
+    #[default]
+    Info = 3,
+    Warn = 4,
+    Error = 5,
+}
+
+impl Level {
+    /// Decodes a level byte as stored in an entry header. 0, which every entry of an older
+    /// format has, reads as [`Level::Info`].
+    pub(crate) fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Level::Info),
+            1 => Some(Level::Trace),
+            2 => Some(Level::Debug),
+            3 => Some(Level::Info),
+            4 => Some(Level::Warn),
+            5 => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        })
+    }
+}
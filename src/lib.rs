@@ -1,26 +1,152 @@
+mod uring;
 mod util;
+#[cfg(feature = "async")]
+use crate::async_support::AckWaiters;
+#[cfg(debug_assertions)]
+use crate::seq_block::SeqRanges;
 use crate::{
+    append::{AppendLayout, find_append_point},
+    codec::{MAX_STORED_PAGE_LEN, PageCodec},
+    durability::BackgroundSync,
+    events::FlushCallback,
+    format::MAX_ENTRY_LEN,
     global::next_seq_id,
-    page::{EntryHeader, Page},
-    util::{get_blksize, get_file_handler},
-    worker::LogWorker,
+    header::{FileHeader, LEVEL_VERSION, MAX_SCHEMA_TAG_LEN, WIDE_LEN_VERSION},
+    index::IndexWriter,
+    inline::InlineWriter,
+    latency::{AckLatency, LatencyTracker},
+    page::{PAGE_META_LEN, Page, PageHeader, PayloadCodec, decode_raw},
+    panic_flush::PanicFlush,
+    pool::PageRegion,
+    raw::PageLayout,
+    reader::RawReader,
+    seq_block::SeqBlocks,
+    stats::SharedStats,
+    stripe::Stripe,
+    testing::FaultInjector,
+    util::{MIN_BLOCK_SIZE, create_parent_dirs, get_blksize, get_file_handler, pin_current_thread},
+    wakeup::Wakeup,
+    worker::{AckOrder, LogWorker, Mailbox, Message, PartialRewrites},
 };
-use crossbeam_channel::Sender;
+use crossbeam::utils::{Backoff, CachePadded};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use slab::Slab;
 use std::cell::UnsafeCell;
-use std::io::Read;
-use std::ptr;
+use std::io::Write;
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::{io::Error, path::PathBuf};
 
+mod append;
+mod archive;
+#[cfg(feature = "async")]
+mod async_support;
+mod backend;
+mod checksum;
+mod clock;
+mod codec;
+mod compression;
+mod config;
+mod cursor;
+mod durability;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod errors;
+mod events;
+mod export;
+pub mod format;
 mod global;
+mod header;
+mod index;
+mod inline;
+pub mod inspect;
+mod latency;
+mod level;
+#[cfg(feature = "log-facade")]
+mod log_facade;
+mod metadata;
+#[cfg(feature = "metrics")]
+mod metrics_export;
+#[cfg(unix)]
+mod mmap;
+mod overflow;
 mod page;
+mod panic_flush;
+#[cfg(feature = "pod")]
+mod pod;
+mod pool;
+mod probe;
+pub mod raw;
+mod reader;
+mod replay;
+mod reserve;
+mod safe_payload;
+mod seq_block;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod shard;
+#[cfg(feature = "sim")]
+pub mod sim;
+mod stats;
+mod stripe;
+mod tail;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+#[doc(hidden)]
+pub mod testing;
+#[cfg(any(feature = "log-facade", feature = "tracing"))]
+mod text;
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+mod wakeup;
 mod worker;
 
+pub use archive::{ARCHIVE_MAGIC, ARCHIVE_VERSION, ArchiveCompression, ArchiveStats};
+pub use backend::Backend;
+pub use clock::{Clock, MonotonicClock};
+pub use compression::Compression;
+pub use config::LoggerConfig;
+pub use cursor::Cursor;
+pub use durability::{Durability, SyncPolicy};
+pub use errors::PicoError;
+pub use events::FlushEvent;
+#[cfg(feature = "serde")]
+pub use export::JsonLines;
+pub use export::{Csv, ExportFormat, RowWriter, ToRow};
+pub use header::{FORMAT_VERSION, FormatVersion, Schema};
+pub use latency::{LatencyPercentiles, LatencyReport};
+pub use level::Level;
+#[cfg(feature = "log-facade")]
+pub use log_facade::{LogRecord, PicoLogAdapter};
+pub use metadata::{FileMetadata, MAX_METADATA_LEN};
+#[cfg(unix)]
+pub use mmap::{MmapReader, RawEntry};
+pub use overflow::{BufferFullPolicy, OverflowPolicy};
+pub use probe::Capabilities;
+pub use reader::{DEFAULT_READ_CHUNK, LogEntry, LogReader, ReadOptions};
+pub use replay::ReplaySummary;
+pub use reserve::Reservation;
+pub use safe_payload::SafePayload;
+// For the impls `safe_payload!` expands to.
+#[cfg(feature = "pod")]
+#[doc(hidden)]
+pub use bytemuck as __bytemuck;
+pub use shard::{MAX_SHARDS, MergeSummary, MergedReader, ShardSelection, ShardedLogger};
+pub use stats::{FlushCounts, LoggerStats};
+pub use stripe::MAX_STRIPES;
+pub use tail::ReverseLogReader;
+#[cfg(feature = "tracing")]
+pub use tracing_layer::{PicoLayer, TraceRecord};
+pub use uring::{FailurePolicy, UringOptions};
+pub use util::IoMode;
+
 #[repr(C, align(64))]
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default)]
 /// A wrapper struct for log data that includes a sequence ID.
 pub struct LogMessage<T> {
     /// Unique sequence identifier for the log message.
@@ -29,31 +155,131 @@ pub struct LogMessage<T> {
     pub data: T,
 }
 
+const DEFAULT_PAGE_COUNT: usize = 256;
+/// Errors the worker can queue before further ones are dropped.
+const ERROR_CHANNEL_CAPACITY: usize = 1024;
+const DEFAULT_WRITE_RETRIES: u32 = 3;
+const DEFAULT_DRAIN_BATCH: usize = 256;
+/// How long [`Logger::pending_snapshot`] waits for the worker to answer.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(1);
+/// How much longer than the shutdown timeout [`Logger::shutdown`] waits for the worker to
+/// acknowledge stopping before leaving it behind.
+const SHUTDOWN_ACK_GRACE: Duration = Duration::from_secs(1);
+/// How long a producer blocked under [`BufferFullPolicy::Block`] sleeps between checks once
+/// spinning hasn't freed a slot.
+const SLOT_WAIT_SLEEP: Duration = Duration::from_micros(50);
+const DEFAULT_GROWTH_CHUNK: u64 = 256 * 1024 * 1024;
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_PAGE_COUNT: usize = 2;
+const MAX_PAGE_COUNT: usize = 32_768;
+/// io_uring's maximum submission queue depth.
+const MAX_RING_DEPTH: u32 = 32_768;
+
 struct PageManager<T> {
     pages: Vec<Page<T>>,
     active_idx: usize,
     pending_status: Vec<bool>,
+    page_size: usize,
+    /// Bytes at the start of each page reserved for the page header.
+    reserved: usize,
+    /// Backing memory for the pages when they were carved out of one mapping. Declared last
+    /// so the pages are dropped before it is unmapped.
+    region: Option<PageRegion>,
 }
 
 impl<T> PageManager<T> {
-    pub fn new(page_size: usize, count: usize) -> Self {
-        let mut pages = Vec::with_capacity(count);
-        let mut pending_status = Vec::with_capacity(count);
-        for _ in 0..count {
-            pages.push(Page::init(page_size));
-            pending_status.push(false);
-        }
+    pub fn new(page_size: usize, count: usize, reserved: usize) -> Self {
+        let pages = (0..count)
+            .map(|_| Page::init(page_size, reserved))
+            .collect();
+        Self::from_pages(pages, page_size, reserved, None)
+    }
+
+    /// Builds the pool out of one huge-page-backed mapping, falling back to individually
+    /// allocated pages if the mapping cannot be created.
+    pub fn with_hugepages(page_size: usize, count: usize, reserved: usize) -> Self {
+        let Some(region) = PageRegion::map(page_size * count) else {
+            return Self::new(page_size, count, reserved);
+        };
+        let pages = (0..count)
+            .map(|i| unsafe { Page::from_region(region.at(i * page_size), page_size, reserved) })
+            .collect();
+        Self::from_pages(pages, page_size, reserved, Some(region))
+    }
+
+    fn from_pages(
+        pages: Vec<Page<T>>,
+        page_size: usize,
+        reserved: usize,
+        region: Option<PageRegion>,
+    ) -> Self {
+        let count = pages.len();
         Self {
             pages,
             active_idx: 0,
-            pending_status,
+            pending_status: vec![false; count],
+            page_size,
+            reserved,
+            region,
         }
     }
 
+    /// Copies the entries of page `from` into the active page.
+    pub fn continue_from(&mut self, from: usize) {
+        let to = self.active_idx;
+        let (src, dst) = if from < to {
+            let (head, tail) = self.pages.split_at_mut(to);
+            (&head[from], &mut tail[0])
+        } else {
+            let (head, tail) = self.pages.split_at_mut(from);
+            (&tail[0], &mut head[to])
+        };
+        dst.copy_entries_from(src);
+    }
+
+    /// Gives up the pool's memory without freeing it, for when the kernel may still be
+    /// reading from it.
+    pub fn leak(&mut self) {
+        std::mem::forget(std::mem::take(&mut self.pages));
+        #[cfg(target_os = "linux")]
+        std::mem::forget(self.region.take());
+    }
+
+    /// Adds a separately allocated page to the pool and returns its index.
+    pub fn grow(&mut self) -> usize {
+        self.pages.push(Page::init(self.page_size, self.reserved));
+        self.pending_status.push(false);
+        self.pages.len() - 1
+    }
+
+    /// Returns `true` if the pages live in a huge-page-backed region.
+    pub fn uses_hugepages(&self) -> bool {
+        self.region.is_some()
+    }
+
     pub fn get_active_page(&mut self) -> &mut Page<T> {
         &mut self.pages[self.active_idx]
     }
 
+    /// Describes every page buffer, in page order, for registering them with a ring as
+    /// fixed buffers whose index is the page index.
+    ///
+    /// The page allocations never move, so a registration stays valid for as long as the
+    /// pages live.
+    #[cfg(target_os = "linux")]
+    pub fn iovecs(&mut self) -> Vec<libc::iovec> {
+        self.pages
+            .iter_mut()
+            .map(|page| {
+                let content = page.get_page_content_mut();
+                libc::iovec {
+                    iov_base: content.as_mut_ptr() as *mut libc::c_void,
+                    iov_len: content.len(),
+                }
+            })
+            .collect()
+    }
+
     pub fn advance(&mut self) -> usize {
         let prev = self.active_idx;
         self.active_idx = (self.active_idx + 1) % self.pages.len();
@@ -61,59 +287,362 @@ impl<T> PageManager<T> {
     }
 }
 
+/// Slot states: never filled, holding an entry the worker has yet to consume, and done with.
+const SLOT_FREE: u8 = 0;
+const SLOT_WRITTEN: u8 = 1;
+const SLOT_CONSUMED: u8 = 2;
+
+struct Slot<T> {
+    entry: UnsafeCell<LogMessage<T>>,
+    /// `SLOT_WRITTEN` from when a producer publishes the entry until the worker has copied
+    /// it into a page.
+    state: AtomicU8,
+    /// Ring position of the entry last written into the slot, which tells the worker
+    /// whether a producer has reused the slot since the position it is reading.
+    position: AtomicU64,
+    /// Level byte of that entry, as stored in its header.
+    level: AtomicU8,
+}
+
+/// The ring buffer shared by a logger and its worker.
+///
+/// Entries are numbered by ring position: the entry at position `p` sits in slot
+/// `p % capacity`. The producer publishes positions by advancing `published` and the
+/// worker reads up to it, so entries never pass through the channel. The worker only
+/// needs waking when it is about to block, which it announces with `parked`.
 struct LogBuffer<T> {
-    inner: Vec<UnsafeCell<LogMessage<T>>>,
+    inner: Vec<Slot<T>>,
+    /// Every position below this one holds a published entry.
+    published: CachePadded<AtomicU64>,
+    /// Every position below this one has been read by the worker.
+    consumed: CachePadded<AtomicU64>,
+    /// Set while the worker is blocked, or about to block, waiting for work.
+    parked: CachePadded<AtomicBool>,
+}
+
+impl<T> LogBuffer<T> {
+    fn new(capacity: usize) -> Self
+    where
+        T: Default,
+    {
+        Self {
+            inner: (0..capacity)
+                .map(|_| Slot {
+                    entry: UnsafeCell::new(LogMessage::default()),
+                    state: AtomicU8::new(SLOT_FREE),
+                    position: AtomicU64::new(u64::MAX),
+                    level: AtomicU8::new(0),
+                })
+                .collect(),
+            published: CachePadded::new(AtomicU64::new(0)),
+            consumed: CachePadded::new(AtomicU64::new(0)),
+            parked: CachePadded::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn entry(&self, index: usize) -> *mut LogMessage<T> {
+        self.inner[index].entry.get()
+    }
+
+    /// Whether slot `index` can be filled without losing an entry.
+    fn is_free(&self, index: usize) -> bool {
+        self.inner[index].state.load(Ordering::Acquire) != SLOT_WRITTEN
+    }
+
+    /// Marks slot `index` as holding the entry at ring `position`, logged at `level`. The
+    /// worker sees it once [`LogBuffer::publish`] is called with a later position.
+    fn mark_written(&self, index: usize, position: u64, level: u8) {
+        let slot = &self.inner[index];
+        slot.level.store(level, Ordering::Relaxed);
+        slot.position.store(position, Ordering::Relaxed);
+        slot.state.store(SLOT_WRITTEN, Ordering::Release);
+    }
+
+    /// Hands the worker every entry below ring position `end`, returning whether it is
+    /// waiting for a message and has to be woken to see them.
+    fn publish(&self, end: u64) -> bool {
+        // Pairs with `park`: either the worker sees the new position before it blocks, or
+        // this sees that it is parked.
+        self.published.store(end, Ordering::SeqCst);
+        self.parked.load(Ordering::SeqCst) && self.parked.swap(false, Ordering::SeqCst)
+    }
+
+    /// Announces that the worker is about to block, returning `false` if
+    /// entries were published in the meantime and it should read them instead.
+    fn park(&self) -> bool {
+        self.parked.store(true, Ordering::SeqCst);
+        if self.published.load(Ordering::SeqCst) != self.consumed.load(Ordering::Relaxed) {
+            self.parked.store(false, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    fn unpark(&self) {
+        self.parked.store(false, Ordering::Relaxed);
+    }
+
+    /// Entries published but not yet read by the worker.
+    fn backlog(&self) -> usize {
+        let consumed = self.consumed.load(Ordering::Acquire);
+        self.published
+            .load(Ordering::Acquire)
+            .saturating_sub(consumed) as usize
+    }
+
+    /// The level of the entry at ring `position` in slot `index`, or `None` if a producer
+    /// has reused the slot for a later entry since.
+    fn level_at(&self, index: usize, position: u64) -> Option<u8> {
+        let slot = &self.inner[index];
+        (slot.position.load(Ordering::Acquire) == position)
+            .then(|| slot.level.load(Ordering::Relaxed))
+    }
+
+    /// Hands slot `index` back to producers once the worker is done with its entry.
+    fn mark_consumed(&self, index: usize) {
+        self.inner[index]
+            .state
+            .store(SLOT_CONSUMED, Ordering::Release);
+    }
 }
 
 unsafe impl<T: Send + Sync> Sync for LogBuffer<T> {}
 unsafe impl<T: Send + Sync> Send for LogBuffer<T> {}
 
+/// Lifecycle of a [`Logger`], as reported by [`Logger::state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoggerState {
+    /// Built but not started; entries are rejected with [`PicoError::NotStarted`].
+    Configured,
+    /// `start()` succeeded and the worker is accepting entries.
+    Running,
+    /// The worker stopped after an unrecoverable error. A logger cannot be restarted.
+    Stopped,
+    /// [`Logger::shutdown`] was called; entries are rejected with [`PicoError::ShutDown`].
+    ShutDown,
+}
+
 /// The main logger struct responsible for handling log writing and reading operations.
 /// It uses a ring buffer and a background worker thread for asynchronous logging.
 pub struct Logger<T> {
     data_buffer: Option<Arc<LogBuffer<T>>>,
-    sender: Option<Sender<usize>>,
-    worker_handle: Option<thread::JoinHandle<()>>,
+    /// Takes the place of `data_buffer` once started with [`Logger::start_inline`].
+    inline: Option<InlineWriter<T>>,
+    sender: Option<Mailbox>,
+    worker_handle: Option<thread::JoinHandle<Result<(), PicoError>>>,
     capacity: usize,
-    logpath: Option<String>,
+    /// How payloads are turned into entry bytes and back.
+    payload_codec: PayloadCodec<T>,
+    /// Number of ring buffer slots this logger has filled; the next entry goes into slot
+    /// `next_slot % capacity`.
+    next_slot: u64,
+    page_capacity: usize,
+    logpath: Option<PathBuf>,
+    /// Files pages are striped across instead of being written to `logpath`, if any.
+    stripe_paths: Vec<PathBuf>,
     flush_interval: Option<u64>,
     poll_interval: Option<u64>,
+    drain_batch: usize,
+    spin: bool,
+    spin_park_after: u32,
     pre_alloc_size: Option<u64>,
+    growth_chunk: u64,
+    compression: Compression,
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<[u8; 32]>,
+    index: bool,
+    schema_tag: Option<String>,
+    metadata_tags: Vec<(String, String)>,
+    format_version: u32,
+    min_level: Level,
+    strict_reads: bool,
+    read_options: ReadOptions,
+    /// What the worker's write path uses, as detected by `start()`.
+    capabilities: Capabilities,
+    uring_options: UringOptions,
+    backend: Backend,
+    page_count: usize,
+    page_size: Option<usize>,
+    ring_depth: Option<u32>,
+    io_mode: IoMode,
+    durability: Durability,
+    sync_policy: Option<SyncPolicy>,
+    worker_affinity: Option<usize>,
+    clock: Option<Arc<dyn Clock>>,
+    create_dirs: bool,
+    block_size: Option<usize>,
+    hugepages: bool,
+    hugepages_active: bool,
+    flush_watermark: Option<f32>,
+    partial_rewrites: Option<(f32, u32)>,
+    stats: Arc<SharedStats>,
+    error_receiver: Option<Receiver<PicoError>>,
+    error_sender: Option<Sender<PicoError>>,
+    max_write_retries: u32,
+    fault_injector: Option<Arc<dyn FaultInjector>>,
+    #[cfg(feature = "sim")]
+    sim_disk: Option<sim::SimDisk>,
+    failure_policy: FailurePolicy,
+    overflow_policy: OverflowPolicy,
+    buffer_full_policy: BufferFullPolicy,
+    shutdown_timeout: Duration,
+    flush_callback: Option<FlushCallback>,
+    /// Set by [`Logger::with_latency_tracking`].
+    latency: Option<Arc<LatencyTracker>>,
+    name: Option<String>,
+    healthy: Arc<AtomicBool>,
+    append: bool,
+    /// Shared with a panic hook set by [`Logger::install_panic_flush`]; `None` until started.
+    panic_flush: Option<Arc<PanicFlush>>,
+    /// Seq_ids reserved with [`Logger::reserve_seq_block`] that are not durable yet.
+    seq_blocks: Arc<SeqBlocks>,
+    seq_block_timeout: Option<Duration>,
+    /// Reserved seq_ids not logged or abandoned yet, to catch misuse in debug builds.
+    #[cfg(debug_assertions)]
+    unlogged_seq_ids: SeqRanges,
+    state: LoggerState,
+    #[cfg(feature = "async")]
+    ack_waiters: Arc<AckWaiters>,
+    /// Sequence ID of the last entry accepted by this logger.
+    #[cfg(feature = "async")]
+    last_seq_id: Option<u64>,
 }
 
 impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
     /// Creates a new instance of `Logger` with default (empty) configuration.
+    ///
+    /// A payload type larger than 16 MiB, the most an entry can carry, or whose entry
+    /// wouldn't fit in the largest page, is rejected at compile time:
+    ///
+    /// ```compile_fail
+    /// # use picologger::Logger;
+    /// #[derive(Clone, Copy)]
+    /// struct Huge([u8; 17 * 1024 * 1024]);
+    /// # impl Default for Huge { fn default() -> Self { Huge([0; 17 * 1024 * 1024]) } }
+    /// let logger = Logger::<Huge>::new();
+    /// ```
     pub fn new() -> Self {
+        const {
+            assert!(
+                size_of::<T>() <= MAX_ENTRY_LEN,
+                "the payload type is larger than an entry can be"
+            );
+            assert!(
+                size_of::<PageHeader>() + PAGE_META_LEN + format::ENTRY_HEADER_LEN + size_of::<T>()
+                    <= MAX_STORED_PAGE_LEN,
+                "an entry of the payload type doesn't fit in a page"
+            );
+        };
         Self {
             data_buffer: None,
+            inline: None,
             sender: None,
             worker_handle: None,
             capacity: 0,
+            payload_codec: PayloadCodec::raw(),
+            next_slot: 0,
+            page_capacity: 0,
             logpath: None,
+            stripe_paths: Vec::new(),
             flush_interval: None,
             poll_interval: None,
+            drain_batch: DEFAULT_DRAIN_BATCH,
+            spin: false,
+            spin_park_after: 0,
             pre_alloc_size: None,
+            growth_chunk: DEFAULT_GROWTH_CHUNK,
+            compression: Compression::None,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+            index: false,
+            schema_tag: None,
+            metadata_tags: Vec::new(),
+            format_version: FORMAT_VERSION,
+            min_level: Level::Trace,
+            strict_reads: false,
+            read_options: ReadOptions::default(),
+            capabilities: Capabilities::default(),
+            uring_options: UringOptions::default(),
+            backend: Backend::Auto,
+            page_count: DEFAULT_PAGE_COUNT,
+            page_size: None,
+            ring_depth: None,
+            io_mode: IoMode::Direct,
+            durability: Durability::None,
+            sync_policy: None,
+            worker_affinity: None,
+            clock: None,
+            create_dirs: false,
+            block_size: None,
+            hugepages: false,
+            hugepages_active: false,
+            flush_watermark: None,
+            partial_rewrites: None,
+            stats: Arc::new(SharedStats::default()),
+            error_receiver: None,
+            error_sender: None,
+            max_write_retries: DEFAULT_WRITE_RETRIES,
+            fault_injector: None,
+            #[cfg(feature = "sim")]
+            sim_disk: None,
+            failure_policy: FailurePolicy::Abort,
+            overflow_policy: OverflowPolicy::Block,
+            buffer_full_policy: BufferFullPolicy::Overwrite,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            flush_callback: None,
+            latency: None,
+            name: None,
+            healthy: Arc::new(AtomicBool::new(false)),
+            append: false,
+            panic_flush: None,
+            seq_blocks: Arc::new(SeqBlocks::default()),
+            seq_block_timeout: None,
+            #[cfg(debug_assertions)]
+            unlogged_seq_ids: SeqRanges::default(),
+            state: LoggerState::Configured,
+            #[cfg(feature = "async")]
+            ack_waiters: Arc::new(AckWaiters::default()),
+            #[cfg(feature = "async")]
+            last_seq_id: None,
         }
     }
 
     /// Configures the logger for writing logs.
     ///
+    /// Superseded by [`LoggerConfig`], which takes the intervals as [`Duration`]s instead of
+    /// bare nanosecond counts.
+    ///
     /// # Arguments
     ///
     /// * `logpath` - Path to the log file.
-    /// * `capacity` - Size of the ring buffer.
+    /// * `capacity` - Size of the ring buffer. Must be non-zero; any size works, it need not be a power of two.
     /// * `flush_interval` - Interval in nanoseconds to flush logs to disk.
-    /// * `poll_interval` - Interval in nanoseconds to poll for uring completions.
+    /// * `poll_interval` - Longest the idle worker sleeps, in nanoseconds; see
+    ///   [`LoggerConfig::poll_interval`].
     /// * `pre_alloc_size` - Size in bytes to pre-allocate for the log file.
+    ///
+    /// Once the logger has been started the configuration is fixed: calling this again
+    /// leaves the running configuration untouched and reports
+    /// [`PicoError::AlreadyStarted`] on the [error channel](Logger::take_error_receiver).
+    #[deprecated(
+        since = "0.9.5",
+        note = "use `LoggerConfig`, which takes `Duration` intervals and any `AsRef<Path>`"
+    )]
     pub fn with_write_config(
         mut self,
-        logpath: String,
+        logpath: impl AsRef<Path>,
         capacity: usize,
         flush_interval: u64,
         poll_interval: u64,
         pre_alloc_size: u64,
     ) -> Self {
-        self.logpath = Some(logpath);
+        if self.state != LoggerState::Configured {
+            if let Some(errors) = &self.error_sender {
+                let _ = errors.try_send(PicoError::AlreadyStarted);
+            }
+            return self;
+        }
+        self.logpath = Some(logpath.as_ref().to_path_buf());
         self.capacity = capacity;
         self.flush_interval = Some(flush_interval);
         self.poll_interval = Some(poll_interval);
@@ -121,131 +650,1323 @@ impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
         self
     }
 
+    /// Sets how many bytes of disk space to reserve for the log file up front; 0, the default
+    /// with [`LoggerConfig`], reserves none.
+    ///
+    /// The space is allocated with `fallocate`, so the first write to each page doesn't pay
+    /// block allocation latency. A file that is already at least this long is left as it is,
+    /// never shrunk. The size must be a multiple of the block size of the file's filesystem;
+    /// `start()` fails with [`PicoError::InvalidPreallocation`] otherwise. Overrides the size
+    /// given to [`Logger::with_write_config`]. A block device has all its space already, so
+    /// nothing is reserved on one.
+    pub fn with_preallocation(mut self, bytes: u64) -> Self {
+        self.pre_alloc_size = Some(bytes);
+        self
+    }
+
+    /// Sets how many bytes a pre-allocated file is extended by once writes approach the end
+    /// of its allocated region (default 256 MiB); 0 turns extension off.
+    ///
+    /// The worker starts the next extension when writes are within half a chunk of the end,
+    /// so in steady state pages never wait for block allocation. Only applies when
+    /// [`Logger::with_preallocation`] reserved space; other files grow as they are written.
+    /// [`LoggerStats::file_len`] reports how far the file reaches.
+    pub fn with_growth_chunk(mut self, bytes: u64) -> Self {
+        self.growth_chunk = bytes;
+        self
+    }
+
+    /// Sets how strongly pages are persisted before their entries are acknowledged.
+    ///
+    /// With [`Durability::None`] (the default), [`Logger::get_last_flushed_entry`] advances
+    /// as soon as a page write completes, which under `O_DIRECT` means the device has the
+    /// data but may still hold it in a volatile cache. [`Durability::Fdatasync`] follows
+    /// every page write with an `fdatasync` and only acknowledges once it completes;
+    /// [`Durability::Odsync`] gets the same guarantee by opening the file with `O_DSYNC`.
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Hardens written pages with background syncs instead of one per page; see
+    /// [`SyncPolicy`].
+    ///
+    /// Only applies under [`Durability::None`]: the other modes harden every page before
+    /// acknowledging it, so [`Logger::get_last_hardened_entry`] follows the acks there.
+    pub fn with_sync_policy(mut self, policy: SyncPolicy) -> Self {
+        self.sync_policy = Some(policy);
+        self
+    }
+
+    /// Pins the worker thread to the CPU core `core_id`.
+    ///
+    /// `start()` fails if the core doesn't exist or isn't available to the process. This is a
+    /// no-op on non-Linux targets.
+    pub fn with_worker_affinity(mut self, core_id: usize) -> Self {
+        self.worker_affinity = Some(core_id);
+        self
+    }
+
+    /// Sets the clock that timestamps each entry (default: [`MonotonicClock`]).
+    ///
+    /// The clock is read once per entry on the worker thread, so it must be cheap. The
+    /// worker also times the flush interval with [`Clock::monotonic_nanos`], which only
+    /// follows this clock if it overrides that method.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Makes `start()` create the directories missing above the log file, instead of failing
+    /// with [`PicoError::MissingDirectory`]. Off by default.
+    ///
+    /// Each directory created is fsynced into its parent, so the path survives a crash along
+    /// with the file.
+    pub fn with_create_dirs(mut self, enabled: bool) -> Self {
+        self.create_dirs = enabled;
+        self
+    }
+
+    /// Allocates the page pool from a single huge-page-backed mapping instead of one
+    /// allocation per page, cutting TLB misses and minor faults on the worker.
+    ///
+    /// Explicitly reserved huge pages (`MAP_HUGETLB`) are tried first, then a regular mapping
+    /// advised for transparent huge pages. If neither mapping can be created the pool falls
+    /// back to the normal allocator; [`Logger::uses_hugepages`] reports the outcome.
+    pub fn with_hugepages(mut self, enabled: bool) -> Self {
+        self.hugepages = enabled;
+        self
+    }
+
+    /// Flushes a page as soon as `fraction` of it is in use, instead of waiting for the flush
+    /// interval or for an entry that no longer fits.
+    ///
+    /// This bounds how long a nearly-full page sits in memory; the flush interval still
+    /// applies during quiet periods. `fraction` must be in `(0, 1]`; `start()` fails
+    /// otherwise.
+    pub fn with_flush_watermark(mut self, fraction: f32) -> Self {
+        self.flush_watermark = Some(fraction);
+        self
+    }
+
+    /// Lets timer flushes of pages less than `min_fill` full write them without moving on to
+    /// the next block (default off).
+    ///
+    /// Later entries are added to the same page, which is written to the same blocks again,
+    /// up to `max_rewrites` times; after that, once it fills up, on an explicit flush, or
+    /// at a timer flush with no new entries, the page is closed and the next one starts in
+    /// fresh blocks. A logger taking entries every flush interval then spends one block per
+    /// filled page instead of one per interval.
+    ///
+    /// A torn rewrite could lose the entries an earlier write of the page put there, so
+    /// entries on a page are only acknowledged once the page is closed and its last write
+    /// has completed, which can hold the watermark back by up to `max_rewrites + 1` flush
+    /// intervals. Pages that are compressed or encrypted are never rewritten. `min_fill`
+    /// must be in `(0, 1]`; `start()` fails otherwise.
+    pub fn with_partial_rewrites(mut self, min_fill: f32, max_rewrites: u32) -> Self {
+        self.partial_rewrites = Some((min_fill, max_rewrites));
+        self
+    }
+
+    /// Sets how many times a page write failing with a transient error (`EAGAIN`, `EINTR`,
+    /// `ENOMEM`) is resubmitted before the page is reported lost (default 3).
+    ///
+    /// Each retry waits a little longer than the last, up to a couple of milliseconds.
+    pub fn with_write_retries(mut self, retries: u32) -> Self {
+        self.max_write_retries = retries;
+        self
+    }
+
+    /// Sets what the worker does when its ring fails with an error it cannot retry
+    /// (default [`FailurePolicy::Abort`]).
+    ///
+    /// Either way the error is reported on the
+    /// [error channel](Logger::take_error_receiver).
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Sets what the worker does when the next page is still being written
+    /// (default [`OverflowPolicy::Block`]).
+    ///
+    /// [`Logger::stats`] reports the resulting stall time, dropped entries and added pages,
+    /// which shows whether the default is enough for a workload.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets what `log()` does when producers have filled every ring buffer slot the worker
+    /// has not consumed yet (default [`BufferFullPolicy::Overwrite`]).
+    ///
+    /// [`BufferFullPolicy::Reject`] and [`BufferFullPolicy::Block`] never lose an entry
+    /// that was handed a seq_id: each slot records whether the worker has consumed its
+    /// entry, and a producer only fills slots it has.
+    pub fn with_buffer_full_policy(mut self, policy: BufferFullPolicy) -> Self {
+        self.buffer_full_policy = policy;
+        self
+    }
+
+    /// Sets how long dropping the logger, or [`Logger::shutdown`], waits for outstanding page
+    /// writes (default 5 seconds).
+    ///
+    /// A disk that has stopped completing writes would otherwise hang the thread dropping
+    /// the logger. Once the timeout passes the worker exits without the remaining writes and
+    /// reports [`PicoError::ShutdownTimedOut`] on the error channel. A worker that can't
+    /// notice the timeout, stuck in a wait the kernel won't cut short, is leaked instead, as
+    /// described on [`Logger::shutdown`].
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Lets the durable watermark advance past seq_ids from [`Logger::reserve_seq_block`]
+    /// that are still neither logged nor abandoned `timeout` after their block was
+    /// reserved. By default such ids hold the watermark back until
+    /// [`Logger::abandon_seq`] is called for them.
+    ///
+    /// Entries logged with a timed-out id are still written, but the watermark may already
+    /// have reported them durable.
+    pub fn with_seq_block_timeout(mut self, timeout: Duration) -> Self {
+        self.seq_block_timeout = Some(timeout);
+        self
+    }
+
+    /// Names the logger, e.g. `with_name("orders-wal")`, to tell it apart from others in the
+    /// same process. With the `metrics` feature, its metrics are labelled with the name
+    /// instead of the log path, and with the `tracing` feature, so are its worker's spans.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Registers a callback invoked each time a page becomes durable, for example to
+    /// acknowledge requests only once their entries are on disk.
+    ///
+    /// The callback runs on the worker thread between completions, so it must be fast: a
+    /// slow callback delays every write behind it. To do real work, forward the event to
+    /// another thread, e.g. with `move |event| { let _ = sender.try_send(event); }`.
+    pub fn with_flush_callback(
+        mut self,
+        callback: impl Fn(FlushEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.flush_callback = Some(Box::new(callback));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_fault_injector(mut self, injector: Arc<dyn FaultInjector>) -> Self {
+        self.fault_injector = Some(injector);
+        self
+    }
+
+    /// Sets the compression applied to each page before it is written.
+    ///
+    /// Compressed pages carry a small page header and are still padded to the block size,
+    /// so compression saves disk bandwidth only when several entries share a page.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Encrypts every page at rest with XChaCha20-Poly1305 under `key`.
+    ///
+    /// The same key must be configured on the reading side; a wrong key makes `read()` fail
+    /// with [`PicoError::AuthFailed`].
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption(mut self, key: [u8; 32]) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Maintains a sidecar index file (`<logpath>.idx`) of each page's first sequence ID and
+    /// file offset, letting [`LogReader::seek`] and [`Logger::read_range`] jump straight to the
+    /// right page instead of scanning the whole file.
+    pub fn with_index(mut self, enabled: bool) -> Self {
+        self.index = enabled;
+        self
+    }
+
+    /// Records `tag` in the file header alongside the payload's size and alignment, and
+    /// checks it when reading.
+    ///
+    /// Size and alignment alone cannot tell apart two types with the same layout, so bump the
+    /// tag (e.g. `"orders_v2"`) whenever the meaning of the payload's bytes changes. Readers
+    /// that set a tag reject files carrying a different one, or none; readers without one
+    /// only compare the layout. At most 64 bytes; `start()` fails otherwise.
+    pub fn with_schema_tag(mut self, tag: impl Into<String>) -> Self {
+        self.schema_tag = Some(tag.into());
+        self
+    }
+
+    /// Writes files in format `version` instead of the latest one, so readers built
+    /// against an older release can still read them.
+    ///
+    /// Versions from 2 up to [`FORMAT_VERSION`] can be written; `start()` fails with
+    /// [`PicoError::InvalidFormatVersion`] otherwise. Version 2 pages go without the
+    /// summary of their seq_ids and entry count that later versions start each page with.
+    /// Before version 4 entries go without their level, and read back as [`Level::Info`].
+    pub fn with_format_version(mut self, version: u32) -> Self {
+        self.format_version = version;
+        self
+    }
+
+    /// Drops entries below `level` before they reach the ring buffer (default
+    /// [`Level::Trace`], which keeps everything).
+    ///
+    /// Entries logged without a level count as [`Level::Info`]. A dropped entry gets no
+    /// sequence ID: [`Logger::log_with_level`] returns `None` for it and
+    /// [`Logger::try_log_with_level`] fails with [`PicoError::LevelFiltered`]. Use
+    /// [`Logger::level_enabled`] to skip building payloads that would be dropped.
+    pub fn with_min_level(mut self, level: Level) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Returns `true` if entries at `level` are kept by the level set with
+    /// [`Logger::with_min_level`].
+    pub fn level_enabled(&self, level: Level) -> bool {
+        level >= self.min_level
+    }
+
+    /// Makes readers fail with an `InvalidData` error when they find a damaged entry.
+    ///
+    /// By default a damaged entry header ends its page: the rest of the page is skipped and
+    /// reading resumes where the next page may start, so a corrupt region costs the entries
+    /// around it but never yields garbage. Entries longer than a `T` are skipped the same
+    /// way; one too short to hold a `T` always fails the read. In strict mode a page whose
+    /// summary of its entries fails its checksum fails the read as well, with an error
+    /// wrapping [`PicoError::ChecksumMismatch`].
+    pub fn with_strict_reads(mut self, strict: bool) -> Self {
+        self.strict_reads = strict;
+        self
+    }
+
+    /// Sets how this logger's readers go through the file, e.g. how much of it they read
+    /// per read call (see [`ReadOptions`]).
+    pub fn with_read_options(mut self, options: ReadOptions) -> Self {
+        self.read_options = options;
+        self
+    }
+
+    /// Sets the number of pages in the worker's page pool (default 256).
+    ///
+    /// Every page is a block-aligned allocation of one block (several with compression, or as
+    /// many as it takes to hold one entry when `T` is larger than a block, unless
+    /// [`Logger::with_page_size`] sets the size) that stays resident and, with registered
+    /// buffers, locked in memory: 256 pages on a 4 KiB
+    /// block size is 1 MiB. The pool also caps the number of page writes in flight, so a
+    /// smaller pool saves memory at the cost of stalling the worker sooner when the disk lags.
+    /// Unless [`Logger::with_ring_depth`] says otherwise, the io_uring queue is sized to
+    /// match. Must be between 2 and 32768; `start()` fails otherwise.
+    pub fn with_pages(mut self, page_count: usize) -> Self {
+        self.page_count = page_count;
+        self
+    }
+
+    /// Sets the block size pages are aligned to and sized by, instead of probing the file for
+    /// it.
+    ///
+    /// By default it is the logical sector size of a block device, or the preferred I/O size
+    /// the filesystem reports, capped at 16 KiB. Either way `start()` checks that it is a
+    /// multiple of the alignment direct I/O on the file needs, failing with
+    /// [`PicoError::MisalignedBlockSize`], and tries a direct write of one page, failing with
+    /// [`PicoError::DirectWriteRejected`]. That write puts a page of zeros where the first
+    /// page will go; a regular file is cut back to its previous length afterwards, a block
+    /// device keeps the zeros until the page overwrites them. `bytes` must be a power of two
+    /// of at least 512; `start()` fails with [`PicoError::InvalidBlockSize`] otherwise.
+    pub fn with_block_size(mut self, bytes: usize) -> Self {
+        self.block_size = Some(bytes);
+        self
+    }
+
+    /// Sets the size of every page in bytes, instead of deriving it from the filesystem's
+    /// block size.
+    ///
+    /// By default a page is one block (four with compression), grown to fit a single entry
+    /// when `T` is larger. Bigger pages mean fewer, larger writes and less padding per entry;
+    /// each one stays in memory until it is written, so the page pool grows with them. The
+    /// size must be a multiple of the block size, hold at least one entry and be at most
+    /// 64 MiB; `start()` fails with [`PicoError::InvalidPageSize`] otherwise. The size is
+    /// recorded in the file header, where readers pick it up.
+    pub fn with_page_size(mut self, bytes: usize) -> Self {
+        self.page_size = Some(bytes);
+        self
+    }
+
+    /// Sets how many queued entries, and then messages, the worker handles per wakeup before
+    /// it checks the flush timer and reaps completions again (default 256).
+    ///
+    /// Each time round, the worker reads up to this many entries from the ring buffer and
+    /// drains up to this many messages without waiting, which keeps it from paying the timer
+    /// and completion bookkeeping per entry under load. An idle worker still wakes as often
+    /// as the poll interval says. A value of 1 handles entries one at a time; 0 is treated
+    /// as 1.
+    pub fn with_drain_batch(mut self, max_messages: usize) -> Self {
+        self.drain_batch = max_messages.max(1);
+        self
+    }
+
+    /// Makes the worker busy-poll the ring buffer, channel and completion queue instead of sleeping
+    /// between messages (default off).
+    ///
+    /// An entry is picked up as soon as it is published rather than when the worker next wakes,
+    /// at the price of one core at 100% for the logger's whole lifetime, even when idle.
+    /// Only worth it with a core to spare, ideally pinned with
+    /// [`Logger::with_worker_affinity`]. Dropping the logger still stops the worker
+    /// promptly.
+    pub fn with_spin_mode(mut self, enabled: bool) -> Self {
+        self.spin = enabled;
+        self
+    }
+
+    /// In spin mode, blocks once as if spin mode were off after this many consecutive empty
+    /// polls (default 0, which spins forever).
+    ///
+    /// Bounds the CPU burnt during quiet periods: the worker sleeps for up to the poll
+    /// interval, then goes back to spinning.
+    pub fn with_spin_park_after(mut self, empty_polls: u32) -> Self {
+        self.spin_park_after = empty_polls;
+        self
+    }
+
+    /// Sets the io_uring submission queue depth (default: the page pool size rounded up to a
+    /// power of two).
+    ///
+    /// A shallow ring keeps locked memory down on small machines; the worker simply waits for
+    /// completions when the queue fills. Must be a power of two no larger than 32768;
+    /// `start()` fails otherwise.
+    pub fn with_ring_depth(mut self, entries: u32) -> Self {
+        self.ring_depth = Some(entries);
+        self
+    }
+
+    /// Sets the options used to create the worker's io_uring instance.
+    ///
+    /// Requesting SQPOLL on a kernel or account that cannot use it falls back to regular
+    /// submission; [`Logger::capabilities`] reports the mode that ended up active.
+    pub fn with_uring_options(mut self, options: UringOptions) -> Self {
+        self.uring_options = options;
+        self
+    }
+
+    /// Selects the worker's write path (default [`Backend::Auto`]).
+    ///
+    /// [`Logger::backend`] reports the backend that ended up active.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    fn codec(&self) -> PageCodec {
+        let codec = PageCodec::new(self.compression);
+        #[cfg(feature = "encryption")]
+        let codec = codec.with_key(self.encryption_key.as_ref());
+        codec
+    }
+
     /// Initializes the internal components (buffer, worker thread) and starts the logging process.
     ///
     /// # Returns
     ///
-    /// * `Result<(), Error>` - Ok if started successfully, Err if configuration is missing.
-    pub fn start(&mut self) -> Result<(), Error> {
+    /// * `Result<(), PicoError>` - Ok if started successfully, [`PicoError::NotConfigured`] if
+    ///   the write configuration is missing, or [`PicoError::AlreadyStarted`]. Invalid settings
+    ///   are rejected before anything is created with the matching variant, e.g.
+    ///   [`PicoError::InvalidInterval`]. [`PicoError::FileLocked`] means another logger is
+    ///   writing to the same path; other failures to open the file or create the worker come
+    ///   back as [`PicoError::Io`].
+    ///
+    /// Entries go through the ring buffer to the worker, which appends them to pages. See
+    /// [`Logger::start_inline`] for appending them on the caller's thread instead.
+    pub fn start(&mut self) -> Result<(), PicoError> {
+        self.launch(false)
+    }
+
+    /// Starts the worker, lending pages of its pool to this logger instead of creating a
+    /// ring buffer for it if `inline` is set.
+    fn launch(&mut self, inline: bool) -> Result<(), PicoError> {
+        if self.state != LoggerState::Configured {
+            return Err(PicoError::AlreadyStarted);
+        }
         if let (Some(logpath), Some(flush_interval), Some(poll_interval), Some(pre_alloc_size)) = (
             &self.logpath,
             self.flush_interval,
             self.poll_interval,
             self.pre_alloc_size,
         ) {
-            let capacity = self.capacity;
-            let mut raw_vec = Vec::with_capacity(capacity);
-            for _ in 0..capacity {
-                raw_vec.push(UnsafeCell::new(LogMessage::default()));
-            }
+            let ring_depth = self.validate_config(logpath, flush_interval, poll_interval)?;
 
-            let data_buffer = Arc::new(LogBuffer { inner: raw_vec });
+            let durability = self.durability;
+            let sync_policy = self.sync_policy.filter(|_| durability == Durability::None);
+            let max_write_retries = self.max_write_retries;
+            let fault_injector = self.fault_injector.clone();
+            let clock = self
+                .clock
+                .clone()
+                .unwrap_or_else(|| Arc::new(MonotonicClock::new()));
+            let capacity = self.capacity;
+            // An inline logger never fills the ring, but the worker still reads it.
+            let data_buffer = Arc::new(LogBuffer::new(if inline { 1 } else { capacity }));
 
-            let (sender, receiver) = crossbeam_channel::bounded::<usize>(capacity);
+            let (sender, receiver) = crossbeam_channel::bounded::<Message>(capacity);
+            let wakeup = Arc::new(Wakeup::new()?);
 
-            let path = PathBuf::from(logpath);
-            let blk_size = get_blksize(&path) as usize;
+            let paths = self.write_paths(logpath);
+            if self.create_dirs {
+                for path in &paths {
+                    create_parent_dirs(path)?;
+                }
+            }
+            // Stripes may sit on devices with different block sizes, which are all powers of
+            // two, so the largest suits them all.
+            let blk_size = match self.block_size {
+                Some(size) => size,
+                None => paths
+                    .iter()
+                    .map(|path| get_blksize(path))
+                    .fold(0, usize::max),
+            };
 
             let worker_buffer = data_buffer.clone();
 
-            let page_manager = PageManager::new(blk_size, 256);
+            let codec = self.codec();
+            // Grows pages for payloads that would not fit a single block-sized page.
+            let layout = PageLayout::new(
+                blk_size,
+                self.page_size,
+                codec.page_blocks(),
+                codec.header_len(),
+                self.format_version,
+                size_of::<T>(),
+            )?;
+            let page_size = layout.page_size;
+            let page_header_len = layout.header_len();
+            if !pre_alloc_size.is_multiple_of(blk_size as u64) {
+                return Err(PicoError::InvalidPreallocation {
+                    bytes: pre_alloc_size,
+                    block_size: blk_size,
+                });
+            }
+            let metadata = FileMetadata::current(&self.metadata_tags).encode();
+            let metadata_max = MAX_METADATA_LEN.min(blk_size - size_of::<FileHeader>());
+            if metadata.len() > metadata_max {
+                return Err(PicoError::MetadataTooLarge {
+                    len: metadata.len(),
+                    max: metadata_max,
+                });
+            }
+            let mut page_manager = if self.hugepages {
+                PageManager::with_hugepages(page_size, self.page_count, page_header_len)
+            } else {
+                PageManager::new(page_size, self.page_count, page_header_len)
+            };
+            let hugepages = page_manager.uses_hugepages();
+            let flush_watermark = self
+                .flush_watermark
+                .map(|fraction| (fraction * page_size as f32) as usize);
+            let partial_rewrites =
+                self.partial_rewrites
+                    .map(|(min_fill, max_rewrites)| PartialRewrites {
+                        min_fill: (min_fill * page_size as f32) as usize,
+                        max_rewrites,
+                    });
+            self.stats = Arc::new(SharedStats::default());
+            let worker_stats = self.stats.clone();
+            let encode = self.payload_codec.encode;
+            let seq_blocks = self.seq_blocks.clone();
+            let seq_block_timeout = self.seq_block_timeout;
+            let (error_sender, error_receiver) = crossbeam_channel::bounded(ERROR_CHANNEL_CAPACITY);
+            self.error_sender = Some(error_sender.clone());
 
-            let file = get_file_handler(&path, pre_alloc_size)?;
+            let mut files = Vec::with_capacity(paths.len());
+            let mut io_mode = IoMode::Direct;
+            for path in &paths {
+                let (file, mode) = get_file_handler(path, pre_alloc_size, durability)?;
+                if mode == IoMode::Buffered {
+                    io_mode = mode;
+                } else if let Some(sector_size) = util::direct_io_alignment(&file)?
+                    && !blk_size.is_multiple_of(sector_size)
+                {
+                    return Err(PicoError::MisalignedBlockSize {
+                        path: path.clone(),
+                        block_size: blk_size,
+                        sector_size,
+                    });
+                }
+                files.push(file);
+            }
+            let devices = files
+                .iter()
+                .map(util::block_device)
+                .collect::<Result<Vec<_>, _>>()?;
+            let append_point = match self.append {
+                true => find_append_point(
+                    &paths[0],
+                    &AppendLayout {
+                        schema: &self.schema(),
+                        version: self.format_version,
+                        block_size: blk_size,
+                        page_size,
+                        codec: &codec,
+                    },
+                )?,
+                false => None,
+            };
+            if let Some(point) = &append_point {
+                // Cut off the torn page, if any, along with whatever follows it. A device
+                // can't be cut short; the next pages overwrite it instead.
+                let file = &files[0];
+                if devices[0].is_none() {
+                    file.set_len(point.end)?;
+                    if pre_alloc_size > point.end {
+                        util::preallocate(file, pre_alloc_size)?;
+                    }
+                    file.sync_all()?;
+                }
+                global::advance_seq_id(point.last_seq + 1);
+                self.stats.record_flushed_seq_id(point.last_seq);
+            }
+            let (next_page_id, next_offset) = match &append_point {
+                Some(point) => (point.last_page_id.map_or(0, |id| id + 1), point.end),
+                None => (0, blk_size as u64),
+            };
+            for stripe in 0..files.len() {
+                self.stats.record_file_offset(stripe, next_offset);
+            }
+            // A page write is the largest direct write the worker makes, so try one where the
+            // first page goes before promising that the rest will work. Nothing valid lives
+            // there yet: the zeros are cut off a regular file again and overwritten on a
+            // device by the first page.
+            let probe = Page::<()>::init(page_size, 0);
+            for (file, path) in files.iter().zip(&paths) {
+                if io_mode == IoMode::Buffered {
+                    break;
+                }
+                match util::probe_direct_write(file, next_offset, probe.get_page_content()) {
+                    Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+                        return Err(PicoError::DirectWriteRejected {
+                            path: path.clone(),
+                            page_size,
+                            block_size: blk_size,
+                            sector_size: util::direct_io_alignment(file)?,
+                        });
+                    }
+                    result => result?,
+                }
+            }
+            // A write the device can't poll for would be waited on forever.
+            if self.uring_options.iopoll {
+                #[cfg(target_os = "linux")]
+                for (file, path) in files.iter().zip(&paths) {
+                    let mut block = Page::<()>::init(blk_size, 0);
+                    uring::probe_iopoll(file, block.get_page_content_mut()).map_err(|errno| {
+                        PicoError::IopollUnavailable {
+                            path: path.clone(),
+                            errno,
+                        }
+                    })?;
+                }
+                #[cfg(not(target_os = "linux"))]
+                return Err(PicoError::UnsupportedPlatform(
+                    "IOPOLL is only available on Linux",
+                ));
+            }
+            let mut stripes = Vec::with_capacity(files.len());
+            for (stripe, ((file, path), device)) in
+                files.iter().zip(&paths).zip(&devices).enumerate()
+            {
+                let device_len = device.map(|device| device.len);
+                let allocated_len = match device_len {
+                    Some(len) => len,
+                    None => file.metadata()?.len().max(blk_size as u64),
+                };
+                self.stats.record_file_len(stripe, allocated_len);
+                stripes.push(Stripe::new(
+                    path.clone(),
+                    next_offset,
+                    allocated_len,
+                    device_len,
+                ));
+            }
+            // Devices are as large as they will ever be.
+            let growth_chunk = if pre_alloc_size > 0 && devices.iter().all(Option::is_none) {
+                self.growth_chunk
+            } else {
+                0
+            };
+            let header_block = layout.header_block(&self.schema(), &metadata, self.format_version);
+            if append_point.is_none() {
+                for mut file in &files {
+                    file.write_all(header_block.get_page_content())?;
+                }
+            }
+            let mut downgrades = Vec::new();
+            let mut open = |files| {
+                backend::open(
+                    self.backend,
+                    &self.uring_options,
+                    ring_depth,
+                    &mut page_manager,
+                    files,
+                    &wakeup,
+                    fault_injector.clone(),
+                    &mut downgrades,
+                )
+            };
+            #[cfg(feature = "sim")]
+            let io_backend = match &self.sim_disk {
+                Some(disk) => disk.backend(
+                    &paths,
+                    files,
+                    self.durability == Durability::Odsync,
+                    self.stats.clone(),
+                ),
+                None => open(files),
+            };
+            #[cfg(not(feature = "sim"))]
+            let io_backend = open(files);
+            let io_backend = io_backend?;
+            if !downgrades.is_empty() {
+                // The channel is empty this early, so the report can't be dropped.
+                let _ = error_sender.try_send(PicoError::OptionsDowngraded { downgrades });
+            }
+            let active_backend = io_backend.kind();
+            let capabilities = io_backend.capabilities();
+            let index = if self.index {
+                let mut index = IndexWriter::create(&paths[0])?;
+                for &(first_seq_id, offset) in append_point.iter().flat_map(|point| &point.pages) {
+                    index.append(first_seq_id, offset)?;
+                }
+                Some(index)
+            } else {
+                None
+            };
+            let (inline_pool, inline_writer) = match inline {
+                true => {
+                    let (pool, writer) = inline::pair(
+                        &page_manager,
+                        wakeup.clone(),
+                        clock.clone(),
+                        flush_interval,
+                        flush_watermark,
+                    );
+                    (Some(pool), Some(writer))
+                }
+                false => (None, None),
+            };
             let flush_interval_duration = flush_interval;
             let poll_interval_duration = poll_interval;
+            let drain_batch = self.drain_batch;
+            let (spin, spin_park_after) = (self.spin, self.spin_park_after);
 
-            let handle = thread::spawn(move || {
-                let ring = io_uring::IoUring::new(256).expect("failed to init io_uring");
-                let mut worker = LogWorker {
-                    receiver,
-                    pages: page_manager,
-                    data_buffer: worker_buffer,
-                    last_flush: Instant::now(),
-                    flush_interval: Duration::from_nanos(flush_interval_duration),
-                    poll_interval: Duration::from_nanos(poll_interval_duration),
-                    logfile: &file,
-                    ring,
-                    pending_writes: 0,
-                };
-                worker.run();
-            });
+            let worker_affinity = self.worker_affinity;
+            let failure_policy = self.failure_policy;
+            let overflow_policy = self.overflow_policy;
+            let shutdown_timeout = self.shutdown_timeout;
+            let flush_callback = self.flush_callback.take();
+            let ack_latency = self.latency.clone().map(AckLatency::new);
+            #[cfg(any(feature = "metrics", feature = "tracing"))]
+            let label = self
+                .name
+                .clone()
+                .unwrap_or_else(|| logpath.display().to_string());
+            #[cfg(feature = "metrics")]
+            let metrics = metrics_export::WorkerMetrics::new(label.clone());
+            #[cfg(feature = "tracing")]
+            let span = tracing::info_span!("picolog_worker", logger = %label);
+            // The worker reports to the subscriber `start` was called under, as if it ran on
+            // the calling thread.
+            #[cfg(feature = "tracing")]
+            let dispatch = tracing::dispatcher::get_default(|dispatch| dispatch.clone());
+            self.healthy = Arc::new(AtomicBool::new(true));
+            let worker_healthy = self.healthy.clone();
+            #[cfg(feature = "async")]
+            let ack_waiters = self.ack_waiters.clone();
+            let worker_wakeup = wakeup.clone();
+            // The flush timer runs from `start`, not from whenever the worker thread gets going.
+            let started = clock.monotonic_nanos();
+            let (ready_tx, ready_rx) = std::sync::mpsc::sync_channel(1);
+            let handle = thread::Builder::new()
+                .name("picolog-worker".to_string())
+                .spawn(move || {
+                    #[cfg(feature = "tracing")]
+                    let _dispatch = tracing::dispatcher::set_default(&dispatch);
+                    #[cfg(feature = "tracing")]
+                    let _span = span.entered();
+                    let pinned = match worker_affinity {
+                        Some(core_id) => pin_current_thread(core_id),
+                        None => Ok(()),
+                    };
+                    let failed = pinned.is_err();
+                    let _ = ready_tx.send(pinned);
+                    if failed {
+                        return Ok(());
+                    }
+
+                    let mut worker = LogWorker {
+                        receiver,
+                        wakeup: worker_wakeup,
+                        pages: page_manager,
+                        data_buffer: worker_buffer,
+                        encode,
+                        last_flush: started,
+                        flush_interval: Duration::from_nanos(flush_interval_duration),
+                        poll_interval: Duration::from_nanos(poll_interval_duration),
+                        drain_batch,
+                        spin,
+                        spin_park_after,
+                        backend: io_backend,
+                        pending_writes: 0,
+                        in_flight: Slab::new(),
+                        codec,
+                        layout,
+                        next_page_id,
+                        scratch: Vec::new(),
+                        block_size: blk_size,
+                        stripes,
+                        growth_chunk,
+                        index,
+                        unsubmitted: 0,
+                        durability,
+                        sync_policy,
+                        background_sync: BackgroundSync::new(clock.monotonic_nanos(), 0),
+                        clock,
+                        flush_watermark,
+                        stats: worker_stats,
+                        errors: error_sender,
+                        completions: Vec::new(),
+                        held_completions: Vec::new(),
+                        max_write_retries,
+                        fault_injector,
+                        failure_policy,
+                        overflow_policy,
+                        awaiting_page: false,
+                        dropped: None,
+                        partial_rewrites,
+                        open_page: None,
+                        flush_callback,
+                        ack_latency,
+                        #[cfg(feature = "metrics")]
+                        metrics,
+                        subscribers: Vec::new(),
+                        acks: AckOrder::default(),
+                        seq_blocks,
+                        seq_block_timeout,
+                        page_reserved: Vec::new(),
+                        newest_seq: 0,
+                        acked_seq: 0,
+                        shutdown_timeout,
+                        shutdown_deadline: None,
+                        stop: None,
+                        healthy: worker_healthy,
+                        inline: inline_pool,
+                        #[cfg(feature = "async")]
+                        ack_waiters,
+                    };
+                    worker.run()
+                })?;
+            if let Ok(Err(e)) = ready_rx.recv() {
+                let _ = handle.join();
+                return Err(e);
+            }
 
-            self.data_buffer = Some(data_buffer);
-            self.sender = Some(sender);
+            self.inline = inline_writer;
+            if self.inline.is_none() {
+                self.data_buffer = Some(data_buffer);
+            }
+            let mailbox = Mailbox::new(sender, wakeup);
+            self.panic_flush = Some(Arc::new(PanicFlush::new(
+                mailbox.clone(),
+                handle.thread().id(),
+            )));
+            self.sender = Some(mailbox);
+            self.page_capacity = page_size - page_header_len;
             self.worker_handle = Some(handle);
+            self.capabilities = capabilities;
+            self.backend = active_backend;
+            self.io_mode = io_mode;
+            self.hugepages_active = hugepages;
+            self.error_receiver = Some(error_receiver);
+            self.state = LoggerState::Running;
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                logger = %label,
+                path = %logpath.display(),
+                pages = self.page_count,
+                page_size,
+                ring_depth,
+                "logger started"
+            );
 
             Ok(())
         } else {
-            Err(Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Config missing",
-            ))
+            Err(PicoError::NotConfigured)
+        }
+    }
+
+    /// Checks the configuration before anything is allocated or spawned, returning the ring
+    /// depth to use.
+    pub(crate) fn validate_config(
+        &self,
+        logpath: &Path,
+        flush_interval: u64,
+        poll_interval: u64,
+    ) -> Result<u32, PicoError> {
+        self.validate_stripes()?;
+        if self.uring_options.iopoll {
+            if self.backend == Backend::Sync {
+                return Err(PicoError::IopollConflict {
+                    setting: "Backend::Sync",
+                });
+            }
+            // The linked fdatasync would be rejected by the ring.
+            if self.durability == Durability::Fdatasync {
+                return Err(PicoError::IopollConflict {
+                    setting: "Durability::Fdatasync",
+                });
+            }
+        }
+        for path in self.write_paths(logpath) {
+            if path.as_os_str().is_empty() {
+                return Err(PicoError::EmptyPath);
+            }
+            let parent = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            if let Some(dir) = parent
+                && !self.create_dirs
+                && !dir.is_dir()
+            {
+                return Err(PicoError::MissingDirectory {
+                    path: dir.to_path_buf(),
+                });
+            }
+        }
+
+        if let Some(size) = self.block_size
+            && (!size.is_power_of_two() || size < MIN_BLOCK_SIZE)
+        {
+            return Err(PicoError::InvalidBlockSize { size });
+        }
+        if self.capacity == 0 {
+            return Err(PicoError::InvalidCapacity {
+                capacity: self.capacity,
+            });
+        }
+        if flush_interval == 0 {
+            return Err(PicoError::InvalidInterval {
+                name: "flush_interval",
+                nanos: flush_interval,
+            });
+        }
+        if poll_interval == 0 {
+            return Err(PicoError::InvalidInterval {
+                name: "poll_interval",
+                nanos: poll_interval,
+            });
+        }
+
+        if !(MIN_PAGE_COUNT..=MAX_PAGE_COUNT).contains(&self.page_count) {
+            return Err(PicoError::InvalidPageCount {
+                count: self.page_count,
+                min: MIN_PAGE_COUNT,
+                max: MAX_PAGE_COUNT,
+            });
+        }
+
+        if let OverflowPolicy::Grow { max_pages } = self.overflow_policy
+            && !(self.page_count..=MAX_PAGE_COUNT).contains(&max_pages)
+        {
+            return Err(PicoError::InvalidPageCount {
+                count: max_pages,
+                min: self.page_count,
+                max: MAX_PAGE_COUNT,
+            });
+        }
+
+        let ring_depth = self
+            .ring_depth
+            .unwrap_or((self.page_count as u32).next_power_of_two());
+        if !ring_depth.is_power_of_two() || ring_depth > MAX_RING_DEPTH {
+            return Err(PicoError::InvalidRingDepth {
+                depth: ring_depth,
+                max: MAX_RING_DEPTH,
+            });
+        }
+
+        if let Some(fraction) = self.flush_watermark
+            && !(fraction > 0.0 && fraction <= 1.0)
+        {
+            return Err(PicoError::InvalidWatermark { fraction });
+        }
+        if let Some((fraction, _)) = self.partial_rewrites
+            && !(fraction > 0.0 && fraction <= 1.0)
+        {
+            return Err(PicoError::InvalidFillLevel { fraction });
+        }
+
+        if let Some(tag) = &self.schema_tag
+            && tag.len() > MAX_SCHEMA_TAG_LEN
+        {
+            return Err(PicoError::SchemaTagTooLong {
+                len: tag.len(),
+                max: MAX_SCHEMA_TAG_LEN,
+            });
+        }
+
+        if !(WIDE_LEN_VERSION..=FORMAT_VERSION).contains(&self.format_version) {
+            return Err(PicoError::InvalidFormatVersion {
+                version: self.format_version,
+                min: WIDE_LEN_VERSION,
+                max: FORMAT_VERSION,
+            });
+        }
+
+        Ok(ring_depth)
+    }
+
+    /// Returns `true` if the worker writes pages from io_uring registered buffers.
+    ///
+    /// Registration happens in [`Logger::start`] and falls back to plain writes when the
+    /// kernel refuses it, for example because `RLIMIT_MEMLOCK` is too low.
+    pub fn uses_registered_buffers(&self) -> bool {
+        self.capabilities.registered_buffers
+    }
+
+    /// Returns `true` if the worker addresses the log file through an io_uring fixed file
+    /// instead of its raw descriptor.
+    ///
+    /// Kernels without file registration keep using the raw descriptor.
+    pub fn uses_fixed_file(&self) -> bool {
+        self.capabilities.fixed_files
+    }
+
+    /// Returns `true` if completed writes wake the idle worker through an eventfd
+    /// registered with its ring, rather than being picked up on its next poll.
+    ///
+    /// Without it, which is the case for [`Backend::Sync`] and kernels that refuse the
+    /// registration, durability lags by up to the poll interval.
+    pub fn wakes_on_completion(&self) -> bool {
+        self.capabilities.completion_wakeups
+    }
+
+    /// Returns `true` while the worker is running and able to persist entries.
+    ///
+    /// Turns `false` once the worker stops after an unrecoverable ring failure, or if its
+    /// thread died; from then on [`Logger::log`] returns `None`. Also `false` before
+    /// `start()`.
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Acquire)
+            && self
+                .worker_handle
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Returns where the logger is in its lifecycle.
+    pub fn state(&self) -> LoggerState {
+        if self.state == LoggerState::Running && !self.is_healthy() {
+            LoggerState::Stopped
+        } else {
+            self.state
         }
     }
 
+    /// Returns how the log file is being written.
+    ///
+    /// [`Logger::start`] falls back to [`IoMode::Buffered`] when the filesystem rejects
+    /// `O_DIRECT`, in which case writes are acknowledged once they reach the page cache.
+    pub fn io_mode(&self) -> IoMode {
+        self.io_mode
+    }
+
+    /// Takes the receiving end of the worker's error channel.
+    ///
+    /// The worker reports every page that fails to persist as a [`PicoError::WriteFailed`]
+    /// carrying the errno and the range of lost sequence IDs, so the application can, for
+    /// example, stop accepting requests on `ENOSPC`. Problems the logger works around, such
+    /// as a disabled index or options the kernel lacks, are reported here too; the library
+    /// never prints them. Returns `None` before `start()` or if the receiver was already
+    /// taken. If nobody drains the channel, errors beyond its capacity are dropped.
+    pub fn take_error_receiver(&mut self) -> Option<Receiver<PicoError>> {
+        self.error_receiver.take()
+    }
+
+    /// Returns a snapshot of the logger's runtime counters since `start()`.
+    ///
+    /// The counters are maintained with relaxed atomics, so calling this is cheap enough to
+    /// poll from a metrics exporter.
+    pub fn stats(&self) -> LoggerStats {
+        self.stats.snapshot(self.queued())
+    }
+
+    /// Estimates how many more bytes of pages the log has room for before writes start
+    /// failing with [`PicoError::DeviceFull`] or `ENOSPC`, to alarm on before they do.
+    ///
+    /// On a block device that is what is left of it past
+    /// [`LoggerStats::current_file_offset`]. A file also grows past its allocated length into
+    /// the free space of its file system, which other files share, so the estimate is what
+    /// is left of the allocated region plus that free space. With stripe paths, each stripe
+    /// is measured from its own next page offset and allocated length, and since pages go
+    /// to each stripe in turn, the one with the least room bounds them all.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<u64>, PicoError>` - The estimate in bytes, or `None` unless the
+    ///   logger is running. Fails with [`PicoError::Io`] if a file or its file system can't
+    ///   be inspected.
+    pub fn estimate_remaining_capacity(&self) -> Result<Option<u64>, PicoError> {
+        let Some(logpath) = self.logpath.as_deref() else {
+            return Ok(None);
+        };
+        if self.state != LoggerState::Running {
+            return Ok(None);
+        }
+        let paths = self.write_paths(logpath);
+        let mut least = u64::MAX;
+        for (stripe, path) in paths.iter().enumerate() {
+            let (file_len, offset) = self.stats.stripe_extent(stripe);
+            least = least.min(util::room_left(path, file_len, offset)?);
+        }
+        Ok(Some(least.saturating_mul(paths.len() as u64)))
+    }
+
+    /// Returns how many accepted entries have not yet reached the disk: those still queued for
+    /// the worker plus those sitting in pages that are filling or being written.
+    ///
+    /// Compare against the capacity passed at configuration time to shed load before
+    /// `log()` starts blocking.
+    pub fn pending_entries(&self) -> usize {
+        self.queued() + self.stats.unflushed_entries()
+    }
+
+    /// Entries published to the ring buffer the worker has not read yet, plus messages in
+    /// its channel.
+    fn queued(&self) -> usize {
+        let backlog = self
+            .data_buffer
+            .as_ref()
+            .map_or(0, |buffer| buffer.backlog());
+        backlog + self.inline_filling() + self.sender.as_ref().map_or(0, |sender| sender.len())
+    }
+
+    /// Returns how many entries were lost before reaching a page since `start()`: the
+    /// worker was gone when they were sent, a producer reused their ring buffer slot
+    /// before the worker read it, [`OverflowPolicy::DropNewest`] discarded them, or
+    /// [`Logger::shutdown_now`] did.
+    pub fn dropped_entries(&self) -> u64 {
+        self.stats.dropped_entries()
+    }
+
+    /// Returns how many pages the worker has flushed for each reason since `start()`.
+    pub fn flush_counts(&self) -> FlushCounts {
+        self.stats.flush_counts()
+    }
+
+    /// Returns `true` if the page pool lives in a huge-page-backed mapping.
+    pub fn uses_hugepages(&self) -> bool {
+        self.hugepages_active
+    }
+
+    /// Returns the write backend in use: the one requested with [`Logger::with_backend`], or
+    /// after `start()` with [`Backend::Auto`], whichever it resolved to.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Returns `true` if the worker's ring runs with a kernel SQ poll thread.
+    pub fn uses_sqpoll(&self) -> bool {
+        self.capabilities.sqpoll
+    }
+
+    /// Returns what the worker's write path ended up using once `start()` has checked what
+    /// the kernel supports: the backend, the io_uring features in effect, and which file
+    /// operations go through the ring rather than running inline.
+    ///
+    /// Where the kernel lacks something that was asked for, such as SQPOLL, or io_uring
+    /// writes under [`Backend::Auto`], `start()` carries on without it and sends a single
+    /// [`PicoError::OptionsDowngraded`] naming everything that was dropped to the
+    /// [error channel](Logger::take_error_receiver).
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     /// Configures the logger for reading logs.
     ///
     /// # Arguments
     ///
     /// * `logpath` - Path to the log file to read from.
-    pub fn with_read_config(mut self, logpath: String) -> Self {
-        self.logpath = Some(logpath);
+    pub fn with_read_config(mut self, logpath: impl AsRef<Path>) -> Self {
+        self.logpath = Some(logpath.as_ref().to_path_buf());
         self
     }
 
     /// Reads all log entries from the configured log file.
     ///
+    /// A running logger can read its own file this way. Reading stops at the newest entry
+    /// the logger has made durable (see [`Logger::get_last_flushed_entry`]): entries still
+    /// in memory or in flight are left out, as is anything past them, such as a page the
+    /// worker is writing.
+    ///
     /// # Returns
     ///
-    /// * `Result<Vec<T>, Error>` - A vector of log data if successful, or an error.
-    pub fn read(&self) -> Result<Vec<T>, Error> {
-        let logpath = self.logpath.as_ref().ok_or(Error::new(
-            std::io::ErrorKind::NotFound,
-            "Log path not configured",
-        ))?;
-        let mut file = std::fs::File::open(logpath)?;
-        let mut vec = Vec::new();
-        let path = PathBuf::from(logpath);
-        let blk_size = get_blksize(&path) as usize;
+    /// * `Result<Vec<T>, PicoError>` - A vector of log data if successful, or an error. A file
+    ///   whose header records a different payload type fails with
+    ///   [`PicoError::SchemaMismatch`].
+    pub fn read(&self) -> Result<Vec<T>, PicoError> {
+        let entries: Result<_, Error> = self
+            .read_iter()?
+            .map(|entry| entry.map(|e| e.data))
+            .collect();
+        Ok(entries?)
+    }
 
-        let mut buffer = vec![0u8; blk_size];
+    /// Reads all log entries like [`Logger::read`], but reinterprets them as `T` even if the
+    /// file header records a different payload type.
+    ///
+    /// Meant for migrating files whose type changed without changing layout, or that were
+    /// tagged under an old name. Entries longer than a `T` are still skipped, and shorter ones
+    /// still fail the read.
+    pub fn read_unchecked(&self) -> Result<Vec<T>, PicoError> {
+        let entries: Result<_, Error> = LogReader::new(self.open_raw()?, self.payload_codec.decode)
+            .durable_up_to(self.durable_bound())
+            .map(|entry| entry.map(|e| e.data))
+            .collect();
+        Ok(entries?)
+    }
 
-        loop {
-            let bytes_read = file.read(&mut buffer)?;
-            if bytes_read == 0 {
-                break;
+    /// Reads all log entries like [`Logger::read`], converting those of a file written with
+    /// an older payload type `Old` through `migrate`.
+    ///
+    /// A file whose header records `T` is read as is. One recorded with the size and
+    /// alignment of `Old` instead is read as `Old`, whatever its schema tag, and every entry
+    /// passed through `migrate`. Legacy files without a header are read as `T`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<T>, Error>` - The entries, converted where needed, or
+    ///   [`PicoError::SchemaMismatch`] if the file matches neither `T` nor `Old`.
+    pub fn read_with_migration<Old: Copy>(
+        &self,
+        migrate: impl Fn(Old) -> T,
+    ) -> Result<Vec<T>, Error> {
+        let raw = self.open_raw()?;
+        let durable = self.durable_bound();
+        match raw.schema().cloned() {
+            Some(found) if !self.schema().accepts(&found) => {
+                if !Schema::of::<Old>(None).accepts(&found) {
+                    return Err(PicoError::SchemaMismatch {
+                        expected: self.schema(),
+                        found,
+                    }
+                    .into());
+                }
+                LogReader::new(raw, decode_raw::<Old>)
+                    .durable_up_to(durable)
+                    .map(|entry| entry.map(|e| migrate(e.data)))
+                    .collect()
             }
+            _ => LogReader::new(raw, self.payload_codec.decode)
+                .durable_up_to(durable)
+                .map(|entry| entry.map(|e| e.data))
+                .collect(),
+        }
+    }
 
-            let mut cursor = 0;
-            while cursor < bytes_read {
-                if cursor + std::mem::size_of::<EntryHeader>() > bytes_read {
-                    break;
-                }
+    /// Opens a streaming reader over the configured log file.
+    ///
+    /// Unlike [`Logger::read`], entries are decoded one page at a time and carry their
+    /// sequence ID and timestamp. On a running logger, the reader ends at the newest entry
+    /// made durable when it was opened, as [`Logger::read`] does.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LogReader<T>, Error>` - The reader, or an error if the file cannot be opened.
+    pub fn read_iter(&self) -> Result<LogReader<T>, Error> {
+        Ok(
+            LogReader::new(self.raw_reader()?, self.payload_codec.decode)
+                .durable_up_to(self.durable_bound()),
+        )
+    }
 
-                let header_ptr = unsafe { buffer.as_ptr().add(cursor) as *const EntryHeader };
-                let header = unsafe { ptr::read_unaligned(header_ptr) };
+    /// Seq_id of the newest entry this logger has made durable, if it is running and may
+    /// still be writing past it.
+    fn durable_bound(&self) -> Option<u64> {
+        (self.state == LoggerState::Running).then(|| self.stats.flushed_seq_id())
+    }
 
-                if header.len == 0 {
-                    break;
-                }
+    /// Reads the entries at `min_level` or above.
+    ///
+    /// Entries of files written before levels were recorded are all [`Level::Info`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LogEntry<T>>, Error>` - The matching entries in file order, or an error
+    ///   if the file cannot be read or was written with a different payload type.
+    pub fn read_filtered(&self, min_level: Level) -> Result<Vec<LogEntry<T>>, Error> {
+        self.read_iter()?
+            .filter(|entry| entry.as_ref().map_or(true, |e| e.level >= min_level))
+            .collect()
+    }
 
-                let msg_size = header.len as usize;
-                let header_size = std::mem::size_of::<EntryHeader>();
-                let total_size = header_size + msg_size;
-                let aligned_size = (total_size + 7) & !7;
+    /// Reads the entries whose sequence IDs fall within `range`.
+    ///
+    /// The sidecar index, if present, is used to jump to the first matching page.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Half-open range of sequence IDs to read.
+    pub fn read_range(&self, range: Range<u64>) -> Result<Vec<LogEntry<T>>, Error> {
+        let mut reader = self.read_iter()?;
+        reader.seek(range.start)?;
+        let mut entries = Vec::new();
+        for entry in reader {
+            let entry = entry?;
+            if entry.seq_id >= range.end {
+                break;
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
 
-                if cursor + total_size > bytes_read {
-                    break;
-                }
+    fn log_path(&self) -> Result<&Path, Error> {
+        self.logpath.as_deref().ok_or(Error::new(
+            std::io::ErrorKind::NotFound,
+            "Log path not configured",
+        ))
+    }
 
-                let data_ptr = unsafe { buffer.as_ptr().add(cursor + header_size) as *const T };
-                let data = unsafe { ptr::read_unaligned(data_ptr) };
-                vec.push(data);
+    /// The payload type this logger writes, and expects when reading.
+    fn schema(&self) -> Schema {
+        Schema::of::<T>(self.schema_tag.as_deref())
+    }
 
-                cursor += aligned_size;
+    /// Fails with [`PicoError::SchemaMismatch`] if a file recorded as `found` cannot be read
+    /// as `T`. Legacy files without a header are always accepted.
+    fn check_schema(&self, found: Option<&Schema>) -> Result<(), Error> {
+        let expected = self.schema();
+        match found {
+            Some(found) if !expected.accepts(found) => Err(PicoError::SchemaMismatch {
+                expected,
+                found: found.clone(),
             }
+            .into()),
+            _ => Ok(()),
         }
-        Ok(vec)
+    }
+
+    fn open_raw(&self) -> Result<RawReader, Error> {
+        self.open_raw_at(self.log_path()?)
+    }
+
+    fn open_raw_at(&self, path: &Path) -> Result<RawReader, Error> {
+        Ok(RawReader::open_with(path, self.codec(), &self.read_options)?.strict(self.strict_reads))
+    }
+
+    fn raw_reader(&self) -> Result<RawReader, Error> {
+        let reader = self.open_raw()?;
+        self.check_schema(reader.schema())?;
+        Ok(reader)
     }
 
     /// Adds a new log entry to the buffer.
@@ -256,44 +1977,527 @@ impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
     ///
     /// # Returns
     ///
-    /// * `Option<u64>` - The sequence ID of the log entry if successful, or `None` if the logger is not started.
+    /// * `Option<u64>` - The sequence ID of the log entry if successful, or `None` if the logger is not started,
+    ///   its worker has stopped, or the ring buffer is full under [`BufferFullPolicy::Reject`].
+    ///   Use [`Logger::try_log`] to find out which.
     pub fn log(&mut self, data: T) -> Option<u64> {
-        if let Some(sender) = &self.sender {
-            let seq_id = next_seq_id();
-            let index = (seq_id as usize) % self.capacity;
-
-            if let Some(data_buffer) = &self.data_buffer {
-                unsafe {
-                    let ptr = data_buffer.inner[index].get();
-                    (*ptr).seq_id = seq_id;
-                    (*ptr).data = data;
+        self.try_log(data).ok()
+    }
+
+    /// Adds a new log entry to the buffer, returning its sequence ID.
+    ///
+    /// Fails with [`PicoError::NotStarted`] before `start()` and with
+    /// [`PicoError::WorkerStopped`] once the worker has stopped (see [`Logger::is_healthy`]).
+    /// Under [`BufferFullPolicy::Reject`] it fails with [`PicoError::BufferFull`] while every
+    /// slot holds an entry the worker has not consumed, and under
+    /// [`BufferFullPolicy::Block`] it waits for a slot instead.
+    pub fn try_log(&mut self, data: T) -> Result<u64, PicoError> {
+        self.try_log_with_level(data, Level::Info)
+    }
+
+    /// Adds a new log entry at `level` to the buffer.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The sequence ID of the log entry if successful, or `None` if the
+    ///   logger is not started, its worker has stopped, or `level` is below the one set with
+    ///   [`Logger::with_min_level`]. Use [`Logger::try_log_with_level`] to find out which.
+    pub fn log_with_level(&mut self, data: T, level: Level) -> Option<u64> {
+        self.try_log_with_level(data, level).ok()
+    }
+
+    /// Adds a new log entry at `level` to the buffer, returning its sequence ID.
+    ///
+    /// Fails like [`Logger::try_log`], and with [`PicoError::LevelFiltered`] if `level` is
+    /// below the one set with [`Logger::with_min_level`].
+    pub fn try_log_with_level(&mut self, data: T, level: Level) -> Result<u64, PicoError> {
+        self.check_running()?;
+        self.check_level(level)?;
+        let started = self.latency_start();
+        if self.inline.is_some() {
+            let level = self.level_byte(level);
+            let encode = self.payload_codec.encode;
+            let seq_ids = self.log_inline(1, started, |page, _, seq_id, now| {
+                page.append_bytes(seq_id, now, level, encode(&data))
+            })?;
+            return Ok(seq_ids.start);
+        }
+        let index = self.claim_slots(1)?;
+        let Some(data_buffer) = &self.data_buffer else {
+            return Err(PicoError::NotStarted);
+        };
+        unsafe { (*data_buffer.entry(index)).data = data };
+        self.publish_slot(index, level, next_seq_id(), started)
+    }
+
+    /// Returns the index of the next ring buffer slot, making sure the `count` slots from
+    /// there can be filled as the buffer-full policy says.
+    fn claim_slots(&mut self, count: usize) -> Result<usize, PicoError> {
+        let Some(data_buffer) = &self.data_buffer else {
+            return Err(PicoError::NotStarted);
+        };
+        let index = (self.next_slot % self.capacity as u64) as usize;
+        // The worker consumes slots in the order they are filled, so once the last of them
+        // is free, so are the ones before it.
+        let last = (index + count - 1) % self.capacity;
+        match self.buffer_full_policy {
+            // A batch never laps the worker: its entries must all be read, or all be lost.
+            BufferFullPolicy::Overwrite
+                if count > 1
+                    && self.next_slot + count as u64
+                        > data_buffer.consumed.load(Ordering::Acquire) + self.capacity as u64 =>
+            {
+                return Err(PicoError::BufferFull);
+            }
+            BufferFullPolicy::Overwrite => {}
+            BufferFullPolicy::Reject if !data_buffer.is_free(last) => {
+                return Err(PicoError::BufferFull);
+            }
+            BufferFullPolicy::Reject => {}
+            BufferFullPolicy::Block => {
+                let backoff = Backoff::new();
+                while !data_buffer.is_free(last) {
+                    if !self.healthy.load(Ordering::Relaxed) {
+                        self.state = LoggerState::Stopped;
+                        return Err(PicoError::WorkerStopped);
+                    }
+                    if backoff.is_completed() {
+                        thread::sleep(SLOT_WAIT_SLEEP);
+                    } else {
+                        backoff.snooze();
+                    }
                 }
             }
+        }
+        Ok(index)
+    }
+
+    /// Adds `items` to the buffer as consecutive entries, publishing them to the worker in
+    /// one step.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Range<u64>>` - The sequence IDs of the entries, or `None` if the logger is
+    ///   not started, its worker has stopped, or the batch is larger than the ring buffer.
+    ///   Use [`Logger::try_log_batch`] to find out which.
+    pub fn log_batch(&mut self, items: &[T]) -> Option<Range<u64>> {
+        self.try_log_batch(items).ok()
+    }
+
+    /// Adds `items` to the buffer as consecutive entries with consecutive sequence IDs.
+    ///
+    /// Fails like [`Logger::try_log`], and with [`PicoError::BatchTooLarge`] if `items` has
+    /// more entries than the ring buffer has slots; split such batches up. An empty batch
+    /// is accepted and returns an empty range. The entries are logged at [`Level::Info`].
+    ///
+    /// Under [`BufferFullPolicy::Overwrite`] a batch never overwrites entries the worker
+    /// has not read: if the slots it needs aren't free yet it fails with
+    /// [`PicoError::BufferFull`] instead, without logging any of `items`.
+    ///
+    /// In [inline mode](Logger::start_inline) batches of any size are accepted and may
+    /// span pages. Their seq_ids are taken before the first entry is appended, so a batch
+    /// that runs out of pages part way through (or whose worker stops) is cut short rather
+    /// than failing: the returned range then covers only the first entries of `items`, the
+    /// rest are counted as dropped, and their seq_ids are skipped. It fails only if none of
+    /// `items` was logged.
+    pub fn try_log_batch(&mut self, items: &[T]) -> Result<Range<u64>, PicoError> {
+        self.check_running()?;
+        self.check_level(Level::Info)?;
+        if self.inline.is_some() {
+            if items.is_empty() {
+                return Ok(0..0);
+            }
+            let started = self.latency_start();
+            let level = self.level_byte(Level::Info);
+            let encode = self.payload_codec.encode;
+            return self.log_inline(items.len(), started, |page, i, seq_id, now| {
+                page.append_bytes(seq_id, now, level, encode(&items[i]))
+            });
+        }
+        if items.len() > self.capacity {
+            return Err(PicoError::BatchTooLarge {
+                len: items.len(),
+                capacity: self.capacity,
+            });
+        }
+        if items.is_empty() {
+            return Ok(0..0);
+        }
+        let started = self.latency_start();
+        let index = self.claim_slots(items.len())?;
+        let level = self.level_byte(Level::Info);
+        let Some(data_buffer) = &self.data_buffer else {
+            return Err(PicoError::NotStarted);
+        };
+
+        let first_seq_id = global::next_seq_ids(items.len() as u64);
+        for (i, item) in items.iter().enumerate() {
+            let slot = (index + i) % self.capacity;
+            let ptr = data_buffer.entry(slot);
+            unsafe {
+                (*ptr).seq_id = first_seq_id + i as u64;
+                (*ptr).data = *item;
+            }
+            data_buffer.mark_written(slot, self.next_slot + i as u64, level);
+        }
+        self.next_slot += items.len() as u64;
+
+        if self.announce().is_err() {
+            self.stats.record_dropped_n(items.len());
+            self.state = LoggerState::Stopped;
+            return Err(PicoError::WorkerStopped);
+        }
+        self.stats.record_logged_n(items.len());
+        let seq_ids = first_seq_id..first_seq_id + items.len() as u64;
+        self.record_latency(started, seq_ids.end - 1, items.len() as u64);
+        #[cfg(feature = "async")]
+        {
+            self.last_seq_id = Some(seq_ids.end - 1);
+        }
+        Ok(seq_ids)
+    }
+
+    /// Gives the entry already written into slot `index` sequence ID `seq_id` and hands it
+    /// to the worker, on behalf of a call that started at `started` when tracking latency.
+    fn publish_slot(
+        &mut self,
+        index: usize,
+        level: Level,
+        seq_id: u64,
+        started: Option<u64>,
+    ) -> Result<u64, PicoError> {
+        let level = self.level_byte(level);
+        let Some(data_buffer) = &self.data_buffer else {
+            return Err(PicoError::NotStarted);
+        };
+
+        unsafe { (*data_buffer.entry(index)).seq_id = seq_id };
+        data_buffer.mark_written(index, self.next_slot, level);
+        self.next_slot += 1;
+
+        if self.announce().is_err() {
+            self.stats.record_dropped();
+            self.state = LoggerState::Stopped;
+            return Err(PicoError::WorkerStopped);
+        }
+        self.stats.record_logged();
+        self.record_latency(started, seq_id, 1);
+        #[cfg(feature = "async")]
+        {
+            // An id from a reserved block may be older than entries logged before it.
+            self.last_seq_id = self.last_seq_id.max(Some(seq_id));
+        }
+        Ok(seq_id)
+    }
+
+    /// Publishes every entry written into the ring so far to the worker, waking it if it
+    /// is blocked waiting for work. Fails if the worker has stopped.
+    fn announce(&self) -> Result<(), PicoError> {
+        let (Some(sender), Some(data_buffer)) = (&self.sender, &self.data_buffer) else {
+            return Err(PicoError::NotStarted);
+        };
+        if data_buffer.publish(self.next_slot) {
+            sender.wake();
+        }
+        // The worker stops reading the ring before it clears the flag.
+        if !self.healthy.load(Ordering::Acquire) {
+            return Err(PicoError::WorkerStopped);
+        }
+        Ok(())
+    }
+
+    /// Asks the worker to write out the partially filled active page now rather than when the
+    /// flush interval elapses.
+    ///
+    /// Returns once the request is queued behind entries already logged; it does not wait
+    /// for the write to complete. Watch [`Logger::get_last_flushed_entry`] or a
+    /// [flush callback](Logger::with_flush_callback) for durability. In
+    /// [inline mode](Logger::start_inline) it hands the page being filled to the worker.
+    pub fn flush(&mut self) -> Result<(), PicoError> {
+        self.check_running()?;
+        if self.inline.is_some() {
+            return self.flush_inline();
+        }
+        let sender = self.sender.as_ref().ok_or(PicoError::NotStarted)?;
+        if sender.send(Message::Flush).is_err() {
+            self.state = LoggerState::Stopped;
+            return Err(PicoError::WorkerStopped);
+        }
+        Ok(())
+    }
+
+    /// Returns a channel that receives the durable watermark, the value
+    /// [`Logger::get_last_flushed_entry`] would return, each time it advances.
+    ///
+    /// Meant for consumers that would otherwise poll, for example to know when to ask a
+    /// [`Cursor`] for its next batch. Updates are coalesced: a receiver that falls behind
+    /// sees at most one outdated value before the latest, never a backlog. The channel
+    /// disconnects when the worker exits, and right away if the logger isn't running.
+    /// Dropping the receiver just unsubscribes it.
+    pub fn subscribe(&self) -> Receiver<u64> {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        if self.state == LoggerState::Running
+            && let Some(worker) = &self.sender
+        {
+            let _ = worker.send(Message::Subscribe(sender));
+        }
+        receiver
+    }
+
+    /// Returns copies of the entries logged so far that are not durable yet, in seq_id order:
+    /// those waiting in the ring buffer, in the page being filled and in page writes still
+    /// in flight.
+    ///
+    /// Meant for crash diagnostics, e.g. dumping what the last few milliseconds logged from
+    /// a panic hook before the process aborts. The request queues behind entries already
+    /// logged, so by the time the worker answers, every one of them has left the ring buffer
+    /// for a page. With a compressing or encrypting codec, pages already handed to the disk
+    /// cannot be copied back, so only the page being filled is included. Entries whose size
+    /// is not that of `T` are left out.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LogMessage<T>>, PicoError>` - The entries, or an error if the logger is
+    ///   not running or its worker does not answer within a second
+    ///   ([`PicoError::SnapshotTimedOut`]).
+    pub fn pending_snapshot(&self) -> Result<Vec<LogMessage<T>>, PicoError> {
+        match self.state {
+            LoggerState::Configured => return Err(PicoError::NotStarted),
+            LoggerState::Stopped => return Err(PicoError::WorkerStopped),
+            LoggerState::ShutDown => return Err(PicoError::ShutDown),
+            LoggerState::Running => {}
+        }
+        let sender = self.sender.as_ref().ok_or(PicoError::NotStarted)?;
+        let (reply, receiver) = crossbeam_channel::bounded(1);
+        if sender.send(Message::Snapshot(reply)).is_err() {
+            return Err(PicoError::WorkerStopped);
+        }
+        let entries = receiver
+            .recv_timeout(SNAPSHOT_TIMEOUT)
+            .map_err(|e| match e {
+                crossbeam_channel::RecvTimeoutError::Timeout => PicoError::SnapshotTimedOut {
+                    timeout: SNAPSHOT_TIMEOUT,
+                },
+                crossbeam_channel::RecvTimeoutError::Disconnected => PicoError::WorkerStopped,
+            })?;
+        Ok(entries
+            .into_iter()
+            .filter(|(_, payload)| payload.len() == size_of::<T>())
+            .map(|(seq_id, payload)| LogMessage {
+                seq_id,
+                data: unsafe { std::ptr::read_unaligned(payload.as_ptr() as *const T) },
+            })
+            .collect())
+    }
+
+    /// Fails with [`PicoError::LevelFiltered`] if entries at `level` are to be dropped.
+    fn check_level(&self, level: Level) -> Result<(), PicoError> {
+        if self.level_enabled(level) {
+            Ok(())
+        } else {
+            Err(PicoError::LevelFiltered {
+                level,
+                min: self.min_level,
+            })
+        }
+    }
 
-            let _ = sender.send(index);
-            return Some(seq_id);
+    /// The byte recorded for `level` in entry headers, which stays 0 in formats without one.
+    fn level_byte(&self, level: Level) -> u8 {
+        if self.format_version >= LEVEL_VERSION {
+            level as u8
+        } else {
+            0
         }
-        None
+    }
+
+    /// Returns an error unless the logger is running, noticing a worker that has stopped since
+    /// the last call.
+    fn check_running(&mut self) -> Result<(), PicoError> {
+        if self.state == LoggerState::Running && !self.healthy.load(Ordering::Relaxed) {
+            self.state = LoggerState::Stopped;
+        }
+        match self.state {
+            LoggerState::Configured => Err(PicoError::NotStarted),
+            LoggerState::Running => Ok(()),
+            LoggerState::Stopped => Err(PicoError::WorkerStopped),
+            LoggerState::ShutDown => Err(PicoError::ShutDown),
+        }
+    }
+
+    /// Sends an already-encoded, variable-length entry to the worker.
+    ///
+    /// The bytes bypass the ring buffer, so this path allocates and is meant for
+    /// payloads that cannot be expressed as a fixed-size `T`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn log_bytes(&mut self, bytes: Vec<u8>) -> Result<u64, PicoError> {
+        self.check_running()?;
+        self.check_level(Level::Info)?;
+        let started = self.latency_start();
+        let sender = self.sender.as_ref().ok_or(PicoError::NotStarted)?;
+        if bytes.is_empty() {
+            return Err(PicoError::EmptyEntry);
+        }
+        let max = (self.page_capacity - format::ENTRY_HEADER_LEN).min(MAX_ENTRY_LEN);
+        if bytes.len() > max {
+            return Err(PicoError::EntryTooLarge {
+                size: bytes.len(),
+                max,
+            });
+        }
+
+        if self.inline.is_some() {
+            let level = self.level_byte(Level::Info);
+            let seq_ids = self.log_inline(1, started, |page, _, seq_id, now| {
+                page.append_bytes(seq_id, now, level, &bytes)
+            })?;
+            return Ok(seq_ids.start);
+        }
+        let seq_id = next_seq_id();
+        let message = Message::Encoded {
+            seq_id,
+            level: self.level_byte(Level::Info),
+            position: self.next_slot,
+            bytes,
+        };
+        if sender.send(message).is_err() {
+            self.stats.record_dropped();
+            self.state = LoggerState::Stopped;
+            return Err(PicoError::WorkerStopped);
+        }
+        self.stats.record_logged();
+        self.record_latency(started, seq_id, 1);
+        #[cfg(feature = "async")]
+        {
+            self.last_seq_id = Some(seq_id);
+        }
+        Ok(seq_id)
     }
 
     /// Retrieves the sequence ID of the last log entry that was successfully flushed to disk.
     ///
+    /// Sequence IDs start at 1, so an entry `seq_id` is durable once this returns at least
+    /// `seq_id`, and 0 means nothing has been flushed yet.
+    ///
+    /// Page writes may complete out of order, but the watermark only moves past a page once
+    /// every page queued before it has completed too, so no entry at or below it is still
+    /// missing from the file. A page whose write fails for good, reported as
+    /// [`PicoError::WriteFailed`], holds the watermark of its logger back from then on, and
+    /// so does a seq_id from [`Logger::reserve_seq_block`] until it is durable or abandoned.
+    ///
+    /// This is the furthest any logger in the process has got, so with more than one
+    /// running, another logger can move it past entries of this one that are held back
+    /// or not written yet. Those guarantees only hold for a logger's own watermark,
+    /// [`Logger::stats`]`().flushed_seq_id`.
+    ///
     /// # Returns
     ///
-    /// * `u64` - The sequence ID.
+    /// * `u64` - The sequence ID, or 0 before the first flush.
     pub fn get_last_flushed_entry() -> u64 {
         global::get_ack_number()
     }
+
+    /// Retrieves the sequence ID of the last log entry known to be on stable storage, past
+    /// any volatile device cache.
+    ///
+    /// Under [`Durability::Fdatasync`] and [`Durability::Odsync`] this follows
+    /// [`Logger::get_last_flushed_entry`]. Under [`Durability::None`] it only advances with
+    /// the full syncs of a [`SyncPolicy`], and stays at 0 without one.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The sequence ID, or 0 before the first entry is hardened.
+    pub fn get_last_hardened_entry() -> u64 {
+        global::get_hardened_number()
+    }
 }
 
-impl<T> Drop for Logger<T> {
-    fn drop(&mut self) {
-        if let Some(sender) = self.sender.take() {
-            drop(sender);
-        }
+impl<T: Send + Sync + Default + Copy + 'static> Default for Logger<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Logger<T> {
+    /// Stops the worker after it has written out everything logged so far, and waits for it
+    /// to exit.
+    ///
+    /// Dropping the logger does the same but cannot report the outcome. Waiting on the disk
+    /// is bounded by [`Logger::with_shutdown_timeout`]; entries still not written by then are
+    /// reported as lost. A worker stuck in a wait it can't cut short is given a second more
+    /// to answer, after which this returns anyway and leaks the worker thread: it is never
+    /// joined, and keeps its ring, pages and file open until its wait returns or the process
+    /// exits. Logging afterwards fails with [`PicoError::ShutDown`], and calling this again,
+    /// or on a logger that was never started, returns `Ok(())`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), PicoError>` - `Ok(())` once the worker has exited, or
+    ///   [`PicoError::ShutdownTimedOut`] with the first seq_id that may not have been written.
+    pub fn shutdown(&mut self) -> Result<(), PicoError> {
+        self.stop_worker(true).map(|_| ())
+    }
 
-        if let Some(handle) = self.worker_handle.take() {
-            let _ = handle.join();
+    /// Stops the worker without writing out the entries it hasn't started writing yet,
+    /// and waits for it to exit.
+    ///
+    /// Entries still in the ring buffer or the partially filled page are discarded and
+    /// counted in [`Logger::dropped_entries`]. The writes already in flight are still
+    /// waited for, within [`Logger::with_shutdown_timeout`], since the kernel may be reading
+    /// their pages. Otherwise this behaves like [`Logger::shutdown`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, PicoError>` - Once the worker has exited, the seq_id of this logger's
+    ///   last durable entry, past which everything was discarded, or 0 if none is. Fails with
+    ///   [`PicoError::ShutdownTimedOut`] if the writes in flight didn't complete in time.
+    pub fn shutdown_now(&mut self) -> Result<u64, PicoError> {
+        self.stop_worker(false)
+    }
+
+    /// Asks the worker to stop, draining or not, and waits for it to acknowledge.
+    ///
+    /// The worker bounds its own wait on the disk by the shutdown timeout, but some waits,
+    /// such as a polled ring's or a ring on a kernel without timed waits, can't be cut
+    /// short. So the caller stops waiting for the acknowledgement a grace period after the
+    /// timeout, and leaves the thread behind instead of joining it.
+    fn stop_worker(&mut self, drain: bool) -> Result<u64, PicoError> {
+        if let Some(panic_flush) = self.panic_flush.take() {
+            panic_flush.disarm();
         }
+        self.release_inline_pages(drain);
+        let sender = self.sender.take();
+        let Some(handle) = self.worker_handle.take() else {
+            return Ok(self.stats.flushed_seq_id());
+        };
+        self.state = LoggerState::ShutDown;
+        // Other copies of the mailbox, like one a panic hook is using, can't keep the worker
+        // running, since it stops on the message rather than on the channel closing.
+        let (reply, acknowledged) = crossbeam_channel::bounded(1);
+        let sent =
+            sender.is_some_and(|sender| sender.send(Message::Shutdown { drain, reply }).is_ok());
+        let mut watermark = None;
+        if sent {
+            let wait = self.shutdown_timeout.saturating_add(SHUTDOWN_ACK_GRACE);
+            match acknowledged.recv_timeout(wait) {
+                Ok(seq_id) => watermark = Some(seq_id),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(PicoError::ShutdownTimedOut {
+                        lost_from_seq: self.stats.flushed_seq_id() + 1,
+                    });
+                }
+                // The worker exited without handling the message, having failed or panicked.
+                Err(RecvTimeoutError::Disconnected) => {}
+            }
+        }
+        // A panicked worker already lost whatever it held; the panic itself was printed.
+        handle.join().unwrap_or(Ok(()))?;
+        Ok(watermark.unwrap_or_else(|| self.stats.flushed_seq_id()))
+    }
+}
+
+impl<T> Drop for Logger<T> {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
     }
 }
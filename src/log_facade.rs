@@ -0,0 +1,192 @@
+//! Optional adapter routing the `log` crate's macros into a picolog file.
+//!
+//! Each record is formatted into a fixed-size [`LogRecord`] without allocating, so
+//! `log::info!` goes through the same ring buffer and page path as any other entry.
+
+use crate::Logger;
+use crate::export::{RowWriter, ToRow};
+use crate::text::{fnv1a, stored_str, write_truncated};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt::{self, Write};
+use std::io::Error;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MESSAGE_CAPACITY: usize = 108;
+
+/// A `log` record flattened into a fixed-size entry.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LogRecord {
+    /// Wall-clock time the record was logged, in nanoseconds since the Unix epoch.
+    pub ts_nanos: u64,
+    /// FNV-1a hash of the record's target, which is usually its module path.
+    pub target_hash: u64,
+    level: u8,
+    truncated: u8,
+    len: u16,
+    message: [u8; MESSAGE_CAPACITY],
+}
+
+impl Default for LogRecord {
+    fn default() -> Self {
+        Self {
+            ts_nanos: 0,
+            target_hash: 0,
+            level: 0,
+            truncated: 0,
+            len: 0,
+            message: [0; MESSAGE_CAPACITY],
+        }
+    }
+}
+
+impl LogRecord {
+    /// Bytes of formatted message kept per record; longer messages are truncated.
+    pub const MESSAGE_CAPACITY: usize = MESSAGE_CAPACITY;
+
+    /// Formats `record` into a fixed-size entry, truncating the message at a character
+    /// boundary if it does not fit.
+    pub fn from_record(record: &Record) -> Self {
+        let mut entry = Self {
+            ts_nanos: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64),
+            target_hash: fnv1a(record.target().as_bytes()),
+            level: record.level() as u8,
+            ..Default::default()
+        };
+        let _ = entry.write_fmt(*record.args());
+        entry
+    }
+
+    /// Returns the record's level, or `None` for an entry that was not written by the adapter.
+    pub fn level(&self) -> Option<Level> {
+        [
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ]
+        .into_iter()
+        .find(|&level| level as u8 == self.level)
+    }
+
+    /// Returns the stored message, possibly truncated.
+    pub fn message(&self) -> &str {
+        stored_str(&self.message, self.len)
+    }
+
+    /// Returns `true` if the message was longer than [`LogRecord::MESSAGE_CAPACITY`] bytes.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated != 0
+    }
+}
+
+impl Write for LogRecord {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !write_truncated(&mut self.message, &mut self.len, s) {
+            self.truncated = 1;
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LogRecord {
+    /// Formats the record as `<ts_nanos> <LEVEL> <target hash> <message>`, marking truncated
+    /// messages with a trailing `…`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.level().map_or("?", |level| level.as_str());
+        write!(
+            f,
+            "{} {:<5} {:016x} {}",
+            self.ts_nanos,
+            level,
+            self.target_hash,
+            self.message()
+        )?;
+        if self.is_truncated() {
+            f.write_char('…')?;
+        }
+        Ok(())
+    }
+}
+
+impl ToRow for LogRecord {
+    fn columns() -> &'static [&'static str] {
+        &["level", "target_hash", "message", "truncated"]
+    }
+
+    fn write_row(&self, row: &mut RowWriter<'_>) -> Result<(), Error> {
+        row.field(self.level().map_or("?", |level| level.as_str()))?;
+        row.field(format_args!("{:016x}", self.target_hash))?;
+        row.field(self.message())?;
+        row.field(self.is_truncated())
+    }
+}
+
+/// Implements [`log::Log`] on top of a started [`Logger<LogRecord>`].
+///
+/// The logger sits behind a mutex because `log::Log` is called through `&self` from any
+/// thread. As the global logger it is never dropped, so call `log::logger().flush()` during
+/// shutdown to write out the last partially filled page.
+pub struct PicoLogAdapter {
+    logger: Mutex<Logger<LogRecord>>,
+    level: LevelFilter,
+}
+
+impl PicoLogAdapter {
+    /// Wraps `logger`, accepting records at `level` and above.
+    pub fn new(logger: Logger<LogRecord>, level: LevelFilter) -> Self {
+        Self {
+            logger: Mutex::new(logger),
+            level,
+        }
+    }
+
+    /// Installs the adapter as the global `log` logger and sets the maximum level to match.
+    pub fn init(self) -> Result<(), log::SetLoggerError> {
+        let level = self.level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for PicoLogAdapter {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogRecord::from_record(record);
+        if let Ok(mut logger) = self.logger.lock() {
+            logger.log(entry);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut logger) = self.logger.lock() {
+            let _ = logger.flush();
+        }
+    }
+}
+
+impl Logger<LogRecord> {
+    /// Reads the configured log file and formats each record as a line of text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, Error>` - One line per record, in the format of
+    ///   [`LogRecord`]'s `Display` implementation.
+    pub fn read_text(&self) -> Result<Vec<String>, Error> {
+        self.read_iter()?
+            .map(|entry| entry.map(|e| e.data.to_string()))
+            .collect()
+    }
+}
@@ -0,0 +1,157 @@
+//! A description of the writer stored in the file header block: which host and process
+//! produced the file, and tags set by the application.
+//!
+//! The block follows the [`FileHeader`] in the same block as it, and the header records its
+//! length, so it never takes a page of its own. Files without it record a length of 0.
+
+use crate::Logger;
+use crate::header::FileHeader;
+use crate::util::{hostname, process_start_nanos};
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read};
+use std::mem::size_of;
+use std::path::Path;
+
+/// Most bytes the encoded metadata may take, on any block size. Blocks too small to hold
+/// this much after the file header lower the limit further.
+pub const MAX_METADATA_LEN: usize = 2048;
+
+/// Who wrote a log file, as returned by [`Logger::read_metadata`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Host name of the machine the file was written on.
+    pub hostname: String,
+    /// ID of the writing process.
+    pub pid: u32,
+    /// When the writing process started, in nanoseconds since the Unix epoch; 0 where the
+    /// platform doesn't tell.
+    pub process_start_nanos: u64,
+    /// Version of picologger the file was written with.
+    pub crate_version: String,
+    /// The key/value pairs set with [`Logger::with_metadata`], in the order they were set.
+    pub tags: Vec<(String, String)>,
+}
+
+impl FileMetadata {
+    /// Describes the current process, with `tags` added.
+    pub(crate) fn current(tags: &[(String, String)]) -> Self {
+        Self {
+            hostname: hostname(),
+            pid: std::process::id(),
+            process_start_nanos: process_start_nanos(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            tags: tags.to_vec(),
+        }
+    }
+
+    /// Encodes the metadata: the pid and start time, then every string as a little-endian
+    /// `u16` length followed by its UTF-8 bytes, the tags preceded by their count.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.pid.to_le_bytes());
+        out.extend_from_slice(&self.process_start_nanos.to_le_bytes());
+        put_str(&mut out, &self.hostname);
+        put_str(&mut out, &self.crate_version);
+        out.extend_from_slice(&(self.tags.len().min(u16::MAX as usize) as u16).to_le_bytes());
+        for (key, value) in &self.tags {
+            put_str(&mut out, key);
+            put_str(&mut out, value);
+        }
+        out
+    }
+
+    /// Decodes metadata written by [`FileMetadata::encode`], or returns `None` if `bytes`
+    /// doesn't hold exactly one.
+    pub(crate) fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut bytes = bytes;
+        let pid = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().ok()?);
+        let process_start_nanos = u64::from_le_bytes(take(&mut bytes, 8)?.try_into().ok()?);
+        let hostname = take_str(&mut bytes)?;
+        let crate_version = take_str(&mut bytes)?;
+        let count = u16::from_le_bytes(take(&mut bytes, 2)?.try_into().ok()?);
+        let tags = (0..count)
+            .map(|_| Some((take_str(&mut bytes)?, take_str(&mut bytes)?)))
+            .collect::<Option<_>>()?;
+        bytes.is_empty().then_some(Self {
+            hostname,
+            pid,
+            process_start_nanos,
+            crate_version,
+            tags,
+        })
+    }
+}
+
+/// Strings longer than a `u16` can count are cut short; the size limit refuses them anyway.
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    let len = s.len().min(u16::MAX as usize);
+    out.extend_from_slice(&(len as u16).to_le_bytes());
+    out.extend_from_slice(&s.as_bytes()[..len]);
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Some(head)
+}
+
+fn take_str(bytes: &mut &[u8]) -> Option<String> {
+    let len = u16::from_le_bytes(take(bytes, 2)?.try_into().ok()?);
+    String::from_utf8(take(bytes, len as usize)?.to_vec()).ok()
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Records `tags` in the file's metadata block alongside the host name, pid, process
+    /// start time and picologger version, e.g.
+    /// `.with_metadata([("service", "matcher"), ("region", "eu")])`.
+    ///
+    /// Adds to any tags set before. The whole block, tags included, must encode to at most
+    /// [`MAX_METADATA_LEN`] bytes and fit in the header block after the file header;
+    /// `start()` fails with [`PicoError::MetadataTooLarge`](crate::PicoError::MetadataTooLarge)
+    /// otherwise.
+    pub fn with_metadata<K, V>(mut self, tags: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.metadata_tags
+            .extend(tags.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Reads the metadata block of the log file at `path` without reading any entries.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileMetadata, Error>` - The metadata, an error of kind `NotFound` for a file
+    ///   written without one, or `InvalidData` if the block is damaged.
+    pub fn read_metadata(path: impl AsRef<Path>) -> Result<FileMetadata, Error> {
+        let mut file = File::open(path.as_ref())?;
+        let mut block = vec![0u8; size_of::<FileHeader>()];
+        let header = match file.read_exact(&mut block) {
+            Ok(()) => FileHeader::read_from(&block),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
+            Err(e) => return Err(e),
+        };
+        let len = header.map_or(0, |header| header.metadata_len as usize);
+        if len == 0 {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "Log file was written without a metadata block",
+            ));
+        }
+        if len > MAX_METADATA_LEN {
+            return Err(corrupt_metadata());
+        }
+        let mut bytes = vec![0u8; len];
+        file.read_exact(&mut bytes)?;
+        FileMetadata::decode(&bytes).ok_or_else(corrupt_metadata)
+    }
+}
+
+fn corrupt_metadata() -> Error {
+    Error::new(ErrorKind::InvalidData, "Corrupt metadata block")
+}
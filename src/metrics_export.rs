@@ -0,0 +1,57 @@
+//! Publishing the worker's counters through the `metrics` crate facade, for whichever
+//! exporter the application installed.
+//!
+//! Every metric carries a `logger` label: the name set with [`Logger::with_name`], or the
+//! log path.
+//!
+//! [`Logger::with_name`]: crate::Logger::with_name
+
+use metrics::{Counter, Gauge, Histogram, counter, gauge, histogram};
+use std::time::Duration;
+
+/// The worker's handles to its metrics, registered once at start.
+pub(crate) struct WorkerMetrics {
+    entries: Counter,
+    pages_flushed: Counter,
+    bytes_written: Counter,
+    write_errors: Counter,
+    pending_pages: Gauge,
+    channel_depth: Gauge,
+    flush_duration: Histogram,
+}
+
+impl WorkerMetrics {
+    pub fn new(logger: String) -> Self {
+        Self {
+            entries: counter!("picolog_entries_total", "logger" => logger.clone()),
+            pages_flushed: counter!("picolog_pages_flushed_total", "logger" => logger.clone()),
+            bytes_written: counter!("picolog_bytes_written_total", "logger" => logger.clone()),
+            write_errors: counter!("picolog_write_errors_total", "logger" => logger.clone()),
+            pending_pages: gauge!("picolog_pending_pages", "logger" => logger.clone()),
+            channel_depth: gauge!("picolog_channel_depth", "logger" => logger.clone()),
+            flush_duration: histogram!("picolog_flush_duration_seconds", "logger" => logger),
+        }
+    }
+
+    /// `count` entries were taken off the channel, which `depth` messages are still in.
+    pub fn received(&self, count: usize, depth: usize) {
+        self.entries.increment(count as u64);
+        self.channel_depth.set(depth as f64);
+    }
+
+    /// `pending` page writes are in flight.
+    pub fn pending(&self, pending: usize) {
+        self.pending_pages.set(pending as f64);
+    }
+
+    /// A page write of `bytes` completed `elapsed` after it was queued.
+    pub fn flushed(&self, bytes: usize, elapsed: Duration) {
+        self.pages_flushed.increment(1);
+        self.bytes_written.increment(bytes as u64);
+        self.flush_duration.record(elapsed.as_secs_f64());
+    }
+
+    pub fn write_failed(&self) {
+        self.write_errors.increment(1);
+    }
+}
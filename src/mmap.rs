@@ -0,0 +1,176 @@
+//! Memory-mapped reader for scanning large log files without copying them through a buffer.
+//!
+//! The file is mapped read-only and entry headers are parsed in place, so entries in pages
+//! without transforms are never copied before they reach the caller.
+
+use crate::Logger;
+use crate::reader::{ByteSource, LogEntry, RawReader, next_typed};
+use crate::util;
+use std::fs::File;
+use std::io::Error;
+use std::os::unix::io::AsRawFd;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// How far the reader may move through the mapping before it checks the file length again.
+const LENGTH_CHECK_WINDOW: u64 = 64 * 1024;
+
+/// A read-only mapping of a whole log file.
+pub(crate) struct MappedFile {
+    file: File,
+    /// Dangling when the file was empty, since empty mappings are not allowed.
+    ptr: NonNull<u8>,
+    map_len: usize,
+    /// Bytes of the mapping still backed by the file as of the last length check.
+    valid_len: u64,
+    /// `offset / LENGTH_CHECK_WINDOW` of the access that triggered the last check.
+    checked_window: u64,
+}
+
+unsafe impl Send for MappedFile {}
+
+impl MappedFile {
+    fn open(file: File) -> Result<Self, Error> {
+        let map_len = util::file_len(&file)? as usize;
+        let ptr = if map_len == 0 {
+            NonNull::dangling()
+        } else {
+            let addr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    map_len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if addr == libc::MAP_FAILED {
+                return Err(Error::last_os_error());
+            }
+            unsafe { libc::madvise(addr, map_len, libc::MADV_SEQUENTIAL) };
+            NonNull::new(addr as *mut u8).ok_or_else(Error::last_os_error)?
+        };
+        Ok(Self {
+            file,
+            ptr,
+            map_len,
+            valid_len: map_len as u64,
+            checked_window: u64::MAX,
+        })
+    }
+}
+
+impl ByteSource for MappedFile {
+    fn fill(&mut self, offset: u64, len: usize) -> Result<usize, Error> {
+        let end = offset.saturating_add(len as u64);
+        let window = end / LENGTH_CHECK_WINDOW;
+        // Touching the mapping past the end of a file that was truncated after it was
+        // mapped raises SIGBUS, so keep accesses within the length the file has now.
+        if window != self.checked_window || end > self.valid_len {
+            self.valid_len = self.len()?;
+            self.checked_window = window;
+        }
+        Ok(self.valid_len.saturating_sub(offset).min(len as u64) as usize)
+    }
+
+    fn bytes(&self, offset: u64, len: usize) -> &[u8] {
+        if len == 0 {
+            return &[];
+        }
+        assert!(offset + len as u64 <= self.map_len as u64);
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr().add(offset as usize), len) }
+    }
+
+    fn len(&self) -> Result<u64, Error> {
+        Ok(util::file_len(&self.file)?.min(self.map_len as u64))
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if self.map_len > 0 {
+            unsafe {
+                libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.map_len);
+            }
+        }
+    }
+}
+
+/// An entry borrowed from an [`MmapReader`], valid until the reader moves on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawEntry<'a> {
+    /// Sequence identifier assigned when the entry was logged.
+    pub seq_id: u64,
+    /// Timestamp recorded when the entry was appended to its page, in nanoseconds since the
+    /// Unix epoch.
+    pub ts_nanos: u64,
+    /// The entry's payload bytes. Borrowed straight from the mapping for pages stored
+    /// without transforms, and from the reader's decoded page otherwise.
+    pub payload: &'a [u8],
+}
+
+/// Reader over a memory-mapped log file, yielding the same entries as [`LogReader`].
+///
+/// Entries appended after the file was mapped are not seen. The file length is re-checked
+/// every 64 KiB, so a file truncated underneath the reader ends the scan early instead of
+/// faulting; a truncation that lands between a check and the next access can still raise
+/// `SIGBUS`, so don't map files another process may shrink.
+///
+/// [`LogReader`]: crate::LogReader
+pub struct MmapReader<T> {
+    raw: RawReader<MappedFile>,
+    failed: bool,
+    decode: fn(&[u8]) -> T,
+}
+
+impl<T: Copy> MmapReader<T> {
+    /// Positions the reader so the next entry returned is the first one whose seq_id is at
+    /// least `seq_id`, using the sidecar index if there is one.
+    pub fn seek(&mut self, seq_id: u64) -> Result<(), Error> {
+        self.failed = false;
+        self.raw.seek(seq_id)
+    }
+
+    /// Returns the next entry without copying its payload, or `None` at the end of the file.
+    ///
+    /// Unlike the iterator, entries whose size differs from `T` are returned as well.
+    pub fn next_raw(&mut self) -> Result<Option<RawEntry<'_>>, Error> {
+        Ok(self.raw.next_entry()?.map(|(header, payload)| RawEntry {
+            seq_id: header.seq_id,
+            ts_nanos: header.ts_nanos,
+            payload,
+        }))
+    }
+}
+
+impl<T: Copy> Iterator for MmapReader<T> {
+    type Item = Result<LogEntry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_typed(&mut self.raw, &mut self.failed, self.decode)
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Memory-maps the configured log file and opens a reader over it.
+    ///
+    /// Faster than [`Logger::read_iter`] for repeated scans of large files, since pages are
+    /// read in place rather than copied into a buffer first.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MmapReader<T>, Error>` - A reader positioned at the first entry, or an error if
+    ///   the file cannot be opened or mapped, or was written with a different payload type.
+    pub fn read_mmap(&self) -> Result<MmapReader<T>, Error> {
+        let path = self.log_path()?;
+        let source = MappedFile::open(File::open(path)?)?;
+        let raw = RawReader::with_source(source, path, self.codec())?.strict(self.strict_reads);
+        self.check_schema(raw.schema())?;
+        Ok(MmapReader {
+            raw,
+            failed: false,
+            decode: self.payload_codec.decode,
+        })
+    }
+}
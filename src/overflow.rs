@@ -0,0 +1,37 @@
+/// What the worker does when it moves on to the next page but that page's previous write
+/// has not completed yet, because the disk is not keeping up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the write to complete. Meanwhile the channel and ring buffer fill up, and
+    /// `log()` starts overwriting slots the worker has not read. Time spent waiting is
+    /// reported as [`LoggerStats::stall_time`](crate::LoggerStats::stall_time).
+    #[default]
+    Block,
+    /// Keep draining the ring buffer, discarding each entry read from it until a page frees
+    /// up, so what was already on a page is kept and the newest entries are lost. Each
+    /// discarded run is reported through the error channel as
+    /// [`PicoError::EntriesDropped`](crate::PicoError::EntriesDropped).
+    DropNewest,
+    /// Allocate another page, up to `max_pages` in total, and wait as with
+    /// [`OverflowPolicy::Block`] once the cap is reached. Pages added this way are never
+    /// freed and are written from unregistered buffers.
+    Grow { max_pages: usize },
+}
+
+/// What `log()` does when the ring buffer slot it would fill next still holds an entry the
+/// worker has not consumed, because producers are outrunning it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BufferFullPolicy {
+    /// Reuse the slot anyway, so producers never wait. The entry it held is lost and
+    /// counted in [`LoggerStats::dropped_entries`](crate::LoggerStats::dropped_entries).
+    /// Batches are the exception: one that doesn't fit in the free slots is refused with
+    /// [`PicoError::BufferFull`](crate::PicoError::BufferFull).
+    #[default]
+    Overwrite,
+    /// Refuse the new entry with [`PicoError::BufferFull`](crate::PicoError::BufferFull).
+    /// Every entry that was handed a seq_id reaches a page.
+    Reject,
+    /// Wait until the worker has consumed the slot. Every entry reaches a page, and
+    /// producers are held back to the worker's pace.
+    Block,
+}
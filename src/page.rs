@@ -1,114 +1,352 @@
 use std::{
-    alloc::{Layout, alloc, dealloc},
+    alloc::{Layout, alloc_zeroed, dealloc},
     marker::PhantomData,
-    ptr::{self, NonNull},
+    mem::size_of,
+    ptr::NonNull,
     slice,
-    time::{SystemTime, UNIX_EPOCH},
 };
 
+use crate::checksum::crc32;
 use crate::errors::PicoError;
+use crate::format::{ENTRY_ALIGN, ENTRY_HEADER_LEN, EntryHeader, encode_entry};
+use crate::header::{le_u32, le_u64};
 
-#[repr(C, packed)]
-pub struct EntryHeader {
-    pub seq_id: u64,
-    pub ts_nanos: u64,
-    pub len: u16,
-    pub _pad: [u8; 6],
+/// Marks a page that starts with a [`PageHeader`]. Legacy pages begin directly with an
+/// [`EntryHeader`], whose leading seq_id never reaches this value in practice.
+pub const PAGE_MAGIC: u64 = u64::from_le_bytes(*b"PICOPAGE");
+
+/// The page payload is LZ4 block-compressed.
+pub const PAGE_FLAG_LZ4: u32 = 1 << 0;
+/// The page payload is encrypted; `nonce` and `tag` are meaningful.
+pub const PAGE_FLAG_ENCRYPTED: u32 = 1 << 1;
+
+/// Optional header at the start of pages written with a page transform (compression or
+/// encryption).
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PageHeader {
+    pub magic: u64,
+    pub flags: u32,
+    /// Bytes of payload stored after the header.
+    pub stored_len: u32,
+    /// Bytes of entry data once the payload is decoded.
+    pub raw_len: u32,
+    pub _pad: [u8; 4],
+    pub nonce: [u8; 24],
+    pub tag: [u8; 16],
+}
+
+impl PageHeader {
+    /// Length of the leading fields that are authenticated along with an encrypted payload.
+    #[cfg(feature = "encryption")]
+    pub const AUTHENTICATED_LEN: usize = 24;
+
+    #[cfg(feature = "encryption")]
+    pub fn authenticated_bytes(&self) -> [u8; Self::AUTHENTICATED_LEN] {
+        let mut bytes = [0u8; Self::AUTHENTICATED_LEN];
+        bytes.copy_from_slice(&self.to_bytes()[..Self::AUTHENTICATED_LEN]);
+        bytes
+    }
+
+    pub fn to_bytes(self) -> [u8; size_of::<Self>()] {
+        let mut bytes = [0u8; size_of::<Self>()];
+        bytes[..8].copy_from_slice(&self.magic.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.flags.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.stored_len.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.raw_len.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self._pad);
+        bytes[24..48].copy_from_slice(&self.nonce);
+        bytes[48..].copy_from_slice(&self.tag);
+        bytes
+    }
+
+    /// Parses the page header at the start of `bytes`, or returns `None` if the page has
+    /// none.
+    pub fn read(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < size_of::<Self>() || le_u64(bytes, 0) != PAGE_MAGIC {
+            return None;
+        }
+        Some(Self {
+            magic: PAGE_MAGIC,
+            flags: le_u32(bytes, 8),
+            stored_len: le_u32(bytes, 12),
+            raw_len: le_u32(bytes, 16),
+            _pad: bytes[20..24].try_into().unwrap(),
+            nonce: bytes[24..48].try_into().unwrap(),
+            tag: bytes[48..64].try_into().unwrap(),
+        })
+    }
+}
+
+/// Marks a [`PageMeta`]. Like [`PAGE_MAGIC`], an entry's leading seq_id never reaches it.
+pub const PAGE_META_MAGIC: u64 = u64::from_le_bytes(*b"PICOMETA");
+
+/// Bytes a [`PageMeta`] takes at the start of a page's entries.
+pub const PAGE_META_LEN: usize = size_of::<PageMeta>();
+
+/// Summary of a page's entries, written ahead of them in files of format version 3 on.
+///
+/// It sits at the start of the page's entry data, so on pages with a [`PageHeader`] it is
+/// compressed and encrypted along with the entries.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageMeta {
+    pub magic: u64,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    /// Position of the page among the pages written since the logger started, counting a
+    /// page rewritten with more entries once.
+    pub page_id: u32,
+    pub entry_count: u32,
+    /// Bytes of entries following the page meta.
+    pub used_bytes: u32,
+    /// CRC-32 of the fields before it.
+    pub checksum: u32,
+}
+
+impl PageMeta {
+    fn to_bytes(self) -> [u8; PAGE_META_LEN] {
+        let mut bytes = [0u8; PAGE_META_LEN];
+        bytes[..8].copy_from_slice(&self.magic.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.first_seq.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.last_seq.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.page_id.to_le_bytes());
+        bytes[28..32].copy_from_slice(&self.entry_count.to_le_bytes());
+        bytes[32..36].copy_from_slice(&self.used_bytes.to_le_bytes());
+        bytes[36..].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parses the page meta at the start of `bytes`, or returns `None` if there is none
+    /// there. The checksum is not checked; see [`PageMeta::is_intact`].
+    pub fn read(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < PAGE_META_LEN || le_u64(bytes, 0) != PAGE_META_MAGIC {
+            return None;
+        }
+        Some(Self {
+            magic: PAGE_META_MAGIC,
+            first_seq: le_u64(bytes, 8),
+            last_seq: le_u64(bytes, 16),
+            page_id: le_u32(bytes, 24),
+            entry_count: le_u32(bytes, 28),
+            used_bytes: le_u32(bytes, 32),
+            checksum: le_u32(bytes, 36),
+        })
+    }
+
+    /// Returns `true` if the stored checksum matches the other fields.
+    pub fn is_intact(&self) -> bool {
+        self.checksum == crc32(&self.to_bytes()[..PAGE_META_LEN - 4])
+    }
 }
 
+/// A page-sized, aligned buffer of entries of type `T`, filled front to back and written
+/// out whole.
 pub struct Page<T> {
-    pub ptr: NonNull<u8>,
-    layout: Layout,
+    pub(crate) ptr: NonNull<u8>,
+    /// `None` when the memory belongs to a pool region rather than this page.
+    layout: Option<Layout>,
     block_size: usize,
+    start: usize,
     cursor: usize,
+    first_entry: u64,
     last_entry: u64,
+    entry_count: usize,
     _frankenstein: PhantomData<T>,
 }
 
-fn align_up(addr: usize, align: usize) -> usize {
-    (addr + align - 1) & !(align - 1)
-}
-
 unsafe impl<T: Send> Send for Page<T> {}
+// Shared references only read the page.
+unsafe impl<T: Sync> Sync for Page<T> {}
 
 impl<T> Page<T> {
-    pub fn init(block_size: usize) -> Self {
-        let layout = Layout::from_size_align(block_size, block_size).unwrap();
-        let ptr = unsafe { alloc(layout) };
+    /// Creates a page whose first `reserved` bytes are kept free for a page header.
+    pub fn init(block_size: usize, reserved: usize) -> Self {
+        // Pages sized to fit a large entry are a multiple of the block size but not
+        // necessarily a power of two; the largest power of two dividing them still covers
+        // the block alignment direct IO needs.
+        let align = 1 << block_size.trailing_zeros();
+        let layout = Layout::from_size_align(block_size, align).unwrap();
+        // Large zeroed allocations come straight from the OS, already zero, so this costs no
+        // memset up front.
+        let ptr = unsafe { alloc_zeroed(layout) };
         let ptr = NonNull::new(ptr).expect("Mem alloc failed");
-        unsafe {
-            ptr.as_ptr().write_bytes(0, block_size);
-        }
+        unsafe { Self::from_raw(ptr, Some(layout), block_size, reserved) }
+    }
+
+    /// Creates a page over `block_size` bytes at `ptr` that it does not own.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for `block_size` zeroed bytes, suitably aligned for direct IO, and
+    /// outlive the page.
+    pub(crate) unsafe fn from_region(ptr: NonNull<u8>, block_size: usize, reserved: usize) -> Self {
+        unsafe { Self::from_raw(ptr, None, block_size, reserved) }
+    }
+
+    unsafe fn from_raw(
+        ptr: NonNull<u8>,
+        layout: Option<Layout>,
+        block_size: usize,
+        reserved: usize,
+    ) -> Self {
         Self {
             ptr,
             layout,
             block_size,
-            cursor: 0,
+            start: reserved,
+            cursor: reserved,
+            first_entry: 0,
             last_entry: 0,
+            entry_count: 0,
             _frankenstein: PhantomData,
         }
     }
 
-    pub fn append(&mut self, seq_id: u64, data: &T) -> Result<(), PicoError> {
-        let msg_size = std::mem::size_of::<T>();
-        let header_size = std::mem::size_of::<EntryHeader>();
-        let total_size = header_size + msg_size;
-        let aligned_size = align_up(total_size, 8);
-
-        if self.cursor + total_size > self.block_size {
-            return Err(PicoError::PageFull {});
+    /// Appends an entry holding `data` as its payload. `level` is a [`Level`] as `u8`, or 0
+    /// for none.
+    ///
+    /// Fails with [`PicoError::PageFull`] if the entry doesn't fit in the rest of the page.
+    ///
+    /// [`Level`]: crate::Level
+    pub fn append_bytes(
+        &mut self,
+        seq_id: u64,
+        ts_nanos: u64,
+        level: u8,
+        data: &[u8],
+    ) -> Result<(), PicoError> {
+        let cursor = self.cursor;
+        let dest = &mut self.get_page_content_mut()[cursor..];
+        let aligned_size = encode_entry(dest, seq_id, ts_nanos, level, data)?;
+        if self.is_empty() {
+            self.first_entry = seq_id;
         }
+        self.cursor += aligned_size;
+        self.last_entry = seq_id;
+        self.entry_count += 1;
+        Ok(())
+    }
 
-        //TODO: Use a faster method to fetch monotonic val
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-
-        let header = EntryHeader {
-            seq_id,
-            ts_nanos: now,
-            len: msg_size as u16,
-            _pad: [0; 6],
-        };
-
-        unsafe {
-            let dest_ptr = self.ptr.as_ptr().add(self.cursor);
-            ptr::write(dest_ptr as *mut EntryHeader, header);
+    /// Empties the page for reuse. The previous entries' bytes stay in place until
+    /// [`Page::zero_tail`] clears whatever the new ones did not overwrite.
+    pub fn reset(&mut self) {
+        self.cursor = self.start;
+        self.first_entry = 0;
+        self.last_entry = 0;
+        self.entry_count = 0;
+    }
 
-            ptr::copy_nonoverlapping(
-                data as *const T as *const u8,
-                dest_ptr.add(header_size),
-                msg_size,
-            );
+    /// Replaces this page's entries with a copy of `other`'s, which must have the same size
+    /// and reserved header space.
+    pub(crate) fn copy_entries_from(&mut self, other: &Page<T>) {
+        let range = other.start..other.cursor;
+        self.get_page_content_mut()[range.clone()]
+            .copy_from_slice(&other.get_page_content()[range]);
+        self.cursor = other.cursor;
+        self.first_entry = other.first_entry;
+        self.last_entry = other.last_entry;
+        self.entry_count = other.entry_count;
+    }
 
-            let padding_bytes = aligned_size - total_size;
-            if padding_bytes > 0 {
-                ptr::write_bytes(dest_ptr.add(total_size), 0, padding_bytes);
-            }
+    /// Creates an empty page over this page's memory that does not own it, for the caller
+    /// to fill in inline mode.
+    ///
+    /// # Safety
+    ///
+    /// This page's memory must outlive the view, and only one of the two may be written to
+    /// at a time.
+    pub(crate) unsafe fn view(&self) -> Page<T> {
+        unsafe { Self::from_raw(self.ptr, None, self.block_size, self.start) }
+    }
 
-            self.cursor += aligned_size;
-        }
-        self.last_entry = seq_id;
-        Ok(())
+    /// Takes over the entries `view`, a [`Page::view`] of this page, appended to the memory
+    /// they share.
+    pub(crate) fn adopt_entries(&mut self, view: &Page<T>) {
+        debug_assert_eq!(self.ptr, view.ptr);
+        self.cursor = view.cursor;
+        self.first_entry = view.first_entry;
+        self.last_entry = view.last_entry;
+        self.entry_count = view.entry_count;
     }
 
-    pub fn reset(&mut self) {
+    /// Zeroes the page past the last entry, so stale bytes from its previous use are not
+    /// written out and readers see the zero padding that ends a page's entries.
+    pub(crate) fn zero_tail(&mut self) {
         unsafe {
-            self.ptr.as_ptr().write_bytes(0, self.block_size);
+            self.ptr
+                .as_ptr()
+                .add(self.cursor)
+                .write_bytes(0, self.block_size - self.cursor);
         }
-        self.cursor = 0;
-        self.last_entry = 0;
     }
 
+    /// Whether no entry has been appended since the page was created or reset.
     pub fn is_empty(&self) -> bool {
-        self.cursor == 0
+        self.cursor == self.start
+    }
+
+    /// Bytes of the page in use, including the reserved header space.
+    pub fn used(&self) -> usize {
+        self.cursor
+    }
+
+    /// Bytes of the page taken by the entries appended so far.
+    pub(crate) fn entry_bytes(&self) -> usize {
+        self.cursor - self.start
+    }
+
+    /// Fills in the [`PageMeta`] for the entries appended so far at byte `at` of the page,
+    /// which must be the last [`PAGE_META_LEN`] bytes of the reserved header space.
+    pub(crate) fn write_meta(&mut self, at: usize, page_id: u32) {
+        debug_assert_eq!(at + PAGE_META_LEN, self.start);
+        let mut meta = PageMeta {
+            magic: PAGE_META_MAGIC,
+            first_seq: self.first_entry,
+            last_seq: self.last_entry,
+            page_id,
+            entry_count: self.entry_count as u32,
+            used_bytes: (self.cursor - self.start) as u32,
+            checksum: 0,
+        };
+        meta.checksum = crc32(&meta.to_bytes()[..PAGE_META_LEN - 4]);
+        let end = self.start;
+        self.get_page_content_mut()[at..end].copy_from_slice(&meta.to_bytes());
     }
 
+    pub(crate) fn get_page_content_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.block_size) }
+    }
+
+    /// Seq_id of the first entry appended, or 0 on an empty page.
+    pub fn get_first_entry(&self) -> u64 {
+        self.first_entry
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entry_count
+    }
+
+    /// Seq_id of the last entry appended, or 0 on an empty page.
     pub fn get_last_entry(&self) -> u64 {
         self.last_entry
     }
 
+    /// Copies out the `(seq_id, payload)` of every entry appended so far.
+    pub(crate) fn copy_entries(&self) -> Vec<(u64, Vec<u8>)> {
+        let bytes = &self.get_page_content()[..self.cursor];
+        let mut entries = Vec::with_capacity(self.entry_count);
+        let mut at = self.start;
+        while at + ENTRY_HEADER_LEN <= bytes.len() {
+            let header = EntryHeader::read(&bytes[at..], true);
+            let payload = at + ENTRY_HEADER_LEN..at + ENTRY_HEADER_LEN + header.len as usize;
+            entries.push((header.seq_id, bytes[payload.clone()].to_vec()));
+            at = payload.end.next_multiple_of(ENTRY_ALIGN);
+        }
+        entries
+    }
+
+    /// The whole page, as it is to be written.
     pub fn get_page_content(&self) -> &[u8] {
         unsafe {
             let raw_ptr = self.ptr.as_ptr();
@@ -117,8 +355,70 @@ impl<T> Page<T> {
     }
 }
 
+#[cfg(feature = "pod")]
+impl<T: bytemuck::Pod> Page<T> {
+    /// Appends an entry holding the bytes of `data`, like [`Page::append_bytes`].
+    pub fn append(
+        &mut self,
+        seq_id: u64,
+        ts_nanos: u64,
+        level: u8,
+        data: &T,
+    ) -> Result<(), PicoError> {
+        self.append_bytes(seq_id, ts_nanos, level, bytemuck::bytes_of(data))
+    }
+}
+
+/// How a logger turns its payloads into the bytes of an entry and back.
+pub(crate) struct PayloadCodec<T> {
+    pub encode: fn(&T) -> &[u8],
+    pub decode: fn(&[u8]) -> T,
+}
+
+impl<T> Clone for PayloadCodec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for PayloadCodec<T> {}
+
+impl<T: Copy> PayloadCodec<T> {
+    /// Copies the bytes of a `T` as they are in memory, padding included, and reinterprets
+    /// them on the way back.
+    pub fn raw() -> Self {
+        Self {
+            encode: encode_raw,
+            decode: decode_raw,
+        }
+    }
+}
+
+#[cfg(feature = "pod")]
+impl<T: bytemuck::Pod> PayloadCodec<T> {
+    /// Goes through [`bytemuck`], for types checked to have no padding and no invalid bit
+    /// patterns.
+    pub fn pod() -> Self {
+        Self {
+            encode: bytemuck::bytes_of,
+            decode: bytemuck::pod_read_unaligned,
+        }
+    }
+}
+
+fn encode_raw<T>(data: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(data as *const T as *const u8, size_of::<T>()) }
+}
+
+/// Reinterprets a payload of `size_of::<T>()` bytes as the `T` it was written from.
+pub(crate) fn decode_raw<T: Copy>(payload: &[u8]) -> T {
+    unsafe { std::ptr::read_unaligned(payload.as_ptr() as *const T) }
+}
+
 impl<T> Drop for Page<T> {
     fn drop(&mut self) {
-        unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+        if let Some(layout) = self.layout {
+            unsafe { dealloc(self.ptr.as_ptr(), layout) }
+        }
     }
 }
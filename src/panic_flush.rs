@@ -0,0 +1,123 @@
+//! A panic hook that gets the entries logged before a panic to disk while the process can
+//! still write them.
+//!
+//! The hook only holds the sending side of the worker's channel while it flushes: a copy
+//! kept for longer would stop the worker from ever seeing the logger hang up.
+
+use crate::Logger;
+use crate::errors::PicoError;
+use crate::worker::{Mailbox, Message};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+/// Longest a panicking thread waits for its entries to become durable.
+const PANIC_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+thread_local! {
+    /// Set while this thread runs a panic flush, so a panic inside it doesn't start another.
+    static IN_PANIC_FLUSH: Cell<bool> = const { Cell::new(false) };
+}
+
+/// What the panic hook needs of a running logger. Shared with the logger, which clears the
+/// sender when it shuts down.
+pub(crate) struct PanicFlush {
+    sender: Mutex<Option<Mailbox>>,
+    worker: ThreadId,
+    installed: AtomicBool,
+}
+
+impl PanicFlush {
+    pub(crate) fn new(sender: Mailbox, worker: ThreadId) -> Self {
+        Self {
+            sender: Mutex::new(Some(sender)),
+            worker,
+            installed: AtomicBool::new(false),
+        }
+    }
+
+    /// Lets go of the worker's channel, after which the hook does nothing.
+    pub(crate) fn disarm(&self) {
+        drop(
+            self.sender
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take(),
+        );
+    }
+
+    /// Asks the worker to write out its active page and waits, up to the timeout, until
+    /// nothing logged before the call is left undurable.
+    ///
+    /// Does nothing on the worker thread itself, which would wait on itself, or once the
+    /// logger has shut down or the worker has exited.
+    fn run(&self) {
+        if thread::current().id() == self.worker || IN_PANIC_FLUSH.with(Cell::get) {
+            return;
+        }
+        // Cloned so the lock is not held while waiting; a shutdown meanwhile is only held up
+        // until the wait ends.
+        let Some(sender) = self
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+        else {
+            return;
+        };
+        IN_PANIC_FLUSH.with(|flag| flag.set(true));
+        let deadline = Instant::now() + PANIC_FLUSH_TIMEOUT;
+        if sender.send(Message::Flush).is_ok() {
+            loop {
+                let (reply, pending) = crossbeam_channel::bounded(1);
+                if sender.send(Message::Snapshot(reply)).is_err() {
+                    break;
+                }
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match pending.recv_timeout(remaining) {
+                    Ok(entries) if entries.is_empty() => break,
+                    Ok(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(1)),
+                    _ => break,
+                }
+            }
+        }
+        IN_PANIC_FLUSH.with(|flag| flag.set(false));
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Registers a panic hook that flushes this logger before the panic goes on, chaining
+    /// whatever hook was set before.
+    ///
+    /// A thread that panics asks the worker to write out its partially filled page and
+    /// waits, for at most half a second, until every entry logged before the panic is
+    /// durable. The previous hook, by default the one printing the panic message, runs
+    /// afterwards. Panics on the worker thread itself skip the flush, as do panics after the
+    /// logger has shut down or its worker has exited; calling this more than once registers
+    /// a single hook.
+    ///
+    /// This matters most in builds with `panic = "abort"`: the hook still runs, but the
+    /// process aborts right after it, without unwinding, so the logger's `Drop` never gets
+    /// to write out what it holds. With unwinding, the hook also saves entries a logger
+    /// owned by another thread would otherwise only write out when its flush interval
+    /// elapses. Neither a `std::process::abort` nor a fatal signal runs the hook.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), PicoError>` - `Ok` once the hook is registered, or
+    ///   [`PicoError::NotStarted`] if the logger is not running.
+    pub fn install_panic_flush(&self) -> Result<(), PicoError> {
+        let target = self.panic_flush.clone().ok_or(PicoError::NotStarted)?;
+        if target.installed.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            target.run();
+            previous(info);
+        }));
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Statically checked payloads for types that are plain old data, with the `pod` feature.
+//!
+//! Entries are stored as the bytes of their `T` and read back by reinterpreting those
+//! bytes, which is only sound when every byte of a `T` is initialized and every bit pattern
+//! is a valid `T`. Padding breaks the first; `bool`, enums and references break the second.
+//! `Logger<T>` itself doesn't require this, since that would rule out the many payloads
+//! with padding that are only ever read back by the program that wrote them. Types that
+//! are [`Pod`] can have it checked instead: a logger from [`LoggerConfig::build_pod`] only
+//! accepts them, and writes and reads their entries through [`bytemuck::bytes_of`] and
+//! [`bytemuck::pod_read_unaligned`] rather than pointer casts. [`Logger::read_pod`] reads
+//! with the latter whichever way the logger was built.
+//!
+//! [`safe_payload!`](crate::safe_payload) implements `Pod` for the structs it declares, and
+//! [`SafePayload`](crate::SafePayload) is implemented for every `Pod` type, so the checked
+//! constructors, [`Logger::new_checked`] and [`LoggerConfig::build_checked`], go through
+//! `bytemuck` as well.
+//!
+//! Enabling the feature doesn't bound `Logger<T>` itself by `Pod`, nor switch loggers from
+//! [`Logger::new`] and [`LoggerConfig::build`] to `bytemuck`: features are additive, and
+//! that would stop every crate in the build that logs a padded type from compiling as soon
+//! as one crate turned `pod` on. The statically checked mode is chosen per logger instead.
+
+use crate::page::PayloadCodec;
+use crate::reader::LogReader;
+use crate::{Logger, LoggerConfig, PicoError};
+use bytemuck::Pod;
+use std::io::Error;
+
+impl LoggerConfig {
+    /// Validates the configuration and returns a logger like [`LoggerConfig::build`], for
+    /// payloads that are checked at compile time to be plain old data: no padding and no
+    /// invalid bit patterns, so writing their bytes and reading them back is sound.
+    ///
+    /// Entries are written through [`bytemuck::bytes_of`], and everything that reads them
+    /// through this logger decodes with [`bytemuck::pod_read_unaligned`].
+    pub fn build_pod<T: Pod + Default + Send + Sync>(self) -> Result<Logger<T>, PicoError> {
+        let mut logger = self.build()?;
+        logger.payload_codec = PayloadCodec::pod();
+        Ok(logger)
+    }
+}
+
+impl<T: Pod + Default + Send + Sync> Logger<T> {
+    /// Reads all log entries like [`Logger::read`], decoding each payload with
+    /// [`bytemuck::pod_read_unaligned`].
+    pub fn read_pod(&self) -> Result<Vec<T>, PicoError> {
+        let entries: Result<_, Error> = self
+            .read_pod_iter()?
+            .map(|entry| entry.map(|e| e.data))
+            .collect();
+        Ok(entries?)
+    }
+
+    /// Streams the entries of the log file like [`Logger::read_iter`], decoding each
+    /// payload with [`bytemuck::pod_read_unaligned`].
+    pub fn read_pod_iter(&self) -> Result<LogReader<T>, Error> {
+        Ok(self
+            .read_iter()?
+            .decoding_with(bytemuck::pod_read_unaligned::<T>))
+    }
+}
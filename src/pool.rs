@@ -0,0 +1,73 @@
+//! A single anonymous mapping that the page pool is carved out of when huge pages are
+//! requested, so the whole pool is covered by a handful of TLB entries.
+
+use std::ptr::NonNull;
+
+#[cfg(target_os = "linux")]
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+pub(crate) struct PageRegion {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+unsafe impl Send for PageRegion {}
+
+impl PageRegion {
+    /// Maps at least `len` bytes, preferring explicitly reserved huge pages (`MAP_HUGETLB`)
+    /// and falling back to a regular mapping advised with `MADV_HUGEPAGE` so transparent
+    /// huge pages can back it. Returns `None` if no mapping could be created.
+    #[cfg(target_os = "linux")]
+    pub fn map(len: usize) -> Option<Self> {
+        let len = len.next_multiple_of(HUGE_PAGE_SIZE);
+        let flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_POPULATE;
+        unsafe {
+            let mut addr = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                flags | libc::MAP_HUGETLB,
+                -1,
+                0,
+            );
+            if addr == libc::MAP_FAILED {
+                addr = libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    flags,
+                    -1,
+                    0,
+                );
+                if addr == libc::MAP_FAILED {
+                    return None;
+                }
+                libc::madvise(addr, len, libc::MADV_HUGEPAGE);
+            }
+            Some(Self {
+                ptr: NonNull::new(addr as *mut u8)?,
+                len,
+            })
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn map(_len: usize) -> Option<Self> {
+        None
+    }
+
+    /// Pointer to the byte at `offset` within the region.
+    pub fn at(&self, offset: usize) -> NonNull<u8> {
+        assert!(offset < self.len);
+        unsafe { NonNull::new_unchecked(self.ptr.as_ptr().add(offset)) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for PageRegion {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.len);
+        }
+    }
+}
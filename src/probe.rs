@@ -0,0 +1,77 @@
+//! Detecting what the running kernel's io_uring supports, once, when the worker's ring is
+//! created.
+//!
+//! io_uring gained the opcodes and setup flags the worker relies on over many releases, so
+//! rather than learning from a failed write halfway through a run, `start()` asks the ring
+//! which opcodes it takes and configures the backend to match. Optional features the kernel
+//! lacks are turned off, operations without an opcode run inline on the worker thread, and
+//! a ring that cannot write pages at all is swapped for the synchronous backend.
+
+use crate::backend::Backend;
+#[cfg(target_os = "linux")]
+use io_uring::{IoUring, Probe, opcode};
+
+/// What the worker's write path ended up using, after downgrading whatever the kernel
+/// doesn't support. Returned by [`Logger::capabilities`](crate::Logger::capabilities);
+/// everything is off before `start()`.
+///
+/// More fields may be added as the worker learns to use other kernel features.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The write backend in use: never [`Backend::Auto`] once started.
+    pub backend: Backend,
+    /// The kernel reported which io_uring opcodes it supports, which it can from 5.6 on.
+    pub opcode_probe: bool,
+    /// File extensions go through the ring as `IORING_OP_FALLOCATE`, rather than running
+    /// inline on the worker thread.
+    pub fallocate: bool,
+    /// Range syncs go through the ring as `IORING_OP_SYNC_FILE_RANGE`, rather than running
+    /// inline on the worker thread.
+    pub sync_file_range: bool,
+    /// Waits for completions can time out (`IORING_FEAT_EXT_ARG`, 5.11). Without it, a wait
+    /// that must end by a deadline, as during shutdown on a stalled disk, checks for
+    /// completions every millisecond instead of blocking in the kernel.
+    pub wait_timeout: bool,
+    /// Pages are written from registered buffers.
+    pub registered_buffers: bool,
+    /// Files are addressed through registered file slots.
+    pub fixed_files: bool,
+    /// A kernel thread polls the submission queue.
+    pub sqpoll: bool,
+    /// Completions are polled from the device.
+    pub iopoll: bool,
+    /// Completions wake the idle worker through its eventfd.
+    pub completion_wakeups: bool,
+}
+
+/// The opcodes the worker submits, as far as the kernel supports them.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Opcodes {
+    /// Whether the kernel could report its opcodes at all; if not, none are assumed beyond
+    /// the fsync and fixed-buffer writes io_uring started with, and `write` is off.
+    pub probed: bool,
+    /// `IORING_OP_WRITE`, for pages outside the registered buffers. Without it the ring
+    /// can't be relied on to write every page.
+    pub write: bool,
+    pub fallocate: bool,
+    pub sync_file_range: bool,
+}
+
+/// Asks the kernel behind `ring` which of the worker's opcodes it supports.
+#[cfg(target_os = "linux")]
+pub(crate) fn opcodes(ring: &IoUring) -> Opcodes {
+    let mut probe = Probe::new();
+    if ring.submitter().register_probe(&mut probe).is_err() {
+        return Opcodes::default();
+    }
+    Opcodes {
+        probed: true,
+        write: probe.is_supported(opcode::Write::CODE)
+            && probe.is_supported(opcode::WriteFixed::CODE)
+            && probe.is_supported(opcode::Fsync::CODE),
+        fallocate: probe.is_supported(opcode::Fallocate::CODE),
+        sync_file_range: probe.is_supported(opcode::SyncFileRange::CODE),
+    }
+}
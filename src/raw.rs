@@ -0,0 +1,249 @@
+//! The log format's building blocks, for writing picolog files from an event loop of your own
+//! instead of through [`Logger`](crate::Logger)'s worker thread.
+//!
+//! A [`PageWriter`] lays out a file the way the worker does: a header block at offset 0,
+//! then pages of entries back to back, each the bytes of a `T` given to
+//! [`Page::append_bytes`]. With the `pod` feature, `Page::append` takes a `T` that is
+//! `bytemuck::Pod` directly; others are for the caller to lay out, padding and all, the way
+//! `T` is read back. Files it writes read back with
+//! [`Logger::with_read_config`](crate::Logger::with_read_config) like any other. Submitting
+//! the writes, waiting for them and deciding when they are durable is left to the caller,
+//! on its own io_uring or otherwise.
+//!
+//! The worker lays out pages with the same code, for the header block and for sealing, but
+//! places them itself rather than through a `PageWriter`: it rewrites partly filled pages in
+//! place, stripes pages across files and compresses or encrypts them, none of which a
+//! `PageWriter` does.
+//!
+//! This is a lower-level interface than the rest of the crate and follows the internals the
+//! worker is built from, so it may change in minor releases. Prefer `Logger` unless you need
+//! to own the thread that does the I/O.
+//!
+//! ```no_run
+//! # use picologger::{Level, raw::PageWriter};
+//! # use std::os::unix::fs::FileExt;
+//! # #[derive(Clone, Copy, Default)] #[repr(C)] struct Trade { id: u64 }
+//! let file = std::fs::File::create("trades.wal")?;
+//! let mut writer = PageWriter::<Trade>::new(4096, None)?;
+//! file.write_all_at(writer.header().get_page_content(), 0)?;
+//!
+//! let mut page = writer.page();
+//! let trade = Trade { id: 7 };
+//! page.append_bytes(1, 0, Level::Info as u8, &trade.id.to_ne_bytes())?;
+//! let slot = writer.seal(&mut page);
+//! file.write_all_at(page.get_page_content(), slot.offset)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::codec::MAX_STORED_PAGE_LEN;
+use crate::errors::PicoError;
+use crate::header::{FORMAT_VERSION, FileHeader, PAGE_META_VERSION, Schema};
+use crate::metadata::FileMetadata;
+use crate::util::MIN_BLOCK_SIZE;
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+pub use crate::format::{
+    ENTRY_ALIGN, ENTRY_HEADER_LEN, ENTRY_LEN_OFFSET, ENTRY_LEVEL_OFFSET, ENTRY_SEQ_ID_OFFSET,
+    ENTRY_TS_OFFSET, EntryHeader, MAX_ENTRY_LEN,
+};
+pub use crate::page::{PAGE_META_LEN, Page};
+
+/// Sizes of a file's pages and what precedes the entries in each of them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PageLayout {
+    pub block_size: usize,
+    pub page_size: usize,
+    /// Where in a page its [`PageMeta`](crate::page::PageMeta) goes: after the codec's page
+    /// header, if any.
+    pub meta_at: usize,
+    pub page_meta: bool,
+}
+
+impl PageLayout {
+    /// Lays out pages of `page_size` bytes, or if `None`, of `page_blocks` blocks grown to fit
+    /// a `payload_len`-byte entry, with `codec_header_len` bytes kept for the codec's page
+    /// header.
+    pub fn new(
+        block_size: usize,
+        page_size: Option<usize>,
+        page_blocks: usize,
+        codec_header_len: usize,
+        format_version: u32,
+        payload_len: usize,
+    ) -> Result<Self, PicoError> {
+        let page_meta = format_version >= PAGE_META_VERSION;
+        let header_len = codec_header_len + if page_meta { PAGE_META_LEN } else { 0 };
+        let min_page = header_len + ENTRY_HEADER_LEN + payload_len;
+        let page_size = match page_size {
+            Some(size) => {
+                if size < min_page || size > MAX_STORED_PAGE_LEN || !size.is_multiple_of(block_size)
+                {
+                    return Err(PicoError::InvalidPageSize {
+                        size,
+                        block_size,
+                        min: min_page.next_multiple_of(block_size),
+                        max: MAX_STORED_PAGE_LEN,
+                    });
+                }
+                size
+            }
+            None => (block_size * page_blocks).max(min_page.next_multiple_of(block_size)),
+        };
+        Ok(Self {
+            block_size,
+            page_size,
+            meta_at: codec_header_len,
+            page_meta,
+        })
+    }
+
+    /// Bytes at the start of each page ahead of its entries.
+    pub fn header_len(&self) -> usize {
+        self.meta_at + if self.page_meta { PAGE_META_LEN } else { 0 }
+    }
+
+    /// Builds the block that starts the file: the file header, followed by `metadata`.
+    pub fn header_block(&self, schema: &Schema, metadata: &[u8], version: u32) -> Page<()> {
+        let mut block = Page::<()>::init(self.block_size, 0);
+        let mut header = FileHeader::new(schema, self.block_size, self.page_size, version);
+        header.metadata_len = metadata.len() as u32;
+        let bytes = block.get_page_content_mut();
+        header.write_to(bytes);
+        bytes[size_of::<FileHeader>()..][..metadata.len()].copy_from_slice(metadata);
+        block
+    }
+
+    /// Readies `page` to be written as page `page_id`: records its page meta and clears
+    /// whatever its entries didn't overwrite.
+    pub fn seal<T>(&self, page: &mut Page<T>, page_id: u32) {
+        if self.page_meta {
+            page.write_meta(self.meta_at, page_id);
+        }
+        page.zero_tail();
+    }
+}
+
+/// Where a sealed page goes in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageSlot {
+    /// File offset to write the page at.
+    pub offset: u64,
+    /// Bytes to write: the whole page.
+    pub len: usize,
+    /// Position of the page in the file, as recorded in its page meta.
+    pub page_id: u32,
+}
+
+/// Lays out a log file of `T` entries in the current format, for a caller that writes the
+/// pages itself.
+///
+/// Pages are handed out one at a time and written back to back after the header block. A
+/// page is done once [`PageWriter::seal`] has given it its slot; write it there, and reuse
+/// it after [`Page::reset`] once the write has completed. Pages are aligned for direct I/O
+/// on files opened with `O_DIRECT`.
+pub struct PageWriter<T> {
+    layout: PageLayout,
+    schema: Schema,
+    next_offset: u64,
+    next_page_id: u32,
+    _payload: PhantomData<T>,
+}
+
+impl<T> PageWriter<T> {
+    /// Lays out a file in `block_size`-byte blocks, with pages of `page_size` bytes or, if
+    /// `None`, of one block grown to fit an entry.
+    ///
+    /// Fails with [`PicoError::InvalidBlockSize`] unless `block_size` is a power of two of at
+    /// least 512, and with [`PicoError::InvalidPageSize`] for a page size that isn't a
+    /// multiple of it or can't hold an entry.
+    pub fn new(block_size: usize, page_size: Option<usize>) -> Result<Self, PicoError> {
+        if !block_size.is_power_of_two() || block_size < MIN_BLOCK_SIZE {
+            return Err(PicoError::InvalidBlockSize { size: block_size });
+        }
+        let layout = PageLayout::new(block_size, page_size, 1, 0, FORMAT_VERSION, size_of::<T>())?;
+        Ok(Self {
+            layout,
+            schema: Schema::of::<T>(None),
+            next_offset: block_size as u64,
+            next_page_id: 0,
+            _payload: PhantomData,
+        })
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.layout.block_size
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.layout.page_size
+    }
+
+    /// The offset the next sealed page will be written at.
+    pub fn next_offset(&self) -> u64 {
+        self.next_offset
+    }
+
+    /// Builds the block to write at offset 0, before any page.
+    pub fn header(&self) -> Page<()> {
+        let metadata = FileMetadata::current(&[]).encode();
+        self.layout
+            .header_block(&self.schema, &metadata, FORMAT_VERSION)
+    }
+
+    /// Allocates an empty page to append entries to.
+    pub fn page(&self) -> Page<T> {
+        Page::init(self.layout.page_size, self.layout.header_len())
+    }
+
+    /// Finishes `page` and assigns it the next slot in the file.
+    ///
+    /// The page must not be appended to or reset until its write has completed, and should
+    /// hold at least one entry: readers take an empty page for the end of the log.
+    pub fn seal(&mut self, page: &mut Page<T>) -> PageSlot {
+        self.layout.seal(page, self.next_page_id);
+        let slot = PageSlot {
+            offset: self.next_offset,
+            len: self.layout.page_size,
+            page_id: self.next_page_id,
+        };
+        self.next_offset += self.layout.page_size as u64;
+        self.next_page_id = self.next_page_id.wrapping_add(1);
+        slot
+    }
+
+    /// Builds an `IORING_OP_WRITE` of the header block `header` to offset 0 of `fd`, for a
+    /// ring of the caller's.
+    ///
+    /// The entry points into `header`, which must stay alive until the write completes.
+    #[cfg(target_os = "linux")]
+    pub fn header_sqe(
+        &self,
+        header: &Page<()>,
+        fd: std::os::unix::io::RawFd,
+    ) -> io_uring::squeue::Entry {
+        write_sqe(fd, header.get_page_content(), 0)
+    }
+
+    /// Builds an `IORING_OP_WRITE` of the sealed `page` to `slot` in `fd`, for a ring of the
+    /// caller's.
+    ///
+    /// The entry points into `page`, which must stay alive and untouched until the write
+    /// completes.
+    #[cfg(target_os = "linux")]
+    pub fn page_sqe(
+        &self,
+        page: &Page<T>,
+        slot: PageSlot,
+        fd: std::os::unix::io::RawFd,
+    ) -> io_uring::squeue::Entry {
+        write_sqe(fd, &page.get_page_content()[..slot.len], slot.offset)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn write_sqe(fd: std::os::unix::io::RawFd, buf: &[u8], offset: u64) -> io_uring::squeue::Entry {
+    io_uring::opcode::Write::new(io_uring::types::Fd(fd), buf.as_ptr(), buf.len() as u32)
+        .offset(offset)
+        .build()
+}
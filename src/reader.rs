@@ -0,0 +1,659 @@
+use crate::codec::{MAX_STORED_PAGE_LEN, PageCodec, stored_page_len};
+use crate::errors::PicoError;
+use crate::format::{
+    ENTRY_ALIGN, ENTRY_HEADER_LEN, EntryFormat, EntryHeader, Parsed, is_plausible, parse_entry,
+};
+use crate::header::{FileHeader, FormatVersion, Schema};
+use crate::index::load_index;
+use crate::level::Level;
+use crate::page::{PAGE_META_LEN, PageHeader, PageMeta};
+use crate::util;
+use std::fs::File;
+use std::io::{Error, Read, Seek, SeekFrom};
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A log entry together with the metadata recorded in its header.
+///
+/// Marked `#[non_exhaustive]` so header fields can be reported without breaking callers
+/// again. This is itself a breaking change from 0.9.4, which had no `level` field: code
+/// that built a `LogEntry` with a struct literal, or destructured one without `..`, must
+/// now read its fields instead.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LogEntry<T> {
+    /// Sequence identifier assigned when the entry was logged.
+    pub seq_id: u64,
+    /// Timestamp recorded when the entry was appended to its page, in nanoseconds since the
+    /// Unix epoch.
+    pub ts_nanos: u64,
+    /// Severity the entry was logged at; [`Level::Info`] unless one was given.
+    pub level: Level,
+    /// The log data payload.
+    pub data: T,
+}
+
+/// Every page starts at a multiple of this many bytes, whatever block size it was written
+/// with, since block sizes are multiples of the 512-byte sector.
+const PAGE_ALIGN: u64 = 512;
+
+/// Bytes read at a time when scanning the file backwards for its last written block.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Bytes read from the file at a time by default.
+pub const DEFAULT_READ_CHUNK: usize = 1024 * 1024;
+
+/// How readers of a log file go through it, set with [`Logger::with_read_options`].
+///
+/// [`Logger::with_read_options`]: crate::Logger::with_read_options
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Bytes read from the file per read call, 1 MiB by default.
+    ///
+    /// A reader holds one chunk in memory, or one page when a page is larger. Entries
+    /// never leave their page, so a page cut in two by the end of a chunk is carried over
+    /// into the next one rather than read again.
+    pub chunk_size: usize,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_READ_CHUNK,
+        }
+    }
+}
+
+/// Where the payload of the entry at the cursor lives.
+#[derive(Clone)]
+enum Payload {
+    /// This range of the decoded page in `RawReader::page`.
+    Page(Range<usize>),
+    /// This many bytes of the file, starting at this offset.
+    Raw(u64, usize),
+}
+
+/// Random access to the bytes of a log file.
+pub(crate) trait ByteSource {
+    /// Makes sure the `len` bytes at `offset` can be borrowed, returning how many of them
+    /// the file holds.
+    fn fill(&mut self, offset: u64, len: usize) -> Result<usize, Error>;
+
+    /// Borrows `len` bytes at `offset`, which the last call to [`ByteSource::fill`] covered.
+    fn bytes(&self, offset: u64, len: usize) -> &[u8];
+
+    /// Current length of the file.
+    fn len(&self) -> Result<u64, Error>;
+
+    /// Forgets anything buffered, so bytes written since are seen.
+    fn refresh(&mut self) {}
+}
+
+/// Reads the file into a buffer a chunk at a time.
+pub(crate) struct FileChunks {
+    file: File,
+    /// Raw file contents starting at offset `chunk_start`.
+    chunk: Vec<u8>,
+    chunk_start: u64,
+    chunk_size: usize,
+}
+
+impl ByteSource for FileChunks {
+    fn fill(&mut self, offset: u64, len: usize) -> Result<usize, Error> {
+        let chunk_end = self.chunk_start + self.chunk.len() as u64;
+        if offset < self.chunk_start || offset + len as u64 > chunk_end {
+            if (self.chunk_start..chunk_end).contains(&offset) {
+                // Keep what was read of the range and read on from where the chunk ended.
+                self.chunk.drain(..(offset - self.chunk_start) as usize);
+            } else {
+                self.chunk.clear();
+            }
+            self.chunk_start = offset;
+            let read_from = offset + self.chunk.len() as u64;
+            let want = len.max(self.chunk_size) - self.chunk.len();
+            self.file.seek(SeekFrom::Start(read_from))?;
+            // Loops over short reads until the chunk is full or the file ends.
+            (&mut self.file)
+                .take(want as u64)
+                .read_to_end(&mut self.chunk)?;
+        }
+        let start = (offset - self.chunk_start) as usize;
+        Ok(self.chunk.len().saturating_sub(start).min(len))
+    }
+
+    fn bytes(&self, offset: u64, len: usize) -> &[u8] {
+        let start = (offset - self.chunk_start) as usize;
+        &self.chunk[start..start + len]
+    }
+
+    fn len(&self) -> Result<u64, Error> {
+        util::file_len(&self.file)
+    }
+
+    fn refresh(&mut self) {
+        self.chunk.clear();
+    }
+}
+
+/// Untyped reader over the entries of a log file.
+///
+/// The reader does not need to know the block size the file was written with. Pages without
+/// a page header are walked entry by entry straight from the file, skipping the zero padding
+/// at the end of each page up to the next 512-byte boundary; pages with one are decoded
+/// whole.
+pub(crate) struct RawReader<S = FileChunks> {
+    source: S,
+    path: PathBuf,
+    codec: PageCodec,
+    /// File offset of the next entry or page header to parse.
+    pos: u64,
+    /// File offset of the page holding the entry at the cursor: the decoded page in `page`,
+    /// or the page the last entry read straight from the file was found in.
+    page_start: u64,
+    /// Page id recorded in the page meta of the page at `page_start`, if it has one.
+    page_id: Option<u32>,
+    /// The decoded page being read, if any, with `entries` covering its unread entries.
+    page: Vec<u8>,
+    scratch: Vec<u8>,
+    entries: Range<usize>,
+    /// Offset of the first page, past the file header if there is one.
+    data_start: u64,
+    /// Size of the pages the writer filled, which is the spacing of pages without a page
+    /// header; 0 for a legacy file.
+    page_size: u64,
+    /// The schema recorded in the file header; `None` for files written without one.
+    schema: Option<Schema>,
+    version: FormatVersion,
+    /// The entry layout of the file's format version.
+    format: EntryFormat,
+    /// Report damaged entries as errors instead of skipping the rest of their page.
+    strict: bool,
+    /// Offset at which the reader stops looking for further pages and entries.
+    end: Option<u64>,
+}
+
+impl RawReader {
+    pub fn open(path: &Path, codec: PageCodec) -> Result<Self, Error> {
+        Self::open_with(path, codec, &ReadOptions::default())
+    }
+
+    pub fn open_with(path: &Path, codec: PageCodec, options: &ReadOptions) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        let source = FileChunks {
+            file,
+            chunk: Vec::new(),
+            chunk_start: 0,
+            chunk_size: options.chunk_size,
+        };
+        Self::with_source(source, path, codec)
+    }
+}
+
+impl<S: ByteSource> RawReader<S> {
+    pub fn with_source(mut source: S, path: &Path, codec: PageCodec) -> Result<Self, Error> {
+        let available = source.fill(0, size_of::<FileHeader>())?;
+        let header = FileHeader::read_from(source.bytes(0, available));
+        let version = FormatVersion::of_header(header.as_ref())?;
+        let data_start = header.map_or(0, |header| header.data_offset as u64);
+        Ok(Self {
+            source,
+            path: path.to_path_buf(),
+            codec,
+            pos: data_start,
+            page_start: data_start,
+            page_id: None,
+            page: Vec::new(),
+            scratch: Vec::new(),
+            entries: 0..0,
+            data_start,
+            // Files that predate the recorded page size used pages of one block.
+            page_size: header.map_or(0, |header| match header.page_size {
+                0 => data_start,
+                size => size as u64,
+            }),
+            schema: header.map(|header| header.schema()),
+            version,
+            format: version.entry_format(),
+            strict: false,
+            end: None,
+        })
+    }
+
+    /// Makes damaged entries fail the read with an `InvalidData` error. Otherwise the rest of
+    /// the page holding a damaged entry is skipped, since nothing after it can be trusted.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The format the file was written in.
+    pub fn format_version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// The schema recorded in the file header, or `None` for a legacy file.
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// Decodes the page with a page header of `stored_len` bytes at `pos`. Returns `false` if
+    /// the file ends before the page does.
+    fn load_page(&mut self, stored_len: usize) -> Result<bool, Error> {
+        if stored_len > MAX_STORED_PAGE_LEN {
+            return Err(PicoError::DamagedPage {
+                offset: self.pos,
+                len: stored_len,
+            }
+            .into());
+        }
+        if self.source.fill(self.pos, stored_len)? < stored_len {
+            return Ok(false);
+        }
+        self.page.clear();
+        self.page
+            .extend_from_slice(self.source.bytes(self.pos, stored_len));
+        self.entries = self.codec.open(&mut self.page, &mut self.scratch)?;
+        self.page_start = self.pos;
+        self.page_id = None;
+        self.pos = (self.pos + stored_len as u64).next_multiple_of(PAGE_ALIGN);
+        if self.format.page_meta {
+            match PageMeta::read(&self.page[self.entries.clone()]) {
+                Some(meta) if meta.is_intact() || !self.strict => {
+                    self.entries.start += PAGE_META_LEN;
+                    self.page_id = Some(meta.page_id);
+                }
+                _ if self.strict => {
+                    return Err(PicoError::ChecksumMismatch {
+                        offset: self.page_start,
+                    }
+                    .into());
+                }
+                _ => self.entries = 0..0,
+            }
+        }
+        Ok(true)
+    }
+
+    /// Finds the entry at the cursor without consuming it, returning its header and where its
+    /// payload lives. `None` means the file holds no further entries.
+    fn locate(&mut self) -> Result<Option<(EntryHeader, Payload)>, Error> {
+        let header_size = ENTRY_HEADER_LEN;
+        let mut skipped = false;
+        loop {
+            match parse_entry(&self.page, self.entries.clone(), self.format) {
+                Parsed::Entry(header, payload) => {
+                    return Ok(Some((header, Payload::Page(payload))));
+                }
+                Parsed::Corrupt if self.strict => {
+                    return Err(PicoError::DamagedEntry {
+                        offset: self.page_start,
+                    }
+                    .into());
+                }
+                Parsed::Corrupt | Parsed::End => {}
+            }
+            self.entries = 0..0;
+            if self.end.is_some_and(|end| self.pos >= end) {
+                return Ok(None);
+            }
+
+            if self.pos.is_multiple_of(PAGE_ALIGN) {
+                let available = self.source.fill(self.pos, size_of::<PageHeader>())?;
+                if let Some(stored_len) = stored_page_len(self.source.bytes(self.pos, available)) {
+                    if !self.load_page(stored_len)? {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+                if self.format.page_meta && self.skip_page_meta()? {
+                    continue;
+                }
+            }
+
+            let available = self.source.fill(self.pos, header_size)?;
+            if available < header_size {
+                return Ok(None);
+            }
+            let raw = self.source.bytes(self.pos, header_size);
+            let header = EntryHeader::read(raw, self.format.wide_len);
+
+            // Only a page's zero padding reads as an empty header, including one that runs
+            // into the next page: a real entry never leaves its page and never has an
+            // all-zero seq_id away from the start of the file.
+            let boundary = (self.pos / PAGE_ALIGN + 1) * PAGE_ALIGN;
+            let before_boundary = (boundary - self.pos) as usize;
+            let is_padding = header.len == 0
+                || (before_boundary < header_size
+                    && raw[..before_boundary].iter().all(|&b| b == 0));
+            if is_padding {
+                self.pos = boundary;
+                skipped = true;
+                continue;
+            }
+            if !is_plausible(&header, raw, self.format) {
+                if self.strict {
+                    return Err(PicoError::DamagedEntry { offset: self.pos }.into());
+                }
+                // The page's remaining entries can't be found without this one's length, so
+                // resume at the next place a page can start.
+                self.pos = boundary;
+                skipped = true;
+                continue;
+            }
+
+            let entry_len = header_size + header.len as usize;
+            if self.source.fill(self.pos, entry_len)? < entry_len {
+                return Ok(None);
+            }
+            // Padding only ever ends a page, and pages without a page header follow each
+            // other a page size apart.
+            if skipped
+                || (self.page_size > 0
+                    && self.pos >= self.data_start
+                    && (self.pos - self.data_start).is_multiple_of(self.page_size))
+            {
+                self.page_start = self.pos;
+            }
+            let payload = Payload::Raw(self.pos + header_size as u64, header.len as usize);
+            return Ok(Some((header, payload)));
+        }
+    }
+
+    /// Steps over the [`PageMeta`] at the cursor of a page without a page header, if there
+    /// is one. Returns `false` if there is none, so the cursor holds an entry or padding.
+    ///
+    /// A damaged page meta is an error in strict mode. Otherwise it is stepped over all the
+    /// same, since the entries after it are checked on their own.
+    fn skip_page_meta(&mut self) -> Result<bool, Error> {
+        let available = self.source.fill(self.pos, PAGE_META_LEN)?;
+        let Some(meta) = PageMeta::read(self.source.bytes(self.pos, available)) else {
+            return Ok(false);
+        };
+        if self.strict && !meta.is_intact() {
+            return Err(PicoError::ChecksumMismatch { offset: self.pos }.into());
+        }
+        self.page_start = self.pos;
+        self.page_id = Some(meta.page_id);
+        self.pos += PAGE_META_LEN as u64;
+        Ok(true)
+    }
+
+    /// Returns the header of the next entry without consuming it.
+    pub fn peek(&mut self) -> Result<Option<EntryHeader>, Error> {
+        Ok(self.locate()?.map(|(header, _)| header))
+    }
+
+    /// Consumes the next entry, returning its header and payload bytes.
+    pub fn next_entry(&mut self) -> Result<Option<(EntryHeader, &[u8])>, Error> {
+        let Some((header, payload)) = self.locate()? else {
+            return Ok(None);
+        };
+        match payload {
+            Payload::Page(payload) => {
+                let aligned_end = payload
+                    .end
+                    .next_multiple_of(ENTRY_ALIGN)
+                    .min(self.entries.end);
+                self.entries.start = aligned_end;
+                Ok(Some((header, &self.page[payload])))
+            }
+            Payload::Raw(offset, len) => {
+                let entry_len = ENTRY_HEADER_LEN + len;
+                self.pos += entry_len.next_multiple_of(ENTRY_ALIGN) as u64;
+                Ok(Some((header, self.source.bytes(offset, len))))
+            }
+        }
+    }
+
+    pub fn seek_offset(&mut self, offset: u64) {
+        self.pos = offset;
+        self.entries = 0..0;
+        self.page_id = None;
+    }
+
+    /// Returns an offset that [`RawReader::seek_offset`] can resume from without skipping
+    /// the next entry: the reader's position, or the start of the decoded page it is in the
+    /// middle of.
+    pub fn resume_offset(&self) -> u64 {
+        if self.entries.is_empty() {
+            self.pos
+        } else {
+            self.page_start
+        }
+    }
+
+    /// File offset the reader has parsed up to.
+    pub fn offset(&self) -> u64 {
+        self.pos
+    }
+
+    /// File offset of the page holding the entry the reader last found.
+    ///
+    /// For pages without a page header this is inferred from the padding at the end of each
+    /// page and the page size recorded in the file header, so two full pages of a legacy
+    /// file can read as one.
+    pub fn page_start(&self) -> u64 {
+        self.page_start
+    }
+
+    /// Page id of the page at [`RawReader::page_start`], or `None` if it has no page meta.
+    /// Pages keep their id across restarts of the logger, so together with the page's offset
+    /// it names the same page for as long as the file holds it.
+    pub fn page_id(&self) -> Option<u32> {
+        self.page_id
+    }
+
+    /// Offset of the first page, past the file header if there is one.
+    pub fn data_start(&self) -> u64 {
+        self.data_start
+    }
+
+    /// Size of the pages the file was written with before any compression, or 0 for a
+    /// legacy file. Files written before the page size was recorded report their block
+    /// size, which is what their pages were.
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+
+    /// Stops the reader from looking for pages and entries at or past `end`. Entries of a
+    /// decoded page that starts before it are still returned.
+    pub fn limit(&mut self, end: Option<u64>) {
+        self.end = end;
+    }
+
+    /// Finds the last `block`-aligned block past the file header holding a non-zero byte,
+    /// reading the file backwards from its end. Returns `None` if every block is zero.
+    pub fn last_written_block(&mut self, block: u64) -> Result<Option<u64>, Error> {
+        let mut chunk_end = self.source.len()?;
+        while chunk_end > self.data_start {
+            let chunk_start =
+                (chunk_end.saturating_sub(CHUNK_SIZE as u64) / block * block).max(self.data_start);
+            let len = (chunk_end - chunk_start) as usize;
+            let available = self.source.fill(chunk_start, len)?;
+            let bytes = self.source.bytes(chunk_start, available);
+            if let Some(last) = bytes.iter().rposition(|&b| b != 0) {
+                return Ok(Some((chunk_start + last as u64) / block * block));
+            }
+            chunk_end = chunk_start;
+        }
+        Ok(None)
+    }
+
+    /// Drops buffered file contents so entries appended since are read.
+    pub fn refresh(&mut self) {
+        self.source.refresh();
+    }
+
+    /// Positions the reader at the first entry whose seq_id is at least `seq_id`.
+    ///
+    /// Uses the sidecar index when present to jump close to the target page, falling back to
+    /// a scan from the start of the file if the index is missing or stale.
+    pub fn seek(&mut self, seq_id: u64) -> Result<(), Error> {
+        let start = self.indexed_offset(seq_id)?.unwrap_or(self.data_start);
+        self.seek_offset(start);
+        while let Some(header) = self.peek()? {
+            if header.seq_id >= seq_id {
+                break;
+            }
+            self.next_entry()?;
+        }
+        Ok(())
+    }
+
+    /// Finds the offset of the last indexed page starting at or before `seq_id`, validating
+    /// that the page really begins with the recorded seq_id.
+    fn indexed_offset(&mut self, seq_id: u64) -> Result<Option<u64>, Error> {
+        let Some(records) = load_index(&self.path) else {
+            return Ok(None);
+        };
+        let candidates = records.partition_point(|&(first, _)| first <= seq_id);
+        if candidates == 0 {
+            return Ok(None);
+        }
+        let (first_seq_id, offset) = records[candidates - 1];
+
+        if offset >= self.source.len()? {
+            return Ok(None);
+        }
+        self.seek_offset(offset);
+        let valid = self
+            .peek()?
+            .is_some_and(|header| header.seq_id == first_seq_id);
+        Ok(valid.then_some(offset))
+    }
+}
+
+/// Streaming reader yielding the entries of a log file in file order.
+///
+/// Memory use is bounded by a read chunk and a single page regardless of the file size.
+pub struct LogReader<T> {
+    raw: RawReader,
+    failed: bool,
+    /// Seq_id of the newest entry known to be durable, when reading the file of a running
+    /// logger; the reader ends at the first entry past it.
+    durable: Option<u64>,
+    /// Offset of the first page the reader doesn't read; it ends at the first entry there.
+    end: Option<u64>,
+    decode: fn(&[u8]) -> T,
+}
+
+impl<T: Copy> LogReader<T> {
+    /// Reads entries from `raw`, turning their payloads into `T`s with `decode`.
+    pub(crate) fn new(raw: RawReader, decode: fn(&[u8]) -> T) -> Self {
+        Self {
+            raw,
+            failed: false,
+            durable: None,
+            end: None,
+            decode,
+        }
+    }
+
+    /// Turns payloads into entries with `decode` instead of the one the reader was opened
+    /// with.
+    #[cfg(feature = "pod")]
+    pub(crate) fn decoding_with(mut self, decode: fn(&[u8]) -> T) -> Self {
+        self.decode = decode;
+        self
+    }
+
+    /// Ends the reader at the first entry whose seq_id is past `durable`, if given.
+    pub(crate) fn durable_up_to(mut self, durable: Option<u64>) -> Self {
+        self.durable = durable;
+        self
+    }
+
+    /// Limits the reader to the pages starting within `pages`, whose start must be that of
+    /// a page.
+    pub(crate) fn within(mut self, pages: Range<u64>) -> Self {
+        self.raw.seek_offset(pages.start);
+        self.end = Some(pages.end);
+        self
+    }
+
+    /// File offset of the page holding the entry last yielded.
+    pub(crate) fn page_start(&self) -> u64 {
+        self.raw.page_start()
+    }
+
+    /// Offset of the first page, past the file header if there is one.
+    pub(crate) fn data_start(&self) -> u64 {
+        self.raw.data_start()
+    }
+
+    /// Positions the reader so the next entry yielded is the first one whose seq_id is at
+    /// least `seq_id`.
+    ///
+    /// If the file was written with [`Logger::with_index`](crate::Logger::with_index), the
+    /// sidecar index is binary-searched to jump straight to the right page; otherwise the
+    /// file is scanned from the start.
+    pub fn seek(&mut self, seq_id: u64) -> Result<(), Error> {
+        self.failed = false;
+        self.raw.seek(seq_id)
+    }
+}
+
+impl<T: Copy> Iterator for LogReader<T> {
+    type Item = Result<LogEntry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = next_typed(&mut self.raw, &mut self.failed, self.decode)?;
+        if let (Ok(entry), Some(durable)) = (&entry, self.durable)
+            && entry.seq_id > durable
+        {
+            self.failed = true;
+            return None;
+        }
+        if entry.is_ok() && self.end.is_some_and(|end| self.raw.page_start() >= end) {
+            self.failed = true;
+            return None;
+        }
+        Some(entry)
+    }
+}
+
+/// Reads the next entry whose payload is exactly a `T`, turned into one with `decode`,
+/// skipping longer ones, or failing on them in strict mode. A payload shorter than a `T` is
+/// always an error. After an error, `failed` is set and nothing more is returned.
+pub(crate) fn next_typed<T: Copy, S: ByteSource>(
+    raw: &mut RawReader<S>,
+    failed: &mut bool,
+    decode: fn(&[u8]) -> T,
+) -> Option<Result<LogEntry<T>, Error>> {
+    if *failed {
+        return None;
+    }
+    let strict = raw.is_strict();
+    loop {
+        let result = match raw.next_entry() {
+            Ok(Some((header, payload))) if payload.len() == size_of::<T>() => {
+                let data = decode(payload);
+                return Some(Ok(LogEntry {
+                    seq_id: header.seq_id,
+                    ts_nanos: header.ts_nanos,
+                    level: header.level(),
+                    data,
+                }));
+            }
+            // Too short to hold a `T` at all, so never skipped as some other kind of entry.
+            Ok(Some((header, payload))) if strict || payload.len() < size_of::<T>() => {
+                Err(PicoError::EntrySizeMismatch {
+                    seq_id: header.seq_id,
+                    len: payload.len(),
+                    expected: size_of::<T>(),
+                }
+                .into())
+            }
+            Ok(Some(_)) => continue,
+            Ok(None) => return None,
+            Err(e) => Err(e),
+        };
+        *failed = true;
+        return Some(result);
+    }
+}
@@ -0,0 +1,49 @@
+//! Streaming replay of a log file through a closure, for rebuilding state at startup.
+
+use crate::Logger;
+use crate::reader::next_typed;
+use std::io::Error;
+use std::ops::ControlFlow;
+
+/// What a [`Logger::replay`] went through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// Entries passed to the closure, including one that stopped the replay.
+    pub entries: u64,
+    /// Sequence ID of the last entry passed to the closure, or `None` if there was none.
+    pub last_seq_id: Option<u64>,
+    /// Bytes of the file read through, counted from its start.
+    pub bytes_scanned: u64,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Streams the configured log file, calling `f` with each entry's seq_id, timestamp and
+    /// payload in file order.
+    ///
+    /// Memory use stays bounded as with [`Logger::read_iter`], however large the file, and
+    /// entries are validated the same way. Returning [`ControlFlow::Break`] from `f` stops
+    /// the replay early.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ReplaySummary, Error>` - How far the replay got, or an error if the file
+    ///   cannot be read or was written with a different payload type.
+    pub fn replay<F>(&self, mut f: F) -> Result<ReplaySummary, Error>
+    where
+        F: FnMut(u64, u64, &T) -> ControlFlow<()>,
+    {
+        let mut raw = self.raw_reader()?;
+        let mut failed = false;
+        let mut summary = ReplaySummary::default();
+        while let Some(entry) = next_typed(&mut raw, &mut failed, self.payload_codec.decode) {
+            let entry = entry?;
+            summary.entries += 1;
+            summary.last_seq_id = Some(entry.seq_id);
+            if f(entry.seq_id, entry.ts_nanos, &entry.data).is_break() {
+                break;
+            }
+        }
+        summary.bytes_scanned = raw.offset();
+        Ok(summary)
+    }
+}
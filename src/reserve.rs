@@ -0,0 +1,74 @@
+//! Building entries in place in the ring buffer instead of copying them in.
+
+use crate::errors::PicoError;
+use crate::{Level, Logger};
+use std::ops::{Deref, DerefMut};
+
+/// A ring buffer slot held by [`Logger::reserve`], dereferencing to the entry being built.
+///
+/// The slot still holds whatever entry last passed through it, so set every field before
+/// calling [`Reservation::commit`]. Dropping the reservation without committing abandons
+/// it; the slot is handed out again by the next `log()` or `reserve()`.
+pub struct Reservation<'a, T: Send + Sync + Default + Copy + 'static> {
+    logger: &'a mut Logger<T>,
+    index: usize,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Reservation<'_, T> {
+    /// Assigns the entry its sequence ID and hands it to the worker at [`Level::Info`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, PicoError>` - The sequence ID of the entry, or
+    ///   [`PicoError::WorkerStopped`] if the worker stopped while the slot was reserved.
+    pub fn commit(self) -> Result<u64, PicoError> {
+        let started = self.logger.latency_start();
+        let seq_id = crate::global::next_seq_id();
+        self.logger
+            .publish_slot(self.index, Level::Info, seq_id, started)
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Deref for Reservation<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        let buffer = self.logger.data_buffer.as_ref().unwrap();
+        unsafe { &(*buffer.entry(self.index)).data }
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> DerefMut for Reservation<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        let buffer = self.logger.data_buffer.as_ref().unwrap();
+        unsafe { &mut (*buffer.entry(self.index)).data }
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Reserves the next ring buffer slot so an entry can be built directly in it, saving
+    /// the copy `log()` makes.
+    ///
+    /// Unlike `log()`, which reuses the slot even if the worker has not read it yet, a slot
+    /// is only reserved once the worker is done with its previous entry.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Reservation<'_, T>>` - The reserved slot, or `None` if the logger is not
+    ///   running, every slot still holds an entry the worker has not consumed,
+    ///   [`Level::Info`] entries are dropped by [`Logger::with_min_level`], or it was
+    ///   started with [`Logger::start_inline`], which has no ring buffer.
+    pub fn reserve(&mut self) -> Option<Reservation<'_, T>> {
+        self.check_running().ok()?;
+        self.check_level(Level::Info).ok()?;
+        let buffer = self.data_buffer.as_ref()?;
+        let index = (self.next_slot % self.capacity as u64) as usize;
+        if !buffer.is_free(index) {
+            return None;
+        }
+        Some(Reservation {
+            logger: self,
+            index,
+        })
+    }
+}
@@ -0,0 +1,203 @@
+//! An opt-in check that a payload type's bytes still mean something when read back.
+//!
+//! `Logger<T>` only needs `T: Copy`, which rules out `Drop` but not references, raw
+//! pointers or anything else holding an address: those are written out as addresses that
+//! point nowhere in the process reading the log. Padding is no better, since its bytes are
+//! uninitialized, and neither are `bool` and `char`, for which most bit patterns are
+//! invalid. [`SafePayload`] marks the types free of all of them. [`Logger::new_checked`]
+//! and [`LoggerConfig::build_checked`] only accept such types, and
+//! [`safe_payload!`](crate::safe_payload) implements it for a struct after checking every
+//! field and that there is no padding between them.
+//!
+//! With the `pod` feature, `SafePayload` is implemented for exactly the `bytemuck::Pod`
+//! types, and `safe_payload!` implements `Pod` instead, so there is a single marker. A
+//! checked logger then accepts the same types as one from
+//! [`LoggerConfig::build_pod`](crate::LoggerConfig::build_pod) and, like it, writes and
+//! reads entries through `bytemuck`.
+
+use crate::page::PayloadCodec;
+use crate::{Logger, LoggerConfig, PicoError};
+
+/// Declares [`SafePayload`] with `$supertraits`, which differ with the `pod` feature.
+macro_rules! safe_payload_trait {
+    ($($supertraits:tt)*) => {
+        /// A payload type made only of initialized values that any bit pattern is valid
+        /// for, with no reference, raw pointer or other address in it, so its bytes read
+        /// back by another process are the value that was logged.
+        ///
+        /// Implemented for the integer and float primitives and for arrays of `SafePayload`
+        /// types, or with the `pod` feature for every `bytemuck::Pod` type. Implement it for
+        /// a struct with [`safe_payload!`](crate::safe_payload).
+        ///
+        /// # Safety
+        ///
+        /// Every field of the type, at any depth, must itself be `SafePayload`, and the type
+        /// must have no padding. Zero-sized types must not implement it, since they leave
+        /// nothing to log.
+        pub unsafe trait SafePayload: $($supertraits)* {}
+    };
+}
+
+#[cfg(not(feature = "pod"))]
+safe_payload_trait!(Copy + 'static);
+
+#[cfg(feature = "pod")]
+safe_payload_trait!(bytemuck::Pod);
+
+#[cfg(not(feature = "pod"))]
+macro_rules! impl_safe_payload {
+    ($($ty:ty),*) => {
+        $(unsafe impl SafePayload for $ty {})*
+    };
+}
+
+#[cfg(not(feature = "pod"))]
+impl_safe_payload!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+#[cfg(not(feature = "pod"))]
+unsafe impl<T: SafePayload, const N: usize> SafePayload for [T; N] {}
+
+#[cfg(feature = "pod")]
+unsafe impl<T: bytemuck::Pod> SafePayload for T {}
+
+/// The codec of a checked logger: the bytemuck one where every `SafePayload` is `Pod`.
+#[cfg(feature = "pod")]
+fn checked_codec<T: SafePayload>() -> PayloadCodec<T> {
+    PayloadCodec::pod()
+}
+
+/// The codec of a checked logger: with no padding to copy, the raw one is sound.
+#[cfg(not(feature = "pod"))]
+fn checked_codec<T: SafePayload>() -> PayloadCodec<T> {
+    PayloadCodec::raw()
+}
+
+/// Declares a `#[repr(C)]` struct with named fields and implements [`SafePayload`] for it,
+/// failing to compile if any field's type isn't `SafePayload` itself, if the fields leave
+/// padding between them, or if the struct is zero-sized.
+///
+/// The struct must still derive `Copy` (and `Default`, to be logged).
+///
+/// ```
+/// picologger::safe_payload! {
+///     #[derive(Clone, Copy, Debug, Default)]
+///     pub struct Trade {
+///         pub id: u64,
+///         pub price: f64,
+///         pub venue: [u8; 8],
+///     }
+/// }
+/// ```
+///
+/// A reference, which reads back as an address in the writing process, is rejected:
+///
+/// ```compile_fail
+/// picologger::safe_payload! {
+///     #[derive(Clone, Copy, Default)]
+///     struct Event {
+///         id: u64,
+///         name: &'static str,
+///     }
+/// }
+/// ```
+///
+/// So is a field that leaves padding behind it:
+///
+/// ```compile_fail
+/// picologger::safe_payload! {
+///     #[derive(Clone, Copy, Default)]
+///     struct Event {
+///         kind: u8,
+///         id: u64,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! safe_payload {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_attr:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        $vis struct $name {
+            $($(#[$field_attr])* $field_vis $field: $ty),*
+        }
+
+        // Every field is checked to be `SafePayload`, and the struct to be unpadded and not
+        // zero-sized, below.
+        $crate::__impl_safe_payload!($name);
+
+        const _: () = {
+            const fn assert_safe_payload<T: $crate::SafePayload>() {}
+            $(assert_safe_payload::<$ty>();)*
+            assert!(
+                ::core::mem::size_of::<$name>() == 0 $(+ ::core::mem::size_of::<$ty>())*,
+                "a SafePayload struct must not have padding"
+            );
+            assert!(
+                ::core::mem::size_of::<$name>() > 0,
+                "a SafePayload type must not be zero-sized"
+            );
+        };
+    };
+}
+
+#[cfg(not(feature = "pod"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_safe_payload {
+    ($name:ident) => {
+        unsafe impl $crate::SafePayload for $name {}
+    };
+}
+
+#[cfg(feature = "pod")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_safe_payload {
+    ($name:ident) => {
+        unsafe impl $crate::__bytemuck::Zeroable for $name {}
+        unsafe impl $crate::__bytemuck::Pod for $name {}
+    };
+}
+
+impl LoggerConfig {
+    /// Validates the configuration and returns a logger like [`LoggerConfig::build`], for
+    /// payloads checked to be [`SafePayload`]. With the `pod` feature it writes and reads
+    /// entries through `bytemuck`, like `LoggerConfig::build_pod`.
+    pub fn build_checked<T: SafePayload + Default + Send + Sync>(
+        self,
+    ) -> Result<Logger<T>, PicoError> {
+        const {
+            assert!(
+                size_of::<T>() > 0,
+                "a SafePayload type must not be zero-sized"
+            )
+        };
+        let mut logger = self.build()?;
+        logger.payload_codec = checked_codec();
+        Ok(logger)
+    }
+}
+
+impl<T: SafePayload + Default + Send + Sync> Logger<T> {
+    /// Creates a logger like [`Logger::new`], for payloads checked to be [`SafePayload`],
+    /// which like [`LoggerConfig::build_checked`] goes through `bytemuck` with the `pod`
+    /// feature.
+    pub fn new_checked() -> Self {
+        const {
+            assert!(
+                size_of::<T>() > 0,
+                "a SafePayload type must not be zero-sized"
+            )
+        };
+        let mut logger = Self::new();
+        logger.payload_codec = checked_codec();
+        logger
+    }
+}
@@ -0,0 +1,204 @@
+//! Blocks of consecutive sequence ids reserved ahead of logging, for multi-entry writes
+//! whose entries have to carry ids no other entry falls between.
+//!
+//! Reserved ids are logged later, possibly after entries with higher ids, so the worker
+//! holds its durable watermark below every reserved id that is not on disk yet. An id
+//! leaves the set once the page holding it is acknowledged, once it is abandoned, or once
+//! its block is older than the configured timeout.
+
+use crate::errors::PicoError;
+use crate::worker::Message;
+use crate::{Level, Logger, global};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A set of seq_ids kept as disjoint ranges, each remembering when its block was reserved.
+#[derive(Default)]
+pub(crate) struct SeqRanges {
+    /// `start -> (end, reserved_at)`.
+    ranges: BTreeMap<u64, (u64, Instant)>,
+}
+
+impl SeqRanges {
+    pub fn insert(&mut self, ids: Range<u64>) {
+        if !ids.is_empty() {
+            self.ranges.insert(ids.start, (ids.end, Instant::now()));
+        }
+    }
+
+    pub fn contains(&self, seq_id: u64) -> bool {
+        self.ranges
+            .range(..=seq_id)
+            .next_back()
+            .is_some_and(|(_, &(end, _))| seq_id < end)
+    }
+
+    /// Takes `seq_id` out of the set, returning whether it was in it.
+    pub fn remove(&mut self, seq_id: u64) -> bool {
+        let Some((&start, &(end, reserved_at))) = self.ranges.range(..=seq_id).next_back() else {
+            return false;
+        };
+        if seq_id >= end {
+            return false;
+        }
+        self.ranges.remove(&start);
+        if start < seq_id {
+            self.ranges.insert(start, (seq_id, reserved_at));
+        }
+        if seq_id + 1 < end {
+            self.ranges.insert(seq_id + 1, (end, reserved_at));
+        }
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the lowest id in the set, after dropping the ids of blocks reserved more
+    /// than `timeout` ago.
+    pub fn lowest(&mut self, timeout: Option<Duration>) -> Option<u64> {
+        if let Some(timeout) = timeout {
+            self.ranges
+                .retain(|_, &mut (_, reserved_at)| reserved_at.elapsed() < timeout);
+        }
+        self.ranges.keys().next().copied()
+    }
+}
+
+/// Reserved seq_ids that are not durable yet, shared by a logger and its worker.
+#[derive(Default)]
+pub(crate) struct SeqBlocks {
+    /// Set while any id is held, so the worker only takes the lock while blocks are in use.
+    in_use: AtomicBool,
+    held: Mutex<SeqRanges>,
+}
+
+impl SeqBlocks {
+    fn lock(&self) -> MutexGuard<'_, SeqRanges> {
+        self.held
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Holds the watermark below `ids` until each of them is durable or abandoned.
+    pub fn reserve(&self, ids: Range<u64>) {
+        let mut held = self.lock();
+        held.insert(ids);
+        self.in_use.store(!held.is_empty(), Ordering::Release);
+    }
+
+    pub fn in_use(&self) -> bool {
+        self.in_use.load(Ordering::Acquire)
+    }
+
+    /// Whether `seq_id` is reserved and not yet released.
+    pub fn holds(&self, seq_id: u64) -> bool {
+        self.in_use() && self.lock().contains(seq_id)
+    }
+
+    /// Stops holding the watermark for `seq_ids`.
+    pub fn release(&self, seq_ids: &[u64]) {
+        if seq_ids.is_empty() || !self.in_use() {
+            return;
+        }
+        let mut held = self.lock();
+        for &seq_id in seq_ids {
+            held.remove(seq_id);
+        }
+        self.in_use.store(!held.is_empty(), Ordering::Release);
+    }
+
+    /// Returns the lowest id still held, letting go of blocks older than `timeout`.
+    pub fn lowest(&self, timeout: Option<Duration>) -> Option<u64> {
+        if !self.in_use() {
+            return None;
+        }
+        let mut held = self.lock();
+        let lowest = held.lowest(timeout);
+        self.in_use.store(lowest.is_some(), Ordering::Release);
+        lowest
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Reserves `n` consecutive seq_ids, with no other entry in the process numbered between
+    /// them, for entries to be logged later with [`Logger::log_with_seq`].
+    ///
+    /// The entries may be logged in any order, and interleave with other entries on disk.
+    /// Until each reserved id is durable or abandoned, it holds this logger's durable
+    /// watermark below itself: [`Logger::stats`]`().flushed_seq_id` never passes a reserved
+    /// id that is not on disk, even once entries with higher ids are. An id that is never
+    /// logged therefore stalls the watermark until it is given up with
+    /// [`Logger::abandon_seq`], or its block times out as set with
+    /// [`Logger::with_seq_block_timeout`].
+    ///
+    /// The process-wide [`Logger::get_last_flushed_entry`] is moved by every logger in the
+    /// process, so it only keeps this promise while no other logger is running.
+    pub fn reserve_seq_block(&mut self, n: usize) -> Range<u64> {
+        let first = global::next_seq_ids(n as u64);
+        let ids = first..first + n as u64;
+        self.seq_blocks.reserve(ids.clone());
+        #[cfg(debug_assertions)]
+        self.unlogged_seq_ids.insert(ids.clone());
+        ids
+    }
+
+    /// Logs `data` at [`Level::Info`] with `seq_id`, an id from a block returned by
+    /// [`Logger::reserve_seq_block`].
+    ///
+    /// Fails like [`Logger::try_log`], in which case the id stays reserved and can be
+    /// logged again, and with [`PicoError::InlineConflict`] in
+    /// [inline mode](Logger::start_inline).
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if `seq_id` is not from a reserved block, or was already logged or
+    /// abandoned.
+    pub fn log_with_seq(&mut self, seq_id: u64, data: T) -> Result<(), PicoError> {
+        #[cfg(debug_assertions)]
+        assert!(
+            self.unlogged_seq_ids.contains(seq_id),
+            "seq_id {seq_id} is not from a reserved block, or was already logged or abandoned"
+        );
+        self.check_running()?;
+        if self.inline.is_some() {
+            return Err(PicoError::InlineConflict {
+                setting: "log_with_seq",
+            });
+        }
+        self.check_level(Level::Info)?;
+        let started = self.latency_start();
+        let index = self.claim_slots(1)?;
+        let Some(data_buffer) = &self.data_buffer else {
+            return Err(PicoError::NotStarted);
+        };
+        unsafe { (*data_buffer.entry(index)).data = data };
+        self.publish_slot(index, Level::Info, seq_id, started)?;
+        #[cfg(debug_assertions)]
+        self.unlogged_seq_ids.remove(seq_id);
+        Ok(())
+    }
+
+    /// Gives up on logging the reserved `seq_id`, so the durable watermark stops waiting
+    /// for it.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if `seq_id` is not from a reserved block, or was already logged or
+    /// abandoned.
+    pub fn abandon_seq(&mut self, seq_id: u64) {
+        #[cfg(debug_assertions)]
+        assert!(
+            self.unlogged_seq_ids.remove(seq_id),
+            "seq_id {seq_id} is not from a reserved block, or was already logged or abandoned"
+        );
+        self.seq_blocks.release(&[seq_id]);
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(Message::SeqAbandoned);
+        }
+    }
+}
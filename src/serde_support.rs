@@ -0,0 +1,42 @@
+//! Optional serde integration for payloads that are not `Copy`/`#[repr(C)]`.
+//!
+//! Values are encoded with `postcard` and written as variable-length entries, so they share
+//! the page layout and worker with the fixed-size path. A file should hold either serde
+//! entries or `T` entries, not a mix of both.
+
+use crate::{Logger, PicoError};
+use serde::{Serialize, de::DeserializeOwned};
+use std::io::{Error, ErrorKind};
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Encodes `value` and adds it to the log as a variable-length entry.
+    ///
+    /// Unlike [`Logger::log`], this allocates for the encoded bytes. When only serde values
+    /// are logged, `Logger::<()>` keeps the fixed-size ring buffer empty.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, PicoError>` - The sequence ID of the entry, or an error if the logger is
+    ///   not started, the value could not be encoded, or it does not fit in a single page.
+    pub fn log_serde<V: Serialize>(&mut self, value: &V) -> Result<u64, PicoError> {
+        let bytes = postcard::to_stdvec(value)?;
+        self.log_bytes(bytes)
+    }
+
+    /// Reads and decodes all serde entries from the configured log file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<V>, Error>` - The decoded values, or an `InvalidData` error if an entry
+    ///   cannot be decoded as `V`.
+    pub fn read_serde<V: DeserializeOwned>(&self) -> Result<Vec<V>, Error> {
+        let mut reader = self.raw_reader()?;
+        let mut vec = Vec::new();
+        while let Some((_, payload)) = reader.next_entry()? {
+            let value =
+                postcard::from_bytes(payload).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            vec.push(value);
+        }
+        Ok(vec)
+    }
+}
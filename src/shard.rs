@@ -0,0 +1,543 @@
+//! Several loggers writing to striped files, so producers on many threads are not all queued
+//! behind one worker.
+//!
+//! Every shard is a complete [`Logger`] with its own worker, page pool and file
+//! (`<path>.0` … `<path>.<n-1>`). Seq_ids still come from the process-wide counter, so the
+//! shard files interleave into one ordered log again when read with
+//! [`Logger::read_merged`].
+
+use crate::global;
+use crate::reader::{LogEntry, LogReader};
+use crate::stats::{LoggerStats, SharedStats};
+use crate::{Logger, PicoError};
+use crossbeam::utils::Backoff;
+use std::cell::{Cell, UnsafeCell};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ffi::OsString;
+use std::io::Error;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering, fence};
+
+/// Most shards a [`ShardedLogger`] can be built with.
+pub const MAX_SHARDS: usize = 256;
+
+/// Hands each thread the next shard hint the first time it logs.
+static NEXT_THREAD_HINT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD_HINT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// How a [`ShardedLogger`] picks the shard an entry goes to first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShardSelection {
+    /// Every thread starts from one shard, assigned in the order threads first log. With no
+    /// more threads than shards, producers never meet on a shard.
+    #[default]
+    Thread,
+    /// Entries start from the shards in turn, whichever thread logs them.
+    RoundRobin,
+}
+
+struct Shard<T> {
+    /// Set while one thread hands an entry to the shard's logger, or flushes or stops it.
+    claimed: AtomicBool,
+    logger: UnsafeCell<Logger<T>>,
+    /// The logger's stats, read without claiming the shard.
+    stats: Arc<SharedStats>,
+    /// Seq_id of the last entry handed to this shard, or 0 if none.
+    last_logged: AtomicU64,
+    /// While an entry is being handed to this shard, the lowest seq_id it can get; 0
+    /// otherwise.
+    busy_from: AtomicU64,
+}
+
+// The logger is only reached through a `Claim`, which one thread holds at a time.
+unsafe impl<T: Send> Sync for Shard<T> {}
+
+impl<T> Shard<T> {
+    fn try_claim(&self) -> Option<Claim<'_, T>> {
+        self.claimed
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| Claim { shard: self })
+    }
+
+    /// Publishes that an entry is about to be handed to the shard, before its seq_id is
+    /// taken, so [`ShardedLogger::merged_watermark`] stays below it until it is recorded.
+    fn begin_logging(&self) {
+        self.busy_from
+            .store(global::current_seq_id(), Ordering::Release);
+        // Pairs with the fence in `merged_watermark`: a call that sees the seq_id taken
+        // also sees the shard busy.
+        fence(Ordering::SeqCst);
+    }
+
+    /// Records the seq_id of the entry handed to the shard, if it was logged, and clears
+    /// what `begin_logging` published.
+    fn end_logging(&self, seq_id: Option<u64>) {
+        if let Some(seq_id) = seq_id {
+            self.last_logged.store(seq_id, Ordering::Release);
+        }
+        self.busy_from.store(0, Ordering::Release);
+    }
+
+    /// Claims the shard, waiting out the thread that holds it.
+    fn claim(&self) -> Claim<'_, T> {
+        let backoff = Backoff::new();
+        loop {
+            if let Some(claim) = self.try_claim() {
+                return claim;
+            }
+            backoff.snooze();
+        }
+    }
+}
+
+/// Exclusive use of a shard's logger, given back when dropped.
+struct Claim<'a, T> {
+    shard: &'a Shard<T>,
+}
+
+impl<T> Deref for Claim<'_, T> {
+    type Target = Logger<T>;
+
+    fn deref(&self) -> &Logger<T> {
+        unsafe { &*self.shard.logger.get() }
+    }
+}
+
+impl<T> DerefMut for Claim<'_, T> {
+    fn deref_mut(&mut self) -> &mut Logger<T> {
+        unsafe { &mut *self.shard.logger.get() }
+    }
+}
+
+impl<T> Drop for Claim<'_, T> {
+    fn drop(&mut self) {
+        self.shard.claimed.store(false, Ordering::Release);
+    }
+}
+
+/// A set of [`Logger`]s sharing one global sequence, written by as many workers.
+///
+/// Built with [`LoggerConfig::build_sharded`](crate::LoggerConfig::build_sharded). Unlike a
+/// `Logger`, it is shared between producer threads by reference (e.g. in an `Arc`). No
+/// producer ever waits on another: one that finds the shard it picked busy fans out to the
+/// next free one, and only backs off if every shard is busy at once. The shards' seq_ids
+/// still interleave into each producer's order when the files are merged.
+pub struct ShardedLogger<T> {
+    shards: Vec<Shard<T>>,
+    paths: Vec<PathBuf>,
+    selection: ShardSelection,
+    next_shard: AtomicUsize,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> ShardedLogger<T> {
+    pub(crate) fn new(loggers: Vec<Logger<T>>, paths: Vec<PathBuf>) -> Self {
+        Self {
+            shards: loggers
+                .into_iter()
+                .map(|logger| Shard {
+                    claimed: AtomicBool::new(false),
+                    stats: logger.stats.clone(),
+                    logger: UnsafeCell::new(logger),
+                    last_logged: AtomicU64::new(0),
+                    busy_from: AtomicU64::new(0),
+                })
+                .collect(),
+            paths,
+            selection: ShardSelection::default(),
+            next_shard: AtomicUsize::new(0),
+        }
+    }
+
+    /// Applies `configure` to every shard's logger before it is started, e.g.
+    /// `.with_each(|logger| logger.with_compression(Compression::Lz4))`.
+    ///
+    /// The closure is called once per shard, in shard order, so it can hand each one its
+    /// own flush callback.
+    pub fn with_each(mut self, mut configure: impl FnMut(Logger<T>) -> Logger<T>) -> Self {
+        for shard in &mut self.shards {
+            let logger = std::mem::take(shard.logger.get_mut());
+            *shard.logger.get_mut() = configure(logger);
+        }
+        self
+    }
+
+    /// Sets how entries are spread over the shards (default [`ShardSelection::Thread`]).
+    pub fn with_shard_selection(mut self, selection: ShardSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Starts every shard's worker.
+    ///
+    /// If a shard fails to start, the ones already started are shut down again and its
+    /// error is returned.
+    pub fn start(&mut self) -> Result<(), PicoError> {
+        for i in 0..self.shards.len() {
+            let shard = &mut self.shards[i];
+            let logger = shard.logger.get_mut();
+            let started = logger.start();
+            // Starting gives the logger fresh stats.
+            shard.stats = logger.stats.clone();
+            if let Err(e) = started {
+                for started in &mut self.shards[..i] {
+                    let _ = started.logger.get_mut().shutdown();
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of shards, each with its own worker and file.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The file shard `shard` writes to.
+    pub fn shard_path(&self, shard: usize) -> &Path {
+        &self.paths[shard]
+    }
+
+    /// Adds a new log entry to one of the shards.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The sequence ID of the log entry, or `None` if the chosen shard is not
+    ///   started or its worker has stopped. Use [`ShardedLogger::try_log`] to find out which.
+    pub fn log(&self, data: T) -> Option<u64> {
+        self.try_log(data).ok()
+    }
+
+    /// Adds a new log entry to one of the shards, returning its sequence ID.
+    ///
+    /// The entry goes to the shard picked by the [`ShardSelection`], or, if another thread
+    /// is handing an entry to that one, to the next shard that is free. Fails like
+    /// [`Logger::try_log`] on the shard it went to; entries are not rerouted after a failure.
+    pub fn try_log(&self, data: T) -> Result<u64, PicoError> {
+        let first = self.pick_shard();
+        let count = self.shards.len();
+        let backoff = Backoff::new();
+        loop {
+            for i in 0..count {
+                let shard = &self.shards[(first + i) % count];
+                let Some(mut logger) = shard.try_claim() else {
+                    continue;
+                };
+                shard.begin_logging();
+                let logged = logger.try_log(data);
+                shard.end_logging(logged.as_ref().ok().copied());
+                return logged;
+            }
+            backoff.snooze();
+        }
+    }
+
+    /// Asks every shard's worker to write out its partially filled page now; see
+    /// [`Logger::flush`].
+    pub fn flush(&self) -> Result<(), PicoError> {
+        self.shards
+            .iter()
+            .try_for_each(|shard| shard.claim().flush())
+    }
+
+    /// Stops every shard's worker once it has written out what was logged to it; see
+    /// [`Logger::shutdown`].
+    ///
+    /// Every shard is shut down even if one fails; the first error is returned.
+    pub fn shutdown(&self) -> Result<(), PicoError> {
+        let mut result = Ok(());
+        for shard in &self.shards {
+            let outcome = shard.claim().shutdown();
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+        result
+    }
+
+    /// Returns the seq_id of the newest entry each shard has made durable, in shard order,
+    /// or 0 for a shard that has made none durable yet.
+    ///
+    /// A shard's watermark covers only the entries that went to that shard: the others may
+    /// still hold older ones in memory.
+    pub fn shard_watermarks(&self) -> Vec<u64> {
+        self.shards
+            .iter()
+            .map(|shard| shard.stats.flushed_seq_id())
+            .collect()
+    }
+
+    /// Returns a seq_id up to which every entry logged through this sharded logger is
+    /// durable, whichever shard it went to; 0 if that cannot be said of any entry yet.
+    ///
+    /// A shard with entries still in flight holds the watermark back to the last one it
+    /// made durable, while a shard that has caught up doesn't hold it back at all. A shard
+    /// that an entry is being handed to holds it below the lowest seq_id that entry can
+    /// get, and entries whose seq_id is taken after the call starts are never counted, so
+    /// an entry logged concurrently is never reported durable before it is.
+    pub fn merged_watermark(&self) -> u64 {
+        // Every seq_id below this one was taken by an entry whose shard is either still
+        // marked busy or has recorded it in `last_logged`.
+        let limit = global::current_seq_id();
+        fence(Ordering::SeqCst);
+        let mut bound = limit - 1;
+        let mut newest = 0;
+        let mut lagging = None::<u64>;
+        for shard in &self.shards {
+            let busy_from = shard.busy_from.load(Ordering::Acquire);
+            if busy_from != 0 {
+                bound = bound.min(busy_from - 1);
+            }
+            let last_logged = shard.last_logged.load(Ordering::Acquire);
+            let flushed = shard.stats.flushed_seq_id();
+            newest = newest.max(last_logged);
+            if flushed < last_logged {
+                lagging = Some(lagging.map_or(flushed, |bound| bound.min(flushed)));
+            }
+        }
+        lagging.unwrap_or(newest).min(bound)
+    }
+
+    /// Returns a snapshot of each shard's runtime counters, in shard order; see
+    /// [`Logger::stats`].
+    pub fn stats(&self) -> Vec<LoggerStats> {
+        self.shards
+            .iter()
+            .map(|shard| shard.claim().stats())
+            .collect()
+    }
+
+    fn pick_shard(&self) -> usize {
+        let hint = match self.selection {
+            ShardSelection::Thread => THREAD_HINT.with(|hint| {
+                hint.get().unwrap_or_else(|| {
+                    let assigned = NEXT_THREAD_HINT.fetch_add(1, Ordering::Relaxed);
+                    hint.set(Some(assigned));
+                    assigned
+                })
+            }),
+            ShardSelection::RoundRobin => self.next_shard.fetch_add(1, Ordering::Relaxed),
+        };
+        hint % self.shards.len()
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Reads the shard files of a [`ShardedLogger`] configured with this logger's path, merged
+    /// into one list ordered by seq_id.
+    ///
+    /// Shard files are found by probing `<path>.0`, `<path>.1`, … until one is missing. If
+    /// there are none, the file at the path itself is read. A logger given
+    /// [stripe paths](Logger::with_stripe_paths) reads those files instead.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LogEntry<T>>, Error>` - The entries of all shards, or an error if a file
+    ///   cannot be read or was written with a different payload type.
+    pub fn read_merged(&self) -> Result<Vec<LogEntry<T>>, Error> {
+        self.read_merged_iter()?.collect()
+    }
+
+    /// Opens a streaming reader over the shard files like [`Logger::read_merged`].
+    ///
+    /// Holds one [`LogReader`] per shard and yields the entry with the lowest seq_id among
+    /// their next ones, so memory use stays bounded by a page per shard.
+    pub fn read_merged_iter(&self) -> Result<MergedReader<T>, Error> {
+        if !self.stripe_paths.is_empty() {
+            return MergedReader::new(self.stripe_readers()?);
+        }
+        let path = self.log_path()?;
+        let mut paths: Vec<_> = (0..)
+            .map(|i| shard_path(path, i))
+            .take_while(|shard| shard.exists())
+            .collect();
+        if paths.is_empty() {
+            paths.push(path.to_path_buf());
+        }
+        self.read_merged_paths(&paths)
+    }
+
+    /// Opens a streaming reader over the log files at `paths`, merged into one stream
+    /// ordered by seq_id, e.g. to stitch together files split up by hand or collected from
+    /// several processes.
+    ///
+    /// Each file must be in seq_id order by itself and is read with this logger's read
+    /// settings. Entries whose seq_id was already yielded are skipped; those and any seq_ids
+    /// missing between consecutive entries are counted in [`MergedReader::summary`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MergedReader<T>, Error>` - The merged reader, or an error if a file cannot
+    ///   be opened or was written with a different payload type.
+    pub fn read_merged_paths(&self, paths: &[PathBuf]) -> Result<MergedReader<T>, Error> {
+        let readers = paths
+            .iter()
+            .map(|path| {
+                let raw = self.open_raw_at(path)?;
+                self.check_schema(raw.schema())?;
+                Ok(LogReader::new(raw, self.payload_codec.decode))
+            })
+            .collect::<Result<_, Error>>()?;
+        MergedReader::new(readers)
+    }
+}
+
+/// What a [`MergedReader`] went through so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Entries yielded.
+    pub entries: u64,
+    /// Entries skipped because one with the same seq_id was already yielded, as happens when
+    /// a page was written twice.
+    pub duplicates: u64,
+    /// Places where consecutive entries yielded skip over seq_ids.
+    pub gaps: u64,
+    /// Seq_ids skipped over across all gaps.
+    pub missing: u64,
+    /// Sequence ID of the last entry yielded, or `None` if there was none.
+    pub last_seq_id: Option<u64>,
+}
+
+/// Iterator over several log files at once, ordered by seq_id, returned by
+/// [`Logger::read_merged_iter`] and [`Logger::read_merged_paths`].
+///
+/// Each file must itself be in seq_id order, as the files of a [`ShardedLogger`] are. An
+/// entry found more than once, as a page rewritten to another stripe can be, is yielded
+/// once. Only the next entry of every file is held, so memory use stays bounded by a page
+/// per file.
+pub struct MergedReader<T> {
+    readers: Vec<LogReader<T>>,
+    /// The next entry of each reader, or `None` once it is exhausted.
+    heads: Vec<Option<LogEntry<T>>>,
+    /// `(seq_id, reader)` of every head, lowest seq_id on top.
+    order: BinaryHeap<Reverse<(u64, usize)>>,
+    /// An error one of the readers returned, yielded by the next call to `next`, after
+    /// which the iterator ends.
+    error: Option<Error>,
+    summary: MergeSummary,
+}
+
+impl<T: Copy> MergedReader<T> {
+    fn new(readers: Vec<LogReader<T>>) -> Result<Self, Error> {
+        let mut merged = Self {
+            heads: (0..readers.len()).map(|_| None).collect(),
+            readers,
+            order: BinaryHeap::new(),
+            error: None,
+            summary: MergeSummary::default(),
+        };
+        for i in 0..merged.readers.len() {
+            merged.refill(i);
+        }
+        match merged.error.take() {
+            Some(e) => Err(e),
+            None => Ok(merged),
+        }
+    }
+
+    /// Counts of the entries yielded, duplicates skipped and gaps between seq_ids so far;
+    /// final once the iterator ended.
+    pub fn summary(&self) -> MergeSummary {
+        self.summary
+    }
+
+    /// Reads the next entry of reader `i` into its head.
+    fn refill(&mut self, i: usize) {
+        match self.readers[i].next() {
+            Some(Ok(entry)) => {
+                self.order.push(Reverse((entry.seq_id, i)));
+                self.heads[i] = Some(entry);
+            }
+            Some(Err(e)) => self.error = Some(e),
+            None => {}
+        }
+    }
+}
+
+impl<T: Copy> Iterator for MergedReader<T> {
+    type Item = Result<LogEntry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.error.take() {
+                self.order.clear();
+                return Some(Err(e));
+            }
+            let Reverse((seq_id, i)) = self.order.pop()?;
+            let entry = self.heads[i].take()?;
+            self.refill(i);
+            let summary = &mut self.summary;
+            match summary.last_seq_id {
+                Some(last) if seq_id <= last => {
+                    summary.duplicates += 1;
+                    continue;
+                }
+                Some(last) if seq_id > last + 1 => {
+                    summary.gaps += 1;
+                    summary.missing += seq_id - last - 1;
+                }
+                _ => {}
+            }
+            summary.entries += 1;
+            summary.last_seq_id = Some(seq_id);
+            return Some(Ok(entry));
+        }
+    }
+}
+
+/// The file shard `shard` of a sharded logger at `path` writes to.
+pub(crate) fn shard_path(path: &Path, shard: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{shard}"));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LoggerConfig, global};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_merged_watermark_stays_below_a_shard_mid_log() {
+        let path = std::env::temp_dir().join(format!(
+            "picolog_merged_watermark_{}.log",
+            std::process::id()
+        ));
+        let mut logger = LoggerConfig::new()
+            .path(&path)
+            .build_sharded::<u64>(2)
+            .unwrap();
+        logger.start().unwrap();
+
+        // Shard 0 has taken a seq_id for an entry it hasn't recorded yet, as `try_log` does.
+        let held = &logger.shards[0];
+        let claim = held.try_claim().unwrap();
+        held.begin_logging();
+        let held_id = global::next_seq_id();
+
+        // A later entry goes to shard 1, the free one, and becomes durable.
+        let later = logger.try_log(1).unwrap();
+        assert!(later > held_id);
+        logger.shards[1].claim().flush().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while logger.shard_watermarks()[1] < later {
+            assert!(Instant::now() < deadline, "entry {later} never flushed");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(logger.merged_watermark() < held_id);
+
+        held.end_logging(None);
+        drop(claim);
+        logger.shutdown().unwrap();
+        for shard in 0..2 {
+            let _ = std::fs::remove_file(logger.shard_path(shard));
+        }
+    }
+}
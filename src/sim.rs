@@ -0,0 +1,366 @@
+//! A simulated disk for replaying crashes at arbitrary points, enabled by the `sim` feature.
+//! Not part of the stable API.
+//!
+//! A logger given a [`SimDisk`] with [`Logger::with_sim_disk`] still creates its file and
+//! writes the header to it, but every page write after that lands in an in-memory image of
+//! the file instead. The disk completes writes out of order and now and then short, as
+//! decided by a seeded generator. A completed write sits in a volatile cache until a sync
+//! covers it: a linked `fdatasync`, a full sync of its file, or `O_DSYNC` on the file. Once
+//! told to crash the disk tears the cached writes and those in flight, and fails the latter,
+//! and every operation after them, with `EIO`, as a device that went away would.
+//! [`SimDisk::image`] is what a reader would find on the device after the crash.
+
+use crate::Logger;
+use crate::backend::{
+    ALLOCATE_COMPLETION, Backend, FULL_SYNC_COMPLETION, IoBackend, PageOp, RANGE_SYNC_COMPLETION,
+    SYNC_COMPLETION,
+};
+use crate::errors::PicoError;
+use crate::stats::SharedStats;
+use std::fs::File;
+use std::io::Error;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The unit a torn or short write lands in.
+const SECTOR: usize = 512;
+
+/// One in this many page writes completes short.
+const SHORT_WRITE_ODDS: u32 = 8;
+
+/// An in-memory device shared between a test and the logger writing to it.
+///
+/// Cloning it yields another handle to the same disk.
+#[derive(Clone)]
+pub struct SimDisk {
+    state: Arc<Mutex<SimState>>,
+}
+
+struct SimState {
+    rng: fastrand::Rng,
+    /// The bytes of each file that would survive a crash now.
+    images: Vec<Vec<u8>>,
+    /// Completed writes to each file that no sync has covered yet, as offsets and bytes,
+    /// oldest first.
+    unsynced: Vec<Vec<(u64, Vec<u8>)>>,
+    /// Whether the files were opened with `O_DSYNC`, so writes are durable once complete.
+    dsync: bool,
+    /// Page writes submitted and not completed yet.
+    in_flight: Vec<SimWrite>,
+    /// Completions not handed to the worker yet.
+    completed: Vec<(u64, i32)>,
+    writes_completed: usize,
+    crash_after: Option<usize>,
+    crashed: bool,
+    /// The stats of the logger writing to the disk, once it has started.
+    stats: Option<Arc<SharedStats>>,
+}
+
+struct SimWrite {
+    user_data: u64,
+    file: usize,
+    offset: u64,
+    bytes: Vec<u8>,
+    sync: bool,
+}
+
+impl SimDisk {
+    /// Creates an empty disk whose choices of completion order, short writes and torn
+    /// sectors all follow from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimState {
+                rng: fastrand::Rng::with_seed(seed),
+                images: Vec::new(),
+                unsynced: Vec::new(),
+                dsync: false,
+                in_flight: Vec::new(),
+                completed: Vec::new(),
+                writes_completed: 0,
+                crash_after: None,
+                crashed: false,
+                stats: None,
+            })),
+        }
+    }
+
+    /// Crashes the disk in place of completing the page write after the first `writes`.
+    pub fn crash_after(&self, writes: usize) {
+        let mut state = self.lock();
+        state.crash_after = Some(writes);
+        if state.writes_completed >= writes {
+            state.crash();
+        }
+    }
+
+    /// Crashes the disk now, unless it already has.
+    pub fn crash(&self) {
+        self.lock().crash();
+    }
+
+    /// Whether the disk has crashed.
+    pub fn crashed(&self) -> bool {
+        self.lock().crashed
+    }
+
+    /// Number of page writes completed so far, counting each attempt at a write that fell
+    /// short.
+    pub fn writes_completed(&self) -> usize {
+        self.lock().writes_completed
+    }
+
+    /// Seq_id of the newest entry the logger writing to the disk has reported on stable
+    /// storage, its hardened watermark, or 0 if none.
+    pub fn durable_seq_id(&self) -> u64 {
+        self.lock()
+            .stats
+            .as_ref()
+            .map_or(0, |stats| stats.hardened_seq_id())
+    }
+
+    /// The bytes of file `file`, the first unless the logger was given stripe paths: those
+    /// that survived once the disk has crashed, and until then those written so far,
+    /// cached or not.
+    ///
+    /// # Panics
+    ///
+    /// If no logger has started on the disk with that many files.
+    pub fn image(&self, file: usize) -> Vec<u8> {
+        let state = self.lock();
+        let mut image = state.images[file].clone();
+        for (offset, bytes) in &state.unsynced[file] {
+            store(&mut image, *offset as usize, bytes);
+        }
+        image
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Creates the backend of a logger writing `files`, found at `paths` and opened with
+    /// `O_DSYNC` if `dsync`, whose contents so far become the images.
+    pub(crate) fn backend(
+        &self,
+        paths: &[PathBuf],
+        files: Vec<File>,
+        dsync: bool,
+        stats: Arc<SharedStats>,
+    ) -> Result<Box<dyn IoBackend>, Error> {
+        let images: Vec<_> = paths.iter().map(std::fs::read).collect::<Result<_, _>>()?;
+        let mut state = self.lock();
+        state.unsynced = vec![Vec::new(); images.len()];
+        state.images = images;
+        state.dsync = dsync;
+        state.in_flight.clear();
+        state.completed.clear();
+        state.stats = Some(stats);
+        Ok(Box::new(SimBackend {
+            disk: self.clone(),
+            _files: files,
+        }))
+    }
+}
+
+impl SimState {
+    fn crash(&mut self) {
+        if self.crashed {
+            return;
+        }
+        self.crashed = true;
+        // Whatever was cached or in flight may have reached the media in any sectors.
+        for file in 0..self.unsynced.len() {
+            for (offset, bytes) in std::mem::take(&mut self.unsynced[file]) {
+                self.tear(file, offset, &bytes);
+            }
+        }
+        for write in std::mem::take(&mut self.in_flight) {
+            self.tear(write.file, write.offset, &write.bytes);
+            self.fail(&write);
+        }
+    }
+
+    /// Stores each sector of a write of `bytes` at `offset` in file `file` with even odds.
+    fn tear(&mut self, file: usize, offset: u64, bytes: &[u8]) {
+        for (sector, bytes) in bytes.chunks(SECTOR).enumerate() {
+            if self.rng.bool() {
+                store(
+                    &mut self.images[file],
+                    offset as usize + sector * SECTOR,
+                    bytes,
+                );
+            }
+        }
+    }
+
+    /// Makes every completed write to file `file` durable.
+    fn sync(&mut self, file: usize) {
+        for (offset, bytes) in std::mem::take(&mut self.unsynced[file]) {
+            store(&mut self.images[file], offset as usize, &bytes);
+        }
+    }
+
+    fn fail(&mut self, write: &SimWrite) {
+        self.completed.push((write.user_data, -libc::EIO));
+        if write.sync {
+            self.completed
+                .push((write.user_data | SYNC_COMPLETION, -libc::ECANCELED));
+        }
+    }
+
+    /// Completes the in-flight write at `index`, or crashes the disk instead if its time
+    /// has come.
+    fn complete(&mut self, index: usize) {
+        if self.crash_after == Some(self.writes_completed) {
+            self.crash();
+            return;
+        }
+        let write = self.in_flight.swap_remove(index);
+        let sectors = write.bytes.len().div_ceil(SECTOR);
+        let landed = if sectors > 1 && self.rng.u32(..SHORT_WRITE_ODDS) == 0 {
+            self.rng.usize(1..sectors) * SECTOR
+        } else {
+            write.bytes.len()
+        };
+        let bytes = write.bytes[..landed].to_vec();
+        if self.dsync {
+            store(&mut self.images[write.file], write.offset as usize, &bytes);
+        } else {
+            self.unsynced[write.file].push((write.offset, bytes));
+        }
+        self.writes_completed += 1;
+        self.completed.push((write.user_data, landed as i32));
+        if write.sync {
+            // Mirrors a linked io_uring fsync, which is cancelled when its write falls short.
+            let result = if landed == write.bytes.len() {
+                self.sync(write.file);
+                0
+            } else {
+                -libc::ECANCELED
+            };
+            self.completed
+                .push((write.user_data | SYNC_COMPLETION, result));
+        }
+    }
+
+    /// Completes each write in flight with even odds, in a random order, and at least one
+    /// if `wait` and there is no completion to hand over yet.
+    fn complete_some(&mut self, wait: bool) {
+        let wait = wait && self.completed.is_empty();
+        let mut index = 0;
+        while index < self.in_flight.len() && !self.crashed {
+            if self.rng.bool() {
+                let pick = self.rng.usize(index..self.in_flight.len());
+                self.in_flight.swap(index, pick);
+                self.complete(index);
+            } else {
+                index += 1;
+            }
+        }
+        if wait && self.completed.is_empty() && !self.in_flight.is_empty() {
+            let pick = self.rng.usize(..self.in_flight.len());
+            self.complete(pick);
+        }
+    }
+
+    /// Completes a file operation, which fails once the disk has crashed.
+    fn file_op(&mut self, user_data: u64) {
+        let result = if self.crashed { -libc::EIO } else { 0 };
+        self.completed.push((user_data, result));
+    }
+}
+
+fn store(image: &mut Vec<u8>, at: usize, bytes: &[u8]) {
+    if image.len() < at + bytes.len() {
+        image.resize(at + bytes.len(), 0);
+    }
+    image[at..at + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Writes pages to a [`SimDisk`].
+///
+/// Reports itself as [`Backend::Sync`], the real backend it stands in for. Allocations and
+/// syncs complete at once; a full sync makes the writes completed to its file so far
+/// durable, and a range sync, which only starts writeback, none.
+struct SimBackend {
+    disk: SimDisk,
+    /// Kept open so the logger's lock on them is held as usual.
+    _files: Vec<File>,
+}
+
+impl IoBackend for SimBackend {
+    fn kind(&self) -> Backend {
+        Backend::Sync
+    }
+
+    fn submit_page(&mut self, op: &PageOp) -> Result<(), PicoError> {
+        // The worker reuses the page once the write completes, so take a copy.
+        let write = SimWrite {
+            user_data: op.user_data,
+            file: op.file,
+            offset: op.offset,
+            bytes: unsafe { std::slice::from_raw_parts(op.buf, op.len) }.to_vec(),
+            sync: op.sync,
+        };
+        let mut state = self.disk.lock();
+        if state.crashed {
+            state.fail(&write);
+        } else {
+            state.in_flight.push(write);
+        }
+        Ok(())
+    }
+
+    fn submit_allocate(&mut self, file: usize, _offset: u64, _len: u64) -> Result<(), PicoError> {
+        self.disk.lock().file_op(ALLOCATE_COMPLETION | file as u64);
+        Ok(())
+    }
+
+    fn submit_range_sync(&mut self, file: usize, _offset: u64, _len: u64) -> Result<(), PicoError> {
+        self.disk
+            .lock()
+            .file_op(RANGE_SYNC_COMPLETION | file as u64);
+        Ok(())
+    }
+
+    fn submit_full_sync(&mut self, file: usize) -> Result<(), PicoError> {
+        let mut state = self.disk.lock();
+        if !state.crashed {
+            state.sync(file);
+        }
+        state.file_op(FULL_SYNC_COMPLETION | file as u64);
+        Ok(())
+    }
+
+    fn submit(&mut self, wait: bool) -> Result<(), PicoError> {
+        self.disk.lock().complete_some(wait);
+        Ok(())
+    }
+
+    fn poll_completions(&mut self, out: &mut Vec<(u64, i32)>) {
+        let mut state = self.disk.lock();
+        state.complete_some(false);
+        out.append(&mut state.completed);
+    }
+
+    fn rebuild(&mut self) -> Result<(), PicoError> {
+        let mut state = self.disk.lock();
+        state.in_flight.clear();
+        state.completed.clear();
+        Ok(())
+    }
+
+    fn polls_for_completions(&self) -> bool {
+        // Writes complete only when asked about.
+        true
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Writes pages to `disk` instead of the log file once started; see the [`sim`](crate::sim)
+    /// module.
+    pub fn with_sim_disk(mut self, disk: SimDisk) -> Self {
+        self.sim_disk = Some(disk);
+        self
+    }
+}
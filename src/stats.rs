@@ -0,0 +1,311 @@
+use crate::stripe::MAX_STRIPES;
+use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Number of pages flushed for each reason since the logger started.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlushCounts {
+    /// Flushed because the flush interval elapsed.
+    pub timer: u64,
+    /// Flushed because the page filled past the flush watermark.
+    pub watermark: u64,
+    /// Flushed because the next entry did not fit.
+    pub page_full: u64,
+    /// Flushed while shutting down.
+    pub shutdown: u64,
+    /// Flushed because [`Logger::flush`](crate::Logger::flush) was called.
+    pub explicit: u64,
+}
+
+/// A point-in-time snapshot of a logger's runtime counters, returned by
+/// [`Logger::stats`](crate::Logger::stats).
+///
+/// Counters are read individually with relaxed ordering, so a snapshot taken while the
+/// worker is busy may be slightly inconsistent across fields.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoggerStats {
+    /// Entries accepted by `log()`.
+    pub entries_logged: u64,
+    /// Entries whose page write has completed.
+    pub entries_flushed: u64,
+    /// Pages whose write has completed.
+    pub pages_flushed: u64,
+    /// Bytes written to the log file, including page padding.
+    pub bytes_written: u64,
+    /// io_uring operations submitted but not yet completed.
+    pub pending_writes: usize,
+    /// Entries in the ring buffer the worker has not read yet, plus messages waiting in
+    /// its channel.
+    pub channel_depth: usize,
+    /// io_uring operations that completed with an error.
+    pub failed_writes: u64,
+    /// errno of the most recent failed write, or 0 if none has failed.
+    pub last_error_code: i32,
+    /// Pages flushed, broken down by reason.
+    pub flushes: FlushCounts,
+    /// Entries lost before reaching a page; see
+    /// [`Logger::dropped_entries`](crate::Logger::dropped_entries).
+    pub dropped_entries: u64,
+    /// Time the worker spent waiting for a page to come back from the disk before it could
+    /// accept more entries.
+    pub stall_time: Duration,
+    /// Pages added by [`OverflowPolicy::Grow`](crate::OverflowPolicy::Grow).
+    pub pages_grown: usize,
+    /// Length of the log file in bytes: the pre-allocated region as extended so far, or the
+    /// end of the furthest page written if that is beyond it.
+    pub file_len: u64,
+    /// Range syncs issued under a [`SyncPolicy`](crate::SyncPolicy).
+    pub range_syncs: u64,
+    /// Full syncs that completed under a [`SyncPolicy`](crate::SyncPolicy).
+    pub full_syncs: u64,
+    /// Seq_id up to which every entry of this logger is durable, or 0 if none is yet: the
+    /// per-logger counterpart of
+    /// [`Logger::get_last_flushed_entry`](crate::Logger::get_last_flushed_entry), which other
+    /// loggers in the process move too.
+    pub flushed_seq_id: u64,
+    /// Seq_id of the newest entry of this logger known to be on stable storage, or 0 if
+    /// none; see [`Logger::get_last_hardened_entry`](crate::Logger::get_last_hardened_entry).
+    pub hardened_seq_id: u64,
+    /// Bytes of the log file taken by the pages handed to the disk, padding included. A
+    /// page written again in place with more entries counts once.
+    pub file_bytes_written: u64,
+    /// Bytes of the entries in those pages, their headers included.
+    pub payload_bytes_written: u64,
+    /// Offset the next new page goes to: the end of the furthest page handed to the disk,
+    /// or of the header before any. With stripe paths, the furthest across the stripes.
+    pub current_file_offset: u64,
+}
+
+impl LoggerStats {
+    /// Fraction of [`LoggerStats::file_bytes_written`] that holds no entry: page headers and
+    /// the unused tail of pages flushed before they filled up. 0 before any page is written,
+    /// and when compression makes pages smaller than their entries.
+    pub fn padding_overhead_ratio(&self) -> f64 {
+        if self.file_bytes_written == 0 {
+            return 0.0;
+        }
+        let padding = self
+            .file_bytes_written
+            .saturating_sub(self.payload_bytes_written);
+        padding as f64 / self.file_bytes_written as f64
+    }
+}
+
+/// Why the worker flushed a page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FlushReason {
+    Timer,
+    Watermark,
+    PageFull,
+    Shutdown,
+    Explicit,
+}
+
+/// Counters shared between the worker and the `Logger`, updated with relaxed atomics.
+#[derive(Default)]
+pub(crate) struct SharedStats {
+    timer_flushes: AtomicU64,
+    watermark_flushes: AtomicU64,
+    page_full_flushes: AtomicU64,
+    shutdown_flushes: AtomicU64,
+    explicit_flushes: AtomicU64,
+    entries_logged: AtomicU64,
+    entries_flushed: AtomicU64,
+    pages_flushed: AtomicU64,
+    bytes_written: AtomicU64,
+    pending_writes: AtomicUsize,
+    failed_writes: AtomicU64,
+    last_error_code: AtomicI32,
+    unflushed_entries: AtomicUsize,
+    dropped_entries: AtomicU64,
+    stall_nanos: AtomicU64,
+    pages_grown: AtomicUsize,
+    file_len: AtomicU64,
+    /// Seq_id of the last entry in a page whose write completed.
+    flushed_seq_id: AtomicU64,
+    range_syncs: AtomicU64,
+    full_syncs: AtomicU64,
+    hardened_seq_id: AtomicU64,
+    file_bytes_written: AtomicU64,
+    payload_bytes_written: AtomicU64,
+    current_file_offset: AtomicU64,
+    /// The file length and next page offset of each stripe, of which `file_len` and
+    /// `current_file_offset` are the largest.
+    stripe_lens: [AtomicU64; MAX_STRIPES],
+    stripe_offsets: [AtomicU64; MAX_STRIPES],
+}
+
+impl SharedStats {
+    pub fn record_flush(&self, reason: FlushReason) {
+        let counter = match reason {
+            FlushReason::Timer => &self.timer_flushes,
+            FlushReason::Watermark => &self.watermark_flushes,
+            FlushReason::PageFull => &self.page_full_flushes,
+            FlushReason::Shutdown => &self.shutdown_flushes,
+            FlushReason::Explicit => &self.explicit_flushes,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_logged(&self) {
+        self.entries_logged.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_logged_n(&self, count: usize) {
+        self.entries_logged
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_appended(&self) {
+        self.unflushed_entries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_appended_n(&self, count: usize) {
+        self.unflushed_entries.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// A page's write finished, successfully or not, so its entries no longer sit in memory.
+    pub fn record_page_settled(&self, entries: usize) {
+        self.unflushed_entries.fetch_sub(entries, Ordering::Relaxed);
+    }
+
+    pub fn record_dropped(&self) {
+        self.dropped_entries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dropped_n(&self, count: usize) {
+        self.dropped_entries
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn unflushed_entries(&self) -> usize {
+        self.unflushed_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped_entries(&self) -> u64 {
+        self.dropped_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn record_stall(&self, stalled: Duration) {
+        self.stall_nanos
+            .fetch_add(stalled.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_page_grown(&self) {
+        self.pages_grown.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Notes that the file of stripe `stripe` now extends to at least `len` bytes.
+    pub fn record_file_len(&self, stripe: usize, len: u64) {
+        self.file_len.fetch_max(len, Ordering::Relaxed);
+        self.stripe_lens[stripe].fetch_max(len, Ordering::Relaxed);
+    }
+
+    /// Notes that a page taking `file_bytes` of the file and holding `payload_bytes` of
+    /// entries not written before was handed to the disk.
+    pub fn record_page_queued(&self, file_bytes: u64, payload_bytes: u64) {
+        self.file_bytes_written
+            .fetch_add(file_bytes, Ordering::Relaxed);
+        self.payload_bytes_written
+            .fetch_add(payload_bytes, Ordering::Relaxed);
+    }
+
+    /// Notes that the next new page of stripe `stripe` goes at `offset` or beyond.
+    pub fn record_file_offset(&self, stripe: usize, offset: u64) {
+        self.current_file_offset
+            .fetch_max(offset, Ordering::Relaxed);
+        self.stripe_offsets[stripe].fetch_max(offset, Ordering::Relaxed);
+    }
+
+    /// The length of the file of stripe `stripe` and the offset its next new page goes to.
+    pub fn stripe_extent(&self, stripe: usize) -> (u64, u64) {
+        (
+            self.stripe_lens[stripe].load(Ordering::Relaxed),
+            self.stripe_offsets[stripe].load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn record_page_flushed(&self, entries: usize) {
+        self.pages_flushed.fetch_add(1, Ordering::Relaxed);
+        self.entries_flushed
+            .fetch_add(entries as u64, Ordering::Relaxed);
+    }
+
+    /// Notes that the page ending with `seq_id` is durable.
+    pub fn record_flushed_seq_id(&self, seq_id: u64) {
+        self.flushed_seq_id.fetch_max(seq_id, Ordering::Release);
+    }
+
+    /// Seq_id of the newest entry this logger has made durable, or 0 if none.
+    pub fn flushed_seq_id(&self) -> u64 {
+        self.flushed_seq_id.load(Ordering::Acquire)
+    }
+
+    pub fn record_range_sync(&self) {
+        self.range_syncs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Notes that a full sync completed, hardening every entry up to `seq_id`.
+    pub fn record_full_sync(&self, seq_id: u64) {
+        self.full_syncs.fetch_add(1, Ordering::Relaxed);
+        self.record_hardened_seq_id(seq_id);
+    }
+
+    pub fn record_hardened_seq_id(&self, seq_id: u64) {
+        self.hardened_seq_id.fetch_max(seq_id, Ordering::Relaxed);
+    }
+
+    /// Seq_id of the newest entry this logger has put on stable storage, or 0 if none.
+    #[cfg(feature = "sim")]
+    pub fn hardened_seq_id(&self) -> u64 {
+        self.hardened_seq_id.load(Ordering::Relaxed)
+    }
+
+    pub fn record_bytes_written(&self, bytes: u64) {
+        self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn record_failed_write(&self, errno: i32) {
+        self.failed_writes.fetch_add(1, Ordering::Relaxed);
+        self.last_error_code.store(errno, Ordering::Relaxed);
+    }
+
+    pub fn set_pending_writes(&self, pending: usize) {
+        self.pending_writes.store(pending, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self, channel_depth: usize) -> LoggerStats {
+        LoggerStats {
+            entries_logged: self.entries_logged.load(Ordering::Relaxed),
+            entries_flushed: self.entries_flushed.load(Ordering::Relaxed),
+            pages_flushed: self.pages_flushed.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            pending_writes: self.pending_writes.load(Ordering::Relaxed),
+            channel_depth,
+            failed_writes: self.failed_writes.load(Ordering::Relaxed),
+            last_error_code: self.last_error_code.load(Ordering::Relaxed),
+            flushes: self.flush_counts(),
+            dropped_entries: self.dropped_entries(),
+            stall_time: Duration::from_nanos(self.stall_nanos.load(Ordering::Relaxed)),
+            pages_grown: self.pages_grown.load(Ordering::Relaxed),
+            file_len: self.file_len.load(Ordering::Relaxed),
+            range_syncs: self.range_syncs.load(Ordering::Relaxed),
+            full_syncs: self.full_syncs.load(Ordering::Relaxed),
+            flushed_seq_id: self.flushed_seq_id(),
+            hardened_seq_id: self.hardened_seq_id.load(Ordering::Relaxed),
+            file_bytes_written: self.file_bytes_written.load(Ordering::Relaxed),
+            payload_bytes_written: self.payload_bytes_written.load(Ordering::Relaxed),
+            current_file_offset: self.current_file_offset.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn flush_counts(&self) -> FlushCounts {
+        FlushCounts {
+            timer: self.timer_flushes.load(Ordering::Relaxed),
+            watermark: self.watermark_flushes.load(Ordering::Relaxed),
+            page_full: self.page_full_flushes.load(Ordering::Relaxed),
+            shutdown: self.shutdown_flushes.load(Ordering::Relaxed),
+            explicit: self.explicit_flushes.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Striping one logger's pages across several files, so writes can use more than one drive.
+//!
+//! Page `page_id` goes to stripe `page_id % stripes`. Each stripe file starts with its own
+//! copy of the file header and takes its pages back to back, so with uncompressed pages page
+//! `page_id` sits `(page_id / stripes) * page_len` past the header of its file. A stripe whose
+//! write fails is taken out of the rotation and its pages go to the next stripe that is still
+//! healthy, which places them after later pages in that file; readers order the pages of all
+//! stripes by the seq_ids in them instead of relying on file order.
+
+use crate::reader::LogReader;
+use crate::{Logger, PicoError};
+use std::io::Error;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Most files a logger can stripe its pages across.
+pub const MAX_STRIPES: usize = 16;
+
+/// The worker's progress through one stripe file.
+pub(crate) struct Stripe {
+    pub path: PathBuf,
+    /// Offset the next new page written to the file goes to.
+    pub next_offset: u64,
+    /// Length of the file's allocated region, as of the last extension that completed.
+    pub allocated_len: u64,
+    /// The allocated length the extension in flight will reach.
+    pub extending_to: Option<u64>,
+    /// Set once a write to the file failed; it takes no further pages.
+    pub failed: bool,
+    /// Size of the block device the stripe is on, which no page may run past; `None` for a
+    /// regular file.
+    pub device_len: Option<u64>,
+}
+
+impl Stripe {
+    pub fn new(
+        path: PathBuf,
+        next_offset: u64,
+        allocated_len: u64,
+        device_len: Option<u64>,
+    ) -> Self {
+        Self {
+            path,
+            next_offset,
+            allocated_len,
+            extending_to: None,
+            failed: false,
+            device_len,
+        }
+    }
+}
+
+/// Returns `preferred`, or if that stripe failed, the next one after it that hasn't.
+///
+/// A stripe is only taken out of the rotation while another one is left, so there always is
+/// one.
+pub(crate) fn healthy_stripe(stripes: &[Stripe], preferred: usize) -> usize {
+    (0..stripes.len())
+        .map(|i| (preferred + i) % stripes.len())
+        .find(|&i| !stripes[i].failed)
+        .unwrap_or(preferred)
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Writes pages to the files at `paths` in turn instead of to the configured path, one
+    /// page at a time, e.g. to spread writes over several drives.
+    ///
+    /// Every file is opened, locked and preallocated like a single log file would be, gets
+    /// its own copy of the file header and is written through its own descriptor, registered
+    /// with the ring alongside the others. If a write to one of them fails, it is reported as
+    /// [`PicoError::StripeFailed`] on the [error channel](Logger::take_error_receiver) and
+    /// the page, along with every later one, goes to the remaining files; only once all of
+    /// them have failed are pages lost.
+    ///
+    /// Files are still readable one by one; [`Logger::read_merged`] on a logger given the
+    /// same paths reads them back as one log in seq_id order. At most [`MAX_STRIPES`] paths;
+    /// with a single one this is the same as configuring that path. Striped logs cannot be
+    /// appended to or indexed: `start()` fails with [`PicoError::StripeConflict`] when
+    /// combined with [`Logger::with_append`] or [`Logger::with_index`].
+    pub fn with_stripe_paths(mut self, paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Self {
+        self.stripe_paths = paths
+            .into_iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .collect();
+        self
+    }
+
+    /// The files this logger writes to: its stripe paths, or failing that its path.
+    pub(crate) fn write_paths(&self, logpath: &Path) -> Vec<PathBuf> {
+        match self.stripe_paths.is_empty() {
+            true => vec![logpath.to_path_buf()],
+            false => self.stripe_paths.clone(),
+        }
+    }
+
+    /// Checks the stripe paths and the settings they are combined with.
+    pub(crate) fn validate_stripes(&self) -> Result<(), PicoError> {
+        if self.stripe_paths.len() > MAX_STRIPES {
+            return Err(PicoError::InvalidStripeCount {
+                count: self.stripe_paths.len(),
+                max: MAX_STRIPES,
+            });
+        }
+        if self.stripe_paths.len() > 1 {
+            if self.append {
+                return Err(PicoError::StripeConflict {
+                    setting: "with_append",
+                });
+            }
+            if self.index {
+                return Err(PicoError::StripeConflict {
+                    setting: "with_index",
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens one reader per run of pages in seq_id order in each of the stripe files, for
+    /// merging back into one log.
+    pub(crate) fn stripe_readers(&self) -> Result<Vec<LogReader<T>>, Error> {
+        let mut readers = Vec::new();
+        for path in &self.stripe_paths {
+            for run in self.in_order_runs(path)? {
+                readers.push(self.open_stripe(path)?.within(run));
+            }
+        }
+        Ok(readers)
+    }
+
+    /// Splits the stripe file at `path` into runs of pages whose seq_ids ascend, returned as
+    /// the file offsets each run starts and ends at. A stripe that took over pages of a
+    /// failed one holds them after pages with higher seq_ids, starting a new run.
+    fn in_order_runs(&self, path: &Path) -> Result<Vec<Range<u64>>, Error> {
+        let mut reader = self.open_stripe(path)?;
+        let mut starts = vec![reader.data_start()];
+        let mut last = None;
+        while let Some(entry) = reader.next() {
+            let seq_id = entry?.seq_id;
+            if last.is_some_and(|last| seq_id < last) {
+                starts.push(reader.page_start());
+            }
+            last = Some(seq_id);
+        }
+        let ends = starts.iter().skip(1).copied().chain([u64::MAX]);
+        Ok(starts.iter().zip(ends).map(|(&s, e)| s..e).collect())
+    }
+
+    fn open_stripe(&self, path: &Path) -> Result<LogReader<T>, Error> {
+        let raw = self.open_raw_at(path)?;
+        self.check_schema(raw.schema())?;
+        Ok(LogReader::new(raw, self.payload_codec.decode))
+    }
+}
@@ -0,0 +1,156 @@
+//! Reading the newest entries of a log file from its end, without scanning what comes before.
+
+use crate::Logger;
+use crate::reader::{LogEntry, RawReader, next_typed};
+use std::io::{Error, ErrorKind};
+
+impl<T: Send + Sync + Default + Copy + 'static> Logger<T> {
+    /// Opens a reader over the configured log file that yields entries newest first.
+    ///
+    /// The file is read backwards a page at a time from the last block that holds anything,
+    /// so a pre-allocated region of zeros or a torn page at the end is passed over. Only one
+    /// page of entries is held at a time, and pages are only read as entries are asked for,
+    /// so `read_iter_rev()?.take_while(|e| ...)` stops reading once the predicate fails.
+    ///
+    /// Files written before the file header existed do not record where pages start; they
+    /// are read whole on the first call to `next`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ReverseLogReader<T>, Error>` - The reader, or an error if the file cannot be
+    ///   opened or was written with a different payload type.
+    pub fn read_iter_rev(&self) -> Result<ReverseLogReader<T>, Error> {
+        ReverseLogReader::new(self.raw_reader()?, self.payload_codec.decode)
+    }
+
+    /// Reads the last `n` entries of the configured log file, oldest first.
+    ///
+    /// Goes through [`Logger::read_iter_rev`], so only as many pages are decoded as it takes
+    /// to find `n` entries.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LogEntry<T>>, Error>` - Up to `n` entries, fewer if the file holds
+    ///   fewer, or an error if the file cannot be read or was written with a different
+    ///   payload type.
+    pub fn read_last(&self, n: usize) -> Result<Vec<LogEntry<T>>, Error> {
+        let mut entries = self
+            .read_iter_rev()?
+            .take(n)
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Reads the last entry of the configured log file, the way [`Logger::read_last`] does.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<LogEntry<T>>, Error>` - The entry, `None` if the file holds no
+    ///   entries, or an error if it cannot be read or was written with a different payload
+    ///   type.
+    pub fn last_entry(&self) -> Result<Option<LogEntry<T>>, Error> {
+        self.read_iter_rev()?.next().transpose()
+    }
+}
+
+/// Reader yielding the entries of a log file newest first.
+///
+/// Created with [`Logger::read_iter_rev`].
+pub struct ReverseLogReader<T> {
+    raw: RawReader,
+    /// The writer's block size, on whose multiples every page starts; 0 for a legacy file.
+    block: u64,
+    /// Next block to look for a page at, going backwards.
+    next_block: Option<u64>,
+    /// Entries at or past this offset were read from a later page already.
+    end: Option<u64>,
+    /// Unread entries of the current page, oldest first.
+    page: Vec<LogEntry<T>>,
+    failed: bool,
+    decode: fn(&[u8]) -> T,
+}
+
+impl<T: Copy> ReverseLogReader<T> {
+    fn new(mut raw: RawReader, decode: fn(&[u8]) -> T) -> Result<Self, Error> {
+        // The file header records the block size as the offset of the first page.
+        let block = raw.data_start();
+        let next_block = if block == 0 {
+            Some(0)
+        } else {
+            raw.last_written_block(block)?
+        };
+        Ok(Self {
+            raw,
+            block,
+            next_block,
+            end: None,
+            page: Vec::new(),
+            failed: false,
+            decode,
+        })
+    }
+
+    /// Loads the entries of the closest page before the current one into `page`. Returns
+    /// `false` once there are no pages left.
+    fn load_previous_page(&mut self) -> Result<bool, Error> {
+        while let Some(start) = self.next_block.take() {
+            if self.block > 0 && start > self.block {
+                self.next_block = Some(start - self.block);
+            }
+            if self.read_page_at(start)? {
+                self.end = Some(start);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads the entries of the page starting at `start` into `page`. Returns `false` if no
+    /// page starts there: the block is in the middle of a page, or its first entry is
+    /// damaged.
+    fn read_page_at(&mut self, start: u64) -> Result<bool, Error> {
+        let raw = &mut self.raw;
+        raw.seek_offset(start);
+        raw.limit(self.end);
+        if self.block > 0 {
+            match raw.peek() {
+                Ok(Some(_)) if raw.page_start() == start => {}
+                Ok(_) => return Ok(false),
+                // Strict reads reject a block that does not start a page rather than
+                // skipping it.
+                Err(e) if e.kind() == ErrorKind::InvalidData => return Ok(false),
+                Err(e) => return Err(e),
+            }
+        }
+        let mut failed = false;
+        self.page.clear();
+        while let Some(entry) = next_typed(raw, &mut failed, self.decode) {
+            self.page.push(entry?);
+        }
+        Ok(true)
+    }
+}
+
+impl<T: Copy> Iterator for ReverseLogReader<T> {
+    type Item = Result<LogEntry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            if let Some(entry) = self.page.pop() {
+                return Some(Ok(entry));
+            }
+            match self.load_previous_page() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
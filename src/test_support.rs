@@ -0,0 +1,161 @@
+//! Helpers for tests that run several loggers in one process, enabled by the `test-support`
+//! feature. Not part of the stable API.
+//!
+//! Every [`TempLogger`] writes to a directory of its own, so tests running in parallel
+//! never share a file. Sequence ids and [`Logger::get_last_flushed_entry`] are still
+//! process-wide, though: a test that needs its own logger's watermark should read it from
+//! [`Logger::stats`] instead, and one that needs ids without gaps should get them from a
+//! single [`Logger::log_batch`] or [`Logger::reserve_seq_block`].
+
+use crate::{Logger, LoggerConfig};
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::{Deref, DerefMut, Range};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Numbers the directories this process creates.
+static NEXT_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// A logger writing to a fresh file in the system temp directory, which dereferences to
+/// the [`Logger`] and deletes the file, along with anything written next to it, when
+/// dropped.
+///
+/// The logger is built but not started.
+pub struct TempLogger<T: Send + Sync + Default + Copy + 'static> {
+    // Declared first so the worker has stopped writing before the directory goes.
+    logger: Logger<T>,
+    path: PathBuf,
+    dir: TempDir,
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> TempLogger<T> {
+    /// Builds a logger with [`LoggerConfig`]'s defaults.
+    ///
+    /// # Panics
+    ///
+    /// If the temp directory can't be created.
+    pub fn new() -> Self {
+        Self::with_config(LoggerConfig::new())
+    }
+
+    /// Builds a logger from `config`, with its path replaced by the temporary one.
+    ///
+    /// # Panics
+    ///
+    /// If the temp directory can't be created or the configuration is invalid.
+    pub fn with_config(config: LoggerConfig) -> Self {
+        let dir = TempDir::new();
+        let path = dir.path.join("test.log");
+        let logger = config
+            .path(&path)
+            .build()
+            .expect("invalid logger configuration");
+        Self { logger, path, dir }
+    }
+
+    /// Applies `with_*` builder methods to the logger, e.g.
+    /// `.configure(|logger| logger.with_pages(2))`.
+    pub fn configure(self, configure: impl FnOnce(Logger<T>) -> Logger<T>) -> Self {
+        let Self { logger, path, dir } = self;
+        Self {
+            logger: configure(logger),
+            path,
+            dir,
+        }
+    }
+
+    /// The log file's path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a separate logger set up to read the log file.
+    pub fn reader(&self) -> Logger<T> {
+        Logger::new().with_read_config(&self.path)
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Default for TempLogger<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> Deref for TempLogger<T> {
+    type Target = Logger<T>;
+
+    fn deref(&self) -> &Logger<T> {
+        &self.logger
+    }
+}
+
+impl<T: Send + Sync + Default + Copy + 'static> DerefMut for TempLogger<T> {
+    fn deref_mut(&mut self) -> &mut Logger<T> {
+        &mut self.logger
+    }
+}
+
+/// A directory no other logger in the process, or a leftover from an earlier run, uses.
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new() -> Self {
+        let base = std::env::temp_dir();
+        loop {
+            let n = NEXT_DIR.fetch_add(1, Ordering::Relaxed);
+            let path = base.join(format!("picolog-{}-{}", std::process::id(), n));
+            match fs::create_dir(&path) {
+                Ok(()) => return Self { path },
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => panic!("can't create {}: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Asserts that the entries of the log at `path`, in file order, carry consecutive sequence
+/// ids, and returns them.
+///
+/// # Panics
+///
+/// If the log can't be read, or at the first entry whose id doesn't follow the one before.
+pub fn assert_contiguous_seq<T: Send + Sync + Default + Copy + 'static>(
+    path: impl AsRef<Path>,
+) -> Range<u64> {
+    let path = path.as_ref();
+    let reader = Logger::<T>::new().with_read_config(path);
+    let entries = reader
+        .read_iter()
+        .unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e));
+    let mut ids: Option<Range<u64>> = None;
+    for (i, entry) in entries.enumerate() {
+        let seq_id = entry
+            .unwrap_or_else(|e| panic!("can't read entry {} of {}: {}", i, path.display(), e))
+            .seq_id;
+        match &mut ids {
+            None => ids = Some(seq_id..seq_id + 1),
+            Some(ids) => {
+                assert_eq!(
+                    seq_id,
+                    ids.end,
+                    "entry {} of {} has seq_id {}, expected {}",
+                    i,
+                    path.display(),
+                    seq_id,
+                    ids.end
+                );
+                ids.end += 1;
+            }
+        }
+    }
+    ids.unwrap_or(0..0)
+}
@@ -0,0 +1,105 @@
+//! Hooks for exercising the worker's failure handling and timing in tests. Not part of the
+//! stable API.
+
+use crate::clock::Clock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Intercepts page write completions before the worker handles them.
+pub trait FaultInjector: Send + Sync {
+    /// Called with the file offset, attempt number (0 for the first submission) and kernel
+    /// result of each page write; the returned value is used as the result instead, so a
+    /// negative errno simulates a failed write.
+    fn on_write_complete(&self, offset: u64, attempt: u32, result: i32) -> i32;
+
+    /// Called like `on_write_complete`, with the index of the stripe file the page was
+    /// written to as well; 0 unless the logger was given stripe paths. Forwards to
+    /// `on_write_complete` unless overridden.
+    fn on_stripe_write_complete(
+        &self,
+        stripe: usize,
+        offset: u64,
+        attempt: u32,
+        result: i32,
+    ) -> i32 {
+        let _ = stripe;
+        self.on_write_complete(offset, attempt, result)
+    }
+
+    /// Called before each `io_uring_enter`; returning an errno makes the call fail with it
+    /// instead of reaching the kernel.
+    fn fail_submit(&self) -> Option<i32> {
+        None
+    }
+
+    /// Called with the file offset and attempt number of each page write completion before
+    /// `on_write_complete`; returning `true` discards the completion, as if the device never
+    /// finished the write.
+    fn swallow_completion(&self, offset: u64, attempt: u32) -> bool {
+        let _ = (offset, attempt);
+        false
+    }
+
+    /// Called like `swallow_completion`, for completions it kept; returning `true` holds the
+    /// completion back until the worker next polls for completions, where it is offered
+    /// again. Holding one page's completion while later ones go through makes writes
+    /// complete out of order.
+    fn hold_completion(&self, offset: u64, attempt: u32) -> bool {
+        let _ = (offset, attempt);
+        false
+    }
+
+    /// Called like `hold_completion`, for the completion of the `fdatasync` chained after a
+    /// page write in [`Durability::Fdatasync`](crate::Durability) mode. Holding it keeps the
+    /// page's entries from being acknowledged even though the write itself has completed.
+    fn hold_sync_completion(&self, offset: u64, attempt: u32) -> bool {
+        let _ = (offset, attempt);
+        false
+    }
+}
+
+/// Makes the next seq_id handed out process-wide at least `next`, for exercising large ids
+/// without logging that many entries. Affects every logger in the process.
+pub fn advance_seq_id(next: u64) {
+    crate::global::advance_seq_id(next);
+}
+
+/// A [`Clock`] that only moves when told to, for asserting exactly when the worker flushes.
+///
+/// Entry timestamps and the flush timer both read it. The worker still wakes up every poll
+/// interval to look at the clock, so a flush that is due happens within one poll interval of
+/// the [`MockClock::advance`] that made it due.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    /// Creates a clock reading `start_nanos`.
+    pub fn new(start_nanos: u64) -> Self {
+        Self {
+            now: AtomicU64::new(start_nanos),
+        }
+    }
+
+    /// Moves the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        self.now.fetch_add(by.as_nanos() as u64, Ordering::Release);
+    }
+
+    /// Sets the clock to `nanos`. Only timestamps may go backwards this way: the flush timer
+    /// treats time before its last flush as no time elapsed.
+    pub fn set(&self, nanos: u64) {
+        self.now.store(nanos, Ordering::Release);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_nanos(&self) -> u64 {
+        self.now.load(Ordering::Acquire)
+    }
+
+    fn monotonic_nanos(&self) -> u64 {
+        self.now.load(Ordering::Acquire)
+    }
+}
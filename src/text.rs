@@ -0,0 +1,29 @@
+//! Fixed-capacity text buffers shared by the `log` and `tracing` integrations.
+
+/// Appends as much of `s` to `buf[*len..]` as fits, cutting at a character boundary.
+/// Returns `false` if anything was cut off.
+pub(crate) fn write_truncated(buf: &mut [u8], len: &mut u16, s: &str) -> bool {
+    let start = *len as usize;
+    let mut take = s.len().min(buf.len() - start);
+    while !s.is_char_boundary(take) {
+        take -= 1;
+    }
+    buf[start..start + take].copy_from_slice(&s.as_bytes()[..take]);
+    *len += take as u16;
+    take == s.len()
+}
+
+/// Returns the text written by [`write_truncated`].
+pub(crate) fn stored_str(buf: &[u8], len: u16) -> &str {
+    let len = (len as usize).min(buf.len());
+    // Truncation only happens at character boundaries, so this fails only for bytes that
+    // were not written by this crate.
+    std::str::from_utf8(&buf[..len]).unwrap_or("<invalid utf-8>")
+}
+
+/// 64-bit FNV-1a, used for identifiers that must stay stable across runs.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,226 @@
+//! Optional `tracing-subscriber` layer writing events and spans through picolog.
+//!
+//! Each event or new span is flattened into a fixed-size [`TraceRecord`], so recording one
+//! costs a format into a stack buffer and a send to the worker, with no allocation.
+
+use crate::Logger;
+use crate::text::{fnv1a, stored_str, write_truncated};
+use std::fmt::{self, Write};
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+const FIELDS_CAPACITY: usize = 108;
+
+const FLAG_TRUNCATED: u8 = 1;
+const FLAG_SPAN: u8 = 2;
+
+/// A `tracing` event or span flattened into a fixed-size entry.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TraceRecord {
+    /// Wall-clock time the record was created, in nanoseconds since the Unix epoch.
+    pub ts_nanos: u64,
+    /// FNV-1a hash of the callsite's name, target and location, stable across runs of the
+    /// same binary.
+    pub callsite_id: u64,
+    level: u8,
+    flags: u8,
+    len: u16,
+    fields: [u8; FIELDS_CAPACITY],
+}
+
+impl Default for TraceRecord {
+    fn default() -> Self {
+        Self {
+            ts_nanos: 0,
+            callsite_id: 0,
+            level: 0,
+            flags: 0,
+            len: 0,
+            fields: [0; FIELDS_CAPACITY],
+        }
+    }
+}
+
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+impl TraceRecord {
+    /// Bytes of formatted fields kept per record; anything beyond is cut off and the record
+    /// is flagged as truncated.
+    pub const FIELDS_CAPACITY: usize = FIELDS_CAPACITY;
+
+    fn new(metadata: &Metadata<'_>, flags: u8) -> Self {
+        let mut callsite = fnv1a(metadata.name().as_bytes());
+        callsite ^= fnv1a(metadata.target().as_bytes()).rotate_left(1);
+        callsite ^= (metadata.line().unwrap_or(0) as u64).rotate_left(32);
+        Self {
+            ts_nanos: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64),
+            callsite_id: callsite,
+            level: LEVELS
+                .iter()
+                .position(|level| level == metadata.level())
+                .map_or(0, |i| i as u8 + 1),
+            flags,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the record's level, or `None` for an entry that was not written by the layer.
+    pub fn level(&self) -> Option<Level> {
+        LEVELS.get((self.level as usize).wrapping_sub(1)).copied()
+    }
+
+    /// Returns the formatted fields, `message` first and the rest as `name=value`.
+    pub fn fields(&self) -> &str {
+        stored_str(&self.fields, self.len)
+    }
+
+    /// Returns `true` if the fields did not fit in [`TraceRecord::FIELDS_CAPACITY`] bytes.
+    pub fn is_truncated(&self) -> bool {
+        self.flags & FLAG_TRUNCATED != 0
+    }
+
+    /// Returns `true` if the record marks the creation of a span rather than an event.
+    pub fn is_span(&self) -> bool {
+        self.flags & FLAG_SPAN != 0
+    }
+}
+
+impl Write for TraceRecord {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !write_truncated(&mut self.fields, &mut self.len, s) {
+            self.flags |= FLAG_TRUNCATED;
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TraceRecord {
+    /// Formats the record as `<ts_nanos> <LEVEL> <callsite id> <fields>`, prefixing span
+    /// records with `span` and marking truncated fields with a trailing `…`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.level().map_or("?", |level| level.as_str());
+        write!(
+            f,
+            "{} {:<5} {:016x} ",
+            self.ts_nanos, level, self.callsite_id
+        )?;
+        if self.is_span() {
+            f.write_str("span ")?;
+        }
+        f.write_str(self.fields())?;
+        if self.is_truncated() {
+            f.write_char('…')?;
+        }
+        Ok(())
+    }
+}
+
+impl Visit for TraceRecord {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.is_truncated() {
+            return;
+        }
+        let separator = if self.len == 0 { "" } else { " " };
+        let _ = if field.name() == "message" {
+            write!(self, "{}{:?}", separator, value)
+        } else {
+            write!(self, "{}{}={:?}", separator, field.name(), value)
+        };
+    }
+}
+
+/// A [`Layer`] recording every event and new span as a [`TraceRecord`].
+///
+/// Picolog's own spans and events are left out, so the worker of the logger behind the
+/// layer does not record its own flushes.
+///
+/// Clones share one logger, so keep a clone outside the subscriber to
+/// [flush](PicoLayer::flush) it during shutdown.
+#[derive(Clone)]
+pub struct PicoLayer {
+    logger: Arc<Mutex<Logger<TraceRecord>>>,
+}
+
+impl PicoLayer {
+    /// Wraps a started `logger`.
+    pub fn new(logger: Logger<TraceRecord>) -> Self {
+        Self {
+            logger: Arc::new(Mutex::new(logger)),
+        }
+    }
+
+    /// Asks the worker to write out the partially filled page (see [`Logger::flush`]).
+    pub fn flush(&self) {
+        if let Ok(mut logger) = self.logger.lock() {
+            let _ = logger.flush();
+        }
+    }
+
+    fn log(&self, record: TraceRecord) {
+        if let Ok(mut logger) = self.logger.lock() {
+            logger.log(record);
+        }
+    }
+}
+
+/// Returns `true` for spans and events picolog emits about itself.
+fn is_internal(metadata: &Metadata<'_>) -> bool {
+    let target = metadata.target();
+    target
+        .strip_prefix(env!("CARGO_CRATE_NAME"))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+impl<S: Subscriber> Layer<S> for PicoLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if is_internal(event.metadata()) {
+            return;
+        }
+        let mut record = TraceRecord::new(event.metadata(), 0);
+        event.record(&mut record);
+        self.log(record);
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if is_internal(attrs.metadata()) {
+            return;
+        }
+        let mut record = TraceRecord::new(attrs.metadata(), FLAG_SPAN);
+        let _ = write!(record, "{}", attrs.metadata().name());
+        attrs.record(&mut record);
+        self.log(record);
+    }
+}
+
+impl Logger<TraceRecord> {
+    /// Reads the configured log file and formats each trace record as a line of text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, Error>` - One line per record, in the format of
+    ///   [`TraceRecord`]'s `Display` implementation.
+    pub fn read_text(&self) -> Result<Vec<String>, Error> {
+        self.read_iter()?
+            .map(|entry| entry.map(|e| e.data.to_string()))
+            .collect()
+    }
+}
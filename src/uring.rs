@@ -0,0 +1,477 @@
+#[cfg(target_os = "linux")]
+use crate::{
+    backend::{
+        self, ALLOCATE_COMPLETION, Backend, FULL_SYNC_COMPLETION, IoBackend, PageOp,
+        RANGE_SYNC_COMPLETION, SYNC_COMPLETION,
+    },
+    errors::PicoError,
+    probe::{Capabilities, Opcodes},
+    testing::FaultInjector,
+    wakeup::Wakeup,
+};
+#[cfg(target_os = "linux")]
+use io_uring::{EnterFlags, IoUring, opcode, squeue, types};
+#[cfg(target_os = "linux")]
+use std::{
+    fs::File,
+    io::{Error, ErrorKind},
+    os::unix::io::AsRawFd,
+    sync::Arc,
+    time::Duration,
+};
+
+/// Tuning knobs for the worker's io_uring instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UringOptions {
+    /// Runs the ring with `IORING_SETUP_SQPOLL`, so a kernel thread picks up submissions and
+    /// `submit()` only enters the kernel when that thread has gone idle.
+    pub sqpoll: bool,
+    /// Milliseconds without submissions before the SQ poll thread goes to sleep.
+    pub sqpoll_idle_ms: u32,
+    /// CPU to pin the SQ poll thread to.
+    pub sqpoll_cpu: Option<u32>,
+    /// Runs the ring with `IORING_SETUP_IOPOLL`, so completions are busy-polled from the
+    /// device instead of raised by interrupts. This suits NVMe drives with poll queues, and
+    /// needs direct I/O to a file whose filesystem and device support polling: `start()`
+    /// checks with a polled read and fails with
+    /// [`PicoError::IopollUnavailable`](crate::PicoError::IopollUnavailable) otherwise.
+    ///
+    /// A polled ring only takes reads and writes, so fallocate, range syncs and full syncs
+    /// run inline on the worker thread, and it cannot be combined with
+    /// [`Durability::Fdatasync`](crate::Durability::Fdatasync) or [`Backend::Sync`]. Without
+    /// SQPOLL the worker spins while writes are in flight, since it is their only reaper.
+    pub iopoll: bool,
+}
+
+impl Default for UringOptions {
+    fn default() -> Self {
+        Self {
+            sqpoll: false,
+            sqpoll_idle_ms: 1000,
+            sqpoll_cpu: None,
+            iopoll: false,
+        }
+    }
+}
+
+/// What the worker does when its ring fails in a way it cannot retry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Stop the worker. [`Logger::is_healthy`](crate::Logger::is_healthy) turns `false` and
+    /// [`Logger::log`](crate::Logger::log) stops accepting entries.
+    #[default]
+    Abort,
+    /// Tear down the ring, build a new one and resubmit every page write that was in flight.
+    /// The worker only aborts if the new ring cannot be created.
+    RebuildRing,
+}
+
+#[cfg(target_os = "linux")]
+/// Writes pages through an io_uring instance, with the page buffers and the log files
+/// registered where the kernel allows it.
+pub(crate) struct UringBackend {
+    /// Declared first so the ring, and any write still in flight on it, is torn down
+    /// before the files it writes to are closed.
+    ring: IoUring,
+    /// The files written to, registered in this order, so a file's index is also its fixed
+    /// file slot.
+    files: Vec<File>,
+    options: UringOptions,
+    depth: u32,
+    /// What the kernel takes; operations it doesn't run inline instead.
+    ops: Opcodes,
+    iovecs: Vec<libc::iovec>,
+    registered_buffers: bool,
+    fixed_file: bool,
+    /// Signalled by the kernel for every completion once registered as the ring's eventfd.
+    wakeup: Arc<Wakeup>,
+    registered_eventfd: bool,
+    /// Completions reaped while making room in a full CQ, or of file operations a polled
+    /// ring ran inline, waiting for the next poll.
+    reaped: Vec<(u64, i32)>,
+    fault_injector: Option<Arc<dyn FaultInjector>>,
+}
+
+// SAFETY: `iovecs` only describe the page buffers, which the worker owns for as long as the
+// backend lives; they are never dereferenced here, only handed to the kernel.
+#[cfg(target_os = "linux")]
+unsafe impl Send for UringBackend {}
+
+#[cfg(target_os = "linux")]
+impl UringBackend {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ring: IoUring,
+        options: UringOptions,
+        depth: u32,
+        ops: Opcodes,
+        iovecs: Vec<libc::iovec>,
+        files: Vec<File>,
+        wakeup: Arc<Wakeup>,
+        fault_injector: Option<Arc<dyn FaultInjector>>,
+    ) -> Self {
+        let mut backend = Self {
+            ring,
+            files,
+            options,
+            depth,
+            ops,
+            iovecs,
+            registered_buffers: false,
+            fixed_file: false,
+            wakeup,
+            registered_eventfd: false,
+            reaped: Vec::new(),
+            fault_injector,
+        };
+        backend.register();
+        backend
+    }
+
+    /// Registers the page buffers and the log files with the ring, falling back to plain
+    /// buffers and the raw descriptors where the kernel refuses registration, and the
+    /// worker's eventfd, without which it polls for completions instead.
+    fn register(&mut self) {
+        let polls = self.polls_for_completions();
+        let submitter = self.ring.submitter();
+        self.registered_buffers = unsafe { submitter.register_buffers(&self.iovecs) }.is_ok();
+        let fds: Vec<_> = self.files.iter().map(AsRawFd::as_raw_fd).collect();
+        self.fixed_file = submitter.register_files(&fds).is_ok();
+        // Completions on a polled ring are only posted while the worker itself polls.
+        self.registered_eventfd = !polls && submitter.register_eventfd(self.wakeup.fd()).is_ok();
+    }
+
+    /// Whether the ring only takes reads and writes, so other file operations run inline.
+    fn iopoll(&self) -> bool {
+        self.ring.params().is_setup_iopoll()
+    }
+
+    /// Whether `io_uring_enter` takes a timeout, so a wait can end by a deadline.
+    fn waits_time_out(&self) -> bool {
+        self.ring.params().is_feature_ext_arg()
+    }
+
+    fn inline_allocate(&self) -> bool {
+        self.iopoll() || !self.ops.fallocate
+    }
+
+    fn inline_range_sync(&self) -> bool {
+        self.iopoll() || !self.ops.sync_file_range
+    }
+
+    /// Queues `entries` together, making room in the submission queue first if it is full.
+    ///
+    /// A full SQ is expected at small ring depths: submitting hands the queued entries to the
+    /// kernel. With SQPOLL the kernel thread drains the queue asynchronously, so wait for it
+    /// to catch up.
+    fn push_sqes(&mut self, entries: &[squeue::Entry]) -> Result<(), PicoError> {
+        while unsafe { self.ring.submission().push_multiple(entries) }.is_err() {
+            self.enter(0, None)?;
+            if self.ring.params().is_setup_sqpoll() {
+                let _ = self.ring.submitter().squeue_wait();
+            }
+        }
+        Ok(())
+    }
+
+    /// Submits queued SQEs and waits for `want` completions, or until `timeout` passes. Only
+    /// pass a timeout if [`UringBackend::waits_time_out`].
+    ///
+    /// Interrupted calls are retried, and when the kernel pushes back because completions
+    /// are backing up, they are reaped before trying again. Any other error means the ring
+    /// is unusable.
+    fn enter(&mut self, want: usize, timeout: Option<Duration>) -> Result<(), PicoError> {
+        let timespec = timeout.map(types::Timespec::from);
+        loop {
+            let injected = self
+                .fault_injector
+                .as_ref()
+                .and_then(|injector| injector.fail_submit());
+            let result = match injected {
+                Some(errno) => Err(Error::from_raw_os_error(errno)),
+                None => match &timespec {
+                    Some(ts) => self
+                        .ring
+                        .submitter()
+                        .submit_with_args(want, &types::SubmitArgs::new().timespec(ts)),
+                    None => self.ring.submit_and_wait(want),
+                },
+            };
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if timespec.is_some() && e.raw_os_error() == Some(libc::ETIME) => {
+                    return Ok(());
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EBUSY | libc::EAGAIN)) => {
+                    std::thread::yield_now();
+                    let mut reaped = std::mem::take(&mut self.reaped);
+                    self.poll_completions(&mut reaped);
+                    self.reaped = reaped;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IoBackend for UringBackend {
+    fn kind(&self) -> Backend {
+        Backend::IoUring
+    }
+
+    fn submit_page(&mut self, op: &PageOp) -> Result<(), PicoError> {
+        macro_rules! page_ops {
+            ($fd:expr) => {{
+                let write_e =
+                    if self.registered_buffers && (op.buf_index as usize) < self.iovecs.len() {
+                        opcode::WriteFixed::new($fd, op.buf, op.len as _, op.buf_index)
+                            .offset(op.offset)
+                            .build()
+                    } else {
+                        opcode::Write::new($fd, op.buf, op.len as _)
+                            .offset(op.offset)
+                            .build()
+                    };
+                let sync_e = opcode::Fsync::new($fd)
+                    .flags(types::FsyncFlags::DATASYNC)
+                    .build();
+                (write_e, sync_e)
+            }};
+        }
+        let (write_e, sync_e) = if self.fixed_file {
+            page_ops!(types::Fixed(op.file as u32))
+        } else {
+            page_ops!(types::Fd(self.files[op.file].as_raw_fd()))
+        };
+        let write_e = write_e.user_data(op.user_data);
+
+        if op.sync {
+            // Linked so the sync only starts once the write has landed.
+            self.push_sqes(&[
+                write_e.flags(squeue::Flags::IO_LINK),
+                sync_e.user_data(op.user_data | SYNC_COMPLETION),
+            ])
+        } else {
+            self.push_sqes(&[write_e])
+        }
+    }
+
+    fn submit_allocate(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError> {
+        if self.inline_allocate() {
+            let result = backend::allocate_now(&self.files[file], offset, len);
+            self.reaped
+                .push((ALLOCATE_COMPLETION | file as u64, result));
+            return Ok(());
+        }
+        let entry = if self.fixed_file {
+            opcode::Fallocate::new(types::Fixed(file as u32), len)
+        } else {
+            opcode::Fallocate::new(types::Fd(self.files[file].as_raw_fd()), len)
+        };
+        let entry = entry
+            .offset(offset)
+            .build()
+            .user_data(ALLOCATE_COMPLETION | file as u64);
+        self.push_sqes(&[entry])
+    }
+
+    fn submit_range_sync(&mut self, file: usize, offset: u64, len: u64) -> Result<(), PicoError> {
+        if self.inline_range_sync() {
+            let result = backend::range_sync_now(&self.files[file], offset, len);
+            self.reaped
+                .push((RANGE_SYNC_COMPLETION | file as u64, result));
+            return Ok(());
+        }
+        // A length of 0 runs to the end of the file, for ranges a u32 can't hold.
+        let len = u32::try_from(len).unwrap_or(0);
+        let entry = if self.fixed_file {
+            opcode::SyncFileRange::new(types::Fixed(file as u32), len)
+        } else {
+            opcode::SyncFileRange::new(types::Fd(self.files[file].as_raw_fd()), len)
+        };
+        let entry = entry
+            .offset(offset)
+            .flags(libc::SYNC_FILE_RANGE_WRITE)
+            .build()
+            .user_data(RANGE_SYNC_COMPLETION | file as u64);
+        self.push_sqes(&[entry])
+    }
+
+    fn submit_full_sync(&mut self, file: usize) -> Result<(), PicoError> {
+        if self.iopoll() {
+            let result = backend::full_sync_now(&self.files[file]);
+            self.reaped
+                .push((FULL_SYNC_COMPLETION | file as u64, result));
+            return Ok(());
+        }
+        let entry = if self.fixed_file {
+            opcode::Fsync::new(types::Fixed(file as u32))
+        } else {
+            opcode::Fsync::new(types::Fd(self.files[file].as_raw_fd()))
+        };
+        let entry = entry
+            .flags(types::FsyncFlags::DATASYNC)
+            .build()
+            .user_data(FULL_SYNC_COMPLETION | file as u64);
+        self.push_sqes(&[entry])
+    }
+
+    fn submit(&mut self, wait: bool) -> Result<(), PicoError> {
+        if wait && !self.reaped.is_empty() {
+            return self.enter(0, None);
+        }
+        self.enter(wait as usize, None)
+    }
+
+    fn submit_timeout(&mut self, timeout: Duration) -> Result<(), PicoError> {
+        if !self.reaped.is_empty() {
+            return self.enter(0, None);
+        }
+        if !self.waits_time_out() {
+            // A blocking wait could outlast the deadline, so submit and look again shortly.
+            self.enter(0, None)?;
+            if self.ring.completion().is_empty() {
+                std::thread::sleep(timeout.min(Duration::from_millis(1)));
+            }
+            return Ok(());
+        }
+        self.enter(1, Some(timeout))
+    }
+
+    fn poll_completions(&mut self, out: &mut Vec<(u64, i32)>) {
+        if self.polls_for_completions() {
+            // Reap whatever the device has finished without submitting or waiting; a failure
+            // here only means nothing is reaped until the next poll.
+            let _ = unsafe {
+                self.ring.submitter().enter::<libc::sigset_t>(
+                    0,
+                    0,
+                    EnterFlags::GETEVENTS.bits(),
+                    None,
+                )
+            };
+        }
+        out.append(&mut self.reaped);
+        out.extend(
+            self.ring
+                .completion()
+                .map(|cqe| (cqe.user_data(), cqe.result())),
+        );
+    }
+
+    fn rebuild(&mut self) -> Result<(), PicoError> {
+        // SQPOLL was either granted or already warned about when the first ring was built.
+        (self.ring, _) = setup_ring(&self.options, self.depth)?;
+        self.reaped.clear();
+        self.register();
+        Ok(())
+    }
+
+    fn drain(&mut self) {
+        if std::mem::take(&mut self.registered_buffers) {
+            let _ = self.ring.submitter().unregister_buffers();
+        }
+        if std::mem::take(&mut self.fixed_file) {
+            let _ = self.ring.submitter().unregister_files();
+        }
+        if std::mem::take(&mut self.registered_eventfd) {
+            let _ = self.ring.submitter().unregister_eventfd();
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        let params = self.ring.params();
+        Capabilities {
+            backend: Backend::IoUring,
+            opcode_probe: self.ops.probed,
+            fallocate: !self.inline_allocate(),
+            sync_file_range: !self.inline_range_sync(),
+            wait_timeout: self.waits_time_out(),
+            registered_buffers: self.registered_buffers,
+            fixed_files: self.fixed_file,
+            sqpoll: params.is_setup_sqpoll(),
+            iopoll: params.is_setup_iopoll(),
+            completion_wakeups: self.registered_eventfd,
+        }
+    }
+
+    fn polls_for_completions(&self) -> bool {
+        // With SQPOLL the kernel thread polls, and posts completions as they come in.
+        self.iopoll() && !self.ring.params().is_setup_sqpoll()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for UringBackend {
+    fn drop(&mut self) {
+        // Closing the ring releases registered files only once the kernel's asynchronous
+        // teardown runs, which would keep the log files locked after the worker is gone.
+        self.drain();
+    }
+}
+
+#[cfg(target_os = "linux")]
+/// Creates the worker's ring with `entries` submission slots.
+///
+/// If SQPOLL is requested but the kernel refuses it (older kernels required `CAP_SYS_NICE`),
+/// a regular ring is created instead and returned along with the reason. IOPOLL is kept
+/// either way.
+pub(crate) fn setup_ring(
+    options: &UringOptions,
+    entries: u32,
+) -> Result<(IoUring, Option<Error>), Error> {
+    // A page write and the sync linked after it are pushed together.
+    let entries = entries.max(2);
+    let regular = || {
+        let mut builder = IoUring::builder();
+        if options.iopoll {
+            builder.setup_iopoll();
+        }
+        builder
+    };
+    if options.sqpoll {
+        let mut builder = regular();
+        builder.setup_sqpoll(options.sqpoll_idle_ms);
+        if let Some(cpu) = options.sqpoll_cpu {
+            builder.setup_sqpoll_cpu(cpu);
+        }
+        match builder.build(entries) {
+            Ok(ring) => return Ok((ring, None)),
+            Err(e) => return Ok((regular().build(entries)?, Some(e))),
+        }
+    }
+    Ok((regular().build(entries)?, None))
+}
+
+#[cfg(target_os = "linux")]
+/// Reads the block at the start of `file` into `buf` through a polled ring, returning the
+/// negative errno of the read, or of creating the ring, if either fails.
+///
+/// Filesystems and devices that cannot be polled reject the read when it is issued, so this
+/// tells them apart before the worker submits writes whose completions would never show up.
+pub(crate) fn probe_iopoll(file: &File, buf: &mut [u8]) -> Result<(), i32> {
+    let errno = |e: Error| e.raw_os_error().unwrap_or(libc::EIO);
+    let mut ring: IoUring = IoUring::builder().setup_iopoll().build(2).map_err(errno)?;
+    let read = opcode::Read::new(
+        types::Fd(file.as_raw_fd()),
+        buf.as_mut_ptr(),
+        buf.len() as _,
+    )
+    .offset(0)
+    .build();
+    unsafe { ring.submission().push(&read) }.map_err(|_| libc::EBUSY)?;
+    loop {
+        match ring.submit_and_wait(1) {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(errno(e)),
+        }
+    }
+    match ring.completion().next() {
+        Some(cqe) if cqe.result() < 0 => Err(-cqe.result()),
+        Some(_) => Ok(()),
+        None => Err(libc::EIO),
+    }
+}
@@ -1,61 +1,502 @@
-use std::fs::{File, OpenOptions};
+use crate::durability::Durability;
+use crate::errors::PicoError;
+use std::fs::File;
 use std::path;
 
-pub fn get_blksize(path: &path::PathBuf) -> u64 {
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(metadata) = std::fs::metadata(path) {
-            use std::os::linux::fs::MetadataExt;
-            return metadata.st_blksize();
-        } else {
-            return 4096; // Default fallback
+/// How the log file is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IoMode {
+    /// Writes bypass the page cache: `O_DIRECT` on Linux, `F_NOCACHE` on macOS.
+    #[default]
+    Direct,
+    /// The filesystem rejected `O_DIRECT` (tmpfs on older kernels, some network and
+    /// container filesystems), so writes go through the page cache.
+    Buffered,
+}
+
+/// Smallest block size there is, and the fallback when nothing better is known.
+pub const MIN_BLOCK_SIZE: usize = 512;
+
+/// Largest block size taken from what a filesystem reports. Some report 64 KiB or more as
+/// their preferred I/O size, which would make every page that large.
+pub const MAX_AUTO_BLOCK_SIZE: usize = 16 * 1024;
+
+/// The block size `start()` picks for a log file at `path` before opening it.
+///
+/// A block device reports its logical sector size. Otherwise `st_blksize` of the file, or
+/// of its directory if it doesn't exist yet, is used when it is a power of two, capped at
+/// [`MAX_AUTO_BLOCK_SIZE`], and 4096 when it isn't. The result is never below the direct
+/// I/O alignment of an existing file.
+pub fn get_blksize(path: &path::Path) -> usize {
+    let mut alignment = None;
+    if let Ok(file) = File::open(path) {
+        if let Ok(Some(device)) = block_device(&file) {
+            return device.sector_size;
         }
+        alignment = direct_io_alignment(&file).ok().flatten();
     }
+    let reported = reported_blksize(path)
+        .filter(|size| size.is_power_of_two() && *size >= MIN_BLOCK_SIZE)
+        .map_or(4096, |size| size.min(MAX_AUTO_BLOCK_SIZE));
+    reported.max(alignment.unwrap_or(0))
+}
+
+#[cfg(unix)]
+fn reported_blksize(path: &path::Path) -> Option<usize> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => std::fs::metadata(parent_dir(path)?).ok()?,
+    };
+    Some(metadata.blksize() as usize)
+}
 
-    #[cfg(target_os = "windows")]
+#[cfg(not(unix))]
+fn reported_blksize(_path: &path::Path) -> Option<usize> {
+    None
+}
+
+/// Returns the offset and length alignment `O_DIRECT` I/O on `file` needs, if the kernel
+/// can tell: `STATX_DIOALIGN` for files, the logical sector size for block devices.
+#[cfg(target_os = "linux")]
+pub fn direct_io_alignment(file: &File) -> Result<Option<usize>, std::io::Error> {
+    if let Some(device) = block_device(file)? {
+        return Ok(Some(device.sector_size));
+    }
+    #[cfg(target_env = "gnu")]
     {
-        //TODO: Implement Windows procedure
-        panic!("Windows block size detection not implemented");
+        use std::os::unix::io::AsRawFd;
+        let mut statx: libc::statx = unsafe { std::mem::zeroed() };
+        let ret = unsafe {
+            libc::statx(
+                file.as_raw_fd(),
+                c"".as_ptr(),
+                libc::AT_EMPTY_PATH,
+                libc::STATX_DIOALIGN,
+                &mut statx,
+            )
+        };
+        // Kernels before 6.1 don't know the field and leave it out of the mask.
+        if ret == 0 && statx.stx_mask & libc::STATX_DIOALIGN != 0 && statx.stx_dio_offset_align > 0
+        {
+            return Ok(Some(statx.stx_dio_offset_align as usize));
+        }
     }
+    Ok(None)
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        //TODO: Implement mac procedure
-        panic!("MacOS block size detection not implemented");
+#[cfg(not(target_os = "linux"))]
+pub fn direct_io_alignment(_file: &File) -> Result<Option<usize>, std::io::Error> {
+    Ok(None)
+}
+
+/// Writes `buf` to `file` at `offset` to check that direct I/O takes writes of its size
+/// there, then cuts a regular file back to the length it had. A block device keeps `buf`
+/// at `offset`, so callers pass zeros and an offset nothing valid lives at yet. Does nothing
+/// if the write would run past the end of a block device.
+#[cfg(unix)]
+pub fn probe_direct_write(file: &File, offset: u64, buf: &[u8]) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::FileExt;
+    let end = offset + buf.len() as u64;
+    match block_device(file)? {
+        Some(device) if end > device.len => Ok(()),
+        Some(_) => file.write_all_at(buf, offset),
+        None => {
+            let len = file.metadata()?.len();
+            file.write_all_at(buf, offset)?;
+            if len < end {
+                file.set_len(len)?;
+            }
+            Ok(())
+        }
     }
 }
 
-pub fn get_file_handler(path: &path::PathBuf, pre_alloc_size: u64) -> Result<File, std::io::Error> {
-    #[cfg(target_os = "linux")]
+#[cfg(not(unix))]
+pub fn probe_direct_write(_file: &File, _offset: u64, _buf: &[u8]) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+pub fn get_file_handler(
+    path: &path::Path,
+    pre_alloc_size: u64,
+    durability: Durability,
+) -> Result<(File, IoMode), std::io::Error> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     {
+        use std::fs::OpenOptions;
         use std::os::unix::fs::OpenOptionsExt;
-        let file = OpenOptions::new()
+        let mut options = OpenOptions::new();
+        let dsync_flag = if durability == Durability::Odsync {
+            libc::O_DSYNC
+        } else {
+            0
+        };
+        options
             .read(true)
             .write(true)
             .create(true)
-            .custom_flags(libc::O_DIRECT)
-            .open(path)
-            .expect("CRITICAL: Failed to open file with O_DIRECT. Verify FS supports it.");
-
-        
-        if let Ok(metadata) = file.metadata() {
-            if metadata.len() < pre_alloc_size {
-                 println!("Pre-allocating disk space...");
-                 file.set_len(pre_alloc_size)?; 
-                 // Force metadata sync to disk
-                 file.sync_all()?; 
-            }
+            .custom_flags(dsync_flag);
+        let (file, io_mode) = open_direct(path, &options, dsync_flag)?;
+        lock_exclusive(&file, path)?;
+        // A device has all its blocks already and no directory entry of ours to sync.
+        if block_device(&file)?.is_some() {
+            return Ok((file, io_mode));
         }
-        Ok(file)
+
+        if let Ok(metadata) = file.metadata()
+            && metadata.len() < pre_alloc_size
+        {
+            preallocate(&file, pre_alloc_size)?;
+            // Force metadata sync to disk
+            file.sync_all()?;
+        }
+        // Done on every start rather than only on creation, in case an earlier run crashed
+        // between creating the file and syncing its directory.
+        sync_parent_dir(path)?;
+        Ok((file, io_mode))
     }
-    #[cfg(target_os = "windows")]
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
     {
-        //TODO: Implement Windows equivalent of IO-uring
-        panic!("Windows file handler with direct IO not implemented");
+        let _ = (path, pre_alloc_size, durability);
+        Err(
+            PicoError::UnsupportedPlatform("writes are only implemented for Linux and macOS")
+                .into(),
+        )
+    }
+}
+
+/// Geometry of a block device written to in place of a regular file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockDevice {
+    /// Logical sector size, the alignment `O_DIRECT` writes need.
+    pub sector_size: usize,
+    /// Size of the device in bytes; nothing can be written past it.
+    pub len: u64,
+}
+
+/// `_IOR(0x12, 114, size_t)`, which libc doesn't define.
+#[cfg(target_os = "linux")]
+const BLKGETSIZE64: u64 = {
+    let read: u64 = if cfg!(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc64"
+    )) {
+        2 << 29
+    } else {
+        2 << 30
+    };
+    read | ((size_of::<usize>() as u64) << 16) | (0x12 << 8) | 114
+};
+
+/// Returns the geometry of `file` if it is a block device, or `None` for anything else.
+#[cfg(target_os = "linux")]
+pub fn block_device(file: &File) -> Result<Option<BlockDevice>, std::io::Error> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::io::AsRawFd;
+    if !file.metadata()?.file_type().is_block_device() {
+        return Ok(None);
+    }
+    let fd = file.as_raw_fd();
+    let mut sector_size: libc::c_int = 0;
+    if unsafe { libc::ioctl(fd, libc::BLKSSZGET, &mut sector_size) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut len: u64 = 0;
+    if unsafe { libc::ioctl(fd, BLKGETSIZE64 as _, &mut len) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(Some(BlockDevice {
+        sector_size: sector_size as usize,
+        len,
+    }))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn block_device(_file: &File) -> Result<Option<BlockDevice>, std::io::Error> {
+    Ok(None)
+}
+
+/// Returns how many bytes `file` holds: its length, or the size of a block device.
+pub fn file_len(file: &File) -> Result<u64, std::io::Error> {
+    match block_device(file)? {
+        Some(device) => Ok(device.len),
+        None => Ok(file.metadata()?.len()),
+    }
+}
+
+/// Returns the directory whose entry for `path` has to be synced: `.` for a bare file name,
+/// and `None` for a path without a parent, such as `/`.
+#[cfg(unix)]
+fn parent_dir(path: &path::Path) -> Option<&path::Path> {
+    match path.parent() {
+        Some(dir) if dir.as_os_str().is_empty() => Some(path::Path::new(".")),
+        parent => parent,
+    }
+}
+
+/// Returns how many more bytes a log at `path` can take past `offset`, where its first
+/// `allocated_len` bytes are already allocated: what is left of a block device, or of a
+/// file's allocated region plus the space its file system has free.
+#[cfg(unix)]
+pub fn room_left(
+    path: &path::Path,
+    allocated_len: u64,
+    offset: u64,
+) -> Result<u64, std::io::Error> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileTypeExt;
+    let allocated_left = allocated_len.saturating_sub(offset);
+    if std::fs::metadata(path)?.file_type().is_block_device() {
+        return Ok(allocated_left);
+    }
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let mut fs_stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut fs_stats) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let free = fs_stats.f_bavail as u64 * fs_stats.f_frsize as u64;
+    Ok(allocated_left + free)
+}
+
+#[cfg(not(unix))]
+pub fn room_left(
+    _path: &path::Path,
+    _allocated_len: u64,
+    _offset: u64,
+) -> Result<u64, std::io::Error> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Fsyncs the directory containing `path`, so a file created or renamed there survives a
+/// crash. Syncing the file itself only persists its contents and size, not its name.
+///
+/// Call this after every operation that adds or renames a file the logger depends on.
+/// Filesystems that cannot sync directories report `EINVAL`, which is ignored.
+#[cfg(unix)]
+pub fn sync_parent_dir(path: &path::Path) -> Result<(), std::io::Error> {
+    let Some(dir) = parent_dir(path) else {
+        return Ok(());
+    };
+    match File::open(dir)?.sync_all() {
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => Ok(()),
+        result => result,
+    }
+}
+
+/// Creates the directories missing above `path`, syncing each one into its parent so the
+/// whole path survives a crash.
+pub fn create_parent_dirs(path: &path::Path) -> Result<(), std::io::Error> {
+    let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+        return Ok(());
+    };
+    let missing: Vec<&path::Path> = dir
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir)?;
+    for created in missing.iter().rev() {
+        sync_parent_dir(created)?;
     }
-    #[cfg(target_os = "macos")]
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn sync_parent_dir(_path: &path::Path) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+/// Takes an exclusive `flock(2)` on the log file, held until `file` is closed.
+///
+/// The lock belongs to the open file description, so a second logger on the same path is
+/// refused even within one process. Readers take no lock and can scan a file being written.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn lock_exclusive(file: &File, path: &path::Path) -> Result<(), std::io::Error> {
+    use std::os::unix::io::AsRawFd;
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            Some(libc::EWOULDBLOCK) => {
+                return Err(PicoError::FileLocked {
+                    path: path.to_path_buf(),
+                }
+                .into());
+            }
+            _ => return Err(err),
+        }
+    }
+}
+
+/// Opens `path` with `O_DIRECT`, falling back to buffered writes on filesystems that
+/// reject it.
+#[cfg(target_os = "linux")]
+fn open_direct(
+    path: &path::Path,
+    options: &std::fs::OpenOptions,
+    dsync_flag: i32,
+) -> Result<(File, IoMode), std::io::Error> {
+    use std::os::unix::fs::OpenOptionsExt;
+    match options
+        .clone()
+        .custom_flags(libc::O_DIRECT | dsync_flag)
+        .open(path)
     {
-        //TODO: Implement MacOS equivalent of IO-uring
-        panic!("MacOS file handler with direct IO not implemented");
+        Ok(file) => Ok((file, IoMode::Direct)),
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                path = %path.display(),
+                "O_DIRECT not supported, falling back to buffered writes"
+            );
+            Ok((options.open(path)?, IoMode::Buffered))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Opens `path` and turns off caching for it with `F_NOCACHE`, macOS's closest equivalent
+/// to `O_DIRECT`. Unlike `O_DIRECT` it places no alignment requirements on writes, and reads
+/// of data still in the cache may be served from it.
+#[cfg(target_os = "macos")]
+fn open_direct(
+    path: &path::Path,
+    options: &std::fs::OpenOptions,
+    _dsync_flag: i32,
+) -> Result<(File, IoMode), std::io::Error> {
+    use std::os::unix::io::AsRawFd;
+    let file = options.open(path)?;
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == 0 {
+        Ok((file, IoMode::Direct))
+    } else {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            path = %path.display(),
+            error = %std::io::Error::last_os_error(),
+            "F_NOCACHE not supported, falling back to buffered writes"
+        );
+        Ok((file, IoMode::Buffered))
+    }
+}
+
+/// Reserves real blocks for the first `size` bytes of `file`, so the first write to each
+/// page doesn't pay for block allocation. Falls back to `set_len`, which usually leaves the
+/// file sparse, on filesystems without fallocate support.
+#[cfg(target_os = "linux")]
+pub fn preallocate(file: &File, size: u64) -> Result<(), std::io::Error> {
+    use std::os::unix::io::AsRawFd;
+    let ret = unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, size as libc::off_t) };
+    if ret == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EOPNOTSUPP) {
+        file.set_len(size)
+    } else {
+        Err(err)
+    }
+}
+
+/// Reserves blocks for the first `size` bytes of `file` with `F_PREALLOCATE`, contiguous if
+/// possible, then extends the file to `size` as `fallocate` does on Linux. Filesystems that
+/// cannot preallocate just get the `set_len`.
+#[cfg(target_os = "macos")]
+pub fn preallocate(file: &File, size: u64) -> Result<(), std::io::Error> {
+    use std::os::unix::io::AsRawFd;
+    let missing = size.saturating_sub(file.metadata()?.len());
+    let mut store = libc::fstore_t {
+        fst_flags: libc::F_ALLOCATECONTIG | libc::F_ALLOCATEALL,
+        fst_posmode: libc::F_PEOFPOSMODE,
+        fst_offset: 0,
+        fst_length: missing as libc::off_t,
+        fst_bytesalloc: 0,
+    };
+    let fd = file.as_raw_fd();
+    if unsafe { libc::fcntl(fd, libc::F_PREALLOCATE, &mut store) } == -1 {
+        store.fst_flags = libc::F_ALLOCATEALL;
+        let _ = unsafe { libc::fcntl(fd, libc::F_PREALLOCATE, &mut store) };
     }
+    file.set_len(size)
+}
+
+/// Restricts the calling thread to the CPU core `core_id`.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core_id: usize) -> Result<(), PicoError> {
+    let failed = |source| PicoError::AffinityFailed {
+        core: core_id,
+        source,
+    };
+    if core_id >= libc::CPU_SETSIZE as usize {
+        return Err(failed(std::io::Error::from_raw_os_error(libc::EINVAL)));
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core_id, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(failed(std::io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_core_id: usize) -> Result<(), PicoError> {
+    Ok(())
+}
+
+/// The machine's host name, or an empty string if it cannot be read.
+#[cfg(unix)]
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return String::new();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+/// When the current process started, in nanoseconds since the Unix epoch, or 0 if unknown.
+///
+/// Derived from the process's start in clock ticks after boot (`/proc/self/stat`) and the
+/// boot time (`/proc/stat`), so it is only accurate to a clock tick.
+#[cfg(target_os = "linux")]
+pub fn process_start_nanos() -> u64 {
+    let read = || -> Option<u64> {
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        // Fields after the parenthesised command name, starting with the state (field 3).
+        let fields = &stat[stat.rfind(')')? + 1..];
+        let start_ticks: u64 = fields.split_whitespace().nth(19)?.parse().ok()?;
+        let boot_secs: u64 = std::fs::read_to_string("/proc/stat")
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("btime "))?
+            .trim()
+            .parse()
+            .ok()?;
+        let ticks_per_sec = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).ok()?;
+        if ticks_per_sec == 0 {
+            return None;
+        }
+        Some(boot_secs * 1_000_000_000 + start_ticks * 1_000_000_000 / ticks_per_sec)
+    };
+    read().unwrap_or(0)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_start_nanos() -> u64 {
+    0
 }
@@ -0,0 +1,92 @@
+//! The single point the worker blocks on while idle.
+//!
+//! On Linux this is an eventfd. Producers and message senders write to it, and it is
+//! registered with the worker's io_uring instance so every completion posted to the ring
+//! writes to it as well, so one wait covers new entries, messages and finished writes alike.
+//! Elsewhere it falls back to a condition variable, which only producers signal.
+
+#[cfg(target_os = "linux")]
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+#[cfg(not(target_os = "linux"))]
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::time::Duration;
+
+pub(crate) struct Wakeup {
+    #[cfg(target_os = "linux")]
+    fd: OwnedFd,
+    #[cfg(not(target_os = "linux"))]
+    pending: Mutex<bool>,
+    #[cfg(not(target_os = "linux"))]
+    signal: Condvar,
+}
+
+#[cfg(target_os = "linux")]
+impl Wakeup {
+    pub fn new() -> std::io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    /// Wakes the worker, or makes its next wait return straight away.
+    pub fn notify(&self) {
+        let one = 1u64;
+        // Only fails once the counter is about to overflow, when a wakeup is pending anyway.
+        let _ = unsafe { libc::write(self.fd(), &one as *const u64 as *const libc::c_void, 8) };
+    }
+
+    /// Blocks until notified or until `timeout` passes, consuming any pending notification.
+    pub fn wait(&self, timeout: Duration) {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timespec = libc::timespec {
+            tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        };
+        // An interrupted wait just returns early; the worker loops round anyway.
+        let ready = unsafe { libc::ppoll(&mut pollfd, 1, &timespec, std::ptr::null()) };
+        if ready > 0 {
+            let mut count = 0u64;
+            let _ =
+                unsafe { libc::read(self.fd(), &mut count as *mut u64 as *mut libc::c_void, 8) };
+        }
+    }
+
+    /// The eventfd, for registering with io_uring.
+    pub fn fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Wakeup {
+    pub fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            pending: Mutex::new(false),
+            signal: Condvar::new(),
+        })
+    }
+
+    /// Wakes the worker, or makes its next wait return straight away.
+    pub fn notify(&self) {
+        *self.pending.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        self.signal.notify_one();
+    }
+
+    /// Blocks until notified or until `timeout` passes, consuming any pending notification.
+    pub fn wait(&self, timeout: Duration) {
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let (mut pending, _) = self
+            .signal
+            .wait_timeout_while(pending, timeout, |pending| !*pending)
+            .unwrap_or_else(PoisonError::into_inner);
+        *pending = false;
+    }
+}
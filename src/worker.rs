@@ -1,35 +1,432 @@
+#[cfg(feature = "async")]
+use crate::async_support::AckWaiters;
+use crate::backend::{
+    ALLOCATE_COMPLETION, FILE_OP_COMPLETIONS, IoBackend, PageOp, RANGE_SYNC_COMPLETION,
+    SYNC_COMPLETION,
+};
+use crate::clock::Clock;
+use crate::codec::PageCodec;
+use crate::durability::{BackgroundSync, Durability, SyncPolicy};
 use crate::errors::PicoError;
-use crate::global::{next_page_id, set_ack_number};
+use crate::events::{FlushCallback, FlushEvent, Subscriber};
+use crate::global::{set_ack_number, set_hardened_number};
+use crate::index::IndexWriter;
+use crate::inline::InlinePool;
+use crate::latency::AckLatency;
+#[cfg(feature = "metrics")]
+use crate::metrics_export::WorkerMetrics;
+use crate::overflow::OverflowPolicy;
+use crate::page::Page;
+use crate::raw::PageLayout;
+use crate::seq_block::SeqBlocks;
+use crate::stats::{FlushReason, SharedStats};
+use crate::stripe::{Stripe, healthy_stripe};
+use crate::testing::FaultInjector;
+use crate::uring::FailurePolicy;
+use crate::wakeup::Wakeup;
 use crate::{LogBuffer, PageManager};
-use crossbeam_channel::{Receiver, RecvTimeoutError};
-use io_uring::{IoUring, opcode, types};
-use std::fs::File;
-use std::os::unix::io::AsRawFd;
+use crossbeam_channel::{Receiver, SendError, Sender, TryRecvError};
+use slab::Slab;
+use std::collections::VecDeque;
+use std::mem::ManuallyDrop;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
-pub struct LogWorker<'a, T> {
-    pub receiver: Receiver<usize>,
+/// Work item sent from the `Logger` to its worker.
+///
+/// Fixed-size entries don't go through the channel: the worker reads them from the ring
+/// buffer up to the position the producer has published.
+pub enum Message {
+    /// Write out the active page now instead of waiting for the flush interval.
+    Flush,
+    /// Send the durable watermark to this channel whenever it advances.
+    Subscribe(Sender<u64>),
+    /// Send copies of the `(seq_id, payload)` of every entry appended to a page but not yet
+    /// durable, in seq_id order.
+    Snapshot(Sender<Vec<(u64, Vec<u8>)>>),
+    /// Stop once everything before this message is handled: after writing out the ring
+    /// buffer and the active page if `drain` is set, or abandoning them otherwise. Either
+    /// way the worker waits, within the shutdown timeout, for the writes already in
+    /// flight, and sends the final durable watermark to `reply` as it exits.
+    ///
+    /// Hanging up the channel is treated as a draining shutdown, for when no message
+    /// could be sent.
+    Shutdown { drain: bool, reply: Sender<u64> },
+    /// Reserved seq_ids were abandoned; advance the durable watermark past them if it was
+    /// held below them.
+    SeqAbandoned,
+    /// A pre-encoded, variable-length entry, logged after the ring buffer entries below
+    /// `position`.
+    #[cfg(feature = "serde")]
+    Encoded {
+        seq_id: u64,
+        level: u8,
+        position: u64,
+        bytes: Vec<u8>,
+    },
+}
+
+/// The sending side of the worker's channel. Every message also wakes the worker, and so
+/// does dropping a mailbox, so the worker notices the logger hanging up right away.
+#[derive(Clone)]
+pub(crate) struct Mailbox {
+    sender: ManuallyDrop<Sender<Message>>,
+    wakeup: Arc<Wakeup>,
+}
+
+impl Mailbox {
+    pub fn new(sender: Sender<Message>, wakeup: Arc<Wakeup>) -> Self {
+        Self {
+            sender: ManuallyDrop::new(sender),
+            wakeup,
+        }
+    }
+
+    pub fn send(&self, message: Message) -> Result<(), SendError<Message>> {
+        self.sender.send(message)?;
+        self.wakeup.notify();
+        Ok(())
+    }
+
+    /// Wakes the worker for entries published to the ring buffer.
+    pub fn wake(&self) {
+        self.wakeup.notify();
+    }
+
+    /// Messages waiting for the worker.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+}
+
+impl Drop for Mailbox {
+    fn drop(&mut self) {
+        // SAFETY: the sender is not used again.
+        unsafe { ManuallyDrop::drop(&mut self.sender) };
+        self.wakeup.notify();
+    }
+}
+
+pub struct LogWorker<T> {
+    /// Declared first so the backend, and any write still in flight on it, is torn down
+    /// before the pages it reads from are freed.
+    pub backend: Box<dyn IoBackend>,
+    pub receiver: Receiver<Message>,
+    /// What the worker waits on while idle; also signalled by the backend's completions
+    /// where it supports that.
+    pub wakeup: Arc<Wakeup>,
     pub data_buffer: Arc<LogBuffer<T>>,
+    /// Turns the payload of each entry taken from `data_buffer` into the bytes appended.
+    pub encode: fn(&T) -> &[u8],
     pub pages: PageManager<T>,
-    pub last_flush: Instant,
+    /// [`Clock::monotonic_nanos`] reading at the last flush.
+    pub last_flush: u64,
     pub flush_interval: Duration,
     pub poll_interval: Duration,
-    pub logfile: &'a File,
-    pub ring: IoUring,
+    /// Most entries, and then messages, handled per wakeup before timers and completions
+    /// are looked at again.
+    pub drain_batch: usize,
+    /// Poll the ring buffer and channel without sleeping instead of waiting on `wakeup`.
+    pub spin: bool,
+    /// Empty polls after which a spinning worker blocks once as usual; 0 never blocks.
+    pub spin_park_after: u32,
     pub pending_writes: usize,
+    /// Page writes submitted to the backend, keyed by the user_data of their completions.
+    pub in_flight: Slab<PendingWrite>,
+    pub codec: PageCodec,
+    /// How pages are laid out, and whether each starts with a `PageMeta`.
+    pub layout: PageLayout,
+    /// `page_id` of the next new page written.
+    pub next_page_id: u32,
+    pub scratch: Vec<u8>,
+    pub block_size: usize,
+    /// The files pages are written to, in turn by page_id; just the log file unless the
+    /// logger was given stripe paths.
+    pub stripes: Vec<Stripe>,
+    /// Bytes a file is extended by when writes near the end of its allocated region; 0
+    /// leaves it to grow as pages are written.
+    pub growth_chunk: u64,
+    pub index: Option<IndexWriter>,
+    pub unsubmitted: usize,
+    pub durability: Durability,
+    /// Background syncs run under [`Durability::None`], whose acks don't harden entries.
+    pub sync_policy: Option<SyncPolicy>,
+    pub background_sync: BackgroundSync,
+    pub clock: Arc<dyn Clock>,
+    /// Page usage in bytes at which a page is flushed without waiting for the timer.
+    pub flush_watermark: Option<usize>,
+    pub stats: Arc<SharedStats>,
+    pub errors: Sender<PicoError>,
+    /// Reused buffer of `(user_data, result)` pairs drained from the backend.
+    pub completions: Vec<(u64, i32)>,
+    /// Completions the fault injector is holding back, handled again on the next poll.
+    pub held_completions: Vec<(u64, i32)>,
+    /// How many times a page write failing with a transient error is resubmitted.
+    pub max_write_retries: u32,
+    pub fault_injector: Option<Arc<dyn FaultInjector>>,
+    pub failure_policy: FailurePolicy,
+    pub overflow_policy: OverflowPolicy,
+    /// Set when a flush moved onto a page whose previous write had not completed, until the
+    /// page is reset and can take entries again.
+    pub awaiting_page: bool,
+    /// Entries discarded under [`OverflowPolicy::DropNewest`] since the last report.
+    pub dropped: Option<DroppedRange>,
+    pub partial_rewrites: Option<PartialRewrites>,
+    /// The block range the active page continues to fill after a partial write of it.
+    pub open_page: Option<OpenPage>,
+    pub flush_callback: Option<FlushCallback>,
+    /// Records entries as durable for [`Logger::latency_report`](crate::Logger::latency_report).
+    pub ack_latency: Option<AckLatency>,
+    #[cfg(feature = "metrics")]
+    pub metrics: WorkerMetrics,
+    pub subscribers: Vec<Subscriber>,
+    pub acks: AckOrder,
+    /// Reserved seq_ids not durable yet, shared with the logger.
+    pub seq_blocks: Arc<SeqBlocks>,
+    /// How long a reserved seq_id that is never logged holds the watermark back; for good
+    /// if `None`.
+    pub seq_block_timeout: Option<Duration>,
+    /// Reserved seq_ids appended to the active page since its last write.
+    pub page_reserved: Vec<u64>,
+    /// Highest seq_id appended so far.
+    pub newest_seq: u64,
+    /// Watermark the completed writes allow, before holding it below reserved seq_ids.
+    pub acked_seq: u64,
+    /// How long the worker keeps waiting for page writes once the logger is dropped.
+    pub shutdown_timeout: Duration,
+    /// When the worker gives up on outstanding writes; set once shutdown begins.
+    pub shutdown_deadline: Option<Instant>,
+    /// Set by [`Message::Shutdown`]: whether to drain, and where to send the final
+    /// watermark.
+    pub stop: Option<(bool, Sender<u64>)>,
+    /// Cleared when the worker stops after an unrecoverable error.
+    pub healthy: Arc<AtomicBool>,
+    /// Pages lent to the logger, when it was started with
+    /// [`Logger::start_inline`](crate::Logger::start_inline).
+    pub inline: Option<InlinePool<T>>,
+    #[cfg(feature = "async")]
+    pub ack_waiters: Arc<AckWaiters>,
+}
+
+/// Maximum number of page writes queued in the SQ before they are submitted, bounding the
+/// extra durability latency that deferred submission adds during a burst.
+const MAX_SUBMIT_BATCH: usize = 16;
+
+const RETRY_BACKOFF_BASE: Duration = Duration::from_micros(50);
+const RETRY_BACKOFF_MAX_SHIFT: u32 = 5;
+
+/// A page write handed to the backend, looked up again when its completions arrive.
+#[derive(Clone, Copy)]
+pub(crate) struct PendingWrite {
+    page_idx: usize,
+    /// Sequence id of the page's last entry, acknowledged once the write is durable.
+    last_seq: u64,
+    /// Entries this write persists that no earlier write of the same block did.
+    entries: usize,
+    /// Set when this write replaces a partial write at the same offset.
+    rewrite: bool,
+    /// Index of the stripe file the page is written to.
+    stripe: usize,
+    offset: u64,
+    len: usize,
+    /// Bytes already written by earlier attempts that came back short.
+    written: usize,
+    attempts: u32,
+    /// When the page was first queued, for the latency reported in [`FlushEvent`].
+    queued_at: Instant,
+    /// Where the write sits in the order the watermark advances in.
+    ack_slot: u64,
+    /// Set once a resubmission has replaced this write while one of its completions is
+    /// still outstanding; that completion is then ignored.
+    superseded: bool,
+}
+
+/// Page writes in the order they were queued, so the durable watermark only moves through
+/// the prefix of them that has completed.
+///
+/// Writes may complete in any order; one that completes ahead of an earlier page waits here
+/// until that page has completed too.
+#[derive(Default)]
+pub(crate) struct AckOrder {
+    /// Every write from the oldest not yet completed on.
+    pending: VecDeque<AckSlot>,
+    /// Slot number of the front of `pending`.
+    front: u64,
+}
+
+struct AckSlot {
+    /// Newest seq_id appended by the time the write was queued.
+    last_seq: u64,
+    completed: bool,
+    /// Reserved seq_ids the write persists, released from their block once it completes.
+    reserved: Vec<u64>,
+}
+
+impl AckOrder {
+    /// Queues a write ending with `last_seq` and persisting the reserved ids `reserved`,
+    /// returning its slot.
+    pub fn push(&mut self, last_seq: u64, reserved: Vec<u64>) -> u64 {
+        self.pending.push_back(AckSlot {
+            last_seq,
+            completed: false,
+            reserved,
+        });
+        self.front + self.pending.len() as u64 - 1
+    }
+
+    /// Marks the write in `slot` completed, returning the new watermark if that completes
+    /// the oldest pending write. The reserved ids of the writes acknowledged are added to
+    /// `released`.
+    pub fn complete(&mut self, slot: u64, released: &mut Vec<u64>) -> Option<u64> {
+        let at = slot.checked_sub(self.front)? as usize;
+        self.pending.get_mut(at)?.completed = true;
+        let mut watermark = None;
+        while self.pending.front().is_some_and(|write| write.completed) {
+            let write = self.pending.pop_front()?;
+            watermark = Some(write.last_seq);
+            released.extend(write.reserved);
+            self.front += 1;
+        }
+        watermark
+    }
+}
+
+/// When a timer flush writes a page that is not done filling, to be rewritten in place.
+#[derive(Clone, Copy)]
+pub(crate) struct PartialRewrites {
+    /// Pages using fewer bytes than this are written without moving on to the next block.
+    pub min_fill: usize,
+    /// Rewrites of one page after which it is left as written and the next one is started.
+    pub max_rewrites: u32,
+}
+
+/// A page partially written at `offset`, whose entries were carried over to the active page.
+#[derive(Clone, Copy)]
+pub(crate) struct OpenPage {
+    offset: u64,
+    stripe: usize,
+    page_id: u32,
+    rewrites: u32,
+    /// Entries already covered by an earlier write at `offset`.
+    written_entries: usize,
+    /// Bytes those entries take.
+    written_bytes: usize,
+}
+
+/// A run of entries discarded while no page was free, reported once one frees up.
+#[derive(Clone, Copy)]
+pub(crate) struct DroppedRange {
+    first_seq_id: u64,
+    last_seq_id: u64,
+    count: u64,
+}
+
+/// What a page write's completion means for the page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WriteOutcome {
+    /// Every requested byte was written.
+    Complete,
+    /// Only part of the request was written; resubmit the rest starting `progress` bytes in.
+    Short { progress: usize },
+    /// The write failed with this errno.
+    Failed(i32),
+}
+
+/// Interprets the `result` of a write that asked for `requested` bytes.
+///
+/// On a short write, progress is rounded down to a whole number of blocks so the resumed
+/// write keeps the offset and buffer alignment O_DIRECT needs; part of a block may be
+/// written twice.
+pub(crate) fn classify_write(requested: usize, result: i32, block_size: usize) -> WriteOutcome {
+    if result < 0 {
+        return WriteOutcome::Failed(-result);
+    }
+    let written = result as usize;
+    if written >= requested {
+        WriteOutcome::Complete
+    } else {
+        WriteOutcome::Short {
+            progress: written / block_size * block_size,
+        }
+    }
 }
 
-impl<'a, T> LogWorker<'a, T> {
-    pub fn run(&mut self) {
+/// Errors worth resubmitting a page write for, rather than giving up on it.
+fn is_transient(errno: i32) -> bool {
+    matches!(errno, libc::EAGAIN | libc::EINTR | libc::ENOMEM)
+}
+
+impl<T> LogWorker<T> {
+    /// Runs the worker until the logger is dropped, returning an error if shutdown timed out.
+    pub fn run(&mut self) -> Result<(), PicoError> {
+        let mut result = Ok(());
+        while let Err(e) = self.serve() {
+            if let PicoError::ShutdownTimedOut { lost_from_seq } = e {
+                self.report_error(PicoError::ShutdownTimedOut { lost_from_seq });
+                // The kernel may still read the buffers of writes that never completed, so
+                // leak them rather than free memory out from under it.
+                self.pages.leak();
+                result = Err(e);
+                break;
+            }
+            // A fresh ring won't make room on a full device.
+            let rebuild = self.failure_policy == FailurePolicy::RebuildRing
+                && !matches!(e, PicoError::DeviceFull { .. });
+            self.report_error(e);
+            if rebuild {
+                match self.rebuild_backend() {
+                    Ok(()) => continue,
+                    Err(e) => self.report_error(e),
+                }
+            }
+            // Stop accepting entries before the receiver is dropped, so `log()` starts
+            // failing instead of handing out seq_ids that will never be written.
+            self.healthy.store(false, Ordering::Release);
+            break;
+        }
+        self.reclaim_inline_pages();
+        if let Some((_, reply)) = self.stop.take() {
+            let _ = reply.send(self.stats.flushed_seq_id());
+        }
+        self.backend.drain();
+        #[cfg(feature = "async")]
+        self.ack_waiters.close();
+        result
+    }
+
+    /// Runs the worker loop until the logger shuts it down or the backend fails.
+    fn serve(&mut self) -> Result<(), PicoError> {
+        let mut empty_polls = 0u32;
         loop {
-            self.process_completions();
+            // Checked here rather than where the message is handled so a shutdown that
+            // failed part way, and rebuilt the ring, resumes the way it started.
+            if let Some((drain, _)) = self.stop {
+                return match drain {
+                    true => self.flush_remaining(),
+                    false => self.abandon_remaining(),
+                };
+            }
+            self.process_completions()?;
+            self.run_sync_policy()?;
+            if self.take_inline_pages()? {
+                empty_polls = 0;
+                continue;
+            }
 
-            if self.last_flush.elapsed() >= self.flush_interval {
-                self.flush_current_page();
+            if self.time_since_flush() >= self.flush_interval {
+                // Entries published before the timer fired belong in its flush; left in the
+                // ring, they would make an open page look idle and close it.
+                self.handle_burst(None)?;
+                self.flush_current_page(FlushReason::Timer)?;
+                // Lets the watermark past reserved blocks that have timed out.
+                if self.seq_block_timeout.is_some() && self.seq_blocks.in_use() {
+                    self.advance_watermark();
+                    self.notify_watermark();
+                }
             }
 
-            let time_since_flush = self.last_flush.elapsed();
+            let time_since_flush = self.time_since_flush();
 
             let time_until_flush = if time_since_flush > self.flush_interval {
                 Duration::ZERO
@@ -39,115 +436,1114 @@ impl<'a, T> LogWorker<'a, T> {
 
             let timeout = std::cmp::min(time_until_flush, self.poll_interval);
 
-            match self.receiver.recv_timeout(timeout) {
-                Ok(msg) => self.handle_message(msg),
-                Err(RecvTimeoutError::Timeout) => {
+            // While a burst keeps the ring busy, page writes pile up in the SQ and go to the
+            // kernel together once the burst drains.
+            let backlog = self.data_buffer.backlog();
+            if backlog == 0 && self.receiver.is_empty() {
+                self.submit()?;
+            }
+
+            if backlog > 0 {
+                empty_polls = 0;
+                self.handle_burst(None)?;
+                continue;
+            }
+
+            match self.receiver.try_recv() {
+                Ok(msg) => {
+                    empty_polls = 0;
+                    self.handle_burst(Some(msg))?;
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => return self.flush_remaining(),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // Writes on a polled ring only complete when polled for, which parking wouldn't.
+            if self.pending_writes > 0 && self.backend.polls_for_completions() {
+                std::hint::spin_loop();
+                continue;
+            }
+
+            if self.spin {
+                empty_polls = empty_polls.saturating_add(1);
+                if self.spin_park_after == 0 || empty_polls < self.spin_park_after {
+                    std::hint::spin_loop();
                     continue;
                 }
-                Err(RecvTimeoutError::Disconnected) => {
-                    self.flush_remaining();
-                    break;
+                empty_polls = 0;
+            }
+
+            // Wakes for a new entry or message, or, where the backend signals them, a
+            // completed write, whichever comes first. The timeout only covers timers.
+            if self.data_buffer.park() {
+                self.wakeup.wait(timeout);
+                self.data_buffer.unpark();
+            }
+        }
+    }
+
+    fn report_error(&self, error: PicoError) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(%error, "worker error");
+        // Errors are dropped rather than blocking the worker if nobody drains the channel.
+        let _ = self.errors.try_send(error);
+    }
+
+    /// Replaces a failed backend instance with a fresh one and resubmits every page write
+    /// that was in flight on the old one.
+    fn rebuild_backend(&mut self) -> Result<(), PicoError> {
+        self.backend.rebuild()?;
+        self.pending_writes = 0;
+        self.unsubmitted = 0;
+        for stripe in &mut self.stripes {
+            stripe.extending_to = None;
+        }
+        self.held_completions.clear();
+        // Syncs in flight on the old instance never complete; the policy issues them again.
+        let sync = &mut self.background_sync;
+        sync.ranges_in_flight = 0;
+        sync.full_in_flight = None;
+        sync.full_pending = 0;
+        sync.full_errno = None;
+
+        let writes: Vec<PendingWrite> = self
+            .in_flight
+            .drain()
+            .filter(|write| !write.superseded)
+            .collect();
+        for mut write in writes {
+            write.attempts += 1;
+            self.queue_page_write(write)?;
+        }
+        self.submit()
+    }
+
+    /// Appends up to `drain_batch` entries waiting in the ring buffer, then handles `first`
+    /// and up to `drain_batch - 1` more messages that are already queued.
+    fn handle_burst(&mut self, first: Option<Message>) -> Result<(), PicoError> {
+        let consumed = self.data_buffer.consumed.load(Ordering::Relaxed);
+        self.drain_ring(consumed.saturating_add(self.drain_batch as u64))?;
+        let Some(first) = first else {
+            return Ok(());
+        };
+        self.handle_message(first)?;
+        // Disconnection is left for the next receive to notice.
+        for _ in 1..self.drain_batch {
+            if self.stop.is_some() {
+                break;
+            }
+            let Ok(msg) = self.receiver.try_recv() else {
+                break;
+            };
+            self.handle_message(msg)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the entries published to the ring buffer below position `end`, or all of
+    /// them if fewer have been.
+    fn drain_ring(&mut self, end: u64) -> Result<(), PicoError> {
+        let consumed = &self.data_buffer.consumed;
+        let start = consumed.load(Ordering::Relaxed);
+        let end = end.min(self.data_buffer.published.load(Ordering::Acquire));
+        if start >= end {
+            return Ok(());
+        }
+        #[cfg(feature = "metrics")]
+        {
+            let queued = self.data_buffer.backlog() + self.receiver.len();
+            self.metrics
+                .received((end - start) as usize, queued - (end - start) as usize);
+        }
+        let capacity = self.data_buffer.inner.len() as u64;
+        for position in start..end {
+            self.append_slot((position % capacity) as usize, position)?;
+            self.data_buffer
+                .consumed
+                .store(position + 1, Ordering::Release);
+            self.flush_at_watermark()?;
+        }
+        Ok(())
+    }
+
+    fn handle_message(&mut self, msg: Message) -> Result<(), PicoError> {
+        // Pages an inline logger handed back before sending the message come first.
+        self.take_inline_pages()?;
+        #[cfg(all(feature = "metrics", feature = "serde"))]
+        if let Message::Encoded { .. } = &msg {
+            self.metrics.received(1, self.receiver.len());
+        }
+        match msg {
+            Message::Flush => {
+                // Queued behind everything logged before it.
+                self.drain_ring(u64::MAX)?;
+                self.flush_current_page(FlushReason::Explicit)
+            }
+            Message::Subscribe(sender) => {
+                let mut subscriber = Subscriber::new(sender);
+                if subscriber.notify(crate::global::get_ack_number()) {
+                    self.subscribers.push(subscriber);
+                }
+                Ok(())
+            }
+            Message::Snapshot(reply) => {
+                self.drain_ring(u64::MAX)?;
+                let _ = reply.send(self.unflushed_entries());
+                Ok(())
+            }
+            Message::Shutdown { drain, reply } => {
+                self.stop = Some((drain, reply));
+                Ok(())
+            }
+            Message::SeqAbandoned => {
+                self.advance_watermark();
+                self.notify_watermark();
+                Ok(())
+            }
+            #[cfg(feature = "serde")]
+            Message::Encoded {
+                seq_id,
+                level,
+                position,
+                bytes,
+            } => {
+                self.drain_ring(position)?;
+                self.append_with(seq_id, |page, now| {
+                    page.append_bytes(seq_id, now, level, &bytes)
+                })?;
+                self.flush_at_watermark()
+            }
+        }
+    }
+
+    /// Copies the entries of the active page and of pages whose writes are still in flight
+    /// that are past the durable watermark, in seq_id order.
+    ///
+    /// Pages sealed by a compressing or encrypting codec no longer hold their entries as
+    /// appended, so only the active page is looked at with one.
+    fn unflushed_entries(&self) -> Vec<(u64, Vec<u8>)> {
+        let durable = self.stats.flushed_seq_id();
+        let active = self.pages.active_idx;
+        let in_flight = self
+            .in_flight
+            .iter()
+            .filter(|_| self.codec.is_identity())
+            .filter(|(_, write)| !write.superseded)
+            .map(|(_, write)| write.page_idx);
+        let active = (!self.pages.pending_status[active]).then_some(active);
+        let mut entries: Vec<_> = in_flight
+            .chain(active)
+            .flat_map(|idx| self.pages.pages[idx].copy_entries())
+            .filter(|&(seq_id, _)| seq_id > durable)
+            .collect();
+        // A page rewritten with more entries shares its first ones with the copy in flight.
+        entries.sort_by_key(|&(seq_id, _)| seq_id);
+        entries.dedup_by_key(|&mut (seq_id, _)| seq_id);
+        entries
+    }
+
+    /// Appends the entry at ring `position`, in slot `index`, to the active page, unless a
+    /// producer has already reused the slot for a later entry.
+    fn append_slot(&mut self, index: usize, position: u64) -> Result<(), PicoError> {
+        let Some(level) = self.data_buffer.level_at(index, position) else {
+            // The slot now belongs to the newer entry, which is read at its own position.
+            self.stats.record_dropped();
+            return Ok(());
+        };
+        let log_msg = unsafe { &*self.data_buffer.entry(index) };
+        let seq_id = log_msg.seq_id;
+        let encode = self.encode;
+        let appended = self.append_with(seq_id, |page, now| {
+            page.append_bytes(log_msg.seq_id, now, level, encode(&log_msg.data))
+        });
+        self.data_buffer.mark_consumed(index);
+        appended
+    }
+
+    /// Runs `append` against the active page, flushing it and retrying once if it is full.
+    /// The entry is discarded instead if there is no page to append it to.
+    fn append_with(
+        &mut self,
+        seq_id: u64,
+        mut append: impl FnMut(&mut Page<T>, u64) -> Result<(), PicoError>,
+    ) -> Result<(), PicoError> {
+        if !self.claim_active_page()? {
+            self.drop_entry(seq_id);
+            return Ok(());
+        }
+        let now = self.clock.now_nanos();
+        let mut appended = append(self.pages.get_active_page(), now);
+        if let Err(PicoError::PageFull { .. }) = appended {
+            self.flush_current_page(FlushReason::PageFull)?;
+            if !self.claim_active_page()? {
+                self.drop_entry(seq_id);
+                return Ok(());
+            }
+            appended = append(self.pages.get_active_page(), now);
+        }
+        if appended.is_ok() {
+            self.stats.record_appended();
+            self.newest_seq = self.newest_seq.max(seq_id);
+            if self.seq_blocks.holds(seq_id) {
+                self.page_reserved.push(seq_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn drop_entry(&mut self, seq_id: u64) {
+        self.stats.record_dropped();
+        let range = self.dropped.get_or_insert(DroppedRange {
+            first_seq_id: seq_id,
+            last_seq_id: seq_id,
+            count: 0,
+        });
+        range.first_seq_id = range.first_seq_id.min(seq_id);
+        range.last_seq_id = range.last_seq_id.max(seq_id);
+        range.count += 1;
+        // A reserved seq_id that will never be durable stops holding the watermark back.
+        self.seq_blocks.release(&[seq_id]);
+    }
+
+    /// Readies the active page for entries after a flush moved onto it, handling a page that
+    /// is still being written as the overflow policy says.
+    ///
+    /// Returns `false` under [`OverflowPolicy::DropNewest`] while the page is unavailable.
+    pub(crate) fn claim_active_page(&mut self) -> Result<bool, PicoError> {
+        if !self.awaiting_page {
+            return Ok(true);
+        }
+        if self.pages.pending_status[self.pages.active_idx] {
+            match self.overflow_policy {
+                OverflowPolicy::Block => self.wait_for_active_page()?,
+                OverflowPolicy::DropNewest => {
+                    self.submit()?;
+                    self.process_completions()?;
+                    if self.pages.pending_status[self.pages.active_idx] {
+                        return Ok(false);
+                    }
+                }
+                OverflowPolicy::Grow { max_pages } if self.pages.pages.len() < max_pages => {
+                    self.pages.active_idx = self.pages.grow();
+                    self.stats.record_page_grown();
                 }
+                OverflowPolicy::Grow { .. } => self.wait_for_active_page()?,
             }
         }
+        self.pages.get_active_page().reset();
+        self.awaiting_page = false;
+
+        if let Some(range) = self.dropped.take() {
+            let error = PicoError::EntriesDropped {
+                first_seq_id: range.first_seq_id,
+                last_seq_id: range.last_seq_id,
+                count: range.count,
+            };
+            self.report_error(error);
+        }
+        Ok(true)
     }
 
-    fn handle_message(&mut self, idx: usize) {
-        let log_msg = unsafe { &*self.data_buffer.inner[idx].get() };
+    fn time_since_flush(&self) -> Duration {
+        Duration::from_nanos(self.clock.monotonic_nanos().saturating_sub(self.last_flush))
+    }
 
-        if let Err(PicoError::PageFull { .. }) =
-            self.pages.get_active_page().append(log_msg.seq_id, &log_msg.data)
+    fn flush_at_watermark(&mut self) -> Result<(), PicoError> {
+        if let Some(watermark) = self.flush_watermark
+            && self.pages.get_active_page().used() >= watermark
         {
-            self.flush_current_page();
-            let _ = self.pages.get_active_page().append(log_msg.seq_id, &log_msg.data);
+            self.flush_current_page(FlushReason::Watermark)?;
         }
+        Ok(())
     }
 
-    fn flush_current_page(&mut self) {
+    pub(crate) fn flush_current_page(&mut self, reason: FlushReason) -> Result<(), PicoError> {
+        // Under DropNewest the active page may still hold an earlier, in-flight write.
+        if !self.claim_active_page()? {
+            self.last_flush = self.clock.monotonic_nanos();
+            return Ok(());
+        }
         let page_idx = self.pages.active_idx;
+
+        // A page carried over from a partial write holds nothing new until an entry arrives.
+        let written = self.open_page.map_or(0, |open| open.written_entries);
+        if self.pages.pages[page_idx].is_empty()
+            || self.pages.pages[page_idx].entry_count() == written
+        {
+            // With nothing new to rewrite it for, the open page is left as written, so its
+            // entries can be acknowledged once that write completes.
+            if self.open_page.take().is_some() {
+                self.pages.get_active_page().reset();
+                self.acknowledge_appended();
+            }
+            self.last_flush = self.clock.monotonic_nanos();
+            return Ok(());
+        }
+        self.stats.record_flush(reason);
+        let open_page = self.open_page.take();
+        let page_id = open_page.map_or(self.next_page_id, |open| open.page_id);
+        self.layout.seal(&mut self.pages.pages[page_idx], page_id);
+
+        let write_len = if !self.codec.is_identity() {
+            let sealed_len = self
+                .codec
+                .seal(&mut self.pages.pages[page_idx], &mut self.scratch);
+            sealed_len.next_multiple_of(self.block_size)
+        } else {
+            self.pages.pages[page_idx].get_page_content().len()
+        };
+        self.pages.pending_status[page_idx] = true;
+
+        let (offset, stripe, page_id, written_entries) = match open_page {
+            Some(open) if !self.stripes[open.stripe].failed => {
+                // Writes to the same blocks may complete in any order, so let the earlier
+                // one land before this one replaces it.
+                while self.in_flight.iter().any(|(_, write)| {
+                    !write.superseded && write.stripe == open.stripe && write.offset == open.offset
+                }) {
+                    self.submit_and_wait()?;
+                }
+                (open.offset, open.stripe, open.page_id, open.written_entries)
+            }
+            // The stripe failed since the page was opened, so it continues on another one.
+            Some(open) => {
+                let stripe = healthy_stripe(&self.stripes, open.stripe);
+                let offset = self.take_page_offset(stripe, write_len)?;
+                (offset, stripe, open.page_id, open.written_entries)
+            }
+            None => {
+                let page_id = self.next_page_id;
+                let preferred = page_id as usize % self.stripes.len();
+                let stripe = healthy_stripe(&self.stripes, preferred);
+                let offset = self.take_page_offset(stripe, write_len)?;
+                self.next_page_id = self.next_page_id.wrapping_add(1);
+                (offset, stripe, page_id, 0)
+            }
+        };
         let page = &self.pages.pages[page_idx];
-        
-        if page.is_empty() {
-            self.last_flush = Instant::now();
+        let entry_count = page.entry_count();
+        let entry_bytes = page.entry_bytes();
+        let rewrite = open_page.is_some_and(|open| (open.stripe, open.offset) == (stripe, offset));
+        // A rewrite takes no more of the file than its first write did.
+        self.stats.record_page_queued(
+            if rewrite { 0 } else { write_len as u64 },
+            (entry_bytes - open_page.map_or(0, |open| open.written_bytes)) as u64,
+        );
+        let keep_open = reason == FlushReason::Timer
+            && self.codec.is_identity()
+            && self.partial_rewrites.is_some_and(|policy| {
+                page.used() < policy.min_fill
+                    && open_page.map_or(0, |open| open.rewrites) < policy.max_rewrites
+            });
+        let last_seq = page.get_last_entry();
+        // A torn rewrite could lose what an earlier write of the page put there, so the
+        // entries of a page that stays open are only acknowledged once it is closed.
+        let ack_slot = if keep_open {
+            self.acks.push(0, Vec::new())
+        } else {
+            let reserved = std::mem::take(&mut self.page_reserved);
+            self.acks.push(self.newest_seq.max(last_seq), reserved)
+        };
+        self.queue_page_write(PendingWrite {
+            page_idx,
+            last_seq,
+            entries: entry_count - written_entries,
+            rewrite,
+            stripe,
+            offset,
+            len: write_len,
+            written: 0,
+            attempts: 0,
+            queued_at: Instant::now(),
+            ack_slot,
+            superseded: false,
+        })?;
+        #[cfg(feature = "metrics")]
+        self.metrics.pending(self.in_flight.len());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            page_id,
+            stripe,
+            bytes = write_len,
+            in_flight = self.in_flight.len(),
+            ?reason,
+            "page flushed"
+        );
+
+        let _ = self.pages.advance();
+        self.awaiting_page = true;
+        // The written page stays untouched while in flight, so later entries go into a copy
+        // of it that is written to the same blocks next time.
+        if self.claim_active_page()? && keep_open {
+            self.pages.continue_from(page_idx);
+            self.open_page = Some(OpenPage {
+                offset,
+                stripe,
+                page_id,
+                rewrites: open_page.map_or(0, |open| open.rewrites) + 1,
+                written_entries: entry_count,
+                written_bytes: entry_bytes,
+            });
+        } else if keep_open {
+            // The page can't be continued after all, so it won't be rewritten either.
+            self.acknowledge_appended();
+        }
+
+        self.last_flush = self.clock.monotonic_nanos();
+        Ok(())
+    }
+
+    /// Acknowledges every entry appended so far once the writes queued before now have
+    /// completed, for when the last of those writes deferred its acknowledgement.
+    fn acknowledge_appended(&mut self) {
+        let reserved = std::mem::take(&mut self.page_reserved);
+        let slot = self.acks.push(self.newest_seq, reserved);
+        let mut released = Vec::new();
+        if let Some(watermark) = self.acks.complete(slot, &mut released) {
+            self.seq_blocks.release(&released);
+            self.acked_seq = self.acked_seq.max(watermark);
+            self.advance_watermark();
+        }
+    }
+
+    /// Hands out the offset of a new page of `len` bytes at the end of stripe `stripe`.
+    fn take_page_offset(&mut self, stripe: usize, len: usize) -> Result<u64, PicoError> {
+        let offset = self.stripes[stripe].next_offset;
+        if let Some(device_len) = self.stripes[stripe].device_len
+            && offset + len as u64 > device_len
+        {
+            return Err(PicoError::DeviceFull {
+                path: self.stripes[stripe].path.clone(),
+                len: device_len,
+            });
+        }
+        self.stripes[stripe].next_offset += len as u64;
+        self.stats
+            .record_file_offset(stripe, self.stripes[stripe].next_offset);
+        self.reserve_file_space(stripe, offset + len as u64)?;
+        Ok(offset)
+    }
+
+    /// Makes sure stripe `stripe` is allocated up to `end` before a page write reaches it,
+    /// and starts extending it by another chunk once writes are within half a chunk of the
+    /// end.
+    fn reserve_file_space(&mut self, stripe: usize, end: u64) -> Result<(), PicoError> {
+        if self.growth_chunk == 0 {
+            return Ok(());
+        }
+        let started = Instant::now();
+        let mut stalled = false;
+        while end > self.stripes[stripe].allocated_len && self.growth_chunk > 0 {
+            if self.stripes[stripe].extending_to.is_none() {
+                self.extend_file(stripe, end)?;
+            }
+            self.submit_and_wait()?;
+            stalled = true;
+        }
+        if stalled {
+            self.stats.record_stall(started.elapsed());
+        }
+        let allocated_len = self.stripes[stripe].allocated_len;
+        if self.stripes[stripe].extending_to.is_none()
+            && self.growth_chunk > 0
+            && allocated_len - end < self.growth_chunk / 2
+        {
+            self.extend_file(stripe, allocated_len)?;
+        }
+        Ok(())
+    }
+
+    /// Queues extending the allocated region of stripe `stripe` to a chunk past `from`.
+    fn extend_file(&mut self, stripe: usize, from: u64) -> Result<(), PicoError> {
+        let target = (from + self.growth_chunk).next_multiple_of(self.block_size as u64);
+        let allocated_len = self.stripes[stripe].allocated_len;
+        self.backend
+            .submit_allocate(stripe, allocated_len, target - allocated_len)?;
+        self.stripes[stripe].extending_to = Some(target);
+        self.unsubmitted += 1;
+        Ok(())
+    }
+
+    /// Handles the completion of the extension of stripe `stripe` in flight.
+    fn file_extended(&mut self, stripe: usize, result: i32) {
+        let Some(target) = self.stripes[stripe].extending_to.take() else {
+            return;
+        };
+        if result < 0 {
+            self.report_error(PicoError::GrowthFailed { errno: -result });
+            self.growth_chunk = 0;
             return;
         }
+        self.stripes[stripe].allocated_len = target;
+        self.stats.record_file_len(stripe, target);
+    }
 
-        self.pages.pending_status[page_idx] = true;
+    /// Issues the range and full syncs of the sync policy that are due.
+    fn run_sync_policy(&mut self) -> Result<(), PicoError> {
+        let Some(policy) = self.sync_policy else {
+            return Ok(());
+        };
+        let now = self.clock.monotonic_nanos();
+        let sync = &self.background_sync;
+        let since_range = Duration::from_nanos(now.saturating_sub(sync.last_range));
+        let range_due = sync.pages > 0
+            && ((policy.range_every_pages > 0 && sync.pages >= policy.range_every_pages)
+                || policy
+                    .range_interval
+                    .is_some_and(|interval| since_range >= interval));
+        if range_due && sync.ranges_supported && sync.ranges_in_flight == 0 {
+            let (start, end) = (sync.range_start, sync.range_end);
+            // Stripes take their pages at about the same offsets, so the range written to
+            // any of them covers what was written to each.
+            for stripe in 0..self.stripes.len() {
+                self.backend.submit_range_sync(stripe, start, end - start)?;
+            }
+            let sync = &mut self.background_sync;
+            sync.pages = 0;
+            sync.last_range = now;
+            sync.ranges_in_flight = self.stripes.len();
+            self.unsubmitted += 1;
+            self.stats.record_range_sync();
+        }
+
+        let sync = &self.background_sync;
+        let since_full = Duration::from_nanos(now.saturating_sub(sync.last_full));
+        if sync.full_in_flight.is_none() && since_full >= policy.full_sync_interval {
+            self.start_full_sync()?;
+        }
+        Ok(())
+    }
+
+    /// Queues an fdatasync of every file hardening every entry written so far, unless that
+    /// is nothing new.
+    ///
+    /// A stripe taken out of the rotation is synced as well, since it still holds the pages
+    /// written to it before it failed.
+    fn start_full_sync(&mut self) -> Result<(), PicoError> {
+        let written = self.stats.flushed_seq_id();
+        if written <= self.background_sync.hardened {
+            return Ok(());
+        }
+        for stripe in 0..self.stripes.len() {
+            self.backend.submit_full_sync(stripe)?;
+        }
+        let sync = &mut self.background_sync;
+        sync.full_in_flight = Some(written);
+        sync.full_pending = self.stripes.len();
+        sync.full_errno = None;
+        sync.last_full = self.clock.monotonic_nanos();
+        self.unsubmitted += 1;
+        Ok(())
+    }
 
-        let page_id = next_page_id();
-        let offset = page_id * (page.get_page_content().len() as u64);
-        let buf = page.get_page_content();
+    /// Handles the completion of one of the range syncs in flight.
+    fn range_synced(&mut self, result: i32) {
+        let sync = &mut self.background_sync;
+        sync.ranges_in_flight = sync.ranges_in_flight.saturating_sub(1);
+        if result < 0 && sync.ranges_supported {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                error = %std::io::Error::from_raw_os_error(-result),
+                "range sync failed, leaving writeback to full syncs"
+            );
+            self.background_sync.ranges_supported = false;
+        }
+    }
+
+    /// Handles the completion of one file's fdatasync of the full sync in flight, which
+    /// hardens its entries once every file's has completed.
+    fn full_synced(&mut self, result: i32) {
+        let sync = &mut self.background_sync;
+        if sync.full_in_flight.is_none() {
+            return;
+        }
+        if result < 0 {
+            sync.full_errno.get_or_insert(-result);
+        }
+        sync.full_pending = sync.full_pending.saturating_sub(1);
+        if sync.full_pending > 0 {
+            return;
+        }
+        let Some(seq_id) = sync.full_in_flight.take() else {
+            return;
+        };
+        if let Some(errno) = sync.full_errno.take() {
+            self.report_error(PicoError::SyncFailed { errno, seq_id });
+            return;
+        }
+        self.background_sync.hardened = seq_id;
+        self.stats.record_full_sync(seq_id);
+        set_hardened_number(seq_id);
+    }
 
-        let seq_id = page.get_last_entry();
-        let user_data = ((page_idx as u64) << 56) | (seq_id & 0x00FF_FFFF_FFFF_FFFF);
+    /// Queues `write`, followed by a linked fdatasync in [`Durability::Fdatasync`] mode.
+    fn queue_page_write(&mut self, write: PendingWrite) -> Result<(), PicoError> {
+        let page_idx = write.page_idx;
+        let buf = &self.pages.pages[page_idx].get_page_content()[write.written..write.len];
+        let offset = write.offset + write.written as u64;
 
-        let write_e = opcode::Write::new(
-            types::Fd(self.logfile.as_raw_fd()),
-            buf.as_ptr(),
-            buf.len() as _,
-        )
-        .offset(offset)
-        .build()
-        .user_data(user_data);
+        // The page is not reset until its write completes, so the completion can still read
+        // the page's entries through the record its user_data points at.
+        let user_data = self.in_flight.insert(write) as u64;
 
-        unsafe {
-            if self.ring.submission().push(&write_e).is_err() {
-                self.ring.submit().expect("Fail to submit to clear SQ");
+        let sync = self.durability == Durability::Fdatasync;
+        self.backend.submit_page(&PageOp {
+            user_data,
+            buf: buf.as_ptr(),
+            len: buf.len(),
+            offset,
+            file: write.stripe,
+            buf_index: page_idx as u16,
+            sync,
+        })?;
+        self.pending_writes += if sync { 2 } else { 1 };
+        self.stats.set_pending_writes(self.pending_writes);
+        self.unsubmitted += 1;
+        if self.unsubmitted >= MAX_SUBMIT_BATCH {
+            self.submit()?;
+        }
+        Ok(())
+    }
 
-                self.ring
-                    .submission()
-                    .push(&write_e)
-                    .expect("SQ full even after submit");
+    /// Takes the write `key` out of service before it is resubmitted. Unless this was its
+    /// last completion, the record stays behind, marked superseded, to absorb the other.
+    fn retire_write(&mut self, key: usize, is_last: bool) -> PendingWrite {
+        if is_last {
+            self.in_flight.remove(key)
+        } else {
+            let write = &mut self.in_flight[key];
+            write.superseded = true;
+            PendingWrite {
+                superseded: false,
+                ..*write
             }
         }
+    }
 
-        let _ = self.ring.submit(); 
-        self.pending_writes += 1;
+    /// Resubmits `write` at the same offset after a short, bounded backoff.
+    fn retry_page_write(&mut self, mut write: PendingWrite) -> Result<(), PicoError> {
+        let backoff = RETRY_BACKOFF_BASE * (1 << write.attempts.min(RETRY_BACKOFF_MAX_SHIFT));
+        write.attempts += 1;
+        std::thread::sleep(backoff);
+        self.queue_page_write(write)
+    }
 
+    /// Resubmits the unwritten tail of `write` after a short write of `progress` bytes.
+    fn resume_page_write(
+        &mut self,
+        mut write: PendingWrite,
+        progress: usize,
+    ) -> Result<(), PicoError> {
+        write.written += progress;
+        write.attempts += 1;
+        self.queue_page_write(write)
+    }
 
-        let _ = self.pages.advance();
-        
-        self.wait_if_next_page_pending();
-        self.pages.get_active_page().reset();
+    /// Hands every queued page write to the kernel in a single `submit()` call.
+    fn submit(&mut self) -> Result<(), PicoError> {
+        if self.unsubmitted > 0 {
+            self.backend.submit(false)?;
+            self.unsubmitted = 0;
+        }
+        Ok(())
+    }
 
-        self.last_flush = Instant::now();
+    /// Submits any queued writes and blocks until at least one completes.
+    ///
+    /// During shutdown the wait is bounded by the deadline, after which this fails with
+    /// [`PicoError::ShutdownTimedOut`].
+    pub(crate) fn submit_and_wait(&mut self) -> Result<(), PicoError> {
+        match self.shutdown_deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(PicoError::ShutdownTimedOut {
+                        lost_from_seq: self.first_unwritten_seq(),
+                    });
+                }
+                self.backend.submit_timeout(remaining)?;
+            }
+            // A held completion already left the backend, so don't wait on it there.
+            None if !self.held_completions.is_empty() => {
+                self.backend.submit_timeout(self.poll_interval)?
+            }
+            None => self.backend.submit(true)?,
+        }
+        self.unsubmitted = 0;
+        self.process_completions()
+    }
+
+    /// Returns the seq_id of the oldest entry not yet known to be on disk.
+    fn first_unwritten_seq(&self) -> u64 {
+        let active = &self.pages.pages[self.pages.active_idx];
+        self.in_flight
+            .iter()
+            .filter(|(_, write)| !write.superseded)
+            .map(|(_, write)| self.pages.pages[write.page_idx].get_first_entry())
+            .chain((active.entry_count() > 0).then(|| active.get_first_entry()))
+            .min()
+            .unwrap_or_else(|| crate::global::get_ack_number() + 1)
     }
 
-    fn wait_if_next_page_pending(&mut self) {
+    /// Blocks until the active page's previous write completes, counting the wait as stall
+    /// time.
+    fn wait_for_active_page(&mut self) -> Result<(), PicoError> {
+        let started = Instant::now();
         let idx = self.pages.active_idx;
         while self.pages.pending_status[idx] {
-            self.ring.submit_and_wait(1).expect("failed to wait");
-            self.process_completions();
+            self.submit_and_wait()?;
+        }
+        self.stats.record_stall(started.elapsed());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(waited = ?started.elapsed(), "waited for a page to come back");
+        Ok(())
+    }
+
+    /// Publishes the watermark the completed writes allow, held below the lowest reserved
+    /// seq_id that is not durable yet.
+    fn advance_watermark(&mut self) {
+        let mut watermark = self.acked_seq;
+        if let Some(held) = self.seq_blocks.lowest(self.seq_block_timeout) {
+            watermark = watermark.min(held - 1);
+        }
+        if watermark <= self.stats.flushed_seq_id() {
+            return;
         }
+        set_ack_number(watermark);
+        self.stats.record_flushed_seq_id(watermark);
+        if let Some(latency) = &mut self.ack_latency {
+            latency.acked(watermark);
+        }
+        if self.durability != Durability::None {
+            self.stats.record_hardened_seq_id(watermark);
+            set_hardened_number(watermark);
+        }
+    }
+
+    /// Tells subscribers, and async waiters, where the watermark stands.
+    fn notify_watermark(&mut self) {
+        let watermark = self.stats.flushed_seq_id();
+        if !self.subscribers.is_empty() {
+            self.subscribers
+                .retain_mut(|subscriber| subscriber.notify(watermark));
+        }
+        #[cfg(feature = "async")]
+        self.ack_waiters.wake_covered(watermark);
     }
 
-    fn process_completions(&mut self) {
-        let mut cq = self.ring.completion();
-        while let Some(cqe) = cq.next() {
+    fn process_completions(&mut self) -> Result<(), PicoError> {
+        // Drain completions up front so handling one may queue writes again.
+        let mut completions = std::mem::take(&mut self.completions);
+        completions.append(&mut self.held_completions);
+        self.backend.poll_completions(&mut completions);
+        for &(user_data, result) in &completions {
+            if user_data & FILE_OP_COMPLETIONS != 0 {
+                let stripe = (user_data & !FILE_OP_COMPLETIONS) as usize;
+                match user_data & FILE_OP_COMPLETIONS {
+                    ALLOCATE_COMPLETION => self.file_extended(stripe, result),
+                    RANGE_SYNC_COMPLETION => self.range_synced(result),
+                    _ => self.full_synced(result),
+                }
+                continue;
+            }
+            let key = (user_data & !SYNC_COMPLETION) as usize;
+            let is_sync = user_data & SYNC_COMPLETION != 0;
+            if let (Some(injector), Some(write)) = (&self.fault_injector, self.in_flight.get(key))
+                && injector.swallow_completion(write.offset, write.attempts)
+            {
+                continue;
+            }
+            if let (Some(injector), Some(write)) = (&self.fault_injector, self.in_flight.get(key))
+                && (injector.hold_completion(write.offset, write.attempts)
+                    || is_sync && injector.hold_sync_completion(write.offset, write.attempts))
+            {
+                self.held_completions.push((user_data, result));
+                continue;
+            }
             if self.pending_writes > 0 {
                 self.pending_writes -= 1;
             }
-            if cqe.result() >= 0 {
-                let user_data = cqe.user_data();
-                let page_idx = (user_data >> 56) as usize;
-                let seq_id = user_data & 0x00FF_FFFF_FFFF_FFFF;
-                
-                if page_idx < self.pages.pending_status.len() {
+            // In Fdatasync mode a page's linked sync completes last, even when the write
+            // failed and the sync was cancelled.
+            let is_last = self.durability != Durability::Fdatasync || is_sync;
+            let Some(&write) = self.in_flight.get(key) else {
+                continue;
+            };
+            if write.superseded {
+                if is_last {
+                    self.in_flight.remove(key);
+                }
+                continue;
+            }
+            let page_idx = write.page_idx;
+            let result = match &self.fault_injector {
+                Some(injector) if !is_sync => injector.on_stripe_write_complete(
+                    write.stripe,
+                    write.offset,
+                    write.attempts,
+                    result,
+                ),
+                _ => result,
+            };
+            let outcome = if is_sync {
+                if result >= 0 {
+                    WriteOutcome::Complete
+                } else {
+                    WriteOutcome::Failed(-result)
+                }
+            } else {
+                if result > 0 {
+                    self.stats.record_bytes_written(result as u64);
+                }
+                classify_write(write.len - write.written, result, self.block_size)
+            };
+
+            let errno = match outcome {
+                WriteOutcome::Complete if !is_last => continue,
+                WriteOutcome::Complete => None,
+                WriteOutcome::Short { progress } if write.attempts < self.max_write_retries => {
+                    let write = self.retire_write(key, is_last);
+                    self.resume_page_write(write, progress)?;
+                    continue;
+                }
+                WriteOutcome::Short { .. } => Some(libc::EIO),
+                WriteOutcome::Failed(errno)
+                    if !is_sync
+                        && is_transient(errno)
+                        && write.attempts < self.max_write_retries =>
+                {
+                    let write = self.retire_write(key, is_last);
+                    self.retry_page_write(write)?;
+                    continue;
+                }
+                WriteOutcome::Failed(errno) => Some(errno),
+            };
+
+            if let Some(errno) = errno
+                && !(is_sync && errno == libc::ECANCELED)
+                && self.relocate_write(key, is_last, errno)?
+            {
+                continue;
+            }
+            if is_last {
+                self.in_flight.remove(key);
+            }
+            if let Some(errno) = errno {
+                // A sync cancelled because its write failed; the write already reported.
+                if !(is_sync && errno == libc::ECANCELED) {
+                    self.report_write_failure(&write, errno);
+                }
+                if is_last {
                     self.pages.pending_status[page_idx] = false;
+                    self.stats.record_page_settled(write.entries);
                 }
-                
-                set_ack_number(seq_id);
             } else {
-                eprintln!("Async write failed: {}", cqe.result());
+                self.pages.pending_status[page_idx] = false;
+                self.stats.record_page_settled(write.entries);
+                self.stats
+                    .record_file_len(write.stripe, write.offset + write.len as u64);
+                let mut released = Vec::new();
+                if let Some(watermark) = self.acks.complete(write.ack_slot, &mut released) {
+                    self.seq_blocks.release(&released);
+                    self.acked_seq = self.acked_seq.max(watermark);
+                    self.advance_watermark();
+                }
+                if self.durability == Durability::None && self.sync_policy.is_some() {
+                    self.background_sync.page_written(write.offset, write.len);
+                }
+                self.stats.record_page_flushed(write.entries);
+                #[cfg(feature = "metrics")]
+                self.metrics.flushed(write.len, write.queued_at.elapsed());
+                if let Some(callback) = &self.flush_callback {
+                    let page = &self.pages.pages[page_idx];
+                    callback(FlushEvent {
+                        first_seq_id: page.get_first_entry(),
+                        last_seq_id: write.last_seq,
+                        bytes: write.len,
+                        latency: write.queued_at.elapsed(),
+                    });
+                }
+                if let Some(index) = self.index.as_mut()
+                    && !write.rewrite
+                {
+                    let first_seq_id = self.pages.pages[page_idx].get_first_entry();
+                    if let Err(e) = index.append(first_seq_id, write.offset) {
+                        self.index = None;
+                        self.report_error(PicoError::IndexFailed(e));
+                    }
+                }
             }
         }
+        #[cfg(feature = "tracing")]
+        if !completions.is_empty() {
+            tracing::debug!(
+                completions = completions.len(),
+                pending = self.pending_writes,
+                watermark = self.stats.flushed_seq_id(),
+                "completions processed"
+            );
+        }
+        completions.clear();
+        self.completions = completions;
+        self.stats.set_pending_writes(self.pending_writes);
+        #[cfg(feature = "metrics")]
+        self.metrics.pending(self.in_flight.len());
+        self.notify_watermark();
+
+        if let Some(index) = self.index.as_mut()
+            && let Err(e) = index.flush()
+        {
+            self.index = None;
+            self.report_error(PicoError::IndexFailed(e));
+        }
+        Ok(())
+    }
+
+    /// Takes the stripe the write `key` failed on with `errno` out of the rotation and
+    /// queues the page again on the next stripe that is still healthy, at the end of it.
+    ///
+    /// Returns `false`, leaving the write be, if no other stripe is left to take the page;
+    /// the last healthy stripe stays in the rotation, as the only file of a logger does.
+    fn relocate_write(&mut self, key: usize, is_last: bool, errno: i32) -> Result<bool, PicoError> {
+        let failed = self.in_flight[key].stripe;
+        let others_left = (0..self.stripes.len()).any(|i| i != failed && !self.stripes[i].failed);
+        if !others_left {
+            return Ok(false);
+        }
+        let mut write = self.retire_write(key, is_last);
+        if !self.stripes[failed].failed {
+            self.stripes[failed].failed = true;
+            self.report_error(PicoError::StripeFailed {
+                path: self.stripes[failed].path.clone(),
+                errno,
+                offset: write.offset,
+            });
+        }
+        let stripe = healthy_stripe(&self.stripes, failed);
+        write.stripe = stripe;
+        write.offset = self.stripes[stripe].next_offset;
+        write.rewrite = false;
+        write.written = 0;
+        write.attempts = 0;
+        // Past the allocated region the file simply grows; waiting for an extension here
+        // would handle completions in the middle of handling this one.
+        self.stripes[stripe].next_offset += write.len as u64;
+        self.stats
+            .record_file_offset(stripe, self.stripes[stripe].next_offset);
+        self.queue_page_write(write)?;
+        Ok(true)
     }
 
-    fn flush_remaining(&mut self) {
-        self.flush_current_page();
-        while self.pending_writes > 0 {
-            self.ring.submit_and_wait(1).expect("failed to wait");
-            self.process_completions();
+    /// Reports that `write` failed; its entries are lost.
+    fn report_write_failure(&mut self, write: &PendingWrite, errno: i32) {
+        let error = PicoError::WriteFailed {
+            errno,
+            offset: write.offset,
+            first_seq_id: self.pages.pages[write.page_idx].get_first_entry(),
+            last_seq_id: write.last_seq,
+        };
+        self.stats.record_failed_write(errno);
+        #[cfg(feature = "metrics")]
+        self.metrics.write_failed();
+        self.report_error(error);
+    }
+
+    /// Stops without writing out the entries still in the ring buffer or the active page,
+    /// counting them as dropped, once the writes already in flight have completed.
+    fn abandon_remaining(&mut self) -> Result<(), PicoError> {
+        // Counted once, not again when resuming after a ring rebuild.
+        if self.shutdown_deadline.is_none() {
+            self.shutdown_deadline = Some(Instant::now() + self.shutdown_timeout);
+            let written = self
+                .open_page
+                .as_ref()
+                .map_or(0, |page| page.written_entries);
+            // A page still waiting on its previous write holds no new entries yet.
+            let unwritten = match self.awaiting_page {
+                true => 0,
+                false => self.pages.pages[self.pages.active_idx]
+                    .entry_count()
+                    .saturating_sub(written),
+            };
+            self.stats
+                .record_dropped_n(self.data_buffer.backlog() + unwritten);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            pending = self.pending_writes,
+            "shutting down without draining"
+        );
+        // The kernel may still be reading the pages of writes in flight.
+        while self.pending_writes > 0 || self.stripes.iter().any(|s| s.extending_to.is_some()) {
+            self.submit_and_wait()?;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(watermark = self.stats.flushed_seq_id(), "shut down");
+        Ok(())
+    }
+
+    fn flush_remaining(&mut self) -> Result<(), PicoError> {
+        // Kept across a ring rebuild, so the timeout covers the whole shutdown.
+        let timeout = self.shutdown_timeout;
+        self.shutdown_deadline
+            .get_or_insert_with(|| Instant::now() + timeout);
+        // The logger is gone, so nothing more is published.
+        self.drain_ring(u64::MAX)?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(pending = self.pending_writes, "shutting down");
+        if self.awaiting_page {
+            // Nothing is lost by waiting for a page now, and it reports any dropped range.
+            self.wait_for_active_page()?;
+            self.claim_active_page()?;
+        }
+        self.flush_current_page(FlushReason::Shutdown)?;
+        // A file extension still in flight would change the file after the stats say it
+        // stopped.
+        while self.pending_writes > 0 || self.stripes.iter().any(|s| s.extending_to.is_some()) {
+            self.submit_and_wait()?;
+        }
+        if self.sync_policy.is_some() {
+            // Harden everything written before the worker exits.
+            while self.background_sync.in_flight() {
+                self.submit_and_wait()?;
+            }
+            self.start_full_sync()?;
+            while self.background_sync.in_flight() {
+                self.submit_and_wait()?;
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(watermark = self.stats.flushed_seq_id(), "shut down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WriteOutcome, classify_write};
+
+    #[test]
+    fn test_classify_write() {
+        let cases = [
+            // (result, requested, expected)
+            (8192, 8192, WriteOutcome::Complete),
+            (8192, 4096, WriteOutcome::Complete),
+            (0, 8192, WriteOutcome::Short { progress: 0 }),
+            (4096, 8192, WriteOutcome::Short { progress: 4096 }),
+            // Progress rounds down to whole blocks.
+            (6000, 8192, WriteOutcome::Short { progress: 4096 }),
+            (100, 8192, WriteOutcome::Short { progress: 0 }),
+            (-libc::EAGAIN, 8192, WriteOutcome::Failed(libc::EAGAIN)),
+            (-libc::EIO, 8192, WriteOutcome::Failed(libc::EIO)),
+            (-libc::ENOSPC, 8192, WriteOutcome::Failed(libc::ENOSPC)),
+        ];
+        for (result, requested, expected) in cases {
+            assert_eq!(
+                classify_write(requested, result, 4096),
+                expected,
+                "result {result} of {requested} bytes"
+            );
         }
     }
 }
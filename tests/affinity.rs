@@ -0,0 +1,72 @@
+#![cfg(target_os = "linux")]
+
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_pinned_worker_round_trip() {
+    let path = "affinity_pinned_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_worker_affinity(0);
+        logger.start().unwrap();
+        for i in 0..100 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read().unwrap().len(), 100);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_invalid_core_is_an_error() {
+    let path = "affinity_invalid_test.log";
+    cleanup(path);
+
+    for core_id in [usize::MAX, 1000] {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_worker_affinity(core_id);
+        let err = logger.start().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(matches!(err, PicoError::AffinityFailed { core, .. } if core == core_id));
+        assert!(logger.log(TestData::default()).is_none());
+    }
+
+    cleanup(path);
+}
@@ -0,0 +1,166 @@
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct OtherData {
+    id: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, ids: std::ops::Range<u64>, append: bool) {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .build::<TestData>()
+        .unwrap()
+        .with_append(append);
+    logger.start().unwrap();
+    for id in ids {
+        logger.log(TestData { id, val: 5 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+}
+
+fn read_ids(path: &str) -> Vec<u64> {
+    Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .with_strict_reads(true)
+        .read()
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect()
+}
+
+#[test]
+fn test_append_continues_an_intact_file() {
+    let path = "append_intact_test.log";
+    cleanup(path);
+    write_entries(path, 0..300, false);
+    write_entries(path, 300..500, true);
+
+    assert_eq!(read_ids(path), (0..500).collect::<Vec<_>>());
+    let entries: Vec<_> = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert!(entries.windows(2).all(|w| w[0].seq_id < w[1].seq_id));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_append_truncates_a_torn_tail_page() {
+    let path = "append_torn_test.log";
+    cleanup(path);
+    // 101 entries fill a 4 KiB page, so the fifth page holds the last 96.
+    write_entries(path, 0..500, false);
+    let mut bytes = fs::read(path).unwrap();
+    assert_eq!(bytes.len(), 6 * 4096);
+    // Tear the last page, as a crash partway through writing it would.
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    for byte in &mut bytes[5 * 4096 + 2048..] {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        *byte = state as u8;
+    }
+    fs::write(path, &bytes).unwrap();
+    assert!(
+        Logger::<TestData>::new()
+            .with_read_config(path.to_string())
+            .with_strict_reads(true)
+            .read()
+            .is_err()
+    );
+
+    write_entries(path, 1000..1100, true);
+    let expected: Vec<_> = (0..404).chain(1000..1100).collect();
+    assert_eq!(read_ids(path), expected);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_append_point_is_recorded_in_the_loggers_stats() {
+    let path = "append_stats_test.log";
+    cleanup(path);
+    write_entries(path, 0..300, false);
+    let last = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read_iter()
+        .unwrap()
+        .last()
+        .unwrap()
+        .unwrap()
+        .seq_id;
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .build::<TestData>()
+        .unwrap()
+        .with_append(true);
+    logger.start().unwrap();
+    // The recovered entries count as flushed for this logger before it writes anything.
+    assert_eq!(logger.stats().flushed_seq_id, last);
+    assert!(logger.log(TestData { id: 300, val: 5 }).unwrap() > last);
+    logger.shutdown().unwrap();
+
+    cleanup(path);
+}
+
+#[test]
+fn test_append_to_a_missing_file_writes_it_anew() {
+    let path = "append_missing_test.log";
+    cleanup(path);
+    write_entries(path, 0..50, true);
+    assert_eq!(read_ids(path), (0..50).collect::<Vec<_>>());
+    cleanup(path);
+}
+
+#[test]
+fn test_append_refuses_a_different_layout() {
+    let path = "append_mismatch_test.log";
+    cleanup(path);
+    write_entries(path, 0..50, false);
+    let before = fs::read(path).unwrap();
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<OtherData>()
+        .unwrap()
+        .with_append(true);
+    let err = logger.start().unwrap_err();
+    assert!(matches!(err, PicoError::AppendMismatch { .. }), "{err:?}");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_format_version(2)
+        .with_append(true);
+    assert!(matches!(
+        logger.start(),
+        Err(PicoError::AppendMismatch { .. })
+    ));
+    assert_eq!(fs::read(path).unwrap(), before);
+
+    cleanup(path);
+}
@@ -0,0 +1,180 @@
+#[cfg(feature = "zstd")]
+use picologger::ArchiveCompression;
+#[cfg(feature = "lz4")]
+use picologger::Compression;
+use picologger::{ARCHIVE_MAGIC, Level, Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::io::ErrorKind;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Fill {
+    order_id: u64,
+    price: f64,
+    qty: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Quote {
+    bid: u32,
+    ask: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_fills(path: &str, count: u64) -> Logger<Fill> {
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .build::<Fill>()
+            .unwrap()
+            .with_schema_tag("fills_v1")
+            .with_metadata([("service", "matcher")]);
+        logger.start().unwrap();
+        for i in 0..count {
+            let fill = Fill {
+                order_id: i,
+                price: 100.5 + i as f64,
+                qty: i as u32 * 10,
+            };
+            let level = if i % 10 == 0 {
+                Level::Warn
+            } else {
+                Level::Info
+            };
+            logger.log_with_level(fill, level).unwrap();
+        }
+    }
+    Logger::<Fill>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_archive_round_trips_every_entry() {
+    let path = "archive_round_trip_test.log";
+    let logger = write_fills(path, 1000);
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+
+    let mut archive = Vec::new();
+    let stats = logger.export_archive(&mut archive).unwrap();
+    assert_eq!(stats.entries, 1000);
+    assert_eq!(stats.bytes, archive.len() as u64);
+    assert_eq!(stats.last_seq_id, entries.last().map(|e| e.seq_id));
+    assert_eq!(archive[..8], ARCHIVE_MAGIC);
+    assert!(archive.len() < fs::metadata(path).unwrap().len() as usize);
+
+    let imported = logger.import_archive(&archive[..]).unwrap();
+    assert_eq!(imported.len(), entries.len());
+    for (imported, entry) in imported.iter().zip(&entries) {
+        assert_eq!(imported.seq_id, entry.seq_id);
+        assert_eq!(imported.ts_nanos, entry.ts_nanos);
+        assert_eq!(imported.level, entry.level);
+        assert_eq!(imported.data, entry.data);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_importing_as_another_type_is_rejected() {
+    let path = "archive_mismatch_test.log";
+    let logger = write_fills(path, 10);
+    let mut archive = Vec::new();
+    logger.export_archive(&mut archive).unwrap();
+
+    let err = Logger::<Quote>::new()
+        .import_archive(&archive[..])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = err.into_inner().unwrap().downcast::<PicoError>().unwrap();
+    assert!(matches!(*err, PicoError::SchemaMismatch { .. }));
+
+    let err = Logger::<Fill>::new()
+        .with_schema_tag("fills_v2")
+        .import_archive(&archive[..])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let tagged = Logger::<Fill>::new().with_schema_tag("fills_v1");
+    assert_eq!(tagged.import_archive(&archive[..]).unwrap().len(), 10);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_damaged_archives_are_rejected() {
+    let path = "archive_damaged_test.log";
+    let logger = write_fills(path, 100);
+    let mut archive = Vec::new();
+    logger.export_archive(&mut archive).unwrap();
+
+    for damaged in [&archive[..archive.len() - 20], &archive[..4], &archive[8..]] {
+        let err = logger.import_archive(damaged).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    cleanup(path);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_compressed_archive_round_trips() {
+    let path = "archive_lz4_test.log";
+    let plain = write_fills(path, 5000);
+    let compressed = Logger::<Fill>::new()
+        .with_read_config(path.to_string())
+        .with_compression(Compression::Lz4);
+
+    let mut raw = Vec::new();
+    plain.export_archive(&mut raw).unwrap();
+    let mut packed = Vec::new();
+    compressed.export_archive(&mut packed).unwrap();
+    assert!(packed.len() < raw.len());
+    assert_eq!(
+        plain.import_archive(&packed[..]).unwrap().len(),
+        plain.read().unwrap().len()
+    );
+
+    cleanup(path);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_archive_round_trips() {
+    let path = "archive_zstd_test.log";
+    let logger = write_fills(path, 5000);
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+
+    let mut raw = Vec::new();
+    logger
+        .export_archive_with(&mut raw, ArchiveCompression::None)
+        .unwrap();
+    let mut packed = Vec::new();
+    let stats = logger
+        .export_archive_with(&mut packed, ArchiveCompression::Zstd { level: 3 })
+        .unwrap();
+    assert_eq!(stats.entries, entries.len() as u64);
+    assert_eq!(stats.bytes, packed.len() as u64);
+    assert!(
+        packed.len() < raw.len() / 2,
+        "{} of {}",
+        packed.len(),
+        raw.len()
+    );
+
+    let imported = logger.import_archive(&packed[..]).unwrap();
+    assert_eq!(imported, entries);
+
+    // A damaged zstd chunk is reported, not read as entries.
+    let last = packed.len() - 20;
+    packed[last] ^= 0xff;
+    let err = logger.import_archive(&packed[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    cleanup(path);
+}
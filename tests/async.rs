@@ -0,0 +1,144 @@
+#![cfg(feature = "async")]
+
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor: polls `future` on the current thread, parking between wakeups.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+fn start_logger(path: &str) -> Logger<TestData> {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(10))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    logger
+}
+
+#[test]
+fn test_async_durability() {
+    let path = "async_test.log";
+    let _ = fs::remove_file(path);
+
+    // The ack watermark is shared by every logger in the process, so the scenarios run
+    // one after another.
+    {
+        let mut logger = start_logger(path);
+        let durable = logger.log_durable(TestData { id: 0, val: 0 });
+        for i in 1..10 {
+            logger.log(TestData { id: i, val: 0 });
+        }
+        // With a 10s flush interval, nothing is written until the explicit flush.
+        let flushed = logger.flush_async();
+        block_on(flushed).unwrap();
+        assert!(block_on(durable).unwrap() <= Logger::<TestData>::get_last_flushed_entry());
+        let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+        assert_eq!(reader.read().unwrap().len(), 10);
+    }
+
+    {
+        let mut logger = start_logger(path);
+        let durable = logger.log_durable(TestData { id: 0, val: 0 });
+        let seq_id = logger.log(TestData { id: 1, val: 0 }).unwrap();
+        let waiter = thread::spawn(move || block_on(durable));
+        thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        drop(logger);
+        assert!(waiter.join().unwrap().unwrap() < seq_id);
+    }
+
+    {
+        let mut logger = Logger::<TestData>::new();
+        assert!(matches!(
+            block_on(logger.log_durable(TestData::default())),
+            Err(PicoError::NotStarted)
+        ));
+    }
+
+    fs::remove_file(path).unwrap();
+}
+
+/// Holds every write completion until released.
+struct HoldWrites(AtomicBool);
+
+impl FaultInjector for HoldWrites {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn hold_completion(&self, _offset: u64, _attempt: u32) -> bool {
+        !self.0.load(Ordering::Acquire)
+    }
+}
+
+#[test]
+fn test_futures_wait_for_their_own_logger() {
+    let held_path = "async_held_test.log";
+    let other_path = "async_other_test.log";
+    let _ = fs::remove_file(held_path);
+    let _ = fs::remove_file(other_path);
+
+    let gate = Arc::new(HoldWrites(AtomicBool::new(false)));
+    let mut held = LoggerConfig::new()
+        .path(held_path)
+        .flush_interval(Duration::from_secs(10))
+        .build::<TestData>()
+        .unwrap()
+        .with_fault_injector(gate.clone());
+    held.start().unwrap();
+    let durable = held.log_durable(TestData { id: 0, val: 0 });
+    held.flush().unwrap();
+
+    // The other logger's entries come later and are acknowledged, which moves the
+    // process-wide watermark past the held entry.
+    let mut other = start_logger(other_path);
+    other.log(TestData { id: 1, val: 0 }).unwrap();
+    block_on(other.flush_async()).unwrap();
+
+    let mut durable = pin!(durable);
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(durable.as_mut().poll(&mut cx).is_pending());
+
+    gate.0.store(true, Ordering::Release);
+    let seq_id = block_on(durable).unwrap();
+    assert!(held.stats().flushed_seq_id >= seq_id);
+
+    drop(held);
+    drop(other);
+    fs::remove_file(held_path).unwrap();
+    fs::remove_file(other_path).unwrap();
+}
@@ -0,0 +1,159 @@
+use picologger::testing::FaultInjector;
+use picologger::{Backend, Durability, Logger, LoggerConfig};
+use std::fs;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Offset of the first page: the file header takes up the first 4 KiB block.
+const FIRST_PAGE: u64 = 4096;
+
+fn round_trip(path: &str, backend: Backend, durability: Durability) {
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .build::<TestData>()
+            .unwrap()
+            .with_backend(backend)
+            .with_durability(durability);
+        logger.start().unwrap();
+        if backend != Backend::Auto {
+            assert_eq!(logger.backend(), backend);
+        }
+        for i in 0..3000 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 3000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+        assert_eq!(item.val, i as u32);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_sync_backend_round_trip() {
+    round_trip("backend_sync_test.log", Backend::Sync, Durability::None);
+}
+
+#[test]
+fn test_sync_backend_fdatasync_round_trip() {
+    round_trip(
+        "backend_sync_fdatasync_test.log",
+        Backend::Sync,
+        Durability::Fdatasync,
+    );
+}
+
+#[test]
+fn test_auto_backend_round_trip() {
+    round_trip("backend_auto_test.log", Backend::Auto, Durability::None);
+}
+
+#[test]
+fn test_auto_backend_resolves_on_start() {
+    let path = "backend_resolve_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+    assert_eq!(logger.backend(), Backend::Auto);
+    logger.start().unwrap();
+    assert_ne!(logger.backend(), Backend::Auto);
+    drop(logger);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_sync_backend_registers_nothing() {
+    let path = "backend_sync_registration_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_backend(Backend::Sync);
+    logger.start().unwrap();
+    assert!(!logger.uses_registered_buffers());
+    assert!(!logger.uses_fixed_file());
+    assert!(!logger.uses_sqpoll());
+    let capabilities = logger.capabilities();
+    assert_eq!(capabilities.backend, Backend::Sync);
+    assert!(!capabilities.wait_timeout);
+    assert!(!capabilities.completion_wakeups);
+    drop(logger);
+
+    cleanup(path);
+}
+
+/// Fails the first attempt of the write of the first page with EAGAIN.
+struct FlakyFirstWrite {
+    attempts: Mutex<Vec<u32>>,
+}
+
+impl FaultInjector for FlakyFirstWrite {
+    fn on_write_complete(&self, offset: u64, attempt: u32, result: i32) -> i32 {
+        if offset != FIRST_PAGE {
+            return result;
+        }
+        self.attempts.lock().unwrap().push(attempt);
+        if attempt == 0 { -libc::EAGAIN } else { result }
+    }
+}
+
+#[test]
+fn test_sync_backend_retries_transient_failures() {
+    let path = "backend_sync_retry_test.log";
+    cleanup(path);
+
+    let injector = Arc::new(FlakyFirstWrite {
+        attempts: Mutex::new(Vec::new()),
+    });
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .build::<TestData>()
+            .unwrap()
+            .with_backend(Backend::Sync)
+            .with_fault_injector(injector.clone());
+        logger.start().unwrap();
+        let errors = logger.take_error_receiver().unwrap();
+        for i in 0..500 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+        drop(logger);
+        assert_eq!(errors.try_iter().count(), 0);
+    }
+    assert_eq!(*injector.attempts.lock().unwrap(), vec![0, 1]);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read().unwrap().len(), 500);
+
+    cleanup(path);
+}
@@ -0,0 +1,178 @@
+use picologger::testing::FaultInjector;
+use picologger::{BufferFullPolicy, Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str, capacity: usize) -> Logger<TestData> {
+    LoggerConfig::new()
+        .path(path)
+        .capacity(capacity)
+        .build::<TestData>()
+        .unwrap()
+}
+
+fn items(range: std::ops::Range<u64>) -> Vec<TestData> {
+    range
+        .map(|id| TestData {
+            id,
+            val: id as u32 * 5,
+        })
+        .collect()
+}
+
+#[test]
+fn test_batches_round_trip_with_contiguous_seq_ids() {
+    let path = "batch_round_trip.log";
+    cleanup(path);
+
+    let mut ranges = Vec::new();
+    {
+        let mut logger = logger(path, 4096);
+        logger.start().unwrap();
+        for chunk in items(0..2000).chunks(250) {
+            let seq_ids = logger.log_batch(chunk).unwrap();
+            assert_eq!(seq_ids.end - seq_ids.start, 250);
+            ranges.push(seq_ids);
+        }
+        assert_eq!(logger.stats().entries_logged, 2000);
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 2000);
+    let seq_ids: Vec<u64> = ranges.into_iter().flatten().collect();
+    for ((entry, &seq_id), i) in entries.iter().zip(&seq_ids).zip(0..) {
+        assert_eq!(entry.seq_id, seq_id);
+        assert_eq!(
+            entry.data,
+            TestData {
+                id: i,
+                val: i as u32 * 5
+            }
+        );
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_batches_interleave_with_single_entries() {
+    let path = "batch_interleave.log";
+    cleanup(path);
+
+    {
+        // Batches wrap around the end of a ring this small; blocking keeps the producer
+        // from lapping the worker.
+        let mut logger = logger(path, 100).with_buffer_full_policy(BufferFullPolicy::Block);
+        logger.start().unwrap();
+        let mut next = 0;
+        while next < 600 {
+            logger.log(items(next..next + 1)[0]).unwrap();
+            logger.log_batch(&items(next + 1..next + 30)).unwrap();
+            next += 30;
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result, items(0..600));
+
+    cleanup(path);
+}
+
+/// Holds every write completion until released, which keeps the worker from reading the
+/// ring once its pages are all in flight.
+struct HoldWrites(AtomicBool);
+
+impl FaultInjector for HoldWrites {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn hold_completion(&self, _offset: u64, _attempt: u32) -> bool {
+        !self.0.load(Ordering::Acquire)
+    }
+}
+
+#[test]
+fn test_overwriting_batches_never_lap_the_worker() {
+    let path = "batch_overwrite.log";
+    cleanup(path);
+
+    let gate = Arc::new(HoldWrites(AtomicBool::new(false)));
+    let mut logged = Vec::new();
+    {
+        let mut logger = logger(path, 64)
+            .with_pages(2)
+            .with_fault_injector(gate.clone());
+        logger.start().unwrap();
+        let mut next = 0;
+        let refused = loop {
+            let batch = items(next..next + 16);
+            match logger.try_log_batch(&batch) {
+                Ok(_) => logged.extend(batch),
+                Err(PicoError::BufferFull) => break batch,
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+            next += 16;
+            assert!(next < 10_000, "the batches lapped the worker");
+        };
+        assert_eq!(logger.stats().dropped_entries, 0);
+
+        gate.0.store(true, Ordering::Release);
+        while logger.try_log_batch(&refused).is_err() {
+            std::thread::yield_now();
+        }
+        logged.extend(refused);
+        logger.shutdown().unwrap();
+        assert_eq!(logger.stats().dropped_entries, 0);
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read().unwrap(), logged);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_batch_larger_than_ring_is_rejected() {
+    let path = "batch_too_large.log";
+    cleanup(path);
+
+    let mut logger = logger(path, 64);
+    logger.start().unwrap();
+    match logger.try_log_batch(&items(0..65)) {
+        Err(PicoError::BatchTooLarge { len, capacity }) => {
+            assert_eq!((len, capacity), (65, 64));
+        }
+        other => panic!("expected BatchTooLarge, got {:?}", other),
+    }
+    assert_eq!(logger.try_log_batch(&[]).unwrap(), 0..0);
+    assert!(logger.log_batch(&items(0..64)).is_some());
+    drop(logger);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_batch_before_start() {
+    let mut logger = logger("batch_unstarted.log", 16);
+    assert!(matches!(
+        logger.try_log_batch(&items(0..4)),
+        Err(PicoError::NotStarted)
+    ));
+}
@@ -1,5 +1,10 @@
+//! Throughput and latency reports. None of them asserts anything, and the longest writes
+//! gigabytes, so they are ignored by default; run them one at a time with
+//! `cargo test --release --test benchmark -- --ignored --test-threads=1 --nocapture`.
+
 use crossbeam_channel::unbounded;
-use picologger::Logger;
+use picologger::test_support::TempLogger;
+use picologger::{Durability, LoggerConfig, SyncPolicy};
 use std::fs;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -22,24 +27,243 @@ impl Default for Data {
     }
 }
 
+/// A config holding `capacity` entries, flushing every `flush_interval`.
+fn config(capacity: usize, flush_interval: Duration) -> LoggerConfig {
+    LoggerConfig::new()
+        .capacity(capacity)
+        .flush_interval(flush_interval)
+        .poll_interval(Duration::from_micros(10))
+}
+
+/// Time from `log()` to durable ack for each of `pages` entries, each forced into a fresh
+/// page so every write lands on blocks the file has never touched.
+fn first_write_latencies(pre_alloc_size: u64, pages: usize) -> Vec<Duration> {
+    let mut logger = TempLogger::<Data>::with_config(config(1024, Duration::from_micros(100)))
+        .configure(|logger| logger.with_preallocation(pre_alloc_size));
+    logger.start().unwrap();
+
+    let mut latencies = Vec::with_capacity(pages);
+    for i in 0..pages {
+        let start = Instant::now();
+        let seq_id = logger.log(Data {
+            val: i as u64,
+            ts: 0,
+            padding: [0; 84],
+        });
+        if let Some(id) = seq_id {
+            while logger.stats().flushed_seq_id < id {
+                thread::yield_now();
+            }
+        }
+        latencies.push(start.elapsed());
+    }
+    latencies
+}
+
 #[test]
-fn benchmark_throughput_latency() {
-    let path = "bench_test.log";
-    if std::path::Path::new(path).exists() {
-        fs::remove_file(path).unwrap();
+#[ignore = "benchmark"]
+fn report_first_write_latency() {
+    let pages = 256;
+    for (label, pre_alloc_size) in [("sparse", 0), ("fallocated", 64 * 1024 * 1024)] {
+        let mut latencies = first_write_latencies(pre_alloc_size, pages);
+        latencies.sort();
+        println!(
+            "First-Write Latency, {} file (P50): {:.2?}",
+            label,
+            latencies[pages / 2]
+        );
+        println!(
+            "First-Write Latency, {} file (P99): {:.2?}",
+            label,
+            latencies[pages * 99 / 100]
+        );
+    }
+}
+
+/// Entries logged per second over `duration` with or without a huge-page-backed pool,
+/// including the time the worker takes to catch up afterwards.
+fn burst_throughput(hugepages: bool, drain_batch: usize, duration: Duration) -> (f64, bool) {
+    let mut logger = TempLogger::<Data>::with_config(config(4096, Duration::from_millis(1)))
+        .configure(|logger| {
+            logger
+                .with_hugepages(hugepages)
+                .with_drain_batch(drain_batch)
+        });
+    logger.start().unwrap();
+    let active = logger.uses_hugepages();
+
+    let start = Instant::now();
+    let mut count = 0u64;
+    while start.elapsed() < duration {
+        logger.log(Data {
+            val: count,
+            ts: 0,
+            padding: [0; 84],
+        });
+        count += 1;
     }
+    logger.shutdown().unwrap();
+    let elapsed = start.elapsed();
+
+    (count as f64 / elapsed.as_secs_f64(), active)
+}
 
-    let mut logger =
-        Logger::<Data>::new().with_write_config(path.to_string(), 4096, 1_000_000, 10_000, 10 * 1024 * 1024 * 1024);
+#[test]
+#[ignore = "benchmark"]
+fn report_hugepage_throughput() {
+    for hugepages in [false, true] {
+        let (throughput, active) = burst_throughput(hugepages, 256, Duration::from_secs(2));
+        println!(
+            "Burst Throughput, hugepages {} (active: {}): {:.2} Op/s",
+            if hugepages { "on" } else { "off" },
+            active,
+            throughput
+        );
+    }
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn report_drain_batch_throughput() {
+    for drain_batch in [1, 256] {
+        let (throughput, _) = burst_throughput(false, drain_batch, Duration::from_secs(2));
+        println!(
+            "Burst Throughput, drain batch {}: {:.2} Op/s",
+            drain_batch, throughput
+        );
+    }
+}
+
+/// Entries logged per second over `duration` with the given durability mode and sync
+/// policy, and how many of them were not yet hardened when logging stopped.
+fn sync_policy_throughput(
+    durability: Durability,
+    policy: Option<SyncPolicy>,
+    duration: Duration,
+) -> (f64, u64) {
+    let mut logger = TempLogger::<Data>::with_config(config(4096, Duration::from_millis(1)))
+        .configure(|logger| logger.with_durability(durability));
+    if let Some(policy) = policy {
+        logger = logger.configure(|logger| logger.with_sync_policy(policy));
+    }
+    logger.start().unwrap();
+
+    let start = Instant::now();
+    let mut seq_ids = None;
+    while start.elapsed() < duration {
+        if let Some(seq_id) = logger.log(Data {
+            val: 0,
+            ts: 0,
+            padding: [0; 84],
+        }) {
+            seq_ids = Some((seq_ids.map_or(seq_id, |(first, _)| first), seq_id));
+        }
+    }
+    let unhardened = seq_ids.map_or(0, |(first, last)| {
+        last - logger.stats().hardened_seq_id.max(first - 1)
+    });
+    logger.shutdown().unwrap();
+    let elapsed = start.elapsed();
+
+    (
+        logger.stats().entries_logged as f64 / elapsed.as_secs_f64(),
+        unhardened,
+    )
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn report_sync_policy_throughput() {
+    let eager = SyncPolicy {
+        range_every_pages: 4,
+        range_interval: Some(Duration::from_millis(1)),
+        full_sync_interval: Duration::from_millis(10),
+    };
+    let runs = [
+        ("no syncs", Durability::None, None),
+        ("fdatasync per page", Durability::Fdatasync, None),
+        ("eager sync policy", Durability::None, Some(eager)),
+        (
+            "default sync policy",
+            Durability::None,
+            Some(SyncPolicy::default()),
+        ),
+    ];
+    for (label, durability, policy) in runs {
+        let (throughput, unhardened) =
+            sync_policy_throughput(durability, policy, Duration::from_secs(2));
+        println!(
+            "Burst Throughput, {}: {:.2} Op/s ({} entries not hardened at the end)",
+            label, throughput, unhardened
+        );
+    }
+}
+
+/// Entries per second read back with the streaming and the memory-mapped reader, best of
+/// `passes` scans each.
+fn read_throughput(entries: u64, passes: usize) {
+    let mut writer = TempLogger::<Data>::with_config(config(8192, Duration::from_millis(1)));
+    writer.start().unwrap();
+    for i in 0..entries {
+        writer.log(Data {
+            val: i,
+            ts: 0,
+            padding: [0; 84],
+        });
+    }
+    writer.shutdown().unwrap();
+
+    let logger = writer.reader();
+    let file_len = fs::metadata(writer.path()).unwrap().len() as f64;
+    let scan = |mapped: bool| {
+        let start = Instant::now();
+        let mut sum = 0u64;
+        if mapped {
+            for entry in logger.read_mmap().unwrap() {
+                sum = sum.wrapping_add(entry.unwrap().data.val);
+            }
+        } else {
+            for entry in logger.read_iter().unwrap() {
+                sum = sum.wrapping_add(entry.unwrap().data.val);
+            }
+        }
+        std::hint::black_box(sum);
+        start.elapsed()
+    };
+    for (label, mapped) in [("read_iter", false), ("read_mmap", true)] {
+        let best = (0..passes).map(|_| scan(mapped)).min().unwrap();
+        println!(
+            "Read Throughput, {}: {:.2} MB/s ({:.2?} per scan)",
+            label,
+            file_len / best.as_secs_f64() / 1e6,
+            best
+        );
+    }
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn report_read_throughput() {
+    read_throughput(2_000_000, 3);
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn benchmark_throughput_latency() {
+    let mut logger = TempLogger::<Data>::with_config(
+        config(4096, Duration::from_millis(1)).preallocate(1024 * 1024 * 1024),
+    );
     logger.start().unwrap();
 
     let (tx, rx) = unbounded::<(u64, Instant)>();
+    let watermarks = logger.subscribe();
 
     let monitor_handle = thread::spawn(move || {
         let mut disk_latencies = Vec::with_capacity(100_000);
+        let mut durable = 0;
         while let Ok((seq_id, start_ts)) = rx.recv() {
-            while Logger::<Data>::get_last_flushed_entry() < seq_id {
-                thread::yield_now();
+            while durable < seq_id {
+                durable = watermarks.recv().unwrap();
             }
             disk_latencies.push(start_ts.elapsed());
         }
@@ -115,9 +339,4 @@ fn benchmark_throughput_latency() {
     println!("Disk Latency (P50): {:.2?}", d_p50);
     println!("Disk Latency (P95): {:.2?}", d_p95);
     println!("Disk Latency (P99): {:.2?}", d_p99);
-
-    drop(logger);
-    if std::path::Path::new(path).exists() {
-        fs::remove_file(path).unwrap();
-    }
 }
@@ -0,0 +1,201 @@
+//! Logging straight to a block device. These overwrite the device named by
+//! `PICOLOG_TEST_DEVICE`, so they are ignored unless asked for, on a loop device with
+//! 4 KiB sectors:
+//!
+//! ```text
+//! truncate -s 64M scratch.img
+//! sudo losetup --find --show --sector-size 4096 scratch.img   # prints e.g. /dev/loop0
+//! PICOLOG_TEST_DEVICE=/dev/loop0 cargo test --test block_device -- --ignored
+//! ```
+//!
+//! The direct write probe is checked on a file instead, on a filesystem with 4 KiB sectors
+//! that doesn't report the alignment `O_DIRECT` needs, mounted at `PICOLOG_TEST_MOUNT`.
+//! Kernels from 6.1 on report it through `STATX_DIOALIGN` and `start()` fails earlier with
+//! `MisalignedBlockSize`, so this needs an older kernel, e.g. with ext4 on a second loop
+//! device:
+//!
+//! ```text
+//! truncate -s 64M scratch-fs.img
+//! sudo losetup --find --show --sector-size 4096 scratch-fs.img   # prints e.g. /dev/loop1
+//! sudo mkfs.ext4 /dev/loop1 && sudo mount /dev/loop1 /mnt/scratch
+//! PICOLOG_TEST_MOUNT=/mnt/scratch cargo test --test block_device -- --ignored
+//! ```
+#![cfg(target_os = "linux")]
+
+use picologger::{BufferFullPolicy, Logger, LoggerConfig, PicoError};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+/// The tests share one device, so they take turns.
+static DEVICE: Mutex<()> = Mutex::new(());
+
+/// Returns the test device, zeroed so nothing an earlier test wrote is read back, and its
+/// size.
+fn scratch_device() -> (PathBuf, u64) {
+    let path = PathBuf::from(
+        std::env::var_os("PICOLOG_TEST_DEVICE")
+            .expect("PICOLOG_TEST_DEVICE must name a block device to overwrite"),
+    );
+    let mut device = OpenOptions::new().write(true).open(&path).unwrap();
+    let len = device.seek(SeekFrom::End(0)).unwrap();
+    device.seek(SeekFrom::Start(0)).unwrap();
+    let zeros = vec![0u8; 1024 * 1024];
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(zeros.len() as u64) as usize;
+        device.write_all(&zeros[..n]).unwrap();
+        left -= n as u64;
+    }
+    device.sync_all().unwrap();
+    (path, len)
+}
+
+#[test]
+#[ignore = "overwrites the block device named by PICOLOG_TEST_DEVICE"]
+fn test_device_round_trip() {
+    let _turn = DEVICE.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, len) = scratch_device();
+
+    let mut logger = LoggerConfig::new()
+        .path(&path)
+        .flush_interval(Duration::from_millis(1))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_buffer_full_policy(BufferFullPolicy::Block);
+    logger.start().unwrap();
+    for id in 0..10_000 {
+        logger.log(TestData { id, val: 7 }).unwrap();
+    }
+    logger.flush().unwrap();
+    assert_eq!(logger.stats().file_len, len);
+    logger.shutdown().unwrap();
+
+    let reader = Logger::<TestData>::new().with_read_config(&path);
+    let result = reader.read().unwrap();
+    assert_eq!(result.len(), 10_000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+}
+
+#[test]
+#[ignore = "overwrites the block device named by PICOLOG_TEST_DEVICE"]
+fn test_writing_past_the_end_of_the_device_fails() {
+    let _turn = DEVICE.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, len) = scratch_device();
+
+    // Blocking keeps the producer from outrunning the worker, so logging stops once the
+    // worker does.
+    let mut logger = LoggerConfig::new()
+        .path(&path)
+        .build::<TestData>()
+        .unwrap()
+        .with_buffer_full_policy(BufferFullPolicy::Block);
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    let entries = len / size_of::<TestData>() as u64;
+    let mut logged = 0;
+    while logged < entries && logger.log(TestData::default()).is_some() {
+        logged += 1;
+    }
+    assert!(logged < entries);
+
+    let err = errors.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(err.kind(), ErrorKind::StorageFull);
+    match err {
+        PicoError::DeviceFull {
+            path: full,
+            len: at,
+        } => {
+            assert_eq!(full, path);
+            assert_eq!(at, len);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    let _ = logger.shutdown();
+
+    let result = Logger::<TestData>::new()
+        .with_read_config(&path)
+        .read()
+        .unwrap();
+    assert!(!result.is_empty());
+    assert!(result.len() as u64 <= logged);
+}
+
+#[test]
+#[ignore = "overwrites the block device named by PICOLOG_TEST_DEVICE"]
+fn test_block_size_must_cover_the_sector_size() {
+    let _turn = DEVICE.lock().unwrap_or_else(|e| e.into_inner());
+    let (path, _) = scratch_device();
+    let sector_size = {
+        let mut logger = LoggerConfig::new().path(&path).build::<TestData>().unwrap();
+        logger.start().unwrap();
+        logger.shutdown().unwrap();
+        picologger::inspect::verify(&path)
+            .unwrap()
+            .page_size
+            .unwrap() as usize
+    };
+    assert!(
+        sector_size > 512,
+        "the test device needs sectors larger than 512 bytes, e.g. `losetup --sector-size 4096`"
+    );
+
+    let mut logger = LoggerConfig::new()
+        .path(&path)
+        .build::<TestData>()
+        .unwrap()
+        .with_block_size(sector_size / 2);
+    match logger.start().unwrap_err() {
+        PicoError::MisalignedBlockSize {
+            block_size,
+            sector_size: found,
+            ..
+        } => {
+            assert_eq!(block_size, sector_size / 2);
+            assert_eq!(found, sector_size);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+#[ignore = "needs a pre-6.1 kernel and a 4 KiB sector filesystem at PICOLOG_TEST_MOUNT"]
+fn test_rejected_direct_writes_fail_start() {
+    let dir = PathBuf::from(
+        std::env::var_os("PICOLOG_TEST_MOUNT")
+            .expect("PICOLOG_TEST_MOUNT must name a directory on a 4 KiB sector filesystem"),
+    );
+    let path = dir.join("test_rejected_direct_writes_fail_start.log");
+    let _ = std::fs::remove_file(&path);
+    let mut logger = LoggerConfig::new()
+        .path(&path)
+        .build::<TestData>()
+        .unwrap()
+        .with_block_size(512);
+    match logger.start().unwrap_err() {
+        PicoError::DirectWriteRejected {
+            path: rejected,
+            page_size,
+            block_size,
+            ..
+        } => {
+            assert_eq!(rejected, path);
+            assert_eq!(page_size, 512);
+            assert_eq!(block_size, 512);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,78 @@
+use picologger::Logger;
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Lays out `count` entries the way the worker does on a machine with `block_size`-byte
+/// blocks: 24-byte entry headers, 8-byte aligned entries, zero padding up to each page end.
+fn write_pages(path: &str, block_size: usize, count: u64) {
+    let mut file = Vec::new();
+    let mut page = Vec::with_capacity(block_size);
+    for id in 0..count {
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&(id + 1).to_le_bytes());
+        entry.extend_from_slice(&(1_000 + id).to_le_bytes());
+        entry.extend_from_slice(&16u16.to_le_bytes());
+        entry.extend_from_slice(&[0; 6]);
+        entry.extend_from_slice(&id.to_ne_bytes());
+        entry.extend_from_slice(&(id as u32 * 3).to_ne_bytes());
+        entry.extend_from_slice(&[0; 4]);
+        if page.len() + entry.len() > block_size {
+            page.resize(block_size, 0);
+            file.append(&mut page);
+        }
+        page.extend_from_slice(&entry);
+    }
+    page.resize(block_size, 0);
+    file.append(&mut page);
+    fs::write(path, file).unwrap();
+}
+
+fn assert_reads_back(path: &str, count: u64) {
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len() as u64, count);
+    for (i, entry) in entries.iter().enumerate() {
+        assert_eq!(entry.seq_id, i as u64 + 1);
+        assert_eq!(entry.ts_nanos, 1_000 + i as u64);
+        assert_eq!(entry.data.id, i as u64);
+        assert_eq!(entry.data.val, i as u32 * 3);
+    }
+}
+
+#[test]
+fn test_reads_files_written_with_other_block_sizes() {
+    // 40-byte entries leave between 8 and 32 bytes of padding at the end of these pages.
+    for block_size in [512, 1024, 2048, 4096, 8192, 65536] {
+        let path = format!("block_size_{}_test.log", block_size);
+        cleanup(&path);
+        write_pages(&path, block_size, 5000);
+        assert_reads_back(&path, 5000);
+        cleanup(&path);
+    }
+}
+
+#[test]
+fn test_trailing_partial_entry_is_ignored() {
+    let path = "block_size_partial_test.log";
+    cleanup(path);
+
+    write_pages(path, 4096, 10);
+    let mut bytes = fs::read(path).unwrap();
+    bytes.truncate(10 * 40 - 8);
+    fs::write(path, bytes).unwrap();
+    assert_reads_back(path, 9);
+
+    cleanup(path);
+}
@@ -0,0 +1,110 @@
+use picologger::{Level, Logger, LoggerConfig, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+/// Format version 4 file with 512-byte blocks and the schema tag "fixture": three entries
+/// with seq_ids 1 to 3, timestamps 1_000_000_001 to 1_000_000_003 and levels Info, Warn and
+/// Error, written byte by byte rather than by a logger.
+const FIXTURE: &str = "tests/fixtures/little_endian_v4.log";
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_reads_little_endian_fixture() {
+    let entries = Logger::<TestData>::new()
+        .with_read_config(FIXTURE.to_string())
+        .with_schema_tag("fixture")
+        .with_strict_reads(true)
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        entries
+            .iter()
+            .map(|e| (e.seq_id, e.ts_nanos, e.level))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, 1_000_000_001, Level::Info),
+            (2, 1_000_000_002, Level::Warn),
+            (3, 1_000_000_003, Level::Error),
+        ]
+    );
+    // Payloads are the raw bytes of `T`, so only a little-endian host reads them back as
+    // written.
+    #[cfg(target_endian = "little")]
+    assert_eq!(
+        entries.iter().map(|e| e.data).collect::<Vec<_>>(),
+        vec![
+            TestData {
+                id: 7,
+                val: 0x0A0B_0C0D
+            },
+            TestData {
+                id: 8,
+                val: 0x0102_0304
+            },
+            TestData {
+                id: 9,
+                val: 0xDEAD_BEEF
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_written_framing_is_little_endian() {
+    let path = "byte_order_written_test.log";
+    cleanup(path);
+    let seq_id = {
+        let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+        logger.start().unwrap();
+        logger.log(TestData { id: 1, val: 2 }).unwrap()
+    };
+    let bytes = fs::read(path).unwrap();
+
+    assert_eq!(&bytes[..8], b"PICOFILE");
+    assert_eq!(bytes[8..12], 4u32.to_le_bytes());
+    assert_eq!(bytes[26], 1);
+    let block = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    let page = &bytes[block..];
+    assert_eq!(&page[..8], b"PICOMETA");
+    assert_eq!(page[8..16], seq_id.to_le_bytes());
+    // The entry header follows the 40-byte page meta.
+    assert_eq!(page[40..48], seq_id.to_le_bytes());
+    assert_eq!(page[56..60], 16u32.to_le_bytes());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_unknown_byte_order_is_rejected() {
+    let path = "byte_order_unknown_test.log";
+    let mut bytes = fs::read(FIXTURE).unwrap();
+    bytes[26] = 2;
+    fs::write(path, &bytes).unwrap();
+
+    let err = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    assert!(
+        matches!(err, PicoError::ByteOrderMismatch { found: 2 }),
+        "{:?}",
+        err
+    );
+
+    cleanup(path);
+}
@@ -0,0 +1,95 @@
+use picologger::{FlushEvent, Logger, LoggerConfig};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[test]
+fn test_flush_callback_reports_every_page() {
+    let path = "callback_test.log";
+    let _ = fs::remove_file(path);
+
+    let events = Arc::new(Mutex::new(Vec::<FlushEvent>::new()));
+    let sink = events.clone();
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TestData>()
+        .unwrap()
+        .with_flush_callback(move |event| sink.lock().unwrap().push(event));
+    logger.start().unwrap();
+
+    let mut seq_ids = Vec::new();
+    for i in 0..2000 {
+        seq_ids.push(logger.log(TestData { id: i, val: 0 }).unwrap());
+    }
+    let stats_before_drop = logger.stats();
+    drop(logger);
+
+    let events = events.lock().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.len() as u64 >= stats_before_drop.pages_flushed);
+    let mut covered = 0;
+    for event in events.iter() {
+        assert!(event.first_seq_id <= event.last_seq_id);
+        assert!(event.bytes > 0);
+        assert!(event.latency > Duration::ZERO);
+        covered += seq_ids
+            .iter()
+            .filter(|&&id| (event.first_seq_id..=event.last_seq_id).contains(&id))
+            .count();
+    }
+    assert_eq!(covered, seq_ids.len());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_subscriber_sees_watermark_advance() {
+    let path = "callback_subscribe_test.log";
+    let _ = fs::remove_file(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TestData>()
+        .unwrap();
+    assert!(logger.subscribe().recv().is_err());
+    logger.start().unwrap();
+    let watermarks = logger.subscribe();
+    // Nobody reads this one; it must not hold the worker up.
+    let ignored = logger.subscribe();
+    drop(logger.subscribe());
+
+    let mut last = 0;
+    for i in 0..2000 {
+        last = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut previous = 0;
+    loop {
+        let watermark = watermarks
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .expect("watermark never reached the last entry");
+        assert!(watermark > previous);
+        assert!(watermark <= Logger::<TestData>::get_last_flushed_entry());
+        previous = watermark;
+        if watermark >= last {
+            break;
+        }
+    }
+    assert!(ignored.len() <= 1);
+
+    drop(logger);
+    // The worker is gone, so the channel disconnects once drained.
+    while watermarks.recv().is_ok() {}
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,153 @@
+use picologger::testing::MockClock;
+use picologger::{Clock, Logger, LoggerConfig, MonotonicClock};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Deterministic clock advancing by a fixed step on every read.
+struct StepClock {
+    next: AtomicU64,
+    step: u64,
+}
+
+impl Clock for StepClock {
+    fn now_nanos(&self) -> u64 {
+        self.next.fetch_add(self.step, Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_injected_clock_timestamps() {
+    let path = "clock_injected_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_clock(Arc::new(StepClock {
+                next: AtomicU64::new(1_000),
+                step: 10,
+            }));
+        logger.start().unwrap();
+        for i in 0..300 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), 300);
+    for (i, entry) in entries.iter().enumerate() {
+        assert_eq!(entry.data.id, i as u64);
+        assert_eq!(entry.ts_nanos, 1_000 + 10 * i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_monotonic_clock_tracks_wall_clock() {
+    let clock = MonotonicClock::new();
+    let wall = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let first = clock.now_nanos();
+    let second = clock.now_nanos();
+
+    assert!(second >= first);
+    assert!(first.abs_diff(wall) < 1_000_000_000);
+}
+
+#[test]
+fn test_mock_clock_drives_the_flush_timer() {
+    let path = "clock_mock_flush_test.log";
+    cleanup(path);
+    let clock = Arc::new(MockClock::new(5_000));
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_millis(100))
+        .poll_interval(Duration::from_millis(1))
+        .build::<TestData>()
+        .unwrap()
+        .with_clock(clock.clone());
+    logger.start().unwrap();
+    logger.log(TestData { id: 1, val: 1 }).unwrap();
+
+    // Real time passing well beyond the interval flushes nothing.
+    thread::sleep(Duration::from_millis(250));
+    assert_eq!(logger.stats().flushes.timer, 0);
+    clock.advance(Duration::from_millis(99));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(logger.stats().flushes.timer, 0);
+
+    clock.advance(Duration::from_millis(1));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed == 0 {
+        assert!(Instant::now() < deadline, "timer flush never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(logger.stats().flushes.timer, 1);
+    drop(logger);
+
+    let entries: Vec<_> = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].ts_nanos, 5_000);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_custom_clock_keeps_real_time_flushing() {
+    let path = "clock_real_flush_test.log";
+    cleanup(path);
+    // Only timestamps come from the clock, so the timer still fires on real time.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_millis(10))
+        .build::<TestData>()
+        .unwrap()
+        .with_clock(Arc::new(StepClock {
+            next: AtomicU64::new(0),
+            step: 0,
+        }));
+    logger.start().unwrap();
+    logger.log(TestData { id: 1, val: 1 }).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().flushes.timer == 0 {
+        assert!(Instant::now() < deadline, "timer flush never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+    drop(logger);
+
+    cleanup(path);
+}
@@ -0,0 +1,233 @@
+use picologger::inspect::verify;
+use picologger::test_support::TempLogger;
+use picologger::{Csv, FormatVersion, Level, LogEntry, Logger, PicoError, RowWriter, ToRow};
+use std::fs;
+use std::io::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+impl ToRow for TestData {
+    fn columns() -> &'static [&'static str] {
+        &["id", "val"]
+    }
+
+    fn write_row(&self, row: &mut RowWriter<'_>) -> Result<(), Error> {
+        row.field(self.id)?;
+        row.field(self.val)
+    }
+}
+
+/// Written byte by byte in the layout of releases before the file header: two 4096-byte
+/// pages holding 102 and 3 entries, with seq_ids 1 to 105, timestamps
+/// `LEGACY_TS + seq_id` and payloads from [`data`].
+const LEGACY: &str = "tests/fixtures/legacy.log";
+/// [`LEGACY`] cut off in the middle of the third entry of its second page.
+const LEGACY_TORN: &str = "tests/fixtures/legacy_torn.log";
+/// What exporting [`LEGACY`] with [`Csv`] must produce, worked out from the entries it was
+/// written with rather than by a reader.
+const LEGACY_CSV: &str = "tests/fixtures/legacy.csv";
+/// Format version 1 with 512-byte blocks and no page size recorded: two pages holding 12
+/// and 8 entries, with seq_ids 1 to 20, timestamps `V1_TS + seq_id` and payloads from
+/// [`data`].
+const V1: &str = "tests/fixtures/v1.log";
+const V4: &str = "tests/fixtures/little_endian_v4.log";
+
+const LEGACY_TS: u64 = 1_600_000_000_000_000_000;
+const V1_TS: u64 = 1_650_000_000_000_000_000;
+
+fn data(seq_id: u64) -> TestData {
+    TestData {
+        id: seq_id * 100,
+        val: seq_id as u32 ^ 0xA5A5_0000,
+    }
+}
+
+/// `(seq_id, ts_nanos, level, data)` of each entry.
+type Fields = (u64, u64, Level, TestData);
+
+fn expected(seq_ids: impl Iterator<Item = u64>, ts: u64) -> Vec<Fields> {
+    seq_ids
+        .map(|seq_id| (seq_id, ts + seq_id, Level::Info, data(seq_id)))
+        .collect()
+}
+
+fn fields(entry: LogEntry<TestData>) -> Fields {
+    (entry.seq_id, entry.ts_nanos, entry.level, entry.data)
+}
+
+fn all_fields(entries: Vec<LogEntry<TestData>>) -> Vec<Fields> {
+    entries.into_iter().map(fields).collect()
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::new().with_read_config(path)
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_detects_format() {
+    assert_eq!(
+        Logger::<TestData>::detect_format(LEGACY).unwrap(),
+        FormatVersion::Legacy
+    );
+    assert_eq!(
+        Logger::<TestData>::detect_format(LEGACY_TORN).unwrap(),
+        FormatVersion::Legacy
+    );
+    assert_eq!(
+        Logger::<TestData>::detect_format(V1).unwrap(),
+        FormatVersion::V1
+    );
+    assert_eq!(
+        Logger::<TestData>::detect_format(V4).unwrap(),
+        FormatVersion::V4
+    );
+
+    let mut logger = TempLogger::<TestData>::new();
+    logger.start().unwrap();
+    logger.log(TestData::default()).unwrap();
+    logger.shutdown().unwrap();
+    assert_eq!(
+        Logger::<TestData>::detect_format(logger.path()).unwrap(),
+        FormatVersion::CURRENT
+    );
+    assert_eq!(
+        FormatVersion::CURRENT.number(),
+        Some(picologger::FORMAT_VERSION)
+    );
+}
+
+#[test]
+fn test_detects_empty_and_newer_files() {
+    let empty = "compat_empty.log";
+    fs::write(empty, b"").unwrap();
+    assert_eq!(
+        Logger::<TestData>::detect_format(empty).unwrap(),
+        FormatVersion::Legacy
+    );
+    cleanup(empty);
+
+    let newer = "compat_newer.log";
+    let mut bytes = fs::read(V1).unwrap();
+    bytes[8..12].copy_from_slice(&99u32.to_le_bytes());
+    fs::write(newer, bytes).unwrap();
+    let err = Logger::<TestData>::detect_format(newer).unwrap_err();
+    assert!(matches!(
+        PicoError::from(err),
+        PicoError::FormatMismatch { found: 99, .. }
+    ));
+    cleanup(newer);
+}
+
+#[test]
+fn test_reads_legacy_fixture() {
+    let all = expected(1..106, LEGACY_TS);
+    let reader = reader(LEGACY);
+    assert_eq!(
+        reader.read().unwrap(),
+        all.iter().map(|e| e.3).collect::<Vec<_>>()
+    );
+    let entries: Vec<_> = reader
+        .read_iter()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(entries, all);
+    let strict: Vec<_> = reader
+        .with_strict_reads(true)
+        .read_iter()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(strict, all);
+}
+
+#[test]
+fn test_reads_legacy_fixture_through_every_reader() {
+    let all = expected(1..106, LEGACY_TS);
+    let reader = reader(LEGACY);
+    assert_eq!(
+        all_fields(reader.read_range(100..104).unwrap()),
+        &all[99..103]
+    );
+    let mapped: Vec<_> = reader
+        .read_mmap()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(mapped, all);
+    let reversed: Vec<_> = reader
+        .read_iter_rev()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(reversed, all.iter().rev().cloned().collect::<Vec<_>>());
+    assert_eq!(
+        reader.last_entry().unwrap().map(fields).as_ref(),
+        all.last()
+    );
+}
+
+#[test]
+fn test_exports_legacy_fixture_unchanged() {
+    let mut out = Vec::new();
+    let count = reader(LEGACY).export(&mut out, Csv::new()).unwrap();
+    assert_eq!(count, 105);
+    assert_eq!(out, fs::read(LEGACY_CSV).unwrap());
+}
+
+#[test]
+fn test_verifies_legacy_fixture() {
+    let report = verify(LEGACY).unwrap();
+    assert_eq!(report.format, FormatVersion::Legacy);
+    assert_eq!(report.schema, None);
+    assert_eq!(report.page_size, None);
+    assert_eq!(report.entries, 105);
+    assert_eq!(report.first_seq_id, Some(1));
+    assert_eq!(report.last_seq_id, Some(105));
+    assert!(report.is_consistent(), "{report:?}");
+}
+
+#[test]
+fn test_reads_torn_legacy_fixture() {
+    let entries: Vec<_> = reader(LEGACY_TORN)
+        .read_iter()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(entries, expected(1..105, LEGACY_TS));
+
+    let report = verify(LEGACY_TORN).unwrap();
+    assert_eq!(report.entries, 104);
+    assert_eq!(report.corruption, None);
+}
+
+#[test]
+fn test_reads_v1_fixture() {
+    let all = expected(1..21, V1_TS);
+    let reader = reader(V1).with_strict_reads(true);
+    let entries: Vec<_> = reader
+        .read_iter()
+        .unwrap()
+        .map(|e| fields(e.unwrap()))
+        .collect();
+    assert_eq!(entries, all);
+    assert_eq!(all_fields(reader.read_range(12..14).unwrap()), &all[11..13]);
+
+    let report = verify(V1).unwrap();
+    assert_eq!(report.format, FormatVersion::V1);
+    assert_eq!(report.schema.as_ref().map(|schema| schema.size), Some(16));
+    assert_eq!(report.page_size, Some(512));
+    assert_eq!(report.entries, 20);
+    assert!(report.is_consistent(), "{report:?}");
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "lz4")]
+
+use picologger::{Compression, Logger, LoggerConfig};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Quote {
+    symbol_id: u64,
+    price: u64,
+    qty: u64,
+    venue: [u8; 32],
+}
+
+fn remove(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_all<T: Send + Sync + Default + Copy + 'static>(
+    path: &str,
+    compression: Compression,
+    items: &[T],
+) {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<T>()
+        .unwrap()
+        .with_compression(compression);
+    logger.start().unwrap();
+    for item in items {
+        logger.log(*item);
+    }
+}
+
+fn used_blocks(path: &str) -> usize {
+    fs::read(path)
+        .unwrap()
+        .chunks(4096)
+        .filter(|block| block.iter().any(|b| *b != 0))
+        .count()
+}
+
+#[test]
+fn test_lz4_round_trip_uses_fewer_pages() {
+    let raw_path = "lz4_raw_test.log";
+    let lz4_path = "lz4_compressed_test.log";
+    remove(raw_path);
+    remove(lz4_path);
+
+    let quotes: Vec<Quote> = (0..2000)
+        .map(|i| Quote {
+            symbol_id: i % 4,
+            price: 10_000,
+            qty: 100,
+            venue: [b'X'; 32],
+        })
+        .collect();
+
+    write_all(raw_path, Compression::None, &quotes);
+    write_all(lz4_path, Compression::Lz4, &quotes);
+
+    let result = Logger::<Quote>::new()
+        .with_read_config(lz4_path.to_string())
+        .read()
+        .unwrap();
+    assert_eq!(result, quotes);
+    assert!(
+        used_blocks(lz4_path) < used_blocks(raw_path),
+        "compressed log should occupy fewer blocks"
+    );
+
+    remove(raw_path);
+    remove(lz4_path);
+}
+
+#[test]
+fn test_lz4_incompressible_pages_stored_raw() {
+    let path = "lz4_incompressible_test.log";
+    remove(path);
+
+    let noise: Vec<[u64; 8]> = (0..500)
+        .map(|_| std::array::from_fn(|_| fastrand::u64(..)))
+        .collect();
+
+    write_all(path, Compression::Lz4, &noise);
+
+    let result = Logger::<[u64; 8]>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap();
+    assert_eq!(result, noise);
+
+    remove(path);
+}
@@ -0,0 +1,257 @@
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn start_error(logger: Logger<TestData>) -> (ErrorKind, PicoError) {
+    let mut logger = logger;
+    let err = logger.start().unwrap_err();
+    (err.kind(), err)
+}
+
+// The deprecated wrapper leaves validation to `start()`, which these tests rely on.
+#[allow(deprecated)]
+fn config(path: &str, capacity: usize, flush: u64, poll: u64) -> Logger<TestData> {
+    Logger::<TestData>::new().with_write_config(path.to_string(), capacity, flush, poll, 4096)
+}
+
+#[test]
+fn test_invalid_write_config_is_rejected() {
+    let path = "config_invalid_test.log";
+
+    let (kind, err) = start_error(config(path, 0, 1_000_000, 100_000));
+    assert_eq!(kind, ErrorKind::InvalidInput);
+    assert!(matches!(err, PicoError::InvalidCapacity { capacity: 0 }));
+
+    let (_, err) = start_error(config(path, 1024, 0, 100_000));
+    assert!(matches!(
+        err,
+        PicoError::InvalidInterval {
+            name: "flush_interval",
+            nanos: 0
+        }
+    ));
+
+    let (_, err) = start_error(config(path, 1024, 1_000_000, 0));
+    assert!(matches!(
+        err,
+        PicoError::InvalidInterval {
+            name: "poll_interval",
+            ..
+        }
+    ));
+
+    let (_, err) = start_error(config("", 1024, 1_000_000, 100_000));
+    assert!(matches!(err, PicoError::EmptyPath));
+
+    assert!(!std::path::Path::new(path).exists());
+}
+
+#[test]
+fn test_missing_directory_is_reported() {
+    let (kind, err) = start_error(config(
+        "config_missing_dir/test.log",
+        1024,
+        1_000_000,
+        100_000,
+    ));
+    assert_eq!(kind, ErrorKind::NotFound);
+    match err {
+        PicoError::MissingDirectory { path } => {
+            assert_eq!(path, std::path::Path::new("config_missing_dir"))
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_tuning_is_rejected() {
+    let path = "config_tuning_test.log";
+
+    let (_, err) = start_error(config(path, 1024, 1_000_000, 100_000).with_pages(1));
+    assert!(matches!(err, PicoError::InvalidPageCount { count: 1, .. }));
+
+    let (_, err) = start_error(config(path, 1024, 1_000_000, 100_000).with_ring_depth(48));
+    assert!(matches!(err, PicoError::InvalidRingDepth { depth: 48, .. }));
+
+    let (_, err) = start_error(config(path, 1024, 1_000_000, 100_000).with_flush_watermark(1.5));
+    assert!(matches!(err, PicoError::InvalidWatermark { .. }));
+    assert!(err.to_string().contains("1.5"));
+}
+
+#[test]
+fn test_logger_config_round_trip() {
+    let path = std::path::PathBuf::from("config_builder_test.log");
+    if path.exists() {
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    let mut logger = LoggerConfig::new()
+        .path(&path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .pages(8)
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for i in 0..300 {
+        logger
+            .try_log(TestData {
+                id: i,
+                val: i as u32,
+            })
+            .unwrap();
+    }
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path.to_str().unwrap().to_string());
+    let result = reader.read().unwrap();
+    assert_eq!(result.len(), 300);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_logger_config_is_validated_on_build() {
+    assert!(matches!(
+        LoggerConfig::new().build::<TestData>(),
+        Err(PicoError::EmptyPath)
+    ));
+    assert!(matches!(
+        LoggerConfig::new()
+            .path("config_build_invalid_test.log")
+            .flush_interval(Duration::ZERO)
+            .build::<TestData>(),
+        Err(PicoError::InvalidInterval {
+            name: "flush_interval",
+            ..
+        })
+    ));
+    assert!(matches!(
+        LoggerConfig::new()
+            .path("config_build_invalid_test.log")
+            .capacity(0)
+            .build::<TestData>(),
+        Err(PicoError::InvalidCapacity { .. })
+    ));
+}
+
+fn write_and_read(path: &std::path::Path, count: u64) -> Vec<TestData> {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .create_dirs(true)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for id in 0..count {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+    Logger::<TestData>::new()
+        .with_read_config(path)
+        .read()
+        .unwrap()
+}
+
+#[test]
+fn test_missing_directories_are_created() {
+    let root = std::path::Path::new("config_create_dirs");
+    let _ = std::fs::remove_dir_all(root);
+    let path = root.join("a/b/test.log");
+
+    assert!(matches!(
+        LoggerConfig::new().path(&path).build::<TestData>(),
+        Err(PicoError::MissingDirectory { .. })
+    ));
+    assert_eq!(write_and_read(&path, 100).len(), 100);
+    assert!(path.is_file());
+    // Directories that already exist are fine too.
+    assert_eq!(write_and_read(&path, 10).len(), 10);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_str_and_path_arguments_are_accepted() {
+    let path = "config_as_ref_path_test.log";
+    let _ = std::fs::remove_file(path);
+
+    let mut logger =
+        Logger::<TestData>::new().with_write_config(path, 1024, 1_000_000, 100_000, 4096);
+    logger.start().unwrap();
+    for id in 0..10 {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let by_str = Logger::<TestData>::new().with_read_config(path);
+    let by_path = Logger::<TestData>::new().with_read_config(std::path::Path::new(path));
+    let by_path_buf = Logger::<TestData>::new().with_read_config(std::path::PathBuf::from(path));
+    for reader in [by_str, by_path, by_path_buf] {
+        assert_eq!(reader.read().unwrap().len(), 10);
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_non_utf8_paths_are_kept_as_they_are() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let root = std::path::Path::new(OsStr::from_bytes(b"config_non_utf8_\xff\xfe"));
+    let _ = std::fs::remove_dir_all(root);
+    let path = root.join(OsStr::from_bytes(b"log_\x80.wal"));
+    assert!(path.to_str().is_none());
+
+    let entries = write_and_read(&path, 100);
+    assert_eq!(entries.len(), 100);
+    assert_eq!(entries[99].id, 99);
+    assert!(path.is_file());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_block_size_override() {
+    let path = "config_block_size_test.log";
+    let _ = std::fs::remove_file(path);
+
+    for size in [1000, 256, 0] {
+        let (kind, err) = start_error(config(path, 1024, 1_000_000, 100_000).with_block_size(size));
+        assert_eq!(kind, ErrorKind::InvalidInput);
+        assert!(matches!(err, PicoError::InvalidBlockSize { size: s } if s == size));
+    }
+    assert!(!std::path::Path::new(path).exists());
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_block_size(8192);
+    logger.start().unwrap();
+    for id in 0..1000 {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let report = picologger::inspect::verify(path).unwrap();
+    assert_eq!(report.page_size, Some(8192));
+    assert_eq!(report.entries, 1000);
+    assert!(std::fs::metadata(path).unwrap().len().is_multiple_of(8192));
+
+    std::fs::remove_file(path).unwrap();
+}
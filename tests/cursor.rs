@@ -0,0 +1,224 @@
+use picologger::{Cursor, Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    for file in [path.to_string(), format!("{}.idx", path)] {
+        if std::path::Path::new(&file).exists() {
+            fs::remove_file(file).unwrap();
+        }
+    }
+}
+
+fn write_entries(path: &str, count: u64) {
+    append_entries(path, 0..count, false);
+}
+
+fn append_entries(path: &str, ids: std::ops::Range<u64>, append: bool) {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build::<TestData>()
+        .unwrap()
+        .with_index(true)
+        .with_append(append);
+    logger.start().unwrap();
+    for id in ids {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::<TestData>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_cursor_reads_in_batches() {
+    let path = "cursor_batches_test.log";
+    cleanup(path);
+    write_entries(path, 1000);
+
+    let mut cursor = reader(path).cursor_from(0).unwrap();
+    assert!(cursor.clamped());
+    let mut ids = Vec::new();
+    loop {
+        let batch = cursor.next_batch(300).unwrap();
+        if batch.is_empty() {
+            break;
+        }
+        assert!(batch.len() <= 300);
+        ids.extend(batch.iter().map(|entry| entry.data.id));
+        assert_eq!(cursor.position(), batch.last().unwrap().seq_id + 1);
+    }
+    assert_eq!(ids, (0..1000).collect::<Vec<_>>());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_saved_cursor_resumes() {
+    let path = "cursor_resume_test.log";
+    let saved = "cursor_resume_test.cursor";
+    cleanup(path);
+    write_entries(path, 2000);
+
+    let logger = reader(path);
+    let mut cursor = logger.cursor_from(0).unwrap();
+    let first = cursor.next_batch(1234).unwrap();
+    assert_eq!(first.last().unwrap().data.id, 1233);
+    cursor.save(saved).unwrap();
+    drop(cursor);
+
+    let mut cursor = Cursor::load(saved, &reader(path)).unwrap();
+    assert!(!cursor.clamped());
+    let rest = cursor.next_batch(usize::MAX).unwrap();
+    assert_eq!(rest.len(), 766);
+    assert_eq!(rest[0].data.id, 1234);
+    assert_eq!(rest[0].seq_id, first.last().unwrap().seq_id + 1);
+    assert!(cursor.next_batch(10).unwrap().is_empty());
+
+    // A position past the end of the file waits for entries there.
+    cursor.save(saved).unwrap();
+    let mut cursor = Cursor::load(saved, &reader(path)).unwrap();
+    assert!(cursor.next_batch(10).unwrap().is_empty());
+
+    fs::remove_file(saved).unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_saved_cursor_resumes_after_the_logger_restarts() {
+    let path = "cursor_restart_test.log";
+    let saved = "cursor_restart_test.cursor";
+    cleanup(path);
+    write_entries(path, 1000);
+
+    let mut cursor = reader(path).cursor_from(0).unwrap();
+    assert_eq!(cursor.next_batch(600).unwrap().len(), 600);
+    cursor.save(saved).unwrap();
+    drop(cursor);
+
+    // A new logger appends to the file; the page the cursor was saved in is still there.
+    append_entries(path, 1000..2000, true);
+    let mut cursor = Cursor::load(saved, &reader(path)).unwrap();
+    let rest = cursor.next_batch(usize::MAX).unwrap();
+    let ids: Vec<u64> = rest.iter().map(|entry| entry.data.id).collect();
+    assert_eq!(ids, (600..2000).collect::<Vec<_>>());
+
+    fs::remove_file(saved).unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_saved_cursor_seeks_when_its_page_moved() {
+    let path = "cursor_moved_test.log";
+    let saved = "cursor_moved_test.cursor";
+    cleanup(path);
+    write_entries(path, 1000);
+
+    let mut cursor = reader(path).cursor_from(0).unwrap();
+    let first = cursor.next_batch(600).unwrap();
+    cursor.save(saved).unwrap();
+
+    // Point the saved cursor two pages further on, at a page with another page id.
+    let mut bytes = fs::read(saved).unwrap();
+    let offset = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) + 2 * 4096;
+    bytes[8..16].copy_from_slice(&offset.to_le_bytes());
+    fs::write(saved, bytes).unwrap();
+
+    let mut cursor = Cursor::load(saved, &reader(path)).unwrap();
+    let rest = cursor.next_batch(usize::MAX).unwrap();
+    assert_eq!(rest[0].seq_id, first.last().unwrap().seq_id + 1);
+    assert_eq!(rest.len(), 400);
+
+    fs::remove_file(saved).unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_cursor_from_middle() {
+    let path = "cursor_middle_test.log";
+    cleanup(path);
+    write_entries(path, 1500);
+
+    let logger = reader(path);
+    let target = logger
+        .read_iter()
+        .unwrap()
+        .nth(700)
+        .unwrap()
+        .unwrap()
+        .seq_id;
+    let mut cursor = logger.cursor_from(target).unwrap();
+    assert!(!cursor.clamped());
+    let batch = cursor.next_batch(usize::MAX).unwrap();
+    assert_eq!(batch.len(), 800);
+    assert_eq!(batch[0].seq_id, target);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_live_cursor_only_sees_durable_entries() {
+    let path = "cursor_live_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(5))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    let mut cursor = logger.cursor_from(0).unwrap();
+
+    let mut seen = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    for round in 0..10 {
+        for i in 0..100 {
+            logger
+                .log(TestData {
+                    id: round * 100 + i,
+                    val: 0,
+                })
+                .unwrap();
+        }
+        let batch = cursor.next_batch(usize::MAX).unwrap();
+        let durable = logger.stats().flushed_seq_id;
+        assert!(batch.iter().all(|entry| entry.seq_id <= durable));
+        seen.extend(batch.iter().map(|entry| entry.data.id));
+    }
+    while seen.len() < 1000 {
+        assert!(Instant::now() < deadline, "only saw {} entries", seen.len());
+        std::thread::sleep(Duration::from_millis(1));
+        seen.extend(
+            cursor
+                .next_batch(usize::MAX)
+                .unwrap()
+                .iter()
+                .map(|entry| entry.data.id),
+        );
+    }
+    assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_loading_garbage_fails() {
+    let saved = "cursor_garbage_test.cursor";
+    fs::write(saved, b"not a cursor").unwrap();
+    let err = Cursor::load(saved, &reader("cursor_garbage_test.log"))
+        .err()
+        .unwrap();
+    assert!(matches!(err, PicoError::InvalidCursor { .. }), "{err}");
+    fs::remove_file(saved).unwrap();
+}
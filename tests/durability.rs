@@ -0,0 +1,135 @@
+use picologger::test_support::TempLogger;
+use picologger::testing::FaultInjector;
+use picologger::{Durability, Logger, LoggerConfig};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn round_trip(path: &str, durability: Durability) {
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(4096)
+            .flush_interval(Duration::from_micros(100))
+            .poll_interval(Duration::from_micros(10))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_durability(durability);
+        logger.start().unwrap();
+
+        let mut last = 0;
+        for i in 0..2000 {
+            last = logger
+                .log(TestData {
+                    id: i as u64,
+                    val: i as u32,
+                })
+                .unwrap();
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Logger::<TestData>::get_last_flushed_entry() < last {
+            assert!(Instant::now() < deadline, "ack never reached {}", last);
+            std::thread::yield_now();
+        }
+
+        // Everything acknowledged so far must already be readable from the file.
+        let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+        let result = reader.read().unwrap();
+        assert_eq!(result.len(), 2000);
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 2000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_sync_modes_round_trip() {
+    // Run serially: the flushed watermark is shared by every logger in the process.
+    round_trip("durability_fdatasync_test.log", Durability::Fdatasync);
+    round_trip("durability_odsync_test.log", Durability::Odsync);
+}
+
+/// Holds back every page's sync completion until released, counting how often one was
+/// offered.
+#[derive(Default)]
+struct SyncGate {
+    released: AtomicBool,
+    offered: AtomicU32,
+}
+
+impl FaultInjector for SyncGate {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn hold_sync_completion(&self, _offset: u64, _attempt: u32) -> bool {
+        self.offered.fetch_add(1, Ordering::AcqRel);
+        !self.released.load(Ordering::Acquire)
+    }
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "{what} never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_fdatasync_acks_only_after_the_sync_completes() {
+    let gate = Arc::new(SyncGate::default());
+    // A single-entry ring still takes the linked write and sync.
+    let mut logger = TempLogger::<TestData>::with_config(LoggerConfig::new().capacity(64))
+        .configure(|logger| {
+            logger
+                .with_durability(Durability::Fdatasync)
+                .with_ring_depth(1)
+                .with_fault_injector(gate.clone())
+        });
+    logger.start().unwrap();
+    let mut last = 0;
+    for i in 0..10 {
+        last = logger.try_log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+
+    // The page write has landed and its sync completed, but the sync is held back.
+    wait_until("the page write", || logger.stats().bytes_written > 0);
+    let offered = gate.offered.load(Ordering::Acquire);
+    wait_until("the sync completion", || {
+        gate.offered.load(Ordering::Acquire) > offered + 2
+    });
+    let stats = logger.stats();
+    assert_eq!(stats.flushed_seq_id, 0);
+    assert_eq!(stats.entries_flushed, 0);
+
+    gate.released.store(true, Ordering::Release);
+    wait_until("the ack", || logger.stats().flushed_seq_id >= last);
+    assert_eq!(logger.stats().entries_flushed, 10);
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "encryption")]
+
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Record {
+    account: u64,
+    balance: u64,
+    marker: [u8; 16],
+}
+
+const KEY: [u8; 32] = [7; 32];
+const MARKER: [u8; 16] = *b"SECRET-ACCOUNT!!";
+
+fn remove(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_records(path: &str, count: u64) -> Vec<Record> {
+    let records: Vec<Record> = (0..count)
+        .map(|i| Record {
+            account: i,
+            balance: i * 100,
+            marker: MARKER,
+        })
+        .collect();
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<Record>()
+        .unwrap()
+        .with_encryption(KEY);
+    logger.start().unwrap();
+    for record in &records {
+        logger.log(*record);
+    }
+    records
+}
+
+#[test]
+fn test_encrypted_round_trip() {
+    let path = "encryption_round_trip_test.log";
+    remove(path);
+
+    let records = write_records(path, 300);
+
+    let raw = fs::read(path).unwrap();
+    assert!(
+        !raw.windows(MARKER.len()).any(|w| w == MARKER),
+        "plaintext must not reach the disk"
+    );
+
+    let result = Logger::<Record>::new()
+        .with_read_config(path.to_string())
+        .with_encryption(KEY)
+        .read()
+        .unwrap();
+    assert_eq!(result, records);
+
+    remove(path);
+}
+
+#[test]
+fn test_wrong_key_fails_authentication() {
+    let path = "encryption_wrong_key_test.log";
+    remove(path);
+
+    write_records(path, 10);
+
+    let err = Logger::<Record>::new()
+        .with_read_config(path.to_string())
+        .with_encryption([8; 32])
+        .read()
+        .unwrap_err();
+    assert!(matches!(err, PicoError::AuthFailed), "got {:?}", err);
+
+    let err = Logger::<Record>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    remove(path);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_encrypted_and_compressed_round_trip() {
+    use picologger::Compression;
+
+    let path = "encryption_lz4_test.log";
+    remove(path);
+
+    let records: Vec<Record> = (0..2000)
+        .map(|i| Record {
+            account: i % 3,
+            balance: 0,
+            marker: MARKER,
+        })
+        .collect();
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(4096)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<Record>()
+            .unwrap()
+            .with_compression(Compression::Lz4)
+            .with_encryption(KEY);
+        logger.start().unwrap();
+        for record in &records {
+            logger.log(*record);
+        }
+    }
+
+    let result = Logger::<Record>::new()
+        .with_read_config(path.to_string())
+        .with_compression(Compression::Lz4)
+        .with_encryption(KEY)
+        .read()
+        .unwrap();
+    assert_eq!(result, records);
+
+    remove(path);
+}
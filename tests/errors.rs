@@ -0,0 +1,140 @@
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Caps the file size for the whole test process, so writes past `limit` fail with EFBIG
+/// instead of raising SIGXFSZ.
+fn limit_file_size(limit: u64) {
+    unsafe {
+        libc::signal(libc::SIGXFSZ, libc::SIG_IGN);
+        let rlim = libc::rlimit {
+            rlim_cur: limit,
+            rlim_max: libc::RLIM_INFINITY,
+        };
+        assert_eq!(libc::setrlimit(libc::RLIMIT_FSIZE, &rlim), 0);
+    }
+}
+
+#[test]
+fn test_write_failures_reach_the_error_channel() {
+    let path = "errors_write_failure_test.log";
+    cleanup(path);
+    let limit = 64 * 1024;
+    limit_file_size(limit);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(32_768)
+        .flush_interval(Duration::from_micros(100))
+        .poll_interval(Duration::from_micros(10))
+        .build::<TestData>()
+        .unwrap();
+    assert!(logger.take_error_receiver().is_none());
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    assert!(logger.take_error_receiver().is_none());
+
+    let mut seq_ids = Vec::new();
+    for i in 0..20_000 {
+        seq_ids.push(
+            logger
+                .log(TestData {
+                    id: i as u64,
+                    val: i as u32,
+                })
+                .unwrap(),
+        );
+    }
+
+    let error = errors
+        .recv_timeout(Duration::from_secs(5))
+        .expect("no write failure reported");
+    match error {
+        PicoError::WriteFailed {
+            errno,
+            offset,
+            first_seq_id,
+            last_seq_id,
+        } => {
+            assert_eq!(errno, libc::EFBIG);
+            assert!(offset >= limit);
+            assert!(first_seq_id <= last_seq_id);
+            assert!(seq_ids.contains(&first_seq_id));
+            assert!(seq_ids.contains(&last_seq_id));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // Failed pages must not wedge the page pool: the worker keeps draining and shuts down.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().channel_depth > 0 {
+        assert!(Instant::now() < deadline, "worker stalled");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(logger.stats().failed_writes > 0);
+    assert_eq!(logger.stats().last_error_code, libc::EFBIG);
+    drop(logger);
+
+    limit_file_size(libc::RLIM_INFINITY);
+    cleanup(path);
+}
+
+#[test]
+fn test_errors_convert_to_and_from_io_errors() {
+    let io_err: std::io::Error = PicoError::InvalidCapacity { capacity: 0 }.into();
+    assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(matches!(
+        PicoError::from(io_err),
+        PicoError::InvalidCapacity { capacity: 0 }
+    ));
+
+    let err = PicoError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(err, PicoError::Io(_)));
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    let err = Logger::<TestData>::new()
+        .with_read_config("errors_no_such_file.log".to_string())
+        .read()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    assert!(matches!(
+        Logger::<TestData>::new().start(),
+        Err(PicoError::NotConfigured)
+    ));
+}
+
+#[test]
+fn test_newer_format_version_is_rejected() {
+    let path = "errors_format_version_test.log";
+    let mut header = vec![0u8; 4096];
+    header[..8].copy_from_slice(b"PICOFILE");
+    header[8..12].copy_from_slice(&99u32.to_le_bytes());
+    header[12..16].copy_from_slice(&4096u32.to_le_bytes());
+    fs::write(path, header).unwrap();
+
+    let err = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap_err();
+    assert!(
+        matches!(err, PicoError::FormatMismatch { found: 99, .. }),
+        "got {:?}",
+        err
+    );
+
+    cleanup(path);
+}
@@ -0,0 +1,147 @@
+use picologger::{Csv, Logger, LoggerConfig, RowWriter, ToRow};
+use std::fs;
+use std::io::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct Fill {
+    order_id: u64,
+    price: f64,
+    qty: u32,
+}
+
+impl ToRow for Fill {
+    fn columns() -> &'static [&'static str] {
+        &["order_id", "price", "qty"]
+    }
+
+    fn write_row(&self, row: &mut RowWriter<'_>) -> Result<(), Error> {
+        row.field(self.order_id)?;
+        row.field(self.price)?;
+        row.field(self.qty)
+    }
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_fills(path: &str, count: u64) -> Logger<Fill> {
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new().path(path).build::<Fill>().unwrap();
+        logger.start().unwrap();
+        for i in 0..count {
+            logger.log(Fill {
+                order_id: i,
+                price: 100.5 + i as f64,
+                qty: i as u32 * 10,
+            });
+        }
+    }
+    Logger::<Fill>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_export_csv() {
+    let path = "export_csv_test.log";
+    let logger = write_fills(path, 250);
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+
+    let mut out = Vec::new();
+    assert_eq!(logger.export(&mut out, Csv::new()).unwrap(), 250);
+    let text = String::from_utf8(out).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("seq_id,ts_nanos,order_id,price,qty"));
+    for (line, entry) in lines.by_ref().zip(&entries) {
+        assert_eq!(
+            line,
+            format!(
+                "{},{},{},{},{}",
+                entry.seq_id, entry.ts_nanos, entry.data.order_id, entry.data.price, entry.data.qty
+            )
+        );
+    }
+    assert_eq!(lines.next(), None);
+
+    cleanup(path);
+}
+
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+struct Note {
+    len: u8,
+    text: [u8; 31],
+}
+
+impl Note {
+    fn new(text: &str) -> Self {
+        let mut note = Note {
+            len: text.len() as u8,
+            ..Default::default()
+        };
+        note.text[..text.len()].copy_from_slice(text.as_bytes());
+        note
+    }
+}
+
+impl ToRow for Note {
+    fn columns() -> &'static [&'static str] {
+        &["note"]
+    }
+
+    fn write_row(&self, row: &mut RowWriter<'_>) -> Result<(), Error> {
+        row.field(std::str::from_utf8(&self.text[..self.len as usize]).unwrap())
+    }
+}
+
+#[test]
+fn test_export_csv_quotes_fields() {
+    let path = "export_quote_test.log";
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new().path(path).build::<Note>().unwrap();
+        logger.start().unwrap();
+        logger.log(Note::new("plain"));
+        logger.log(Note::new("a, \"quoted\"\nvalue"));
+    }
+
+    let logger = Logger::<Note>::new().with_read_config(path.to_string());
+    let mut out = Vec::new();
+    assert_eq!(logger.export(&mut out, Csv::new()).unwrap(), 2);
+    let text = String::from_utf8(out).unwrap();
+    let rows: Vec<_> = text.splitn(3, '\n').collect();
+    assert!(rows[1].ends_with(",plain"));
+    assert!(rows[2].ends_with(",\"a, \"\"quoted\"\"\nvalue\"\n"));
+
+    cleanup(path);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_export_json_lines() {
+    use picologger::JsonLines;
+
+    let path = "export_json_test.log";
+    let logger = write_fills(path, 100);
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+
+    let mut out = Vec::new();
+    assert_eq!(logger.export(&mut out, JsonLines).unwrap(), 100);
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().count(), 100);
+    for (line, entry) in text.lines().zip(&entries) {
+        assert_eq!(
+            line,
+            format!(
+                "{{\"seq_id\":{},\"ts_nanos\":{},\"data\":{{\"order_id\":{},\"price\":{:?},\"qty\":{}}}}}",
+                entry.seq_id, entry.ts_nanos, entry.data.order_id, entry.data.price, entry.data.qty
+            )
+        );
+    }
+
+    cleanup(path);
+}
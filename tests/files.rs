@@ -0,0 +1,159 @@
+use picologger::{Backend, Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn round_trip(path: &Path) {
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_index(true);
+        logger.start().unwrap();
+        for i in 0..200 {
+            logger.log(TestData { id: i, val: 3 }).unwrap();
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string_lossy().into_owned());
+    assert_eq!(logger.read().unwrap().len(), 200);
+}
+
+#[test]
+fn test_log_in_nested_directory() {
+    let dir = std::env::temp_dir().join(format!("picolog_files_{}", std::process::id()));
+    fs::create_dir_all(dir.join("nested")).unwrap();
+
+    round_trip(&dir.join("nested").join("files_nested.log"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_log_with_bare_file_name() {
+    let path = Path::new("files_bare_test.log");
+
+    round_trip(path);
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file("files_bare_test.log.idx").unwrap();
+}
+
+#[test]
+fn test_restart_on_existing_file() {
+    let path = Path::new("files_existing_test.log");
+    fs::write(path, []).unwrap();
+
+    round_trip(path);
+    round_trip(path);
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file("files_existing_test.log.idx").unwrap();
+}
+
+/// Writes about 1.3 MiB of pages into a file pre-allocated with 64 KiB, returning how far
+/// past the last page the file extends, and how far the stats say it does.
+fn write_past_preallocation(path: &str, backend: Backend, growth_chunk: u64) -> (u64, u64) {
+    let _ = fs::remove_file(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .preallocate(64 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_backend(backend)
+        .with_growth_chunk(growth_chunk);
+    logger.start().unwrap();
+    for round in 0..20 {
+        for i in 0..2000 {
+            logger.log(TestData { id: i, val: round }).unwrap();
+        }
+        logger.flush().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    logger.shutdown().unwrap();
+    let stats = logger.stats();
+    assert!(stats.bytes_written > 1024 * 1024);
+
+    let len = fs::metadata(path).unwrap().len();
+    fs::remove_file(path).unwrap();
+    // The header block plus every page written.
+    let data_end = 4096 + stats.bytes_written;
+    (len - data_end, stats.file_len - data_end)
+}
+
+#[test]
+fn test_preallocated_file_grows_in_chunks() {
+    for (path, backend) in [
+        ("files_grow_uring.log", Backend::Auto),
+        ("files_grow_sync.log", Backend::Sync),
+    ] {
+        let (slack, reported) = write_past_preallocation(path, backend, 256 * 1024);
+        assert_eq!(slack, reported, "{:?}", backend);
+        // Extended ahead of the writes, by at most one chunk more than was needed.
+        assert!(slack > 0 && slack <= 256 * 1024, "{:?}: {}", backend, slack);
+    }
+}
+
+#[test]
+fn test_growth_can_be_turned_off() {
+    let (slack, reported) = write_past_preallocation("files_no_grow.log", Backend::Auto, 0);
+    assert_eq!((slack, reported), (0, 0));
+}
+
+#[test]
+fn test_preallocation_must_be_whole_blocks() {
+    let path = "files_prealloc_odd.log";
+    let _ = fs::remove_file(path);
+    let err = LoggerConfig::new()
+        .path(path)
+        .preallocate(64 * 1024 + 100)
+        .build::<TestData>()
+        .unwrap()
+        .start()
+        .unwrap_err();
+    assert!(
+        matches!(err, PicoError::InvalidPreallocation { bytes, block_size: 4096 } if bytes == 64 * 1024 + 100),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_preallocation_never_shrinks_a_file() {
+    let path = "files_prealloc_shrink.log";
+    let _ = fs::remove_file(path);
+    for _ in 0..2 {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .preallocate(256 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_growth_chunk(0);
+        logger.start().unwrap();
+        logger.log(TestData { id: 1, val: 1 }).unwrap();
+        logger.shutdown().unwrap();
+        assert_eq!(fs::metadata(path).unwrap().len(), 256 * 1024);
+    }
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .preallocate(64 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    logger.shutdown().unwrap();
+    assert_eq!(fs::metadata(path).unwrap().len(), 256 * 1024);
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,170 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Logs `count` entries and waits until the worker has flushed at least `min_flushes` pages.
+/// The tests use a 10s flush interval, so the timer never fires while they run.
+fn log_entries(logger: &mut Logger<TestData>, count: usize, min_flushes: u64) {
+    for i in 0..count {
+        logger.log(TestData {
+            id: i as u64,
+            val: i as u32,
+        });
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let counts = logger.flush_counts();
+        if counts.watermark + counts.page_full >= min_flushes {
+            break;
+        }
+        assert!(Instant::now() < deadline, "worker flushed too few pages");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_watermark_flushes_before_page_full() {
+    let path = "flush_watermark_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(8192)
+            .flush_interval(Duration::from_secs(10))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_flush_watermark(0.5);
+        logger.start().unwrap();
+        log_entries(&mut logger, 2000, 10);
+
+        let counts = logger.flush_counts();
+        assert!(counts.watermark > 0);
+        assert_eq!(counts.page_full, 0);
+        assert_eq!(counts.timer, 0);
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 2000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_without_watermark_pages_fill() {
+    let path = "flush_no_watermark_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .flush_interval(Duration::from_secs(10))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    log_entries(&mut logger, 2000, 10);
+
+    let counts = logger.flush_counts();
+    assert!(counts.page_full > 0);
+    assert_eq!(counts.watermark, 0);
+    drop(logger);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_invalid_watermark() {
+    for fraction in [0.0, -0.5, 1.5, f32::NAN] {
+        let mut logger = LoggerConfig::new()
+            .path("flush_invalid_watermark_test.log")
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_flush_watermark(fraction);
+        let err = logger.start().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_explicit_flush_writes_partial_page() {
+    let path = "flush_explicit_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .flush_interval(Duration::from_secs(10))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for i in 0..5 {
+        logger.log(TestData { id: i, val: 0 });
+    }
+    logger.flush().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed < 5 {
+        assert!(Instant::now() < deadline, "explicit flush never completed");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let counts = logger.flush_counts();
+    assert_eq!(counts.explicit, 1);
+    assert_eq!(counts.timer + counts.page_full + counts.watermark, 0);
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_drain_batch_sizes_write_every_entry() {
+    for (path, drain_batch) in [("flush_drain_one.log", 1), ("flush_drain_many.log", 1024)] {
+        cleanup(path);
+        {
+            let mut logger = LoggerConfig::new()
+                .path(path)
+                .capacity(8192)
+                .flush_interval(Duration::from_secs(10))
+                .poll_interval(Duration::from_micros(100))
+                .build::<TestData>()
+                .unwrap()
+                .with_drain_batch(drain_batch);
+            logger.start().unwrap();
+            log_entries(&mut logger, 3000, 5);
+        }
+
+        let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+        let result = logger.read().unwrap();
+        assert_eq!(result.len(), 3000);
+        for (i, item) in result.iter().enumerate() {
+            assert_eq!(item.id, i as u64);
+        }
+        cleanup(path);
+    }
+}
@@ -0,0 +1,99 @@
+use picologger::format::{
+    ENTRY_ALIGN, ENTRY_HEADER_LEN, ENTRY_LEVEL_OFFSET, EntryFormat, encode_entry, parse_page,
+};
+use picologger::test_support::TempLogger;
+use picologger::{Level, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[test]
+fn test_encoded_entries_parse_back() {
+    let mut page = vec![0u8; 512];
+    let mut at = 0;
+    for (seq_id, payload) in [(1, &b"abc"[..]), (2, &[7u8; 40][..])] {
+        let len = encode_entry(&mut page[at..], seq_id, 50, Level::Warn as u8, payload).unwrap();
+        assert_eq!(
+            len,
+            (ENTRY_HEADER_LEN + payload.len()).next_multiple_of(ENTRY_ALIGN)
+        );
+        at += len;
+    }
+    // An empty payload would leave a zero length, which reads as the page's padding.
+    assert!(matches!(
+        encode_entry(&mut page[at..], 3, 50, 0, b""),
+        Err(PicoError::EmptyEntry)
+    ));
+
+    let entries: Vec<_> = parse_page(&page).map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!({ entries[0].header.seq_id }, 1);
+    assert_eq!(entries[0].header.level(), Level::Warn);
+    assert_eq!(entries[0].payload, b"abc");
+    assert_eq!(entries[1].payload, [7u8; 40]);
+}
+
+#[test]
+fn test_entry_that_does_not_fit_is_rejected() {
+    let mut page = vec![0u8; ENTRY_HEADER_LEN + 3];
+    assert!(matches!(
+        encode_entry(&mut page, 1, 0, 0, b"abcd"),
+        Err(PicoError::PageFull {})
+    ));
+    assert_eq!(encode_entry(&mut page, 1, 0, 0, b"abc").unwrap(), 32);
+}
+
+#[test]
+fn test_corrupt_entry_ends_the_page() {
+    let mut page = vec![0u8; 256];
+    let mut at = encode_entry(&mut page, 1, 0, 0, b"first").unwrap();
+    at += encode_entry(&mut page[at..], 2, 0, 0, b"second").unwrap();
+    encode_entry(&mut page[at..], 3, 0, 0, b"third").unwrap();
+    page[at + ENTRY_LEVEL_OFFSET] = 0xff;
+
+    let mut entries = parse_page(&page);
+    assert_eq!(entries.next().unwrap().unwrap().payload, b"first");
+    assert_eq!(entries.next().unwrap().unwrap().payload, b"second");
+    assert_eq!(entries.offset(), at);
+    assert!(matches!(
+        entries.next(),
+        Some(Err(PicoError::CorruptEntry { offset })) if offset == at
+    ));
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn test_logger_pages_parse() {
+    let mut logger = TempLogger::<TestData>::new()
+        .configure(|logger| logger.with_block_size(4096).with_page_size(8192));
+    logger.start().unwrap();
+    let seq_ids = logger
+        .log_batch(&[1, 2, 3].map(|id| TestData { id, val: 9 }))
+        .unwrap();
+    logger.shutdown().unwrap();
+
+    let file = fs::read(logger.path()).unwrap();
+    let entries: Vec<_> = parse_page(&file[4096..4096 + 8192])
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 3);
+    for (entry, seq_id) in entries.iter().zip(seq_ids) {
+        assert_eq!({ entry.header.seq_id }, seq_id);
+        assert_eq!(entry.payload.len(), size_of::<TestData>());
+    }
+}
+
+#[test]
+fn test_formats_by_version() {
+    assert_eq!(
+        EntryFormat::of_version(picologger::FORMAT_VERSION),
+        EntryFormat::CURRENT
+    );
+    let legacy = EntryFormat::of_version(0);
+    assert!(!legacy.wide_len && !legacy.levels && !legacy.page_meta);
+}
@@ -0,0 +1,169 @@
+use picologger::{FORMAT_VERSION, Logger, LoggerConfig, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, version: u32, count: u64) -> Vec<u8> {
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(4096)
+            .build::<TestData>()
+            .unwrap()
+            .with_format_version(version);
+        logger.start().unwrap();
+        for i in 0..count {
+            logger.log(TestData { id: i, val: 5 }).unwrap();
+        }
+    }
+    fs::read(path).unwrap()
+}
+
+fn read_all(path: &str) -> Vec<TestData> {
+    Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap()
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[test]
+fn test_pages_start_with_page_meta() {
+    let path = "format_v3_test.log";
+    let bytes = write_entries(path, FORMAT_VERSION, 500);
+    assert_eq!(u32_at(&bytes, 8), 4);
+
+    let entries: Vec<_> = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 500);
+    assert!(
+        entries
+            .iter()
+            .enumerate()
+            .all(|(i, e)| e.data.id == i as u64)
+    );
+
+    // 101 40-byte entries fit after the page meta of each 4 KiB page.
+    let page = &bytes[4096..8192];
+    assert_eq!(&page[..8], b"PICOMETA");
+    assert_eq!(u64_at(page, 8), entries[0].seq_id);
+    assert_eq!(u64_at(page, 16), entries[100].seq_id);
+    assert_eq!(u32_at(page, 24), 0);
+    assert_eq!(u32_at(page, 28), 101);
+    assert_eq!(u32_at(page, 32), 101 * 40);
+    let next = &bytes[8192..12288];
+    assert_eq!(u64_at(next, 8), entries[101].seq_id);
+    assert_eq!(u32_at(next, 24), 1);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_version_2_files_round_trip() {
+    let path = "format_v2_test.log";
+    let bytes = write_entries(path, 2, 500);
+    assert_eq!(u32_at(&bytes, 8), 2);
+    // Pages start straight with an entry.
+    assert_ne!(&bytes[4096..4104], b"PICOMETA");
+
+    let entries = read_all(path);
+    assert_eq!(entries.len(), 500);
+    assert!(entries.iter().enumerate().all(|(i, e)| e.id == i as u64));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_unsupported_versions_are_rejected() {
+    for version in [0, 1, FORMAT_VERSION + 1] {
+        let result = LoggerConfig::new()
+            .path("format_invalid_test.log")
+            .build::<TestData>()
+            .unwrap()
+            .with_format_version(version)
+            .start();
+        assert!(
+            matches!(result, Err(PicoError::InvalidFormatVersion { version: v, .. }) if v == version),
+            "{:?}",
+            result
+        );
+    }
+    assert!(!std::path::Path::new("format_invalid_test.log").exists());
+}
+
+#[test]
+fn test_damaged_page_meta() {
+    let path = "format_damaged_meta_test.log";
+    let mut bytes = write_entries(path, FORMAT_VERSION, 300);
+    // The first page's entry count, covered by its checksum.
+    bytes[4096 + 28] ^= 0xFF;
+    fs::write(path, &bytes).unwrap();
+
+    let err = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .with_strict_reads(true)
+        .read()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Without strict reads, the entries after it are still checked on their own.
+    assert_eq!(read_all(path).len(), 300);
+
+    cleanup(path);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_compressed_pages_in_both_versions() {
+    use picologger::Compression;
+
+    for version in [2, FORMAT_VERSION] {
+        let path = format!("format_lz4_v{}_test.log", version);
+        cleanup(&path);
+        {
+            let mut logger = LoggerConfig::new()
+                .path(&path)
+                .capacity(4096)
+                .build::<TestData>()
+                .unwrap()
+                .with_compression(Compression::Lz4)
+                .with_format_version(version);
+            logger.start().unwrap();
+            for i in 0..3000 {
+                logger.log(TestData { id: i, val: 5 }).unwrap();
+            }
+        }
+        let entries = Logger::<TestData>::new()
+            .with_read_config(path.clone())
+            .with_compression(Compression::Lz4)
+            .with_strict_reads(true)
+            .read()
+            .unwrap();
+        assert_eq!(entries.len(), 3000, "version {}", version);
+        assert!(entries.iter().enumerate().all(|(i, e)| e.id == i as u64));
+        cleanup(&path);
+    }
+}
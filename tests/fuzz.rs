@@ -0,0 +1,115 @@
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, count: u64) -> Vec<u8> {
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+        logger.start().unwrap();
+        for i in 0..count {
+            logger.log(TestData {
+                id: i,
+                val: i as u32 * 3,
+            });
+        }
+    }
+    fs::read(path).unwrap()
+}
+
+/// Reads `bytes` back as a log file through every reader, returning the entries `read()`
+/// found. None of the readers may panic or find more than `written` entries.
+fn read_back(path: &str, bytes: &[u8], written: usize) -> Vec<TestData> {
+    fs::write(path, bytes).unwrap();
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries = logger.read().unwrap_or_default();
+    assert!(entries.len() <= written);
+    let mapped = logger.read_mmap().map_or(0, |reader| reader.count());
+    assert!(mapped <= written);
+    let strict = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .with_strict_reads(true)
+        .read()
+        .map_or(0, |entries| entries.len());
+    assert!(strict <= written);
+    entries
+}
+
+#[test]
+fn test_random_bytes_yield_no_entries() {
+    let path = "fuzz_random_test.log";
+    let mut rng = fastrand::Rng::with_seed(0x5eed);
+    for round in 0..200 {
+        let len = rng.usize(0..64 * 1024);
+        let mut bytes: Vec<u8> = (0..len).map(|_| rng.u8(..)).collect();
+        // Also try random contents behind a valid file header.
+        if round % 2 == 1 && len >= 4096 {
+            let header = write_entries(path, 0);
+            bytes[..4096].copy_from_slice(&header[..4096]);
+        }
+        assert!(read_back(path, &bytes, 0).is_empty(), "round {}", round);
+    }
+    cleanup(path);
+}
+
+#[test]
+fn test_truncated_and_damaged_files_never_yield_garbage() {
+    let path = "fuzz_damaged_test.log";
+    let written = 2000;
+    let valid = write_entries(path, written as u64);
+    let expected = |item: &TestData| item.val == item.id as u32 * 3 && item.id < written as u64;
+
+    let mut rng = fastrand::Rng::with_seed(0xdead);
+    for _ in 0..100 {
+        let truncated = &valid[..rng.usize(0..=valid.len())];
+        let entries = read_back(path, truncated, written);
+        assert!(entries.iter().all(expected));
+        for (i, item) in entries.iter().enumerate() {
+            assert_eq!(item.id, i as u64);
+        }
+    }
+
+    for _ in 0..100 {
+        let mut damaged = valid.clone();
+        for _ in 0..rng.usize(1..16) {
+            let at = rng.usize(..damaged.len());
+            damaged[at] = rng.u8(..);
+        }
+        read_back(path, &damaged, written);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_strict_reads_report_damaged_entries() {
+    let path = "fuzz_strict_test.log";
+    let mut bytes = write_entries(path, 300);
+    // Set a padding byte in the header of the second entry of the first page, past its
+    // 40-byte page meta.
+    bytes[4096 + 40 + 40 + 22] = 0xAA;
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let lenient = read_back(path, &bytes, 300);
+    assert!(!lenient.is_empty() && lenient.len() < 300);
+    assert_eq!(lenient[0].id, 0);
+    assert!(lenient[1..].iter().all(|item| item.id > 1));
+
+    let err = logger.with_strict_reads(true).read().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(err, PicoError::DamagedEntry { offset: 4176 }));
+
+    cleanup(path);
+}
@@ -0,0 +1,131 @@
+#![cfg(target_os = "linux")]
+
+use picologger::testing::FaultInjector;
+use picologger::{FailurePolicy, Logger, LoggerConfig, LoggerState, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Fails the next `remaining` calls into the kernel with EIO.
+struct FailingSubmits {
+    remaining: AtomicU32,
+}
+
+impl FaultInjector for FailingSubmits {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn fail_submit(&self) -> Option<i32> {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .ok()
+            .map(|_| libc::EIO)
+    }
+}
+
+fn start_logger(path: &str, failures: u32, policy: FailurePolicy) -> Logger<TestData> {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_failure_policy(policy)
+        .with_fault_injector(Arc::new(FailingSubmits {
+            remaining: AtomicU32::new(failures),
+        }));
+    logger.start().unwrap();
+    logger
+}
+
+fn assert_ring_error(errors: &[PicoError]) {
+    assert!(
+        errors
+            .iter()
+            .any(|e| matches!(e, PicoError::Io(io) if io.raw_os_error() == Some(libc::EIO))),
+        "expected an EIO ring error, got {:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_ring_failure_aborts_worker() {
+    let path = "health_abort_test.log";
+    cleanup(path);
+
+    let mut logger = start_logger(path, u32::MAX, FailurePolicy::Abort);
+    assert!(logger.is_healthy());
+    let errors = logger.take_error_receiver().unwrap();
+    for i in 0..10 {
+        logger.log(TestData { id: i, val: 0 });
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.is_healthy() {
+        assert!(
+            Instant::now() < deadline,
+            "worker never reported the failure"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(logger.state(), LoggerState::Stopped);
+    assert_eq!(logger.log(TestData { id: 10, val: 0 }), None);
+    assert!(matches!(
+        logger.try_log(TestData { id: 11, val: 0 }),
+        Err(PicoError::WorkerStopped)
+    ));
+    assert!(logger.start().is_err());
+    assert_ring_error(&errors.try_iter().collect::<Vec<_>>());
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_ring_rebuilt_after_failure() {
+    let path = "health_rebuild_test.log";
+    cleanup(path);
+
+    let mut logger = start_logger(path, 1, FailurePolicy::RebuildRing);
+    let errors = logger.take_error_receiver().unwrap();
+    for i in 0..500 {
+        assert!(
+            logger
+                .log(TestData {
+                    id: i as u64,
+                    val: i as u32,
+                })
+                .is_some()
+        );
+    }
+    assert!(logger.is_healthy());
+    drop(logger);
+    assert_ring_error(&errors.try_iter().collect::<Vec<_>>());
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 500);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
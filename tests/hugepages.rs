@@ -0,0 +1,53 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_hugepage_pool_round_trip() {
+    let path = "hugepages_round_trip_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(32_768)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_hugepages(true);
+        assert!(!logger.uses_hugepages());
+        logger.start().unwrap();
+
+        for i in 0..20_000 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 20_000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+        assert_eq!(item.val, i as u32);
+    }
+
+    cleanup(path);
+}
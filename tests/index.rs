@@ -0,0 +1,95 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Tick {
+    id: u64,
+    px: u64,
+}
+
+fn remove(path: &str) {
+    for p in [path.to_string(), format!("{}.idx", path)] {
+        if std::path::Path::new(&p).exists() {
+            fs::remove_file(&p).unwrap();
+        }
+    }
+}
+
+/// Writes `count` ticks and returns the seq_id assigned to each one.
+fn write_ticks(path: &str, count: u64, index: bool) -> Vec<u64> {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(32_768)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(4 * 1024 * 1024)
+        .build::<Tick>()
+        .unwrap()
+        .with_index(index);
+    logger.start().unwrap();
+    (0..count)
+        .map(|i| logger.log(Tick { id: i, px: i * 3 }).unwrap())
+        .collect()
+}
+
+fn check_range(path: &str, seq_ids: &[u64], from: usize, to: usize) {
+    let logger = Logger::<Tick>::new().with_read_config(path.to_string());
+    let entries = logger.read_range(seq_ids[from]..seq_ids[to]).unwrap();
+
+    assert_eq!(entries.len(), to - from);
+    for (entry, i) in entries.iter().zip(from..to) {
+        assert_eq!(entry.seq_id, seq_ids[i]);
+        assert_eq!(entry.data.id, i as u64);
+    }
+}
+
+#[test]
+fn test_read_range_with_index() {
+    let path = "index_range_test.log";
+    remove(path);
+
+    let seq_ids = write_ticks(path, 20_000, true);
+    assert!(std::path::Path::new(&format!("{}.idx", path)).exists());
+
+    check_range(path, &seq_ids, 15_000, 15_500);
+    check_range(path, &seq_ids, 0, 10);
+
+    let logger = Logger::<Tick>::new().with_read_config(path.to_string());
+    let mut reader = logger.read_iter().unwrap();
+    reader.seek(seq_ids[19_999]).unwrap();
+    let last = reader.next().unwrap().unwrap();
+    assert_eq!(last.data.id, 19_999);
+    assert!(reader.next().is_none());
+
+    // Make the first page unreadable: a linear scan now fails, an indexed seek does not.
+    let mut bytes = fs::read(path).unwrap();
+    bytes[4096..4104].copy_from_slice(b"PICOPAGE");
+    bytes[4104..4112].copy_from_slice(&[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+    fs::write(path, bytes).unwrap();
+    assert!(logger.read().is_err());
+    check_range(path, &seq_ids, 15_000, 15_500);
+
+    remove(path);
+}
+
+#[test]
+fn test_seek_falls_back_without_or_with_stale_index() {
+    let path = "index_fallback_test.log";
+    remove(path);
+
+    let seq_ids = write_ticks(path, 5_000, false);
+    check_range(path, &seq_ids, 4_000, 4_100);
+
+    // An index whose records point at the wrong pages must be ignored.
+    let mut stale = Vec::new();
+    for (seq_id, offset) in [(seq_ids[0], 4096u64 * 3), (seq_ids[4_000], 0)] {
+        stale.extend_from_slice(&seq_id.to_le_bytes());
+        stale.extend_from_slice(&offset.to_le_bytes());
+    }
+    fs::write(format!("{}.idx", path), stale).unwrap();
+    check_range(path, &seq_ids, 4_000, 4_100);
+
+    remove(path);
+}
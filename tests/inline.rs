@@ -0,0 +1,203 @@
+use picologger::test_support::{TempLogger, assert_contiguous_seq};
+use picologger::{BufferFullPolicy, LoggerConfig, OverflowPolicy, PicoError};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn data(id: u64) -> TestData {
+    TestData {
+        id,
+        val: id as u32 * 3,
+    }
+}
+
+/// An inline logger that waits for a free page, and whose flush interval never elapses
+/// while a test runs.
+fn inline_logger() -> TempLogger<TestData> {
+    TempLogger::with_config(LoggerConfig::new().flush_interval(Duration::from_secs(10))).configure(
+        |logger| {
+            logger
+                .with_block_size(4096)
+                .with_buffer_full_policy(BufferFullPolicy::Block)
+        },
+    )
+}
+
+fn wait_for_flushed(logger: &TempLogger<TestData>, entries: u64) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed < entries {
+        assert!(Instant::now() < deadline, "entries never became durable");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_inline_entries_read_back_in_order() {
+    let mut logger = inline_logger();
+    logger.start_inline().unwrap();
+    assert!(logger.is_inline());
+    for id in 0..1000 {
+        logger.try_log(data(id)).unwrap();
+    }
+    let batch = logger
+        .try_log_batch(&(1000..1200).map(data).collect::<Vec<_>>())
+        .unwrap();
+    assert_eq!(batch.end - batch.start, 200);
+    logger.shutdown().unwrap();
+
+    let counts = logger.flush_counts();
+    assert!(counts.page_full > 0, "{counts:?}");
+    assert_eq!(counts.shutdown, 1);
+    assert_eq!(logger.stats().entries_logged, 1200);
+    assert_eq!(logger.dropped_entries(), 0);
+
+    assert_eq!(
+        assert_contiguous_seq::<TestData>(logger.path()).end,
+        batch.end
+    );
+    let read = logger.reader().read().unwrap();
+    assert_eq!(read, (0..1200).map(data).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_inline_flush_hands_the_page_to_the_worker() {
+    let mut logger = inline_logger();
+    logger.start_inline().unwrap();
+    let seq_ids = logger.try_log_batch(&[data(1), data(2)]).unwrap();
+    assert_eq!(logger.pending_entries(), 2);
+    logger.flush().unwrap();
+    wait_for_flushed(&logger, 2);
+    assert_eq!(logger.flush_counts().explicit, 1);
+    assert_eq!(logger.pending_entries(), 0);
+    logger.shutdown().unwrap();
+    assert_eq!(logger.flush_counts().shutdown, 0);
+
+    let read = logger.reader().read_iter().unwrap();
+    let ids: Vec<_> = read.map(|entry| entry.unwrap().seq_id).collect();
+    assert_eq!(ids, seq_ids.collect::<Vec<_>>());
+}
+
+#[test]
+fn test_inline_pages_come_back_with_two_pages() {
+    let mut logger = inline_logger().configure(|logger| logger.with_pages(2));
+    logger.start_inline().unwrap();
+    for id in 0..5000 {
+        logger.try_log(data(id)).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let read = logger.reader().read().unwrap();
+    assert_eq!(read.len(), 5000);
+    assert!(read.iter().enumerate().all(|(i, d)| *d == data(i as u64)));
+}
+
+#[test]
+fn test_inline_watermark_and_timer() {
+    let mut logger = TempLogger::<TestData>::with_config(
+        LoggerConfig::new().flush_interval(Duration::from_millis(1)),
+    )
+    .configure(|logger| logger.with_block_size(4096));
+    logger.start_inline().unwrap();
+    logger.try_log(data(0)).unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+    logger.try_log(data(1)).unwrap();
+    wait_for_flushed(&logger, 2);
+    assert_eq!(logger.flush_counts().timer, 1);
+    logger.shutdown().unwrap();
+
+    let mut logger = inline_logger().configure(|logger| logger.with_flush_watermark(0.5));
+    logger.start_inline().unwrap();
+    for id in 0..1000 {
+        logger.try_log(data(id)).unwrap();
+    }
+    logger.shutdown().unwrap();
+    let counts = logger.flush_counts();
+    assert!(counts.watermark > 0, "{counts:?}");
+    assert_eq!(counts.page_full, 0);
+    assert_eq!(logger.reader().read().unwrap().len(), 1000);
+}
+
+#[test]
+fn test_inline_batch_cut_short_returns_the_logged_range() {
+    let mut logger = inline_logger().configure(|logger| {
+        logger
+            .with_pages(2)
+            .with_buffer_full_policy(BufferFullPolicy::Reject)
+    });
+    logger.start_inline().unwrap();
+    let items: Vec<_> = (0..50_000).map(data).collect();
+    let batch = logger.try_log_batch(&items).unwrap();
+    let logged = (batch.end - batch.start) as usize;
+    assert!(logged > 0);
+    assert_eq!(logger.stats().entries_logged, logged as u64);
+    assert_eq!(logger.dropped_entries() as usize, items.len() - logged);
+    let next = loop {
+        match logger.try_log(data(0)) {
+            Ok(seq_id) => break seq_id,
+            Err(PicoError::BufferFull) => std::thread::sleep(Duration::from_millis(1)),
+            Err(e) => panic!("{e}"),
+        }
+    };
+    assert!(next >= batch.start + items.len() as u64);
+    logger.shutdown().unwrap();
+
+    let read = logger.reader().read().unwrap();
+    assert_eq!(read[..logged], items[..logged]);
+    assert_eq!(read.len(), logged + 1);
+}
+
+#[test]
+fn test_inline_shutdown_now_drops_the_page_being_filled() {
+    let mut logger = inline_logger();
+    logger.start_inline().unwrap();
+    for id in 0..5 {
+        logger.try_log(data(id)).unwrap();
+    }
+    assert_eq!(logger.shutdown_now().unwrap(), 0);
+    assert_eq!(logger.dropped_entries(), 5);
+    assert!(logger.reader().read().unwrap().is_empty());
+}
+
+#[test]
+fn test_inline_conflicts_are_rejected() {
+    let mut logger = inline_logger().configure(|logger| logger.with_partial_rewrites(0.5, 3));
+    assert!(matches!(
+        logger.start_inline(),
+        Err(PicoError::InlineConflict {
+            setting: "with_partial_rewrites"
+        })
+    ));
+
+    let mut logger =
+        inline_logger().configure(|logger| logger.with_overflow_policy(OverflowPolicy::DropNewest));
+    assert!(matches!(
+        logger.start_inline(),
+        Err(PicoError::InlineConflict { .. })
+    ));
+
+    let mut logger = inline_logger();
+    logger.start().unwrap();
+    assert!(!logger.is_inline());
+    assert!(matches!(
+        logger.start_inline(),
+        Err(PicoError::AlreadyStarted)
+    ));
+
+    let mut logger = inline_logger();
+    logger.start_inline().unwrap();
+    assert!(logger.reserve().is_none());
+    let ids = logger.reserve_seq_block(1);
+    assert!(matches!(
+        logger.log_with_seq(ids.start, data(0)),
+        Err(PicoError::InlineConflict {
+            setting: "log_with_seq"
+        })
+    ));
+    logger.abandon_seq(ids.start);
+    logger.shutdown().unwrap();
+}
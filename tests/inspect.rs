@@ -0,0 +1,163 @@
+use picologger::{Logger, LoggerConfig, inspect, testing};
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+/// Seq_ids are handed out process-wide, so files only come out without gaps when written
+/// one at a time.
+static WRITING: Mutex<()> = Mutex::new(());
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Writes `count` entries, then skips `skip` seq_ids and writes `count` more.
+fn write_entries(path: &str, count: u64, skip: u64) {
+    let _guard = WRITING.lock().unwrap();
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    let mut last = 0;
+    for i in 0..count {
+        last = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    if skip > 0 {
+        testing::advance_seq_id(last + 1 + skip);
+        for i in 0..count {
+            logger.log(TestData { id: i, val: 1 }).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_verify_consistent_file() {
+    let path = "inspect_consistent_test.log";
+    write_entries(path, 2000, 0);
+
+    let report = inspect::verify(path).unwrap();
+    assert!(report.is_consistent(), "{:?}", report);
+    assert_eq!(report.entries, 2000);
+    assert_eq!(report.schema.unwrap().size, size_of::<TestData>());
+    let (first, last) = (report.first_seq_id.unwrap(), report.last_seq_id.unwrap());
+    assert_eq!(last - first, 1999);
+    assert!(report.bytes_scanned > 2000 * size_of::<TestData>() as u64);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_verify_reports_gaps() {
+    let path = "inspect_gaps_test.log";
+    write_entries(path, 100, 500);
+
+    let report = inspect::verify(path).unwrap();
+    assert_eq!(report.entries, 200);
+    assert_eq!(report.corruption, None);
+    assert_eq!(report.out_of_order, 0);
+    let first = report.first_seq_id.unwrap();
+    assert_eq!(report.gaps, vec![first + 100..first + 600]);
+    assert_eq!(report.last_seq_id, Some(first + 699));
+    assert!(!report.is_consistent());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_verify_finds_first_corruption() {
+    let path = "inspect_corrupt_test.log";
+    write_entries(path, 50, 0);
+
+    // Entries are 40 bytes, following the first page's 40-byte page meta at 4096; dirty the
+    // header padding of the eleventh.
+    let corrupt_at = 4096 + 40 + 10 * 40;
+    let mut bytes = fs::read(path).unwrap();
+    bytes[corrupt_at + 20] = 0xFF;
+    fs::write(path, bytes).unwrap();
+
+    let report = inspect::verify(path).unwrap();
+    assert_eq!(report.corruption, Some(corrupt_at as u64));
+    assert_eq!(report.entries, 10);
+    assert!(!report.is_consistent());
+
+    cleanup(path);
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::<TestData>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_read_checked_clean_file() {
+    let path = "inspect_checked_clean_test.log";
+    write_entries(path, 1000, 0);
+
+    let checked = reader(path).read_checked().unwrap();
+    assert_eq!(checked.entries.len(), 1000);
+    assert!(checked.gaps.is_empty());
+    assert_eq!((checked.out_of_order, checked.out_of_order_pages), (0, 0));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_read_checked_reports_gaps() {
+    let path = "inspect_checked_gaps_test.log";
+    write_entries(path, 100, 50);
+
+    let checked = reader(path).read_checked().unwrap();
+    assert_eq!(checked.entries.len(), 200);
+    let last_before = checked.entries[99].seq_id;
+    assert_eq!(checked.gaps, vec![last_before + 1..last_before + 51]);
+    assert_eq!(checked.entries[100].seq_id, last_before + 51);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_read_checked_tells_page_disorder_apart() {
+    let path = "inspect_checked_order_test.log";
+    write_entries(path, 400, 0);
+
+    // Swap the seq_ids of the eleventh and twelfth entries of the first page, and leave a
+    // copy of the first page where the third one was.
+    let mut bytes = fs::read(path).unwrap();
+    let first_page = bytes[4096..8192].to_vec();
+    bytes[12288..16384].copy_from_slice(&first_page);
+    let (a, b) = (4096 + 40 + 10 * 40, 4096 + 40 + 11 * 40);
+    for i in 0..8 {
+        bytes.swap(a + i, b + i);
+    }
+    fs::write(path, bytes).unwrap();
+
+    let checked = reader(path).read_checked().unwrap();
+    assert_eq!(checked.entries.len(), 400);
+    assert_eq!(checked.out_of_order, 1);
+    assert_eq!(checked.out_of_order_pages, 1);
+    let first = checked.entries[0].seq_id;
+    // The swapped entry looks skipped until it turns up, and the third page's entries went
+    // missing under the stale copy.
+    assert_eq!(checked.gaps.len(), 2);
+    assert_eq!(checked.gaps[0], first + 10..first + 11);
+    assert_eq!(checked.gaps[1], first + 202..first + 303);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_verify_missing_file() {
+    let err = inspect::verify("inspect_missing_test.log").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
@@ -1,5 +1,7 @@
-use picologger::Logger;
+use picologger::LoggerConfig;
+use picologger::test_support::{TempLogger, assert_contiguous_seq};
 use std::fs;
+use std::time::Duration;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(C)]
@@ -8,6 +10,7 @@ struct TestData {
     val: u32,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for TestData {
     fn default() -> Self {
         Self { id: 0, val: 0 }
@@ -16,28 +19,25 @@ impl Default for TestData {
 
 #[test]
 fn test_file_integrity() {
-    let path = "integrity_test.log";
-    if std::path::Path::new(path).exists() {
-        fs::remove_file(path).unwrap();
-    }
-
     // Write
-    {
-        let mut logger = Logger::<TestData>::new()
-            .with_write_config(path.to_string(), 1024, 1_000_000, 100_000, 10 * 1024 * 1024); // 1ms flush
-        logger.start().unwrap();
-
-        for i in 0..100 {
-            logger.log(TestData {
-                id: i as u64,
-                val: (i * 10) as u32,
-            });
-        }
-        // Drop logger to flush and close
+    let mut logger = TempLogger::<TestData>::with_config(
+        LoggerConfig::new()
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .preallocate(10 * 1024 * 1024),
+    );
+    logger.start().unwrap();
+
+    for i in 0..100 {
+        logger.log(TestData {
+            id: i as u64,
+            val: (i * 10) as u32,
+        });
     }
+    // Shut down to flush and close
+    logger.shutdown().unwrap();
 
-    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
-    let result = logger.read().unwrap();
+    let result = logger.reader().read().unwrap();
 
     assert_eq!(result.len(), 100, "Should have read 100 items");
 
@@ -45,8 +45,56 @@ fn test_file_integrity() {
         assert_eq!(item.id, i as u64, "ID mismatch at index {}", i);
         assert_eq!(item.val, (i * 10) as u32, "Value mismatch at index {}", i);
     }
+}
+
+#[test]
+fn test_batch_seq_ids_are_contiguous() {
+    let mut logger = TempLogger::<TestData>::new();
+    logger.start().unwrap();
+    let items: Vec<_> = (0..500).map(|id| TestData { id, val: 0 }).collect();
+    let seq_ids = logger.log_batch(&items).unwrap();
+    logger.shutdown().unwrap();
+
+    assert_eq!(assert_contiguous_seq::<TestData>(logger.path()), seq_ids);
+}
 
-    if std::path::Path::new(path).exists() {
-        fs::remove_file(path).unwrap();
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Filled {
+    bytes: [u8; 40],
+}
+
+impl Default for Filled {
+    fn default() -> Self {
+        Self { bytes: [0xFF; 40] }
     }
 }
+
+#[test]
+fn test_reused_page_padding_is_zeroed() {
+    // 63 64-byte entries fill a 4 KiB page after its 40-byte page meta, so with two pages
+    // entries 0..63 and 63..126 fill both of them with 0xFF, and the last two land in the
+    // first page again.
+    let mut logger = TempLogger::<Filled>::with_config(
+        LoggerConfig::new()
+            .capacity(1024)
+            .flush_interval(Duration::from_secs(10))
+            .pages(2),
+    );
+    logger.start().unwrap();
+    for _ in 0..128 {
+        logger.log(Filled::default()).unwrap();
+    }
+    logger.flush().unwrap();
+    logger.shutdown().unwrap();
+
+    let file = fs::read(logger.path()).unwrap();
+    let reused = &file[3 * 4096..4 * 4096];
+    assert!(reused[..168].contains(&0xFF));
+    assert!(
+        reused[168..].iter().all(|&b| b == 0),
+        "stale bytes from the page's previous use reached the disk"
+    );
+
+    assert_eq!(logger.reader().read().unwrap().len(), 128);
+}
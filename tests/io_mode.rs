@@ -0,0 +1,90 @@
+use picologger::{IoMode, Logger, LoggerConfig};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_tmpfs_round_trip() {
+    if !Path::new("/dev/shm").is_dir() {
+        return;
+    }
+    let path = "/dev/shm/picolog_io_mode_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap();
+        logger.start().unwrap();
+        // tmpfs only gained O_DIRECT support in Linux 6.6, so entries must read back in
+        // either mode.
+        for i in 0..500 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 500);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_io_mode_reported() {
+    let path = "io_mode_direct_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    assert_eq!(logger.io_mode(), IoMode::Direct);
+    logger.start().unwrap();
+    drop(logger);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_open_failure_is_an_error() {
+    let Err(err) = LoggerConfig::new()
+        .path("no_such_dir/io_mode_test.log")
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+    else {
+        panic!("a missing directory was accepted");
+    };
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
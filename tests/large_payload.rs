@@ -0,0 +1,79 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+
+const BLOB_LEN: usize = 128 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Snapshot {
+    id: u64,
+    bytes: [u8; BLOB_LEN - 8],
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            bytes: [0; BLOB_LEN - 8],
+        }
+    }
+}
+
+impl Snapshot {
+    fn new(id: u64) -> Self {
+        let mut snapshot = Self {
+            id,
+            ..Default::default()
+        };
+        for (i, byte) in snapshot.bytes.iter_mut().enumerate() {
+            *byte = (i as u64 + id) as u8;
+        }
+        snapshot
+    }
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_128k_payload_round_trip() {
+    // Unoptimized builds keep several copies of each 128 KiB value on the stack, more than
+    // the test harness's default thread stack holds.
+    std::thread::Builder::new()
+        .stack_size(32 * 1024 * 1024)
+        .spawn(round_trip_snapshots)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn round_trip_snapshots() {
+    let path = "large_payload_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(64)
+            .build::<Snapshot>()
+            .unwrap()
+            .with_pages(8);
+        logger.start().unwrap();
+        for i in 0..40 {
+            logger.log(Snapshot::new(i));
+        }
+    }
+
+    let logger = Logger::<Snapshot>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 40);
+    for (i, entry) in entries.iter().enumerate() {
+        assert!(entry.data == Snapshot::new(i as u64), "entry {} differs", i);
+    }
+    assert_eq!(logger.read_mmap().unwrap().count(), 40);
+
+    cleanup(path);
+}
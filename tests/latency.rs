@@ -0,0 +1,98 @@
+use picologger::{LatencyPercentiles, Logger, LoggerConfig};
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str, tracking: bool) -> Logger<TestData> {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .build::<TestData>()
+        .unwrap()
+        .with_latency_tracking(tracking);
+    logger.start().unwrap();
+    logger
+}
+
+fn wait_for_durability(logger: &Logger<TestData>, count: u64) -> LatencyPercentiles {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let durability = logger.latency_report().unwrap().durability;
+        if durability.count >= count {
+            return durability;
+        }
+        assert!(Instant::now() < deadline, "{durability:?}");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn assert_ordered(percentiles: &LatencyPercentiles) {
+    assert!(percentiles.p50 <= percentiles.p90);
+    assert!(percentiles.p90 <= percentiles.p99);
+    assert!(percentiles.p99 <= percentiles.p999);
+    assert!(percentiles.p999 <= percentiles.max);
+    assert!(percentiles.max > Duration::ZERO);
+}
+
+#[test]
+fn test_latency_tracking_is_off_by_default() {
+    let path = "latency_off_test.log";
+    let mut logger = logger(path, false);
+    logger.log(TestData { id: 1, val: 0 }).unwrap();
+    assert!(logger.latency_report().is_none());
+    logger.shutdown().unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_every_entry_is_sampled_until_durable() {
+    let path = "latency_entries_test.log";
+    let mut logger = logger(path, true);
+    for id in 0..10_000 {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+    let durability = wait_for_durability(&logger, 10_000);
+    let report = logger.latency_report().unwrap();
+
+    assert_eq!(report.submission.count, 10_000);
+    assert_eq!(durability.count, 10_000);
+    assert_ordered(&report.submission);
+    assert_ordered(&durability);
+    assert!(durability.max >= report.submission.p50);
+
+    logger.shutdown().unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_a_batch_is_one_call_of_many_entries() {
+    let path = "latency_batch_test.log";
+    let mut logger = logger(path, true);
+    let items = [TestData::default(); 100];
+    for _ in 0..10 {
+        logger.log_batch(&items).unwrap();
+    }
+    logger.flush().unwrap();
+    let durability = wait_for_durability(&logger, 1000);
+
+    assert_eq!(logger.latency_report().unwrap().submission.count, 10);
+    assert_eq!(durability.count, 1000);
+
+    logger.shutdown().unwrap();
+    cleanup(path);
+}
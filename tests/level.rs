@@ -0,0 +1,157 @@
+use picologger::{Level, Logger, LoggerConfig, PicoError};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str) -> Logger<TestData> {
+    cleanup(path);
+    LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .build::<TestData>()
+        .unwrap()
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::<TestData>::new().with_read_config(path.to_string())
+}
+
+const LEVELS: [Level; 5] = [
+    Level::Trace,
+    Level::Debug,
+    Level::Info,
+    Level::Warn,
+    Level::Error,
+];
+
+#[test]
+fn test_levels_round_trip() {
+    let path = "level_round_trip_test.log";
+    {
+        let mut logger = logger(path);
+        logger.start().unwrap();
+        for i in 0..500 {
+            logger
+                .log_with_level(TestData { id: i, val: 0 }, LEVELS[i as usize % 5])
+                .unwrap();
+        }
+        logger.log(TestData { id: 500, val: 0 }).unwrap();
+    }
+
+    let entries: Vec<_> = reader(path)
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 501);
+    for entry in &entries[..500] {
+        assert_eq!(entry.level, LEVELS[entry.data.id as usize % 5]);
+    }
+    assert_eq!(entries[500].level, Level::Info);
+
+    let warnings = reader(path).read_filtered(Level::Warn).unwrap();
+    assert_eq!(warnings.len(), 200);
+    assert!(warnings.iter().all(|e| e.level >= Level::Warn));
+    assert_eq!(reader(path).read_filtered(Level::Trace).unwrap(), entries);
+
+    // The level sits in the byte after the first entry's length, past the page meta.
+    let bytes = fs::read(path).unwrap();
+    assert_eq!(bytes[4096 + 40 + 20], Level::Trace as u8);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_min_level_drops_entries_before_the_ring() {
+    let path = "level_min_test.log";
+    {
+        let mut logger = logger(path).with_min_level(Level::Warn);
+        logger.start().unwrap();
+        assert!(!logger.level_enabled(Level::Info));
+        assert!(logger.level_enabled(Level::Error));
+
+        let err = logger
+            .try_log_with_level(TestData { id: 0, val: 0 }, Level::Debug)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PicoError::LevelFiltered {
+                level: Level::Debug,
+                min: Level::Warn
+            }
+        ));
+        // Entries without a level count as Info.
+        assert!(logger.log(TestData { id: 1, val: 0 }).is_none());
+        assert!(logger.log_batch(&[TestData::default()]).is_none());
+        assert!(logger.reserve().is_none());
+        logger
+            .log_with_level(TestData { id: 2, val: 0 }, Level::Warn)
+            .unwrap();
+        logger
+            .log_with_level(TestData { id: 3, val: 0 }, Level::Error)
+            .unwrap();
+        assert_eq!(logger.stats().entries_logged, 2);
+    }
+
+    let entries = reader(path).read_iter().unwrap().map(Result::unwrap);
+    let logged: Vec<_> = entries.map(|e| (e.data.id, e.level)).collect();
+    assert_eq!(logged, vec![(2, Level::Warn), (3, Level::Error)]);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_older_formats_read_as_info() {
+    let path = "level_v3_test.log";
+    {
+        let mut logger = logger(path).with_format_version(3);
+        logger.start().unwrap();
+        logger
+            .log_with_level(TestData { id: 0, val: 0 }, Level::Error)
+            .unwrap();
+    }
+
+    // Version 3 entry headers keep the byte as padding.
+    let bytes = fs::read(path).unwrap();
+    assert_eq!(bytes[4096 + 40 + 20], 0);
+    let entries = reader(path)
+        .with_strict_reads(true)
+        .read_filtered(Level::Trace)
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].level, Level::Info);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_unknown_level_is_damage() {
+    let path = "level_damaged_test.log";
+    {
+        let mut logger = logger(path);
+        logger.start().unwrap();
+        for i in 0..10 {
+            logger.log(TestData { id: i, val: 0 }).unwrap();
+        }
+    }
+    let mut bytes = fs::read(path).unwrap();
+    bytes[4096 + 40 + 20] = 9;
+    fs::write(path, &bytes).unwrap();
+
+    let err = reader(path).with_strict_reads(true).read().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(reader(path).read().unwrap().is_empty());
+
+    cleanup(path);
+}
@@ -0,0 +1,125 @@
+use picologger::{Logger, LoggerConfig, LoggerState, PicoError};
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn configured(path: &str) -> Logger<TestData> {
+    LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+}
+
+#[test]
+fn test_log_before_start_is_rejected() {
+    let mut logger = configured("lifecycle_unstarted_test.log");
+    assert_eq!(logger.state(), LoggerState::Configured);
+    assert!(matches!(
+        logger.try_log(TestData::default()),
+        Err(PicoError::NotStarted)
+    ));
+    assert_eq!(logger.log(TestData::default()), None);
+}
+
+#[test]
+fn test_second_start_is_rejected() {
+    let path = "lifecycle_restart_test.log";
+    cleanup(path);
+
+    let mut logger = configured(path);
+    logger.start().unwrap();
+    assert_eq!(logger.state(), LoggerState::Running);
+
+    let err = logger.start().unwrap_err();
+    assert!(matches!(err, PicoError::AlreadyStarted));
+    assert_eq!(logger.state(), LoggerState::Running);
+    assert!(logger.try_log(TestData { id: 1, val: 1 }).is_ok());
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_reconfigure_after_start_is_ignored() {
+    let path = "lifecycle_reconfigure_test.log";
+    let other = "lifecycle_reconfigure_other_test.log";
+    cleanup(path);
+    cleanup(other);
+
+    let mut logger = configured(path);
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    let mut logger = logger.with_write_config(other.to_string(), 1, 1, 1, 4096);
+    assert!(matches!(errors.try_recv(), Ok(PicoError::AlreadyStarted)));
+    for i in 0..100 {
+        logger
+            .try_log(TestData {
+                id: i,
+                val: i as u32,
+            })
+            .unwrap();
+    }
+    drop(logger);
+
+    assert!(!std::path::Path::new(other).exists());
+    let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(reader.read().unwrap().len(), 100);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_second_writer_on_same_path_is_rejected() {
+    let path = "lifecycle_locked_test.log";
+    cleanup(path);
+
+    let mut first = configured(path);
+    first.start().unwrap();
+    first.try_log(TestData { id: 0, val: 0 }).unwrap();
+
+    let mut second = configured(path);
+    let err = second.start().unwrap_err();
+    assert!(matches!(err, PicoError::FileLocked { .. }), "{:?}", err);
+    assert_eq!(second.state(), LoggerState::Configured);
+    drop(second);
+
+    for i in 1..100 {
+        first
+            .try_log(TestData {
+                id: i,
+                val: i as u32,
+            })
+            .unwrap();
+    }
+    first.shutdown().unwrap();
+
+    let entries = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap();
+    assert_eq!(entries.len(), 100);
+
+    // The lock goes away with the writer.
+    let mut third = configured(path);
+    third.start().unwrap();
+    drop(third);
+
+    cleanup(path);
+}
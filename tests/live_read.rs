@@ -0,0 +1,124 @@
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Lets every page write reach the file, but never reports those past the first page as
+/// done.
+struct UnackedAfterFirstPage;
+
+impl FaultInjector for UnackedAfterFirstPage {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn swallow_completion(&self, offset: u64, _attempt: u32) -> bool {
+        offset > 4096
+    }
+}
+
+fn wait_until_durable(logger: &Logger<TestData>, entries: u64) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed < entries {
+        assert!(Instant::now() < deadline, "entries were never flushed");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_running_logger_reads_its_durable_entries() {
+    let path = "live_read_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+
+    let mut watermark = 0;
+    for i in 0..100 {
+        watermark = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+    wait_until_durable(&logger, 100);
+    // Logged after the flush, so still in memory while the file is read.
+    for i in 100..150 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 100);
+    assert_eq!(entries.last().unwrap().seq_id, watermark);
+    assert!(entries.iter().all(|e| e.seq_id <= watermark));
+    assert_eq!(
+        logger.read().unwrap(),
+        (0..100)
+            .map(|id| TestData { id, val: 0 })
+            .collect::<Vec<_>>()
+    );
+
+    // Once stopped, everything it wrote is read.
+    logger.shutdown().unwrap();
+    assert_eq!(logger.read().unwrap().len(), 150);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_running_logger_skips_written_but_unacknowledged_pages() {
+    let path = "live_read_unacked_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap()
+        .with_fault_injector(Arc::new(UnackedAfterFirstPage))
+        .with_shutdown_timeout(Duration::from_millis(100));
+    logger.start().unwrap();
+
+    let mut watermark = 0;
+    for i in 0..100 {
+        watermark = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+    wait_until_durable(&logger, 100);
+    for i in 100..150 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+
+    // A separate reader sees the second page once it is on disk; the logger itself doesn't,
+    // as the write was never acknowledged.
+    let other = Logger::<TestData>::new().with_read_config(path.to_string());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while other.read().unwrap().len() < 150 {
+        assert!(
+            Instant::now() < deadline,
+            "second page never reached the file"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 100);
+    assert_eq!(entries.last().unwrap().seq_id, watermark);
+
+    let _ = logger.shutdown();
+    drop(logger);
+    cleanup(path);
+}
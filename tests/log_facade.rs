@@ -0,0 +1,66 @@
+#![cfg(feature = "log-facade")]
+
+use log::{Level, LevelFilter, Log, Record};
+use picologger::{LogRecord, Logger, LoggerConfig, PicoLogAdapter};
+use std::fs;
+use std::time::Duration;
+
+fn adapter(path: &str, level: LevelFilter) -> PicoLogAdapter {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_millis(1))
+        .build::<LogRecord>()
+        .unwrap();
+    logger.start().unwrap();
+    PicoLogAdapter::new(logger, level)
+}
+
+fn emit(adapter: &PicoLogAdapter, level: Level, message: &str) {
+    adapter.log(
+        &Record::builder()
+            .level(level)
+            .target("orders::matching")
+            .args(format_args!("{}", message))
+            .build(),
+    );
+}
+
+#[test]
+fn test_records_round_trip_as_text() {
+    let path = "log_facade_test.log";
+    let _ = fs::remove_file(path);
+
+    let adapter = adapter(path, LevelFilter::Info);
+    assert!(adapter.enabled(&log::Metadata::builder().level(Level::Warn).build()));
+    assert!(!adapter.enabled(&log::Metadata::builder().level(Level::Debug).build()));
+
+    emit(&adapter, Level::Info, "order 42 filled");
+    emit(&adapter, Level::Debug, "filtered out");
+    emit(&adapter, Level::Error, &"é".repeat(100));
+    adapter.flush();
+    drop(adapter);
+
+    let reader = Logger::<LogRecord>::new().with_read_config(path.to_string());
+    let records: Vec<LogRecord> = reader.read().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].level(), Some(Level::Info));
+    assert_eq!(records[0].message(), "order 42 filled");
+    assert!(!records[0].is_truncated());
+    assert_eq!(records[0].target_hash, records[1].target_hash);
+
+    assert_eq!(records[1].level(), Some(Level::Error));
+    assert!(records[1].is_truncated());
+    assert_eq!(
+        records[1].message().len(),
+        LogRecord::MESSAGE_CAPACITY / 2 * 2
+    );
+    assert!(records[1].message().chars().all(|c| c == 'é'));
+
+    let lines = reader.read_text().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(" INFO  "));
+    assert!(lines[0].ends_with("order 42 filled"));
+    assert!(lines[1].ends_with('…'));
+
+    fs::remove_file(path).unwrap();
+}
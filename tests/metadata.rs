@@ -0,0 +1,115 @@
+use picologger::{Logger, LoggerConfig, MAX_METADATA_LEN, PicoError};
+use std::fs;
+use std::io::ErrorKind;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str) -> Logger<TestData> {
+    cleanup(path);
+    LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .build::<TestData>()
+        .unwrap()
+}
+
+#[test]
+fn test_metadata_round_trip() {
+    let path = "metadata_round_trip_test.log";
+    {
+        let mut logger = logger(path)
+            .with_metadata([("service", "matcher"), ("region", "eu")])
+            .with_metadata([("build".to_string(), "1234".to_string())]);
+        logger.start().unwrap();
+        for i in 0..300 {
+            logger.log(TestData { id: i, val: 0 }).unwrap();
+        }
+    }
+
+    let metadata = Logger::<TestData>::read_metadata(path).unwrap();
+    assert_eq!(metadata.pid, std::process::id());
+    assert_eq!(metadata.crate_version, env!("CARGO_PKG_VERSION"));
+    assert!(!metadata.hostname.is_empty());
+    assert_eq!(
+        metadata.tags,
+        vec![
+            ("service".to_string(), "matcher".to_string()),
+            ("region".to_string(), "eu".to_string()),
+            ("build".to_string(), "1234".to_string()),
+        ]
+    );
+    #[cfg(target_os = "linux")]
+    {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        assert!(metadata.process_start_nanos > 0 && metadata.process_start_nanos <= now);
+    }
+
+    // The block lives in the header block; entries are unaffected.
+    let entries = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap();
+    assert_eq!(entries.len(), 300);
+    assert!(entries.iter().enumerate().all(|(i, e)| e.id == i as u64));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_damaged_metadata_is_reported() {
+    let path = "metadata_damaged_test.log";
+    {
+        let mut logger = logger(path).with_metadata([("service", "matcher")]);
+        logger.start().unwrap();
+        logger.log(TestData::default()).unwrap();
+    }
+    let mut bytes = fs::read(path).unwrap();
+    // The metadata length in the file header, now past the end of the block's contents.
+    bytes[100] = bytes[100].wrapping_add(1);
+    fs::write(path, &bytes).unwrap();
+
+    let err = Logger::<TestData>::read_metadata(path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_oversized_metadata_is_rejected() {
+    let path = "metadata_oversized_test.log";
+    let err = logger(path)
+        .with_metadata([("notes", "x".repeat(MAX_METADATA_LEN))])
+        .start()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PicoError::MetadataTooLarge {
+            max: MAX_METADATA_LEN,
+            ..
+        }
+    ));
+    assert!(!std::path::Path::new(path).exists());
+}
+
+#[test]
+fn test_file_without_metadata() {
+    let path = "metadata_missing_test.log";
+    fs::write(path, vec![0u8; 4096]).unwrap();
+    let err = Logger::<TestData>::read_metadata(path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    cleanup(path);
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "metrics")]
+
+use metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use picologger::{Logger, LoggerConfig};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+/// Counts the samples recorded into a histogram.
+struct Samples(AtomicU64);
+
+impl HistogramFn for Samples {
+    fn record(&self, _value: f64) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Keeps every metric registered, keyed by `<name>{<logger label>}`.
+#[derive(Default)]
+struct TestRecorder {
+    values: Mutex<HashMap<String, Arc<AtomicU64>>>,
+    samples: Mutex<HashMap<String, Arc<Samples>>>,
+}
+
+fn key_string(key: &Key) -> String {
+    let labels: Vec<_> = key
+        .labels()
+        .map(|label| format!("{}={}", label.key(), label.value()))
+        .collect();
+    format!("{}{{{}}}", key.name(), labels.join(","))
+}
+
+impl TestRecorder {
+    fn value(&self, key: &str) -> u64 {
+        self.values.lock().unwrap()[key].load(Ordering::Relaxed)
+    }
+
+    fn gauge(&self, key: &str) -> f64 {
+        f64::from_bits(self.value(key))
+    }
+
+    fn samples(&self, key: &str) -> u64 {
+        self.samples.lock().unwrap()[key].0.load(Ordering::Relaxed)
+    }
+
+    fn atomic(&self, key: &Key) -> Arc<AtomicU64> {
+        let mut values = self.values.lock().unwrap();
+        values.entry(key_string(key)).or_default().clone()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.atomic(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.atomic(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        let mut samples = self.samples.lock().unwrap();
+        let samples = samples
+            .entry(key_string(key))
+            .or_insert_with(|| Arc::new(Samples(AtomicU64::new(0))));
+        Histogram::from_arc(samples.clone())
+    }
+}
+
+fn started(recorder: &TestRecorder, logger: Logger<TestData>) -> Logger<TestData> {
+    let mut logger = logger;
+    metrics::with_local_recorder(recorder, || logger.start().unwrap());
+    logger
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "{what} never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_worker_publishes_its_counters() {
+    let path = "metrics_counters_test.log";
+    let _ = fs::remove_file(path);
+    let recorder = TestRecorder::default();
+    let logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_name("orders-wal");
+    let mut logger = started(&recorder, logger);
+    for id in 0..1000 {
+        logger.log(TestData { id, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+    wait_until("the flush", || logger.stats().entries_flushed == 1000);
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    let label = "{logger=orders-wal}";
+    assert_eq!(
+        recorder.value(&format!("picolog_entries_total{label}")),
+        1000
+    );
+    assert_eq!(
+        recorder.value(&format!("picolog_pages_flushed_total{label}")),
+        stats.pages_flushed
+    );
+    assert_eq!(
+        recorder.value(&format!("picolog_bytes_written_total{label}")),
+        stats.bytes_written
+    );
+    assert_eq!(
+        recorder.value(&format!("picolog_write_errors_total{label}")),
+        0
+    );
+    assert_eq!(
+        recorder.gauge(&format!("picolog_pending_pages{label}")),
+        0.0
+    );
+    assert_eq!(
+        recorder.samples(&format!("picolog_flush_duration_seconds{label}")),
+        stats.pages_flushed
+    );
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_metrics_are_labelled_with_the_path_by_default() {
+    let path = "metrics_path_label_test.log";
+    let _ = fs::remove_file(path);
+    let recorder = TestRecorder::default();
+    let logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+    let mut logger = started(&recorder, logger);
+    logger.log(TestData { id: 1, val: 0 }).unwrap();
+    logger.shutdown().unwrap();
+
+    let key = format!("picolog_entries_total{{logger={path}}}");
+    assert_eq!(recorder.value(&key), 1);
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,131 @@
+#![cfg(unix)]
+
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, count: u64) {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build::<TestData>()
+        .unwrap()
+        .with_index(true);
+    logger.start().unwrap();
+    for i in 0..count {
+        logger.log(TestData {
+            id: i,
+            val: i as u32 * 7,
+        });
+    }
+}
+
+#[test]
+fn test_mmap_reader_matches_streaming_reader() {
+    let path = "mmap_match_test.log";
+    cleanup(path);
+    write_entries(path, 5000);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let streamed: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    let mapped: Vec<_> = logger.read_mmap().unwrap().map(Result::unwrap).collect();
+    assert_eq!(mapped.len(), 5000);
+    assert_eq!(mapped, streamed);
+
+    cleanup(path);
+    cleanup(&format!("{}.idx", path));
+}
+
+#[test]
+fn test_mmap_raw_payloads() {
+    let path = "mmap_raw_test.log";
+    cleanup(path);
+    write_entries(path, 300);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let mut reader = logger.read_mmap().unwrap();
+    let mut count = 0u64;
+    while let Some(entry) = reader.next_raw().unwrap() {
+        assert_eq!(entry.payload.len(), size_of::<TestData>());
+        assert_eq!(entry.payload[..8], count.to_ne_bytes());
+        count += 1;
+    }
+    assert_eq!(count, 300);
+
+    cleanup(path);
+    cleanup(&format!("{}.idx", path));
+}
+
+#[test]
+fn test_mmap_seek() {
+    let path = "mmap_seek_test.log";
+    cleanup(path);
+    write_entries(path, 3000);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    // Other tests log concurrently, so seq_ids need not be contiguous.
+    let target = logger
+        .read_iter()
+        .unwrap()
+        .nth(2500)
+        .unwrap()
+        .unwrap()
+        .seq_id;
+    let mut reader = logger.read_mmap().unwrap();
+    reader.seek(target).unwrap();
+    let entry = reader.next().unwrap().unwrap();
+    assert_eq!(entry.seq_id, target);
+    assert_eq!(entry.data.id, 2500);
+
+    cleanup(path);
+    cleanup(&format!("{}.idx", path));
+}
+
+#[test]
+fn test_mmap_stops_at_truncated_end() {
+    let path = "mmap_truncate_test.log";
+    cleanup(path);
+    write_entries(path, 8000);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let reader = logger.read_mmap().unwrap();
+    // Opening the reader checked the length for the first 64 KiB window, so truncate past
+    // it: the next check happens when the reader crosses into a new window.
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_len(128 * 1024)
+        .unwrap();
+    let entries: Vec<_> = reader.map(Result::unwrap).collect();
+    assert!(!entries.is_empty() && entries.len() < 8000);
+    for (i, entry) in entries.iter().enumerate() {
+        assert_eq!(entry.data.id, i as u64);
+    }
+
+    cleanup(path);
+    cleanup(&format!("{}.idx", path));
+}
+
+#[test]
+fn test_mmap_empty_file() {
+    let path = "mmap_empty_test.log";
+    fs::write(path, []).unwrap();
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read_mmap().unwrap().count(), 0);
+
+    cleanup(path);
+}
@@ -0,0 +1,105 @@
+use picologger::testing::FaultInjector;
+use picologger::{BufferFullPolicy, Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Holds up the worker for a while on every completed page write, so producers outrun it.
+struct SlowWorker;
+
+impl FaultInjector for SlowWorker {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        std::thread::sleep(Duration::from_millis(2));
+        result
+    }
+}
+
+fn slow_logger(path: &str, policy: BufferFullPolicy) -> Logger<TestData> {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(64)
+        .build::<TestData>()
+        .unwrap()
+        .with_pages(2)
+        .with_fault_injector(Arc::new(SlowWorker))
+        .with_buffer_full_policy(policy);
+    logger.start().unwrap();
+    logger
+}
+
+fn read_back(path: &str) -> Vec<(u64, u64)> {
+    Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .with_strict_reads(true)
+        .read_iter()
+        .unwrap()
+        .map(|e| {
+            let e = e.unwrap();
+            (e.seq_id, e.data.id)
+        })
+        .collect()
+}
+
+#[test]
+fn test_blocking_producer_loses_nothing() {
+    let path = "no_loss_block_test.log";
+    let mut logger = slow_logger(path, BufferFullPolicy::Block);
+    let mut logged = Vec::new();
+    for id in 0..20_000 {
+        logged.push((logger.try_log(TestData { id, val: 0 }).unwrap(), id));
+    }
+    for batch in 0..100 {
+        let items: Vec<_> = (0..50)
+            .map(|i| TestData {
+                id: 20_000 + batch * 50 + i,
+                val: 0,
+            })
+            .collect();
+        let seq_ids = logger.try_log_batch(&items).unwrap();
+        logged.extend(seq_ids.zip(items.iter().map(|item| item.id)));
+    }
+    logger.shutdown().unwrap();
+
+    assert_eq!(logger.stats().dropped_entries, 0);
+    assert_eq!(read_back(path), logged);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_rejecting_producer_loses_nothing_it_was_given_a_seq_id_for() {
+    let path = "no_loss_reject_test.log";
+    let mut logger = slow_logger(path, BufferFullPolicy::Reject);
+    let mut logged = Vec::new();
+    let mut rejected = 0;
+    let mut id = 0;
+    while logged.len() < 20_000 {
+        match logger.try_log(TestData { id, val: 0 }) {
+            Ok(seq_id) => logged.push((seq_id, id)),
+            Err(PicoError::BufferFull) => rejected += 1,
+            Err(e) => panic!("{e}"),
+        }
+        id += 1;
+    }
+    logger.shutdown().unwrap();
+
+    assert!(rejected > 0);
+    assert_eq!(logger.stats().dropped_entries, 0);
+    assert_eq!(read_back(path), logged);
+
+    cleanup(path);
+}
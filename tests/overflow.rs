@@ -0,0 +1,153 @@
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig, LoggerStats, OverflowPolicy, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+const SLOW_ATTEMPTS: u32 = 20;
+
+/// Fails every page write with EAGAIN a number of times before letting it through, so pages
+/// stay in flight long enough for the worker to run out of them.
+struct SlowDisk;
+
+impl FaultInjector for SlowDisk {
+    fn on_write_complete(&self, _offset: u64, attempt: u32, result: i32) -> i32 {
+        if attempt < SLOW_ATTEMPTS {
+            -libc::EAGAIN
+        } else {
+            result
+        }
+    }
+}
+
+struct Outcome {
+    stats: LoggerStats,
+    errors: Vec<PicoError>,
+    entries: Vec<TestData>,
+}
+
+/// Logs 1000 entries through a two-page pool on a slow disk.
+fn log_on_slow_disk(path: &str, policy: OverflowPolicy) -> Outcome {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .pages(2)
+        .build::<TestData>()
+        .unwrap()
+        .with_overflow_policy(policy)
+        .with_write_retries(SLOW_ATTEMPTS)
+        .with_fault_injector(Arc::new(SlowDisk));
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    for i in 0..1000 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(20);
+    let stats = loop {
+        let stats = logger.stats();
+        if stats.entries_flushed + stats.dropped_entries >= 1000 {
+            break stats;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "entries never settled: {:?}",
+            stats
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries = reader.read().unwrap();
+    cleanup(path);
+    Outcome {
+        stats,
+        errors: errors.try_iter().collect(),
+        entries,
+    }
+}
+
+fn assert_in_order(entries: &[TestData]) {
+    assert!(entries.windows(2).all(|pair| pair[0].id < pair[1].id));
+}
+
+#[test]
+fn test_block_counts_stall_time() {
+    let outcome = log_on_slow_disk("overflow_block.log", OverflowPolicy::Block);
+    assert_eq!(outcome.entries.len(), 1000);
+    assert_eq!(outcome.stats.dropped_entries, 0);
+    assert!(outcome.stats.stall_time > Duration::ZERO);
+    assert_eq!(outcome.stats.pages_grown, 0);
+}
+
+#[test]
+fn test_drop_newest_reports_dropped_ranges() {
+    let outcome = log_on_slow_disk("overflow_drop.log", OverflowPolicy::DropNewest);
+    assert!(outcome.stats.dropped_entries > 0);
+    assert_eq!(outcome.stats.stall_time, Duration::ZERO);
+    assert_eq!(
+        outcome.entries.len() as u64 + outcome.stats.dropped_entries,
+        1000
+    );
+    assert_in_order(&outcome.entries);
+
+    let mut reported = 0;
+    for error in &outcome.errors {
+        match error {
+            PicoError::EntriesDropped {
+                first_seq_id,
+                last_seq_id,
+                count,
+            } => {
+                assert!(first_seq_id <= last_seq_id);
+                reported += count;
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+    assert_eq!(reported, outcome.stats.dropped_entries);
+}
+
+#[test]
+fn test_grow_adds_pages_up_to_the_cap() {
+    let outcome = log_on_slow_disk("overflow_grow.log", OverflowPolicy::Grow { max_pages: 6 });
+    assert_eq!(outcome.entries.len(), 1000);
+    assert_in_order(&outcome.entries);
+    assert_eq!(outcome.stats.dropped_entries, 0);
+    assert!(outcome.stats.pages_grown > 0 && outcome.stats.pages_grown <= 4);
+}
+
+#[test]
+fn test_grow_cap_below_page_count_is_rejected() {
+    let mut logger = LoggerConfig::new()
+        .path("overflow_invalid.log")
+        .capacity(64)
+        .pages(8)
+        .build::<TestData>()
+        .unwrap()
+        .with_overflow_policy(OverflowPolicy::Grow { max_pages: 4 });
+    assert!(matches!(
+        logger.start(),
+        Err(PicoError::InvalidPageCount {
+            count: 4,
+            min: 8,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,177 @@
+use picologger::{Logger, LoggerConfig, PicoError, inspect};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Large([u8; 6000]);
+
+impl Default for Large {
+    fn default() -> Self {
+        Self([0; 6000])
+    }
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str) -> Logger<TestData> {
+    LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build::<TestData>()
+        .unwrap()
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[test]
+fn test_large_pages_round_trip() {
+    let path = "page_size_large_test.log";
+    cleanup(path);
+    {
+        let mut logger = logger(path).with_page_size(64 * 1024);
+        logger.start().unwrap();
+        for i in 0..5000 {
+            logger.log(TestData { id: i, val: 1 }).unwrap();
+        }
+    }
+
+    let bytes = fs::read(path).unwrap();
+    // The file header records the page size; pages follow it 64 KiB apart.
+    assert_eq!(u32_at(&bytes, 96), 64 * 1024);
+    let per_page = (64 * 1024 - 40) / 40;
+    for page in 0..3 {
+        let start = 4096 + page * 64 * 1024;
+        assert_eq!(&bytes[start..start + 8], b"PICOMETA");
+        assert_eq!(u32_at(&bytes, start + 24), page as u32);
+        assert_eq!(u32_at(&bytes, start + 28), per_page as u32);
+    }
+    assert_eq!(bytes.len(), 4096 + 4 * 64 * 1024);
+
+    let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+    let ids: Vec<_> = reader.read().unwrap().iter().map(|data| data.id).collect();
+    assert_eq!(ids, (0..5000).collect::<Vec<_>>());
+    let last: Vec<_> = reader
+        .read_last(per_page + 10)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.data.id)
+        .collect();
+    assert_eq!(
+        last,
+        (5000 - per_page as u64 - 10..5000).collect::<Vec<_>>()
+    );
+
+    let report = inspect::verify(path).unwrap();
+    assert_eq!(report.page_size, Some(64 * 1024));
+    assert_eq!(report.entries, 5000);
+    assert_eq!(report.corruption, None);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_default_page_size_is_recorded() {
+    let path = "page_size_default_test.log";
+    cleanup(path);
+    {
+        let mut logger = logger(path);
+        logger.start().unwrap();
+        logger.log(TestData { id: 0, val: 0 }).unwrap();
+    }
+
+    assert_eq!(u32_at(&fs::read(path).unwrap(), 96), 4096);
+    assert_eq!(inspect::verify(path).unwrap().page_size, Some(4096));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_large_payload_fits_a_chosen_page_size() {
+    let path = "page_size_payload_test.log";
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(64)
+            .build::<Large>()
+            .unwrap()
+            .with_page_size(32 * 1024);
+        logger.start().unwrap();
+        for i in 0..20 {
+            logger.log(Large([i; 6000])).unwrap();
+        }
+    }
+
+    let entries = Logger::<Large>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap();
+    assert_eq!(entries.len(), 20);
+    assert!(entries.iter().enumerate().all(|(i, e)| e.0[0] == i as u8));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_invalid_page_sizes_are_rejected() {
+    let path = "page_size_invalid_test.log";
+    cleanup(path);
+
+    for size in [0, 6000, 4096 + 512, 128 * 1024 * 1024] {
+        let err = logger(path).with_page_size(size).start().unwrap_err();
+        assert!(
+            matches!(err, PicoError::InvalidPageSize { size: found, block_size: 4096, .. } if found == size),
+            "{size}: {err}"
+        );
+    }
+    // A page has to hold at least one entry.
+    let err = LoggerConfig::new()
+        .path(path)
+        .build::<Large>()
+        .unwrap()
+        .with_page_size(4096)
+        .start()
+        .unwrap_err();
+    assert!(matches!(err, PicoError::InvalidPageSize { min: 8192, .. }));
+    assert!(!std::path::Path::new(path).exists());
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_large_compressed_pages_round_trip() {
+    use picologger::Compression;
+
+    let path = "page_size_lz4_test.log";
+    cleanup(path);
+    {
+        let mut logger = logger(path)
+            .with_compression(Compression::Lz4)
+            .with_page_size(128 * 1024);
+        logger.start().unwrap();
+        for i in 0..5000 {
+            logger.log(TestData { id: i, val: 7 }).unwrap();
+        }
+    }
+
+    let reader = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .with_compression(Compression::Lz4);
+    let ids: Vec<_> = reader.read().unwrap().iter().map(|data| data.id).collect();
+    assert_eq!(ids, (0..5000).collect::<Vec<_>>());
+    assert_eq!(reader.last_entry().unwrap().unwrap().data.id, 4999);
+
+    cleanup(path);
+}
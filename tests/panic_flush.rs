@@ -0,0 +1,120 @@
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// Panic hooks are process-wide, so tests that install one take turns.
+static HOOKS: Mutex<()> = Mutex::new(());
+
+/// Puts back the panic hook that was set when it was created, along with dropping whatever
+/// the test chained onto it.
+struct RestoreHook {
+    previous: Option<Hook>,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl RestoreHook {
+    fn new() -> Self {
+        let turn = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            previous: Some(panic::take_hook()),
+            _turn: turn,
+        }
+    }
+}
+
+impl Drop for RestoreHook {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            panic::set_hook(previous);
+        }
+    }
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "{what} never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn logger(path: &str) -> Logger<TestData> {
+    LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap()
+}
+
+#[test]
+fn test_panic_flushes_before_unwinding() {
+    let path = "panic_flush_test.log";
+    cleanup(path);
+    let _restore = RestoreHook::new();
+    let mut logger = logger(path);
+    assert!(matches!(
+        logger.install_panic_flush(),
+        Err(PicoError::NotStarted)
+    ));
+    logger.start().unwrap();
+    logger.install_panic_flush().unwrap();
+    logger.install_panic_flush().unwrap();
+    for i in 0..50 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+
+    // With a minute-long flush interval, only the hook writes these out before the logger
+    // is looked at again.
+    let result = thread::spawn(|| panic!("boom")).join();
+    assert!(result.is_err());
+    assert_eq!(logger.stats().entries_flushed, 50);
+
+    logger.shutdown().unwrap();
+    // Once shut down, the hook leaves the logger alone.
+    let stats = logger.stats();
+    assert!(thread::spawn(|| panic!("again")).join().is_err());
+    assert_eq!(logger.stats(), stats);
+    assert_eq!(logger.read().unwrap().len(), 50);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_worker_panic_does_not_wait_on_itself() {
+    let path = "panic_flush_worker_test.log";
+    cleanup(path);
+    let _restore = RestoreHook::new();
+    let mut logger = logger(path).with_flush_callback(|_| panic!("callback failed"));
+    logger.start().unwrap();
+    logger.install_panic_flush().unwrap();
+    logger.log(TestData { id: 1, val: 0 }).unwrap();
+    logger.flush().unwrap();
+
+    // The worker's hook returns without waiting on the worker, so it goes on to unwind.
+    wait_until("the worker panic", || !logger.is_healthy());
+    // A panic elsewhere now finds no worker to flush.
+    let stats = logger.stats();
+    assert!(thread::spawn(|| panic!("after the worker")).join().is_err());
+    assert_eq!(logger.stats(), stats);
+    let _ = logger.shutdown();
+
+    cleanup(path);
+}
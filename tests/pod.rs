@@ -0,0 +1,100 @@
+#![cfg(feature = "pod")]
+
+use bytemuck::{Pod, Zeroable};
+use picologger::{BufferFullPolicy, Logger, LoggerConfig};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+struct Tick {
+    id: u64,
+    price: u32,
+    qty: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_pod_round_trip() {
+    let path = "pod_round_trip.log";
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .build_pod::<Tick>()
+            .unwrap()
+            .with_buffer_full_policy(BufferFullPolicy::Block);
+        logger.start().unwrap();
+        for id in 0..1000 {
+            logger
+                .log(Tick {
+                    id,
+                    price: id as u32 * 3,
+                    qty: 7,
+                })
+                .unwrap();
+        }
+        logger.shutdown().unwrap();
+    }
+
+    let reader = Logger::<Tick>::new().with_read_config(path);
+    let ticks = reader.read_pod().unwrap();
+    assert_eq!(ticks.len(), 1000);
+    assert_eq!(ticks, reader.read().unwrap());
+    for (i, tick) in ticks.iter().enumerate() {
+        assert_eq!(tick.id, i as u64);
+        assert_eq!(tick.price, i as u32 * 3);
+    }
+
+    let entries: Vec<_> = reader
+        .read_pod_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 1000);
+    assert!(entries.windows(2).all(|w| w[0].seq_id < w[1].seq_id));
+    cleanup(path);
+}
+
+#[test]
+fn test_pod_pages_read_back() {
+    use picologger::raw::PageWriter;
+    use std::os::unix::fs::FileExt;
+
+    let path = "pod_pages.log";
+    cleanup(path);
+    let file = fs::File::create(path).unwrap();
+    let mut writer = PageWriter::<Tick>::new(4096, None).unwrap();
+    file.write_all_at(writer.header().get_page_content(), 0)
+        .unwrap();
+    let mut page = writer.page();
+    for id in 1..=10 {
+        page.append(
+            id,
+            0,
+            0,
+            &Tick {
+                id,
+                price: 5,
+                qty: 1,
+            },
+        )
+        .unwrap();
+    }
+    let slot = writer.seal(&mut page);
+    file.write_all_at(page.get_page_content(), slot.offset)
+        .unwrap();
+    drop(file);
+
+    let ticks = Logger::<Tick>::new()
+        .with_read_config(path)
+        .read_pod()
+        .unwrap();
+    assert_eq!(ticks.len(), 10);
+    assert!(ticks.iter().zip(1..).all(|(tick, id)| tick.id == id));
+    cleanup(path);
+}
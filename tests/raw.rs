@@ -0,0 +1,196 @@
+use picologger::raw::{ENTRY_LEVEL_OFFSET, ENTRY_SEQ_ID_OFFSET, PAGE_META_LEN, PageWriter};
+use picologger::{Level, Logger, PicoError};
+use std::fs::{self, File};
+use std::os::unix::fs::FileExt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Lays out `data` the way `TestData` is read back: its fields in order, then the padding
+/// that rounds it up to 16 bytes, zeroed.
+fn encode(data: &TestData) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16);
+    bytes.extend_from_slice(&data.id.to_ne_bytes());
+    bytes.extend_from_slice(&data.val.to_ne_bytes());
+    bytes.resize(16, 0);
+    bytes
+}
+
+fn read_back(path: &str) -> Vec<(u64, Level, TestData)> {
+    Logger::<TestData>::new()
+        .with_read_config(path)
+        .read_iter()
+        .unwrap()
+        .map(Result::unwrap)
+        .map(|entry| (entry.seq_id, entry.level, entry.data))
+        .collect()
+}
+
+#[test]
+fn test_pages_written_by_hand_read_back() {
+    let path = "raw_pwrite.log";
+    cleanup(path);
+    let file = File::create(path).unwrap();
+    let mut writer = PageWriter::<TestData>::new(4096, None).unwrap();
+    file.write_all_at(writer.header().get_page_content(), 0)
+        .unwrap();
+
+    let mut page = writer.page();
+    let mut seq_id = 1;
+    let mut slots = Vec::new();
+    for _ in 0..2 {
+        loop {
+            let data = TestData { id: seq_id, val: 1 };
+            match page.append_bytes(seq_id, 0, Level::Warn as u8, &encode(&data)) {
+                Ok(()) => seq_id += 1,
+                Err(PicoError::PageFull {}) => break,
+                Err(e) => panic!("{e}"),
+            }
+        }
+        let slot = writer.seal(&mut page);
+        file.write_all_at(page.get_page_content(), slot.offset)
+            .unwrap();
+        slots.push(slot);
+        page.reset();
+    }
+    assert_eq!(slots[0].offset, 4096);
+    assert_eq!(slots[1].offset, 4096 + writer.page_size() as u64);
+    assert_eq!(slots[1].page_id, 1);
+    assert_eq!(writer.next_offset(), 4096 + 2 * writer.page_size() as u64);
+    drop(file);
+
+    let entries = read_back(path);
+    assert_eq!(entries.len() as u64, seq_id - 1);
+    for (i, (seq_id, level, data)) in entries.into_iter().enumerate() {
+        assert_eq!(seq_id, i as u64 + 1);
+        assert_eq!(level, Level::Warn);
+        assert_eq!(data, TestData { id: seq_id, val: 1 });
+    }
+    cleanup(path);
+}
+
+#[test]
+fn test_entry_header_constants_match_the_page() {
+    let writer = PageWriter::<TestData>::new(4096, None).unwrap();
+    let mut page = writer.page();
+    page.append_bytes(
+        0x0102_0304,
+        9,
+        Level::Error as u8,
+        &encode(&TestData::default()),
+    )
+    .unwrap();
+
+    let entry = &page.get_page_content()[PAGE_META_LEN..];
+    let seq_id = &entry[ENTRY_SEQ_ID_OFFSET..][..8];
+    assert_eq!(seq_id, &0x0102_0304u64.to_le_bytes());
+    assert_eq!(entry[ENTRY_LEVEL_OFFSET], Level::Error as u8);
+}
+
+#[test]
+fn test_entries_shorter_than_t_fail_the_read() {
+    let path = "raw_short_entry.log";
+    cleanup(path);
+    let file = File::create(path).unwrap();
+    let mut writer = PageWriter::<TestData>::new(4096, None).unwrap();
+    file.write_all_at(writer.header().get_page_content(), 0)
+        .unwrap();
+    let mut page = writer.page();
+    page.append_bytes(1, 0, 0, &encode(&TestData { id: 1, val: 1 }))
+        .unwrap();
+    page.append_bytes(2, 0, 0, &[7; 4]).unwrap();
+    let slot = writer.seal(&mut page);
+    file.write_all_at(page.get_page_content(), slot.offset)
+        .unwrap();
+    drop(file);
+
+    let mut entries = Logger::<TestData>::new()
+        .with_read_config(path)
+        .read_iter()
+        .unwrap();
+    assert_eq!(entries.next().unwrap().unwrap().seq_id, 1);
+    let err = entries.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(
+        PicoError::from(err),
+        PicoError::EntrySizeMismatch { seq_id: 2, len: 4, expected }
+            if expected == std::mem::size_of::<TestData>()
+    ));
+    assert!(entries.next().is_none());
+    cleanup(path);
+}
+
+#[test]
+fn test_invalid_sizes_are_rejected() {
+    assert!(matches!(
+        PageWriter::<TestData>::new(1000, None),
+        Err(PicoError::InvalidBlockSize { size: 1000 })
+    ));
+    assert!(matches!(
+        PageWriter::<TestData>::new(4096, Some(6000)),
+        Err(PicoError::InvalidPageSize { size: 6000, .. })
+    ));
+    let writer = PageWriter::<[u8; 8192]>::new(4096, None).unwrap();
+    assert_eq!(writer.page_size(), 12288);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_pages_submitted_on_a_caller_ring_read_back() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = "raw_uring.log";
+    cleanup(path);
+    let file = File::create(path).unwrap();
+    let fd = file.as_raw_fd();
+    let mut ring = io_uring::IoUring::new(8).unwrap();
+    let mut writer = PageWriter::<TestData>::new(4096, Some(8192)).unwrap();
+
+    let header = writer.header();
+    let mut pages = [writer.page(), writer.page()];
+    let mut seq_id = 10;
+    let mut entries = Vec::new();
+    for page in &mut pages {
+        for _ in 0..3 {
+            page.append_bytes(
+                seq_id,
+                0,
+                Level::Info as u8,
+                &encode(&TestData { id: seq_id, val: 2 }),
+            )
+            .unwrap();
+            entries.push(seq_id);
+            seq_id += 1;
+        }
+    }
+    let slots = pages.each_mut().map(|page| writer.seal(page));
+    unsafe {
+        let mut sq = ring.submission();
+        sq.push(&writer.header_sqe(&header, fd)).unwrap();
+        for (page, slot) in pages.iter().zip(slots) {
+            sq.push(&writer.page_sqe(page, slot, fd)).unwrap();
+        }
+    }
+    ring.submit_and_wait(3).unwrap();
+    let mut completed = 0;
+    for cqe in ring.completion() {
+        assert!(cqe.result() > 0, "write failed: {}", cqe.result());
+        completed += 1;
+    }
+    assert_eq!(completed, 3);
+    drop(file);
+
+    let read: Vec<u64> = read_back(path).into_iter().map(|(id, ..)| id).collect();
+    assert_eq!(read, entries);
+    cleanup(path);
+}
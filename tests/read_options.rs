@@ -0,0 +1,90 @@
+use picologger::{BufferFullPolicy, DEFAULT_READ_CHUNK, Logger, LoggerConfig, ReadOptions};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write(path: &str, page_size: Option<usize>, count: u64) {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_buffer_full_policy(BufferFullPolicy::Block);
+    if let Some(size) = page_size {
+        logger = logger.with_page_size(size);
+    }
+    logger.start().unwrap();
+    for id in 0..count {
+        logger
+            .log(TestData {
+                id,
+                val: id as u32 * 7,
+            })
+            .unwrap();
+    }
+}
+
+fn read_with_chunks(path: &str, chunk_size: usize) -> Vec<TestData> {
+    Logger::<TestData>::new()
+        .with_read_config(path)
+        .with_read_options(ReadOptions { chunk_size })
+        .read()
+        .unwrap()
+}
+
+#[test]
+fn test_default_chunk_size() {
+    assert_eq!(ReadOptions::default().chunk_size, DEFAULT_READ_CHUNK);
+    assert_eq!(DEFAULT_READ_CHUNK, 1024 * 1024);
+}
+
+#[test]
+fn test_any_chunk_size_reads_every_entry() {
+    for (path, page_size) in [
+        ("read_options_default_pages.log", None),
+        ("read_options_large_pages.log", Some(64 * 1024)),
+    ] {
+        write(path, page_size, 20_000);
+        let expected = read_with_chunks(path, DEFAULT_READ_CHUNK);
+        assert_eq!(expected.len(), 20_000);
+        for (i, entry) in expected.iter().enumerate() {
+            assert_eq!(entry.id, i as u64);
+        }
+        // Chunks smaller than an entry, cutting pages and entries at odd offsets, and
+        // larger than the whole file.
+        for chunk_size in [0, 1, 100, 4095, 4096, 5000, 100_000, 64 << 20] {
+            assert_eq!(
+                read_with_chunks(path, chunk_size),
+                expected,
+                "chunk size {chunk_size}, page size {page_size:?}"
+            );
+        }
+        cleanup(path);
+    }
+}
+
+#[test]
+fn test_iterating_with_small_chunks() {
+    let path = "read_options_iter.log";
+    write(path, None, 5000);
+    let logger = Logger::<TestData>::new()
+        .with_read_config(path)
+        .with_read_options(ReadOptions { chunk_size: 777 });
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 5000);
+    let mut reader = logger.read_iter().unwrap();
+    reader.seek(entries[4321].seq_id).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap().data.id, 4321);
+    cleanup(path);
+}
@@ -0,0 +1,109 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::ops::ControlFlow;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, count: u64) {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for i in 0..count {
+        logger
+            .log(TestData {
+                id: i,
+                val: i as u32 * 3,
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_replay_visits_every_entry() {
+    let path = "replay_all_test.log";
+    cleanup(path);
+    write_entries(path, 5000);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let expected: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    let mut visited = Vec::new();
+    let summary = logger
+        .replay(|seq_id, ts_nanos, data| {
+            visited.push((seq_id, ts_nanos, *data));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+    assert_eq!(visited.len(), 5000);
+    for ((seq_id, ts_nanos, data), entry) in visited.iter().zip(&expected) {
+        assert_eq!(
+            (*seq_id, *ts_nanos, *data),
+            (entry.seq_id, entry.ts_nanos, entry.data)
+        );
+    }
+    assert_eq!(summary.entries, 5000);
+    assert_eq!(summary.last_seq_id, Some(expected.last().unwrap().seq_id));
+    assert!(summary.bytes_scanned > 5000 * size_of::<TestData>() as u64);
+    assert!(summary.bytes_scanned <= fs::metadata(path).unwrap().len());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_replay_stops_on_break() {
+    let path = "replay_break_test.log";
+    cleanup(path);
+    write_entries(path, 3000);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let full = logger.replay(|_, _, _| ControlFlow::Continue(())).unwrap();
+    let mut ids = Vec::new();
+    let summary = logger
+        .replay(|_, _, data| {
+            ids.push(data.id);
+            if data.id == 9 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    assert_eq!(summary.entries, 10);
+    assert!(summary.bytes_scanned < full.bytes_scanned);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_replay_empty_file() {
+    let path = "replay_empty_test.log";
+    cleanup(path);
+    write_entries(path, 0);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let summary = logger
+        .replay(|_, _, _| panic!("no entries expected"))
+        .unwrap();
+    assert_eq!(summary.entries, 0);
+    assert_eq!(summary.last_seq_id, None);
+    // Reading starts past the file header.
+    assert!(summary.bytes_scanned >= 4096);
+
+    cleanup(path);
+}
@@ -0,0 +1,120 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str, capacity: usize) -> Logger<TestData> {
+    LoggerConfig::new()
+        .path(path)
+        .capacity(capacity)
+        .build::<TestData>()
+        .unwrap()
+}
+
+#[test]
+fn test_reserve_before_start() {
+    let mut logger = logger("reserve_unstarted.log", 16);
+    assert!(logger.reserve().is_none());
+}
+
+#[test]
+fn test_reserve_commit_round_trip() {
+    let path = "reserve_round_trip.log";
+    cleanup(path);
+
+    let mut seq_ids = Vec::new();
+    {
+        let mut logger = logger(path, 1024);
+        logger.start().unwrap();
+        for i in 0..500 {
+            let mut slot = logger.reserve().unwrap();
+            slot.id = i;
+            slot.val = i as u32 * 3;
+            seq_ids.push(slot.commit().unwrap());
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 500);
+    for ((entry, &seq_id), i) in entries.iter().zip(&seq_ids).zip(0..) {
+        assert_eq!(entry.seq_id, seq_id);
+        assert_eq!(
+            entry.data,
+            TestData {
+                id: i,
+                val: i as u32 * 3
+            }
+        );
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_abandoned_reservation_is_not_written() {
+    let path = "reserve_abandoned.log";
+    cleanup(path);
+
+    {
+        let mut logger = logger(path, 64);
+        logger.start().unwrap();
+        logger.log(TestData { id: 1, val: 1 }).unwrap();
+        {
+            let mut slot = logger.reserve().unwrap();
+            slot.id = 99;
+        }
+        logger.log(TestData { id: 2, val: 2 }).unwrap();
+        let mut slot = logger.reserve().unwrap();
+        *slot = TestData { id: 3, val: 3 };
+        slot.commit().unwrap();
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let ids: Vec<u64> = logger.read().unwrap().iter().map(|data| data.id).collect();
+    assert_eq!(ids, [1, 2, 3]);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_reserve_never_overwrites_unconsumed_slots() {
+    let path = "reserve_small_ring.log";
+    cleanup(path);
+
+    {
+        // A ring this small fills up constantly, so reservations have to wait for the worker.
+        let mut logger = logger(path, 4);
+        logger.start().unwrap();
+        let mut i = 0;
+        while i < 5000 {
+            let Some(mut slot) = logger.reserve() else {
+                std::thread::yield_now();
+                continue;
+            };
+            slot.id = i;
+            slot.commit().unwrap();
+            i += 1;
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 5000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
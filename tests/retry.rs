@@ -0,0 +1,168 @@
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Offset of the first page: the file header takes up the first 4 KiB block.
+const FIRST_PAGE: u64 = 4096;
+
+/// Fails the first `failures` attempts of the write of the first page with EAGAIN.
+struct FlakyFirstPage {
+    failures: u32,
+    attempts: Mutex<Vec<u32>>,
+}
+
+impl FaultInjector for FlakyFirstPage {
+    fn on_write_complete(&self, offset: u64, attempt: u32, result: i32) -> i32 {
+        if offset != FIRST_PAGE {
+            return result;
+        }
+        self.attempts.lock().unwrap().push(attempt);
+        if attempt < self.failures {
+            -libc::EAGAIN
+        } else {
+            result
+        }
+    }
+}
+
+fn write_entries(path: &str, injector: Arc<FlakyFirstPage>, retries: u32) -> Vec<PicoError> {
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_write_retries(retries)
+        .with_fault_injector(injector);
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    for i in 0..500 {
+        logger.log(TestData {
+            id: i as u64,
+            val: i as u32,
+        });
+    }
+    drop(logger);
+    errors.try_iter().collect()
+}
+
+#[test]
+fn test_page_persists_after_transient_failures() {
+    let path = "retry_transient_test.log";
+    cleanup(path);
+
+    let injector = Arc::new(FlakyFirstPage {
+        failures: 2,
+        attempts: Mutex::new(Vec::new()),
+    });
+    let errors = write_entries(path, injector.clone(), 3);
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    assert_eq!(*injector.attempts.lock().unwrap(), vec![0, 1, 2]);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 500);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_page_reported_after_retries_run_out() {
+    let path = "retry_exhausted_test.log";
+    cleanup(path);
+
+    let injector = Arc::new(FlakyFirstPage {
+        failures: u32::MAX,
+        attempts: Mutex::new(Vec::new()),
+    });
+    let errors = write_entries(path, injector.clone(), 2);
+    assert_eq!(*injector.attempts.lock().unwrap(), vec![0, 1, 2]);
+    assert_eq!(errors.len(), 1);
+    match errors[0] {
+        PicoError::WriteFailed { errno, offset, .. } => {
+            assert_eq!(errno, libc::EAGAIN);
+            assert_eq!(offset, FIRST_PAGE);
+        }
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+
+    cleanup(path);
+}
+
+/// Reports the first attempt of the first page's write as having written only 100 bytes.
+struct ShortFirstWrite {
+    attempts: Mutex<Vec<u32>>,
+}
+
+impl FaultInjector for ShortFirstWrite {
+    fn on_write_complete(&self, offset: u64, attempt: u32, result: i32) -> i32 {
+        if offset != FIRST_PAGE {
+            return result;
+        }
+        self.attempts.lock().unwrap().push(attempt);
+        if attempt == 0 {
+            result.min(100)
+        } else {
+            result
+        }
+    }
+}
+
+#[test]
+fn test_short_write_is_resubmitted() {
+    let path = "retry_short_write_test.log";
+    cleanup(path);
+
+    let injector = Arc::new(ShortFirstWrite {
+        attempts: Mutex::new(Vec::new()),
+    });
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(4096)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_fault_injector(injector.clone());
+        logger.start().unwrap();
+        for i in 0..500 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+    assert_eq!(*injector.attempts.lock().unwrap(), vec![0, 1]);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 500);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
@@ -0,0 +1,123 @@
+use picologger::testing::MockClock;
+use picologger::{Logger, LoggerConfig, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        std::thread::sleep(Duration::from_micros(200));
+    }
+}
+
+/// A logger flushing on `clock`'s 1ms timer with partial rewrites of pages under half full.
+fn rewriting_logger(path: &str, clock: &Arc<MockClock>, max_rewrites: u32) -> Logger<TestData> {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .build::<TestData>()
+        .unwrap()
+        .with_clock(clock.clone())
+        .with_partial_rewrites(0.5, max_rewrites);
+    logger.start().unwrap();
+    logger
+}
+
+/// Logs 40 entries one timer flush at a time and returns the resulting file length.
+fn trickle(path: &str, max_rewrites: u32) -> u64 {
+    let clock = Arc::new(MockClock::new(0));
+    {
+        let mut logger = rewriting_logger(path, &clock, max_rewrites);
+        for i in 0..40 {
+            logger.log(TestData { id: i, val: 0 }).unwrap();
+            // Each entry is written by a timer flush of its own before the next is logged,
+            // and the clock stands still in between, so no flush finds the page idle.
+            clock.advance(Duration::from_millis(1));
+            wait_until("the entry to be written", || {
+                logger.stats().entries_flushed == i + 1
+            });
+        }
+    }
+
+    let len = fs::metadata(path).unwrap().len();
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 40);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+    cleanup(path);
+    len
+}
+
+#[test]
+fn test_partial_pages_are_rewritten_in_place() {
+    // The header block plus a single page, rewritten for every entry.
+    assert_eq!(trickle("rewrite_unbounded.log", u32::MAX), 2 * 4096);
+}
+
+#[test]
+fn test_rewrites_stop_at_the_limit() {
+    // Each page is written once and rewritten once, taking two entries.
+    assert_eq!(trickle("rewrite_limited.log", 1), 21 * 4096);
+}
+
+#[test]
+fn test_open_pages_are_acknowledged_once_closed() {
+    let path = "rewrite_ack.log";
+    let clock = Arc::new(MockClock::new(0));
+    let mut logger = rewriting_logger(path, &clock, u32::MAX);
+
+    let first = logger.log(TestData { id: 0, val: 0 }).unwrap();
+    clock.advance(Duration::from_millis(1));
+    wait_until("the first write", || logger.stats().entries_flushed == 1);
+    // Written, but the page may still be rewritten, so not acknowledged.
+    assert!(logger.stats().flushed_seq_id < first);
+
+    // A timer flush with nothing new closes the page.
+    clock.advance(Duration::from_millis(1));
+    wait_until("the page to close", || {
+        logger.stats().flushed_seq_id >= first
+    });
+
+    logger.log(TestData { id: 1, val: 0 }).unwrap();
+    logger.shutdown().unwrap();
+    // The header block, the closed page and the page started after it.
+    assert_eq!(fs::metadata(path).unwrap().len(), 3 * 4096);
+    let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+    assert_eq!(reader.read().unwrap().len(), 2);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_invalid_fill_level() {
+    let mut logger = LoggerConfig::new()
+        .path("rewrite_invalid.log")
+        .capacity(64)
+        .build::<TestData>()
+        .unwrap()
+        .with_partial_rewrites(1.5, 4);
+    assert!(matches!(
+        logger.start(),
+        Err(PicoError::InvalidFillLevel { .. })
+    ));
+}
@@ -0,0 +1,75 @@
+use picologger::test_support::TempLogger;
+use picologger::{Logger, LoggerConfig, SafePayload};
+
+picologger::safe_payload! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Quote {
+        pub id: u64,
+        pub bid: f64,
+        /// Nested arrays of primitives are fine too.
+        pub levels: [[u32; 2]; 3],
+        flags: [u8; 8],
+    }
+}
+
+picologger::safe_payload! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Wrapped {
+        quote: Quote,
+        venue: [u8; 8],
+    }
+}
+
+fn assert_safe<T: SafePayload>() {}
+
+fn quotes() -> Vec<Wrapped> {
+    (0..100)
+        .map(|id| Wrapped {
+            quote: Quote {
+                id,
+                bid: id as f64 / 4.0,
+                levels: [[id as u32, 1], [2, 3], [4, 5]],
+                flags: [id as u8 % 2; 8],
+            },
+            venue: *b"XNAS\0\0\0\0",
+        })
+        .collect()
+}
+
+#[test]
+fn test_primitives_and_declared_structs_are_safe() {
+    assert_safe::<u64>();
+    assert_safe::<[i16; 7]>();
+    assert_safe::<Quote>();
+    assert_safe::<Wrapped>();
+}
+
+#[test]
+fn test_checked_loggers_round_trip() {
+    let temp = TempLogger::<Wrapped>::new();
+    let path = temp.path().to_path_buf();
+    let mut logger = temp.configure(|_| LoggerConfig::new().path(&path).build_checked().unwrap());
+    logger.start().unwrap();
+    let entries = quotes();
+    logger.log_batch(&entries).unwrap();
+    logger.shutdown().unwrap();
+
+    let reader = Logger::<Wrapped>::new_checked().with_read_config(logger.path());
+    assert_eq!(reader.read().unwrap(), entries);
+}
+
+/// With the `pod` feature the macro's structs are `Pod`, the one marker both checked and
+/// pod loggers take.
+#[cfg(feature = "pod")]
+#[test]
+fn test_declared_structs_are_pod() {
+    let temp = TempLogger::<Wrapped>::new();
+    let path = temp.path().to_path_buf();
+    let mut logger = temp.configure(|_| LoggerConfig::new().path(&path).build_pod().unwrap());
+    logger.start().unwrap();
+    let entries = quotes();
+    logger.log_batch(&entries).unwrap();
+    logger.shutdown().unwrap();
+
+    assert_eq!(logger.reader().read_pod().unwrap(), entries);
+}
@@ -0,0 +1,195 @@
+use picologger::{Logger, LoggerConfig, PicoError, Schema};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct OrderV1 {
+    id: u64,
+    qty: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct OrderV2 {
+    id: u64,
+    qty: u32,
+    price: u64,
+}
+
+/// Same layout as `OrderV1`, different meaning.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct Cancel {
+    order_id: u64,
+    reason: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_orders(path: &str, tag: Option<&str>) {
+    cleanup(path);
+    let mut logger = LoggerConfig::new().path(path).build::<OrderV1>().unwrap();
+    if let Some(tag) = tag {
+        logger = logger.with_schema_tag(tag);
+    }
+    logger.start().unwrap();
+    for i in 0..100 {
+        logger.log(OrderV1 { id: i, qty: 10 });
+    }
+}
+
+fn mismatch(err: PicoError) -> (Schema, Schema) {
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    match err {
+        PicoError::SchemaMismatch { expected, found } => (expected, found),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_reading_as_another_type_is_rejected() {
+    let path = "schema_type_test.log";
+    write_orders(path, None);
+
+    let logger = Logger::<OrderV2>::new().with_read_config(path.to_string());
+    let (expected, found) = mismatch(logger.read().unwrap_err());
+    assert_eq!(expected, Schema::of::<OrderV2>(None));
+    assert_eq!(found, Schema::of::<OrderV1>(None));
+    assert!(logger.read_iter().is_err());
+
+    let logger = Logger::<OrderV1>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read().unwrap().len(), 100);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_schema_tag_is_checked_when_the_reader_sets_one() {
+    let path = "schema_tag_test.log";
+    write_orders(path, Some("orders_v1"));
+
+    let logger = Logger::<Cancel>::new()
+        .with_read_config(path.to_string())
+        .with_schema_tag("cancels_v1");
+    let (expected, found) = mismatch(logger.read().unwrap_err());
+    assert_eq!(expected.tag.as_deref(), Some("cancels_v1"));
+    assert_eq!(found.tag.as_deref(), Some("orders_v1"));
+
+    let logger = Logger::<OrderV1>::new()
+        .with_read_config(path.to_string())
+        .with_schema_tag("orders_v1");
+    assert_eq!(logger.read().unwrap().len(), 100);
+
+    // Without a tag only the layout is compared.
+    let logger = Logger::<Cancel>::new().with_read_config(path.to_string());
+    assert_eq!(logger.read().unwrap().len(), 100);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_read_unchecked_ignores_the_schema() {
+    let path = "schema_unchecked_test.log";
+    write_orders(path, Some("orders_v1"));
+
+    let logger = Logger::<Cancel>::new()
+        .with_read_config(path.to_string())
+        .with_schema_tag("cancels_v1");
+    let cancels = logger.read_unchecked().unwrap();
+    assert_eq!(cancels.len(), 100);
+    assert_eq!(
+        cancels[7],
+        Cancel {
+            order_id: 7,
+            reason: 10
+        }
+    );
+
+    cleanup(path);
+}
+
+fn upgrade(old: OrderV1) -> OrderV2 {
+    OrderV2 {
+        id: old.id,
+        qty: old.qty,
+        price: 0,
+    }
+}
+
+#[test]
+fn test_old_layouts_are_read_through_a_migration() {
+    let path = "schema_migration_test.log";
+    write_orders(path, Some("orders_v1"));
+
+    let logger = Logger::<OrderV2>::new()
+        .with_read_config(path.to_string())
+        .with_schema_tag("orders_v2");
+    let orders = logger.read_with_migration(upgrade).unwrap();
+    assert_eq!(orders.len(), 100);
+    assert_eq!(
+        orders[7],
+        OrderV2 {
+            id: 7,
+            qty: 10,
+            price: 0
+        }
+    );
+
+    let err = Logger::<OrderV2>::new()
+        .with_read_config(path.to_string())
+        .read_with_migration(|old: u16| OrderV2 {
+            id: old as u64,
+            ..Default::default()
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = err.into_inner().unwrap().downcast::<PicoError>().unwrap();
+    let (expected, found) = mismatch(*err);
+    assert_eq!(expected, Schema::of::<OrderV2>(None));
+    assert_eq!(found, Schema::of::<OrderV1>(Some("orders_v1")));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_current_layouts_skip_the_migration() {
+    let path = "schema_migration_current_test.log";
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new().path(path).build::<OrderV2>().unwrap();
+        logger.start().unwrap();
+        for i in 0..10 {
+            logger.log(OrderV2 {
+                id: i,
+                qty: 1,
+                price: 99,
+            });
+        }
+    }
+
+    let orders = Logger::<OrderV2>::new()
+        .with_read_config(path.to_string())
+        .read_with_migration(|_: OrderV1| -> OrderV2 { panic!("nothing to migrate") })
+        .unwrap();
+    assert_eq!(orders.len(), 10);
+    assert!(orders.iter().all(|order| order.price == 99));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_overlong_schema_tag_is_rejected() {
+    let path = "schema_long_tag_test.log";
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<OrderV1>()
+        .unwrap()
+        .with_schema_tag("x".repeat(65));
+    let err = logger.start().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!std::path::Path::new(path).exists());
+}
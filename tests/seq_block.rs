@@ -0,0 +1,145 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str) -> Logger<TestData> {
+    cleanup(path);
+    LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(10))
+        .build::<TestData>()
+        .unwrap()
+}
+
+fn wait_for_watermark(logger: &Logger<TestData>, seq_id: u64) {
+    let started = Instant::now();
+    while logger.stats().flushed_seq_id < seq_id {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "watermark stuck at {}, waiting for {}",
+            logger.stats().flushed_seq_id,
+            seq_id
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn wait_for_flushed(logger: &Logger<TestData>, entries: u64) {
+    let started = Instant::now();
+    while logger.stats().entries_flushed < entries {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_reserved_block_is_logged_out_of_order() {
+    let path = "seq_block_round_trip.log";
+    let mut logger = logger(path);
+    logger.start().unwrap();
+
+    let block = logger.reserve_seq_block(5);
+    assert_eq!(block.end - block.start, 5);
+    let foreign = logger.log(TestData { id: 100, val: 0 }).unwrap();
+    assert!(foreign >= block.end);
+    for seq_id in block.clone().rev() {
+        logger
+            .log_with_seq(seq_id, TestData { id: seq_id, val: 1 })
+            .unwrap();
+    }
+    logger.flush().unwrap();
+    wait_for_watermark(&logger, foreign);
+    logger.shutdown().unwrap();
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path);
+    let entries: Vec<_> = reader.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 6);
+    assert_eq!(entries[0].seq_id, foreign);
+    for entry in &entries[1..] {
+        assert!(block.contains(&entry.seq_id));
+        assert_eq!(entry.data.id, entry.seq_id);
+    }
+    cleanup(path);
+}
+
+#[test]
+fn test_watermark_waits_for_reserved_ids() {
+    let path = "seq_block_watermark.log";
+    let mut logger = logger(path);
+    logger.start().unwrap();
+
+    let block = logger.reserve_seq_block(3);
+    let foreign = logger.log(TestData { id: 100, val: 0 }).unwrap();
+    logger
+        .log_with_seq(block.start, TestData::default())
+        .unwrap();
+    logger
+        .log_with_seq(block.start + 1, TestData::default())
+        .unwrap();
+    logger.flush().unwrap();
+    wait_for_flushed(&logger, 3);
+
+    // Everything logged is on disk, but the last reserved id is not.
+    wait_for_watermark(&logger, block.start + 1);
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(logger.stats().flushed_seq_id, block.start + 1);
+
+    logger.abandon_seq(block.start + 2);
+    wait_for_watermark(&logger, foreign);
+    logger.shutdown().unwrap();
+    cleanup(path);
+}
+
+#[test]
+fn test_unlogged_block_times_out() {
+    let path = "seq_block_timeout.log";
+    let mut logger = logger(path).with_seq_block_timeout(Duration::from_millis(100));
+    logger.start().unwrap();
+
+    let block = logger.reserve_seq_block(2);
+    let started = Instant::now();
+    let foreign = logger.log(TestData { id: 100, val: 0 }).unwrap();
+    logger.flush().unwrap();
+    wait_for_flushed(&logger, 1);
+    assert!(logger.stats().flushed_seq_id < block.start);
+
+    wait_for_watermark(&logger, foreign);
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    logger.shutdown().unwrap();
+    cleanup(path);
+}
+
+// Misuse is caught before the logger is looked at, so these never create their files.
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "not from a reserved block")]
+fn test_logging_an_unreserved_id_panics() {
+    let mut logger = logger("seq_block_unreserved.log");
+    let block = logger.reserve_seq_block(2);
+    let _ = logger.log_with_seq(block.end, TestData::default());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "already logged or abandoned")]
+fn test_logging_an_abandoned_id_panics() {
+    let mut logger = logger("seq_block_abandoned.log");
+    let block = logger.reserve_seq_block(2);
+    logger.abandon_seq(block.start);
+    let _ = logger.log_with_seq(block.start, TestData::default());
+}
@@ -0,0 +1,101 @@
+//! Sequence ids are process-wide, so the tests that push them past 2^56 live in their own
+//! binary instead of skewing everyone else's.
+
+use picologger::testing::{self, FaultInjector};
+use picologger::{Durability, Logger, LoggerConfig};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Fails the first attempt of every page write with EAGAIN, so each page is retried once.
+struct RetryEveryPage;
+
+impl FaultInjector for RetryEveryPage {
+    fn on_write_complete(&self, _offset: u64, attempt: u32, result: i32) -> i32 {
+        if attempt == 0 { -libc::EAGAIN } else { result }
+    }
+}
+
+fn log_and_wait_for_ack(
+    path: &str,
+    durability: Durability,
+    fault_injector: Option<Arc<dyn FaultInjector>>,
+) {
+    cleanup(path);
+    testing::advance_seq_id((1 << 56) - 100);
+
+    let mut seq_ids = Vec::new();
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(4096)
+            .build::<TestData>()
+            .unwrap()
+            .with_durability(durability);
+        if let Some(injector) = fault_injector {
+            logger = logger.with_fault_injector(injector);
+        }
+        logger.start().unwrap();
+        let errors = logger.take_error_receiver().unwrap();
+
+        for i in 0..1000 {
+            seq_ids.push(
+                logger
+                    .log(TestData {
+                        id: i,
+                        val: i as u32,
+                    })
+                    .unwrap(),
+            );
+        }
+        let last = *seq_ids.last().unwrap();
+        logger.flush().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Logger::<TestData>::get_last_flushed_entry() < last {
+            assert!(Instant::now() < deadline, "ack never reached {}", last);
+            std::thread::yield_now();
+        }
+        assert!(errors.try_recv().is_err());
+    }
+
+    // The other test may already have moved the counter past the boundary.
+    assert!(*seq_ids.last().unwrap() > 1 << 56);
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 1000);
+    for ((entry, &seq_id), i) in entries.iter().zip(&seq_ids).zip(0..) {
+        assert_eq!(entry.seq_id, seq_id);
+        assert_eq!(entry.data.id, i);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_ack_tracking_past_2_pow_56() {
+    log_and_wait_for_ack("seq_ids_large.log", Durability::None, None);
+}
+
+#[test]
+fn test_retried_synced_writes_past_2_pow_56() {
+    log_and_wait_for_ack(
+        "seq_ids_retry.log",
+        Durability::Fdatasync,
+        Some(Arc::new(RetryEveryPage)),
+    );
+}
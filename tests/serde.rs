@@ -0,0 +1,61 @@
+#![cfg(feature = "serde")]
+
+use picologger::LoggerConfig;
+use picologger::test_support::TempLogger;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Order {
+    symbol: String,
+    fills: Vec<u32>,
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let expected: Vec<Order> = (0..200)
+        .map(|i| Order {
+            symbol: format!("SYM{}", i),
+            fills: (0..(i % 17)).collect(),
+        })
+        .collect();
+
+    let mut logger = TempLogger::<()>::with_config(
+        LoggerConfig::new()
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024),
+    );
+    logger.start().unwrap();
+
+    for order in &expected {
+        logger.log_serde(order).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let result = logger.reader().read_serde::<Order>().unwrap();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_serde_rejects_oversized_entry() {
+    let mut logger = TempLogger::<()>::with_config(
+        LoggerConfig::new()
+            .capacity(16)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024),
+    );
+    assert!(
+        logger.log_serde(&1u32).is_err(),
+        "log_serde before start must fail"
+    );
+
+    logger.start().unwrap();
+    let huge = Order {
+        symbol: "X".repeat(1 << 20),
+        fills: Vec::new(),
+    };
+    assert!(logger.log_serde(&huge).is_err());
+}
@@ -0,0 +1,274 @@
+use picologger::{Logger, LoggerConfig, PicoError, ShardSelection};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str, shards: usize) {
+    for shard in 0..shards {
+        let shard = format!("{path}.{shard}");
+        if std::path::Path::new(&shard).exists() {
+            fs::remove_file(shard).unwrap();
+        }
+    }
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::<TestData>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_sharded_writes_merge_back_in_seq_order() {
+    let path = "shards_merge_test.log";
+    cleanup(path, 4);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build_sharded::<TestData>(4)
+        .unwrap();
+    logger.start().unwrap();
+    let logger = Arc::new(logger);
+
+    let producers: Vec<_> = (0..4)
+        .map(|thread| {
+            let logger = logger.clone();
+            thread::spawn(move || {
+                (0..2000)
+                    .map(|i| logger.log(TestData { id: i, val: thread }).unwrap())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let mut seq_ids: Vec<u64> = producers
+        .into_iter()
+        .flat_map(|producer| producer.join().unwrap())
+        .collect();
+    seq_ids.sort_unstable();
+    logger.shutdown().unwrap();
+
+    assert_eq!(logger.shard_count(), 4);
+    for shard in 0..4 {
+        assert!(logger.shard_path(shard).exists());
+    }
+    let entries = reader(path).read_merged().unwrap();
+    assert_eq!(
+        entries.iter().map(|e| e.seq_id).collect::<Vec<_>>(),
+        seq_ids
+    );
+    // Every producer's own entries keep their order.
+    for thread in 0..4 {
+        let ids: Vec<_> = entries
+            .iter()
+            .filter(|e| e.data.val == thread)
+            .map(|e| e.data.id)
+            .collect();
+        assert_eq!(ids, (0..2000).collect::<Vec<_>>());
+    }
+
+    let last = *seq_ids.last().unwrap();
+    assert_eq!(logger.merged_watermark(), last);
+    assert!(logger.shard_watermarks().contains(&last));
+
+    cleanup(path, 4);
+}
+
+#[test]
+fn test_more_producers_than_shards_fan_out() {
+    let path = "shards_fan_out_test.log";
+    cleanup(path, 2);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .build_sharded::<TestData>(2)
+        .unwrap();
+    logger.start().unwrap();
+    let logger = Arc::new(logger);
+
+    // Eight producers meet on two shards; whoever finds its shard busy takes the other.
+    let producers: Vec<_> = (0..8)
+        .map(|thread| {
+            let logger = logger.clone();
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    logger.log(TestData { id: i, val: thread }).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let logged: u64 = logger
+        .stats()
+        .iter()
+        .map(|shard| shard.entries_flushed)
+        .sum();
+    assert_eq!(logged, 8000);
+    let entries = reader(path).read_merged().unwrap();
+    assert_eq!(entries.len(), 8000);
+    assert!(entries.windows(2).all(|w| w[0].seq_id < w[1].seq_id));
+    for thread in 0..8 {
+        let ids: Vec<_> = entries
+            .iter()
+            .filter(|e| e.data.val == thread)
+            .map(|e| e.data.id)
+            .collect();
+        assert_eq!(ids, (0..1000).collect::<Vec<_>>());
+    }
+
+    cleanup(path, 2);
+}
+
+#[test]
+fn test_round_robin_spreads_entries_evenly() {
+    let path = "shards_round_robin_test.log";
+    cleanup(path, 3);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build_sharded::<TestData>(3)
+        .unwrap()
+        .with_shard_selection(ShardSelection::RoundRobin)
+        .with_each(|logger| logger.with_pages(8));
+    logger.start().unwrap();
+    for i in 0..300 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert!(stats.iter().all(|shard| shard.entries_logged == 100));
+    assert!(stats.iter().all(|shard| shard.entries_flushed == 100));
+    for shard in 0..3 {
+        let entries = reader(&format!("{path}.{shard}")).read().unwrap();
+        assert_eq!(entries.len(), 100);
+        assert!(entries.iter().all(|e| e.id % 3 == shard));
+    }
+    let ids: Vec<_> = reader(path)
+        .read_merged()
+        .unwrap()
+        .iter()
+        .map(|e| e.data.id)
+        .collect();
+    assert_eq!(ids, (0..300).collect::<Vec<_>>());
+
+    cleanup(path, 3);
+}
+
+#[test]
+fn test_merged_watermark_waits_for_every_shard() {
+    let path = "shards_watermark_test.log";
+    cleanup(path, 2);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(std::time::Duration::from_secs(60))
+        .build_sharded::<TestData>(2)
+        .unwrap()
+        .with_shard_selection(ShardSelection::RoundRobin);
+    logger.start().unwrap();
+    assert_eq!(logger.merged_watermark(), 0);
+    let first = logger.log(TestData { id: 0, val: 0 }).unwrap();
+    let second = logger.log(TestData { id: 1, val: 0 }).unwrap();
+    assert_eq!(logger.merged_watermark(), 0);
+    assert_eq!(logger.shard_watermarks(), vec![0, 0]);
+
+    logger.shutdown().unwrap();
+    assert_eq!(logger.shard_watermarks(), vec![first, second]);
+    assert_eq!(logger.merged_watermark(), second);
+
+    cleanup(path, 2);
+}
+
+#[test]
+fn test_read_merged_falls_back_to_a_single_file() {
+    let path = "shards_single_test.log";
+    let _ = fs::remove_file(path);
+    {
+        let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+        logger.start().unwrap();
+        for i in 0..10 {
+            logger.log(TestData { id: i, val: 0 }).unwrap();
+        }
+    }
+
+    let ids: Vec<_> = reader(path)
+        .read_merged()
+        .unwrap()
+        .iter()
+        .map(|e| e.data.id)
+        .collect();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+
+    fs::remove_file(path).unwrap();
+}
+
+fn log_file(path: &str, count: u64) -> Vec<u64> {
+    let _ = fs::remove_file(path);
+    let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+    logger.start().unwrap();
+    let seq_ids = (0..count)
+        .map(|i| logger.log(TestData { id: i, val: 0 }).unwrap())
+        .collect();
+    logger.shutdown().unwrap();
+    seq_ids
+}
+
+#[test]
+fn test_read_merged_paths_reports_duplicates_and_gaps() {
+    let paths = [
+        "merged_paths_a_test.log",
+        "merged_paths_skipped_test.log",
+        "merged_paths_b_test.log",
+    ];
+    let a = log_file(paths[0], 100);
+    log_file(paths[1], 50);
+    let b = log_file(paths[2], 100);
+    let mut expected: Vec<_> = a.iter().chain(&b).copied().collect();
+    expected.sort_unstable();
+
+    let files: Vec<PathBuf> = [paths[2], paths[0], paths[0]].map(PathBuf::from).into();
+    let mut merged = Logger::<TestData>::new().read_merged_paths(&files).unwrap();
+    let seq_ids: Vec<_> = merged.by_ref().map(|e| e.unwrap().seq_id).collect();
+    assert_eq!(seq_ids, expected);
+
+    let summary = merged.summary();
+    let steps: Vec<_> = expected.windows(2).map(|w| w[1] - w[0]).collect();
+    assert_eq!(summary.entries, 200);
+    assert_eq!(summary.duplicates, 100);
+    assert_eq!(
+        summary.gaps,
+        steps.iter().filter(|&&step| step > 1).count() as u64
+    );
+    assert_eq!(
+        summary.missing,
+        steps.iter().map(|step| step - 1).sum::<u64>()
+    );
+    assert!(summary.gaps >= 1 && summary.missing >= 50);
+    assert_eq!(summary.last_seq_id, expected.last().copied());
+
+    for path in paths {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_invalid_shard_counts_are_rejected() {
+    for count in [0, 257] {
+        let err = LoggerConfig::new()
+            .path("shards_invalid_test.log")
+            .build_sharded::<TestData>(count)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, PicoError::InvalidShardCount { count: found, max: 256 } if found == count)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+#![cfg(unix)]
+
+use picologger::testing::FaultInjector;
+use picologger::{Backend, Logger, LoggerConfig, LoggerState, PicoError};
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// Serializes the tests that replace the process-wide panic hook.
+static HOOKS: Mutex<()> = Mutex::new(());
+
+/// Puts back the panic hook that was installed when it was created, on drop, so a test's
+/// hook never outlives it.
+struct RestoreHook {
+    previous: Option<Hook>,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl RestoreHook {
+    fn new() -> Self {
+        let turn = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            previous: Some(panic::take_hook()),
+            _turn: turn,
+        }
+    }
+}
+
+impl Drop for RestoreHook {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            panic::set_hook(previous);
+        }
+    }
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "{what} never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// A disk that accepts writes but never reports any of them as done.
+struct Wedged;
+
+impl FaultInjector for Wedged {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn swallow_completion(&self, _offset: u64, _attempt: u32) -> bool {
+        true
+    }
+}
+
+fn wedged_shutdown_times_out(path: &str, backend: Backend) {
+    cleanup(path);
+    let timeout = Duration::from_millis(200);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_backend(backend)
+        .with_fault_injector(Arc::new(Wedged))
+        .with_shutdown_timeout(timeout);
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+
+    let first = logger.log(TestData { id: 0, val: 0 }).unwrap();
+    for i in 1..100 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+
+    let started = Instant::now();
+    let result = logger.shutdown();
+    let elapsed = started.elapsed();
+    assert!(
+        matches!(result, Err(PicoError::ShutdownTimedOut { lost_from_seq }) if lost_from_seq == first),
+        "unexpected result {:?}",
+        result
+    );
+    assert!(elapsed >= timeout, "gave up after {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(3), "took {:?}", elapsed);
+    assert!(
+        errors
+            .try_iter()
+            .any(|e| matches!(e, PicoError::ShutdownTimedOut { .. }))
+    );
+
+    assert_eq!(logger.state(), LoggerState::ShutDown);
+    assert!(matches!(
+        logger.try_log(TestData::default()),
+        Err(PicoError::ShutDown)
+    ));
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_wedged_uring_shutdown_times_out() {
+    wedged_shutdown_times_out("shutdown_wedged_uring.log", Backend::Auto);
+}
+
+#[test]
+fn test_wedged_sync_shutdown_times_out() {
+    wedged_shutdown_times_out("shutdown_wedged_sync.log", Backend::Sync);
+}
+
+#[test]
+fn test_shutdown_writes_everything() {
+    let path = "shutdown_clean.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for i in 0..500 {
+        logger.log(TestData { id: i, val: 1 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+    assert_eq!(logger.state(), LoggerState::ShutDown);
+    // A second call, and the drop after it, have nothing left to do.
+    logger.shutdown().unwrap();
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path.to_string());
+    let entries: Vec<_> = reader.read_iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 500);
+    cleanup(path);
+}
+
+fn idle_logger(path: &str) -> Logger<TestData> {
+    cleanup(path);
+    // Nothing is flushed on a timer, so whatever isn't flushed explicitly is still pending
+    // when the logger shuts down.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    logger
+}
+
+fn wait_for_flushed(logger: &Logger<TestData>, entries: u64) {
+    let started = Instant::now();
+    while logger.stats().entries_flushed < entries {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_draining_shutdown_writes_the_pending_page() {
+    let path = "shutdown_drain.log";
+    let mut logger = idle_logger(path);
+    for i in 0..10 {
+        logger.log(TestData { id: i, val: 2 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+    assert_eq!(logger.dropped_entries(), 0);
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path);
+    assert_eq!(reader.read().unwrap().len(), 10);
+    cleanup(path);
+}
+
+#[test]
+fn test_shutdown_now_discards_what_is_not_in_flight() {
+    let path = "shutdown_now.log";
+    let mut logger = idle_logger(path);
+    let mut last_durable = 0;
+    for i in 0..10 {
+        last_durable = logger.log(TestData { id: i, val: 3 }).unwrap();
+    }
+    logger.flush().unwrap();
+    wait_for_flushed(&logger, 10);
+    for i in 10..15 {
+        logger.log(TestData { id: i, val: 3 }).unwrap();
+    }
+
+    assert_eq!(logger.shutdown_now().unwrap(), last_durable);
+    assert_eq!(logger.dropped_entries(), 5);
+    assert_eq!(logger.state(), LoggerState::ShutDown);
+    assert!(matches!(
+        logger.try_log(TestData::default()),
+        Err(PicoError::ShutDown)
+    ));
+    // Already stopped, so there is nothing left to drain either.
+    logger.shutdown().unwrap();
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path);
+    let ids: Vec<_> = reader.read().unwrap().iter().map(|e| e.id).collect();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    cleanup(path);
+}
+
+#[test]
+fn test_shutdown_now_with_nothing_durable() {
+    let path = "shutdown_now_empty.log";
+    let mut logger = idle_logger(path);
+    for i in 0..10 {
+        logger.log(TestData { id: i, val: 4 }).unwrap();
+    }
+    assert_eq!(logger.shutdown_now().unwrap(), 0);
+    assert_eq!(logger.dropped_entries(), 10);
+    drop(logger);
+
+    let reader = Logger::<TestData>::new().with_read_config(path);
+    assert!(reader.read().unwrap().is_empty());
+    cleanup(path);
+}
+
+#[test]
+fn test_shutdown_while_the_panic_hook_holds_the_channel() {
+    let _restore = RestoreHook::new();
+    let path = "shutdown_panic_hook.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap()
+        .with_fault_injector(Arc::new(Wedged))
+        .with_shutdown_timeout(Duration::from_millis(100));
+    logger.start().unwrap();
+    logger.install_panic_flush().unwrap();
+    for i in 0..10 {
+        logger.log(TestData { id: i, val: 5 }).unwrap();
+    }
+
+    // The hook keeps its copy of the worker's channel for as long as it waits for the
+    // entries to become durable, which on a wedged disk is its full half second.
+    let flush_hook = panic::take_hook();
+    let entered = Arc::new(AtomicBool::new(false));
+    let returned = Arc::new(AtomicBool::new(false));
+    panic::set_hook({
+        let (entered, returned) = (entered.clone(), returned.clone());
+        Box::new(move |info| {
+            entered.store(true, Ordering::SeqCst);
+            flush_hook(info);
+            returned.store(true, Ordering::SeqCst);
+        })
+    });
+    let panicking = thread::spawn(|| {
+        let _ = thread::spawn(|| panic!("wedged")).join();
+    });
+    wait_until("the panic hook running", || entered.load(Ordering::SeqCst));
+
+    let result = logger.shutdown_now();
+    assert!(
+        matches!(result, Err(PicoError::ShutdownTimedOut { .. })),
+        "unexpected result {:?}",
+        result
+    );
+    assert!(
+        !returned.load(Ordering::SeqCst),
+        "shutdown waited for the panic hook to let go of the channel"
+    );
+    assert_eq!(logger.state(), LoggerState::ShutDown);
+    panicking.join().unwrap();
+    assert!(returned.load(Ordering::SeqCst));
+    drop(logger);
+    cleanup(path);
+}
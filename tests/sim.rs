@@ -0,0 +1,244 @@
+#![cfg(feature = "sim")]
+
+use picologger::sim::SimDisk;
+use picologger::test_support::TempLogger;
+use picologger::{BufferFullPolicy, Durability, LoggerConfig, PicoError, SyncPolicy};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn data(id: u64) -> TestData {
+    TestData {
+        id,
+        val: id as u32 ^ 0x5A5A,
+    }
+}
+
+/// Crash points replayed by each of the randomized tests.
+const CRASHES: u64 = 250;
+const ENTRIES: u64 = 1500;
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Plain,
+    PartialRewrites,
+    Fdatasync,
+    Inline,
+}
+
+/// A logger on `disk` that reports a full ring instead of waiting for room, since a
+/// crashed disk never makes any. Unless it syncs every page, it hardens them with frequent
+/// full syncs.
+fn sim_logger(disk: &SimDisk, rng: &mut fastrand::Rng, mode: Mode) -> TempLogger<TestData> {
+    let flush_interval = Duration::from_micros(rng.u64(50..2000));
+    let pages = rng.usize(2..=8);
+    let watermark = rng.bool().then(|| rng.f32() * 0.9 + 0.05);
+    let sync_policy = SyncPolicy {
+        range_every_pages: rng.u32(..4),
+        range_interval: None,
+        full_sync_interval: Duration::from_micros(rng.u64(50..2000)),
+    };
+    TempLogger::with_config(LoggerConfig::new().flush_interval(flush_interval)).configure(
+        |logger| {
+            let logger = logger
+                .with_block_size(4096)
+                .with_pages(pages)
+                .with_buffer_full_policy(BufferFullPolicy::Reject)
+                .with_shutdown_timeout(Duration::ZERO)
+                .with_sim_disk(disk.clone());
+            let logger = match watermark {
+                Some(fraction) => logger.with_flush_watermark(fraction),
+                None => logger,
+            };
+            match mode {
+                Mode::PartialRewrites => logger
+                    .with_partial_rewrites(0.25, 3)
+                    .with_sync_policy(sync_policy),
+                Mode::Fdatasync => logger.with_durability(Durability::Fdatasync),
+                Mode::Plain | Mode::Inline => logger.with_sync_policy(sync_policy),
+            }
+        },
+    )
+}
+
+/// Logs entries until the disk crashes at a point chosen by `seed`, then asserts that
+/// every entry the logger reported durable can be read back from what the disk kept.
+fn replay(seed: u64, mode: Mode) {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let disk = SimDisk::new(seed);
+    disk.crash_after(rng.usize(..24));
+    let mut logger = sim_logger(&disk, &mut rng, mode);
+    match mode {
+        Mode::Inline => logger.start_inline().unwrap(),
+        _ => logger.start().unwrap(),
+    }
+
+    let mut logged = Vec::new();
+    'log: for id in 0..ENTRIES {
+        loop {
+            match logger.try_log(data(id)) {
+                Ok(seq_id) => break logged.push((seq_id, id)),
+                Err(PicoError::BufferFull) if !disk.crashed() => std::thread::yield_now(),
+                Err(PicoError::BufferFull) => break 'log,
+                Err(e) => panic!("seed {seed}: {e}"),
+            }
+        }
+    }
+    // Leave the disk to reach its crash point most of the time; otherwise crash it with
+    // whatever is in flight.
+    if rng.u32(..4) != 0 {
+        let _ = logger.flush();
+        let last = logged.last().map_or(0, |&(seq_id, _)| seq_id);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !disk.crashed() && disk.durable_seq_id() < last && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_micros(100));
+        }
+    }
+    disk.crash();
+    let _ = logger.shutdown_now();
+    let durable = disk.durable_seq_id();
+
+    fs::write(logger.path(), disk.image(0)).unwrap();
+    // Entries carry no checksum of their own, so those past the watermark may be torn.
+    let read: HashMap<_, _> = logger
+        .reader()
+        .read_iter()
+        .unwrap()
+        .map_while(Result::ok)
+        .map(|entry| (entry.seq_id, entry.data))
+        .collect();
+    for &(seq_id, id) in logged.iter().take_while(|&&(seq_id, _)| seq_id <= durable) {
+        assert_eq!(
+            read.get(&seq_id),
+            Some(&data(id)),
+            "seed {seed}: seq_id {seq_id} was reported durable at {durable} but is lost \
+             ({} of {} entries read back after {} writes)",
+            read.len(),
+            logged.len(),
+            disk.writes_completed(),
+        );
+    }
+}
+
+#[test]
+fn test_crashes_keep_durable_entries() {
+    for seed in 0..CRASHES {
+        replay(seed, Mode::Plain);
+    }
+}
+
+#[test]
+fn test_crashes_keep_durable_entries_with_partial_rewrites() {
+    for seed in 0..CRASHES {
+        replay(seed, Mode::PartialRewrites);
+    }
+}
+
+#[test]
+fn test_crashes_keep_durable_entries_with_fdatasync() {
+    for seed in 0..CRASHES {
+        replay(seed, Mode::Fdatasync);
+    }
+}
+
+#[test]
+fn test_crashes_keep_durable_entries_inline() {
+    for seed in 0..CRASHES {
+        replay(seed, Mode::Inline);
+    }
+}
+
+#[test]
+fn test_disk_keeps_everything_without_a_crash() {
+    let disk = SimDisk::new(7);
+    let mut logger = TempLogger::<TestData>::new().configure(|logger| {
+        logger
+            .with_block_size(4096)
+            .with_sync_policy(SyncPolicy::default())
+            .with_sim_disk(disk.clone())
+    });
+    logger.start().unwrap();
+    let seq_ids = logger
+        .log_batch(&(0..500).map(data).collect::<Vec<_>>())
+        .unwrap();
+    // Shutting down hardens everything with a final full sync.
+    logger.shutdown().unwrap();
+    assert!(!disk.crashed());
+    assert_eq!(disk.durable_seq_id(), seq_ids.end - 1);
+    assert!(disk.writes_completed() > 0);
+
+    // Only the header went to the real file.
+    assert!(logger.reader().read().unwrap().is_empty());
+    fs::write(logger.path(), disk.image(0)).unwrap();
+    assert_eq!(
+        logger.reader().read().unwrap(),
+        (0..500).map(data).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_crash_tears_completed_writes_no_sync_covered() {
+    let disk = SimDisk::new(3);
+    let mut logger = TempLogger::<TestData>::new().configure(|logger| {
+        logger
+            .with_block_size(4096)
+            .with_shutdown_timeout(Duration::ZERO)
+            .with_sim_disk(disk.clone())
+    });
+    logger.start().unwrap();
+    let seq_ids = logger
+        .log_batch(&(0..500).map(data).collect::<Vec<_>>())
+        .unwrap();
+    logger.flush().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().flushed_seq_id < seq_ids.end - 1 {
+        assert!(Instant::now() < deadline, "writes never completed");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(disk.durable_seq_id(), 0);
+
+    // Until the crash the cached writes read back.
+    fs::write(logger.path(), disk.image(0)).unwrap();
+    assert_eq!(logger.reader().read().unwrap().len(), 500);
+    disk.crash();
+    let _ = logger.shutdown_now();
+    fs::write(logger.path(), disk.image(0)).unwrap();
+    let kept = logger
+        .reader()
+        .read_iter()
+        .unwrap()
+        .map_while(Result::ok)
+        .count();
+    assert!(kept < 500, "{kept} entries survived");
+}
+
+#[test]
+fn test_crash_before_any_write_keeps_nothing() {
+    let disk = SimDisk::new(1);
+    disk.crash_after(0);
+    assert!(disk.crashed());
+    let mut logger = TempLogger::<TestData>::new().configure(|logger| {
+        logger
+            .with_block_size(4096)
+            .with_shutdown_timeout(Duration::ZERO)
+            .with_sim_disk(disk.clone())
+    });
+    logger.start().unwrap();
+    logger.log(data(1)).unwrap();
+    logger.flush().unwrap();
+    assert!(matches!(
+        logger.shutdown_now(),
+        Err(PicoError::ShutdownTimedOut { .. })
+    ));
+    assert_eq!(disk.durable_seq_id(), 0);
+    assert_eq!(disk.writes_completed(), 0);
+    fs::write(logger.path(), disk.image(0)).unwrap();
+    assert!(logger.reader().read().unwrap().is_empty());
+}
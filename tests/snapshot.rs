@@ -0,0 +1,114 @@
+use picologger::testing::FaultInjector;
+use picologger::{LoggerConfig, PicoError};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// A disk that accepts writes but never reports any of them as done.
+struct Wedged;
+
+impl FaultInjector for Wedged {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn swallow_completion(&self, _offset: u64, _attempt: u32) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_snapshot_holds_entries_not_yet_durable() {
+    let path = "snapshot_pending_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap();
+    assert!(matches!(
+        logger.pending_snapshot(),
+        Err(PicoError::NotStarted)
+    ));
+    logger.start().unwrap();
+
+    // 101 entries fill the first page, which is written once the next one doesn't fit.
+    let seq_ids: Vec<_> = (0..150)
+        .map(|i| logger.log(TestData { id: i, val: 0 }).unwrap())
+        .collect();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed < 101 {
+        assert!(Instant::now() < deadline, "first page was never written");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let snapshot = logger.pending_snapshot().unwrap();
+    assert_eq!(
+        snapshot.iter().map(|m| m.seq_id).collect::<Vec<_>>(),
+        seq_ids[101..]
+    );
+    assert!(
+        snapshot
+            .iter()
+            .zip(101..)
+            .all(|(m, id)| m.data == TestData { id, val: 0 })
+    );
+
+    logger.flush().unwrap();
+    while logger.stats().entries_flushed < 150 {
+        assert!(Instant::now() < deadline, "second page was never written");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(logger.pending_snapshot().unwrap().is_empty());
+
+    logger.shutdown().unwrap();
+    assert!(matches!(
+        logger.pending_snapshot(),
+        Err(PicoError::ShutDown)
+    ));
+    cleanup(path);
+}
+
+#[test]
+fn test_snapshot_includes_writes_in_flight() {
+    let path = "snapshot_in_flight_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap()
+        .with_fault_injector(Arc::new(Wedged))
+        .with_shutdown_timeout(Duration::from_millis(100));
+    logger.start().unwrap();
+
+    let seq_ids: Vec<_> = (0..250)
+        .map(|i| logger.log(TestData { id: i, val: 1 }).unwrap())
+        .collect();
+    logger.flush().unwrap();
+
+    // Every page went to the disk, none came back as done.
+    let snapshot = logger.pending_snapshot().unwrap();
+    assert_eq!(logger.stats().entries_flushed, 0);
+    assert_eq!(
+        snapshot.iter().map(|m| m.seq_id).collect::<Vec<_>>(),
+        seq_ids
+    );
+
+    let _ = logger.shutdown();
+    drop(logger);
+    cleanup(path);
+}
@@ -0,0 +1,62 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn spin_round_trip(path: &str, park_after: u32) {
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(8192)
+            .flush_interval(Duration::from_micros(50))
+            .build::<TestData>()
+            .unwrap()
+            .with_spin_mode(true)
+            .with_spin_park_after(park_after);
+        logger.start().unwrap();
+        for i in 0..3000 {
+            logger.log(TestData { id: i, val: 0 }).unwrap();
+            if i % 500 == 0 {
+                std::thread::sleep(Duration::from_millis(2));
+            }
+        }
+
+        // The worker never sleeps, so dropping must still notice the disconnect right away.
+        let dropped = Instant::now();
+        drop(logger);
+        assert!(dropped.elapsed() < Duration::from_secs(1));
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 3000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_spin_mode_round_trip() {
+    spin_round_trip("spin_forever.log", 0);
+}
+
+#[test]
+fn test_spin_mode_parks_after_empty_polls() {
+    spin_round_trip("spin_parking.log", 1000);
+}
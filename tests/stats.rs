@@ -0,0 +1,261 @@
+use picologger::format::{ENTRY_ALIGN, ENTRY_HEADER_LEN};
+use picologger::test_support::TempLogger;
+use picologger::testing::MockClock;
+use picologger::{Logger, LoggerConfig, LoggerStats};
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_stats_track_flushed_entries() {
+    let path = "stats_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .flush_interval(Duration::from_micros(100))
+        .poll_interval(Duration::from_micros(10))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap();
+    assert_eq!(logger.stats(), Default::default());
+    logger.start().unwrap();
+
+    for i in 0..1000 {
+        logger.log(TestData {
+            id: i as u64,
+            val: i as u32,
+        });
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let stats = loop {
+        let stats = logger.stats();
+        if stats.entries_flushed == 1000 && stats.pending_writes == 0 {
+            break stats;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "entries never flushed: {:?}",
+            stats
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    };
+
+    assert_eq!(stats.entries_logged, 1000);
+    assert!(stats.pages_flushed > 0);
+    assert!(stats.bytes_written >= stats.pages_flushed * 512);
+    assert_eq!(stats.channel_depth, 0);
+    assert_eq!(stats.failed_writes, 0);
+    assert_eq!(stats.last_error_code, 0);
+    let flushes = stats.flushes;
+    assert_eq!(
+        flushes.timer + flushes.watermark + flushes.page_full + flushes.shutdown,
+        stats.pages_flushed
+    );
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_pending_entries_cover_unflushed_pages() {
+    let path = "stats_pending_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .flush_interval(Duration::from_secs(10))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for i in 0..10 {
+        logger.log(TestData { id: i, val: 0 });
+    }
+
+    // Entries move from the ring buffer into the active page, which is not flushed until
+    // the interval elapses, so they stay pending throughout.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().channel_depth > 0 {
+        assert!(
+            Instant::now() < deadline,
+            "worker never drained the ring buffer"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(logger.pending_entries(), 10);
+    assert_eq!(logger.dropped_entries(), 0);
+
+    drop(logger);
+    cleanup(path);
+}
+
+#[test]
+fn test_overwritten_slots_are_counted_as_dropped() {
+    let path = "stats_dropped_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(2)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    let total = 20_000;
+    for i in 0..total {
+        logger.log(TestData { id: i, val: 0 });
+    }
+
+    // Every message is either appended to a page or found overwritten, never both.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let flushed = loop {
+        let flushed = logger.stats().entries_flushed;
+        if flushed + logger.dropped_entries() == total && logger.pending_entries() == 0 {
+            break flushed;
+        }
+        assert!(Instant::now() < deadline, "entries unaccounted for");
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    drop(logger);
+
+    let written = Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read()
+        .unwrap()
+        .len() as u64;
+    assert_eq!(written, flushed);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_parked_worker_is_woken_for_new_entries() {
+    let path = "stats_wakeup_test.log";
+    cleanup(path);
+
+    // Neither timer would wake the worker during the test, so it only reads the entries if
+    // logging them wakes it.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .poll_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    for round in 0..20u64 {
+        std::thread::sleep(Duration::from_millis(5));
+        if round % 2 == 0 {
+            logger.log(TestData { id: round, val: 0 }).unwrap();
+        } else {
+            logger
+                .log_batch(&[TestData { id: round, val: 1 }; 3])
+                .unwrap();
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while logger.stats().channel_depth > 0 {
+            assert!(
+                Instant::now() < deadline,
+                "worker slept through round {round}"
+            );
+            std::thread::yield_now();
+        }
+    }
+    assert_eq!(logger.pending_entries(), 10 + 10 * 3);
+    assert_eq!(logger.dropped_entries(), 0);
+
+    drop(logger);
+    cleanup(path);
+}
+
+/// Bytes each entry takes in a page.
+const ENTRY_LEN: u64 =
+    (ENTRY_HEADER_LEN + size_of::<TestData>()).next_multiple_of(ENTRY_ALIGN) as u64;
+
+#[test]
+fn test_stats_account_for_file_space() {
+    let mut logger = TempLogger::<TestData>::new().configure(|logger| logger.with_block_size(4096));
+    assert_eq!(logger.stats().current_file_offset, 0);
+    logger.start().unwrap();
+    assert_eq!(logger.stats().current_file_offset, 4096);
+    for i in 0..500 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert_eq!(stats.file_bytes_written, stats.pages_flushed * 4096);
+    assert_eq!(stats.payload_bytes_written, 500 * ENTRY_LEN);
+    assert_eq!(stats.current_file_offset, 4096 + stats.file_bytes_written);
+    assert_eq!(
+        fs::metadata(logger.path()).unwrap().len(),
+        stats.current_file_offset
+    );
+    let padding = stats.file_bytes_written - stats.payload_bytes_written;
+    assert_eq!(
+        stats.padding_overhead_ratio(),
+        padding as f64 / stats.file_bytes_written as f64
+    );
+    assert!(stats.padding_overhead_ratio() > 0.0 && stats.padding_overhead_ratio() < 1.0);
+    assert_eq!(LoggerStats::default().padding_overhead_ratio(), 0.0);
+}
+
+#[test]
+fn test_rewritten_pages_take_file_space_once() {
+    let clock = Arc::new(MockClock::new(0));
+    let mut logger = TempLogger::<TestData>::with_config(
+        LoggerConfig::new().flush_interval(Duration::from_millis(1)),
+    )
+    .configure(|logger| {
+        logger
+            .with_block_size(4096)
+            .with_clock(clock.clone())
+            .with_partial_rewrites(0.5, 10)
+    });
+    logger.start().unwrap();
+    for i in 0..3 {
+        logger.log(TestData { id: i, val: 0 }).unwrap();
+        // A timer flush per entry, with the clock still in between so none finds the page idle.
+        clock.advance(Duration::from_millis(1));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while logger.stats().entries_flushed <= i {
+            assert!(Instant::now() < deadline, "entry {i} never flushed");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert_eq!(stats.pages_flushed, 3);
+    assert_eq!(stats.file_bytes_written, 4096);
+    assert_eq!(stats.payload_bytes_written, 3 * ENTRY_LEN);
+    assert_eq!(stats.current_file_offset, 2 * 4096);
+}
+
+#[test]
+fn test_remaining_capacity_covers_the_preallocated_region() {
+    let mut logger = TempLogger::<TestData>::new()
+        .configure(|logger| logger.with_block_size(4096).with_preallocation(1 << 20));
+    assert_eq!(logger.estimate_remaining_capacity().unwrap(), None);
+    logger.start().unwrap();
+    let room = logger.estimate_remaining_capacity().unwrap().unwrap();
+    assert!(room >= (1 << 20) - 4096, "{room}");
+    logger.shutdown().unwrap();
+    assert_eq!(logger.estimate_remaining_capacity().unwrap(), None);
+}
@@ -0,0 +1,186 @@
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig, MAX_STRIPES, PicoError};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn striped_logger(paths: &[&str]) -> Logger<TestData> {
+    for path in paths {
+        cleanup(path);
+    }
+    LoggerConfig::new()
+        .path(paths[0])
+        .capacity(4096)
+        .build::<TestData>()
+        .unwrap()
+        .with_stripe_paths(paths)
+}
+
+fn read_file(path: &str) -> Vec<u64> {
+    Logger::<TestData>::new()
+        .with_read_config(path.to_string())
+        .read_iter()
+        .unwrap()
+        .map(|e| e.unwrap().data.id)
+        .collect()
+}
+
+fn read_striped(paths: &[&str]) -> Vec<(u64, u64)> {
+    Logger::<TestData>::new()
+        .with_stripe_paths(paths)
+        .read_merged()
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.seq_id, e.data.id))
+        .collect()
+}
+
+#[test]
+fn test_pages_alternate_between_stripes_and_merge_back_in_order() {
+    let paths = ["stripe_a_test.log", "stripe_b_test.log"];
+    let mut logger = striped_logger(&paths);
+    logger.start().unwrap();
+    let mut logged = Vec::new();
+    for id in 0..1000 {
+        logged.push((logger.log(TestData { id, val: 0 }).unwrap(), id));
+    }
+    logger.shutdown().unwrap();
+
+    assert_eq!(read_striped(&paths), logged);
+    let a = read_file(paths[0]);
+    let b = read_file(paths[1]);
+    assert!(!a.is_empty() && !b.is_empty());
+    assert_eq!(a.len() + b.len(), 1000);
+    let a: BTreeSet<_> = a.into_iter().collect();
+    assert!(b.iter().all(|id| !a.contains(id)));
+
+    for path in paths {
+        cleanup(path);
+    }
+}
+
+/// Bytes free on the file system holding `path`.
+fn free_space(path: &str) -> u64 {
+    let c_path = std::ffi::CString::new(path).unwrap();
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) }, 0);
+    stats.f_bavail as u64 * stats.f_frsize as u64
+}
+
+#[test]
+fn test_remaining_capacity_is_bounded_by_the_smallest_stripe() {
+    let paths = ["stripe_room_a_test.log", "stripe_room_b_test.log"];
+    let mut logger = striped_logger(&paths).with_preallocation(1 << 20);
+    // Stripe b starts out far larger than stripe a; a's room is what bounds them.
+    fs::File::create(paths[1])
+        .unwrap()
+        .set_len(64 << 20)
+        .unwrap();
+    logger.start().unwrap();
+    let room = logger.estimate_remaining_capacity().unwrap().unwrap();
+    let free = free_space(paths[0]);
+    logger.shutdown().unwrap();
+    assert!(room < 2 * (free + (32 << 20)), "{room} with {free} free");
+
+    for path in paths {
+        cleanup(path);
+    }
+}
+
+/// Fails every write to stripe 1.
+struct FailingStripe;
+
+impl FaultInjector for FailingStripe {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn on_stripe_write_complete(
+        &self,
+        stripe: usize,
+        offset: u64,
+        attempt: u32,
+        result: i32,
+    ) -> i32 {
+        match stripe {
+            1 => -libc::EIO,
+            _ => self.on_write_complete(offset, attempt, result),
+        }
+    }
+}
+
+#[test]
+fn test_failed_stripe_is_reported_and_its_pages_go_to_the_others() {
+    let paths = ["stripe_fail_a_test.log", "stripe_fail_b_test.log"];
+    let mut logger = striped_logger(&paths).with_fault_injector(Arc::new(FailingStripe));
+    logger.start().unwrap();
+    let errors = logger.take_error_receiver().unwrap();
+    let mut logged = Vec::new();
+    for id in 0..1000 {
+        logged.push((logger.log(TestData { id, val: 0 }).unwrap(), id));
+    }
+    logger.flush().unwrap();
+    logger.shutdown().unwrap();
+
+    match errors.recv_timeout(Duration::from_secs(1)).unwrap() {
+        PicoError::StripeFailed { path, errno, .. } => {
+            assert_eq!(path, Path::new(paths[1]));
+            assert_eq!(errno, libc::EIO);
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+    assert!(
+        errors
+            .try_iter()
+            .all(|e| !matches!(e, PicoError::StripeFailed { .. }))
+    );
+    assert_eq!(logger.stats().entries_flushed, 1000);
+    assert_eq!(read_striped(&paths), logged);
+
+    for path in paths {
+        cleanup(path);
+    }
+}
+
+#[test]
+fn test_striping_rejects_append() {
+    let paths = ["stripe_append_a_test.log", "stripe_append_b_test.log"];
+    let mut logger = striped_logger(&paths).with_append(true);
+    assert!(matches!(
+        logger.start(),
+        Err(PicoError::StripeConflict {
+            setting: "with_append"
+        })
+    ));
+}
+
+#[test]
+fn test_too_many_stripes_are_rejected() {
+    let names: Vec<_> = (0..=MAX_STRIPES)
+        .map(|i| format!("stripe_many_{i}_test.log"))
+        .collect();
+    let paths: Vec<_> = names.iter().map(String::as_str).collect();
+    let mut logger = striped_logger(&paths);
+    assert!(matches!(
+        logger.start(),
+        Err(PicoError::InvalidStripeCount { count, max: MAX_STRIPES }) if count == MAX_STRIPES + 1
+    ));
+    for path in paths {
+        cleanup(path);
+    }
+}
@@ -0,0 +1,133 @@
+use picologger::testing::MockClock;
+use picologger::{Durability, Logger, LoggerConfig, SyncPolicy};
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn logger(path: &str, clock: &Arc<MockClock>, policy: SyncPolicy) -> Logger<TestData> {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(4096)
+        .poll_interval(Duration::from_millis(1))
+        .build::<TestData>()
+        .unwrap()
+        .with_clock(clock.clone())
+        .with_sync_policy(policy);
+    logger.start().unwrap();
+    logger
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "{what} never happened");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_full_syncs_advance_the_hardened_watermark() {
+    let path = "sync_policy_full_test.log";
+    let clock = Arc::new(MockClock::new(0));
+    let policy = SyncPolicy {
+        range_every_pages: 2,
+        range_interval: None,
+        full_sync_interval: Duration::from_secs(1),
+    };
+    let mut logger = logger(path, &clock, policy);
+    let mut last = 0;
+    for i in 0..1000 {
+        last = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.flush().unwrap();
+    wait_until("the flush", || logger.stats().entries_flushed == 1000);
+    assert!(Logger::<TestData>::get_last_flushed_entry() >= last);
+
+    // Pages are acknowledged and their writeback started, but nothing is hardened until
+    // the full sync interval passes.
+    wait_until("a range sync", || logger.stats().range_syncs > 0);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(logger.stats().full_syncs, 0);
+    assert_eq!(logger.stats().hardened_seq_id, 0);
+
+    clock.advance(Duration::from_secs(1));
+    wait_until("the full sync", || logger.stats().hardened_seq_id == last);
+    assert_eq!(logger.stats().full_syncs, 1);
+    assert!(Logger::<TestData>::get_last_hardened_entry() >= last);
+    logger.shutdown().unwrap();
+    // Nothing was written since, so shutdown has nothing left to sync.
+    assert_eq!(logger.stats().full_syncs, 1);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_shutdown_hardens_the_tail() {
+    let path = "sync_policy_shutdown_test.log";
+    let clock = Arc::new(MockClock::new(0));
+    let mut logger = logger(path, &clock, SyncPolicy::default());
+    let mut last = 0;
+    for i in 0..10 {
+        last = logger.log(TestData { id: i, val: 0 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert_eq!(stats.full_syncs, 1);
+    assert_eq!(stats.hardened_seq_id, last);
+    assert!(Logger::<TestData>::get_last_hardened_entry() >= last);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_syncing_durability_modes_harden_every_ack() {
+    let path = "sync_policy_fdatasync_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_durability(Durability::Fdatasync)
+        .with_sync_policy(SyncPolicy::default());
+    logger.start().unwrap();
+    let last = logger.log(TestData { id: 1, val: 0 }).unwrap();
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert_eq!(stats.hardened_seq_id, last);
+    assert_eq!((stats.range_syncs, stats.full_syncs), (0, 0));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_no_policy_hardens_nothing() {
+    let path = "sync_policy_none_test.log";
+    cleanup(path);
+    let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+    logger.start().unwrap();
+    logger.log(TestData { id: 1, val: 0 }).unwrap();
+    logger.shutdown().unwrap();
+
+    let stats = logger.stats();
+    assert_eq!(stats.entries_flushed, 1);
+    assert_eq!(stats.hardened_seq_id, 0);
+
+    cleanup(path);
+}
@@ -0,0 +1,172 @@
+use picologger::{Logger, LoggerConfig};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn write_entries(path: &str, count: u64, preallocate: u64) {
+    cleanup(path);
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(8192)
+        .preallocate(preallocate)
+        .build::<TestData>()
+        .unwrap()
+        // Keep the tail exactly as pre-allocated.
+        .with_growth_chunk(0);
+    logger.start().unwrap();
+    for i in 0..count {
+        logger.log(TestData { id: i, val: 7 }).unwrap();
+    }
+}
+
+fn reader(path: &str) -> Logger<TestData> {
+    Logger::<TestData>::new().with_read_config(path.to_string())
+}
+
+#[test]
+fn test_read_last_matches_full_read() {
+    let path = "tail_last_test.log";
+    write_entries(path, 5000, 0);
+
+    let logger = reader(path);
+    let all: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    for n in [1, 10, 102, 103, 1000] {
+        assert_eq!(
+            logger.read_last(n).unwrap(),
+            all[all.len() - n..],
+            "n = {}",
+            n
+        );
+    }
+    assert_eq!(logger.read_last(10_000).unwrap(), all);
+    assert!(logger.read_last(0).unwrap().is_empty());
+    assert_eq!(logger.last_entry().unwrap().as_ref(), all.last());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_reverse_iteration() {
+    let path = "tail_rev_test.log";
+    write_entries(path, 3000, 1024 * 1024);
+
+    let logger = reader(path);
+    let mut all: Vec<_> = logger.read_iter().unwrap().map(Result::unwrap).collect();
+    let reversed: Vec<_> = logger
+        .read_iter_rev()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    all.reverse();
+    assert_eq!(reversed, all);
+
+    let cutoff = all[499].ts_nanos;
+    let recent: Vec<_> = logger
+        .read_iter_rev()
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|entry| entry.ts_nanos > cutoff)
+        .collect();
+    assert!(recent.len() <= 499);
+    assert!(recent.iter().zip(&all).all(|(a, b)| a == b));
+
+    cleanup(path);
+}
+
+#[test]
+fn test_read_last_skips_preallocated_tail() {
+    let path = "tail_prealloc_test.log";
+    write_entries(path, 300, 4 * 1024 * 1024);
+    assert!(fs::metadata(path).unwrap().len() >= 4 * 1024 * 1024);
+
+    let last = reader(path).read_last(3).unwrap();
+    let ids: Vec<_> = last.iter().map(|entry| entry.data.id).collect();
+    assert_eq!(ids, [297, 298, 299]);
+
+    cleanup(path);
+}
+
+#[test]
+fn test_read_last_skips_torn_page() {
+    let path = "tail_torn_test.log";
+    write_entries(path, 300, 1024 * 1024);
+
+    // Garbage where the next page would have gone, as if its write was torn.
+    let len = fs::read(path)
+        .unwrap()
+        .iter()
+        .rposition(|&b| b != 0)
+        .unwrap() as u64;
+    let mut file = OpenOptions::new().write(true).open(path).unwrap();
+    file.seek(SeekFrom::Start((len / 4096 + 1) * 4096)).unwrap();
+    file.write_all(&[0xAB; 1000]).unwrap();
+    drop(file);
+
+    let logger = reader(path);
+    assert_eq!(logger.last_entry().unwrap().unwrap().data.id, 299);
+    let ids: Vec<_> = logger
+        .read_last(200)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.data.id)
+        .collect();
+    assert_eq!(ids, (100..300).collect::<Vec<_>>());
+
+    cleanup(path);
+}
+
+#[test]
+fn test_last_entry_of_empty_file() {
+    let path = "tail_empty_test.log";
+    write_entries(path, 0, 1024 * 1024);
+
+    let logger = reader(path);
+    assert_eq!(logger.last_entry().unwrap(), None);
+    assert!(logger.read_last(5).unwrap().is_empty());
+
+    cleanup(path);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn test_read_last_compressed() {
+    use picologger::Compression;
+
+    let path = "tail_lz4_test.log";
+    cleanup(path);
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(8192)
+            .build::<TestData>()
+            .unwrap()
+            .with_compression(Compression::Lz4);
+        logger.start().unwrap();
+        for i in 0..5000 {
+            logger.log(TestData { id: i, val: 7 }).unwrap();
+        }
+    }
+
+    let logger = reader(path).with_compression(Compression::Lz4);
+    let ids: Vec<_> = logger
+        .read_last(600)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.data.id)
+        .collect();
+    assert_eq!(ids, (4400..5000).collect::<Vec<_>>());
+
+    cleanup(path);
+}
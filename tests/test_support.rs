@@ -0,0 +1,54 @@
+use picologger::test_support::{TempLogger, assert_contiguous_seq};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[test]
+fn test_temp_loggers_get_their_own_files() {
+    let mut first = TempLogger::<TestData>::new();
+    let mut second = TempLogger::<TestData>::new();
+    assert_ne!(first.path(), second.path());
+    first.start().unwrap();
+    second.start().unwrap();
+
+    first.log(TestData { id: 1, val: 0 }).unwrap();
+    for id in 0..3 {
+        second.log(TestData { id, val: 0 }).unwrap();
+    }
+    first.shutdown().unwrap();
+    second.shutdown().unwrap();
+
+    assert_eq!(first.reader().read().unwrap().len(), 1);
+    assert_eq!(second.reader().read().unwrap().len(), 3);
+}
+
+#[test]
+fn test_temp_logger_cleans_up() {
+    let mut logger = TempLogger::<TestData>::new().configure(|logger| logger.with_index(true));
+    logger.start().unwrap();
+    logger.log(TestData::default()).unwrap();
+    logger.shutdown().unwrap();
+    let dir = logger.path().parent().unwrap().to_path_buf();
+    assert!(logger.path().exists());
+
+    drop(logger);
+    assert!(!dir.exists());
+}
+
+#[test]
+#[should_panic(expected = "has seq_id")]
+fn test_gap_in_seq_ids_is_reported() {
+    let mut logger = TempLogger::<TestData>::new();
+    logger.start().unwrap();
+    logger.log_batch(&[TestData::default(); 3]).unwrap();
+    let skipped = logger.reserve_seq_block(1);
+    logger.abandon_seq(skipped.start);
+    logger.log_batch(&[TestData::default(); 3]).unwrap();
+    logger.shutdown().unwrap();
+
+    assert_contiguous_seq::<TestData>(logger.path());
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "tracing")]
+
+use picologger::{Logger, LoggerConfig, PicoLayer, TraceRecord};
+use std::fmt::Debug;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// An event seen by [`Capture`]: its target, message and the `logger` field of its span.
+#[derive(Debug)]
+struct Captured {
+    target: String,
+    message: String,
+    logger: Option<String>,
+}
+
+/// Collects every event along with the `logger` field of the span it happened in.
+#[derive(Clone, Default)]
+struct Capture {
+    events: Arc<Mutex<Vec<Captured>>>,
+}
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    logger: Option<String>,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            "logger" => self.logger = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(logger) = fields.logger {
+            ctx.span(id).unwrap().extensions_mut().insert(logger);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let logger = ctx
+            .event_span(event)
+            .and_then(|span| span.extensions().get::<String>().cloned())
+            .or(fields.logger);
+        self.events.lock().unwrap().push(Captured {
+            target: event.metadata().target().to_string(),
+            message: fields.message,
+            logger,
+        });
+    }
+}
+
+#[test]
+fn test_events_and_spans_round_trip() {
+    let path = "tracing_layer_test.log";
+    let _ = fs::remove_file(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TraceRecord>()
+        .unwrap();
+    logger.start().unwrap();
+    let subscriber = tracing_subscriber::registry().with(PicoLayer::new(logger));
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::warn_span!("session", venue = "XNAS");
+        let _guard = span.enter();
+        tracing::info!(order_id = 42, side = "buy", "order filled");
+        tracing::error!(payload = "x".repeat(200), "oversized");
+    });
+
+    let reader = Logger::<TraceRecord>::new().with_read_config(path.to_string());
+    let records: Vec<TraceRecord> = reader.read().unwrap();
+    assert_eq!(records.len(), 3);
+
+    assert!(records[0].is_span());
+    assert_eq!(records[0].level(), Some(Level::WARN));
+    assert_eq!(records[0].fields(), "session venue=XNAS");
+
+    assert!(!records[1].is_span());
+    assert_eq!(records[1].level(), Some(Level::INFO));
+    assert_eq!(records[1].fields(), "order filled order_id=42 side=buy");
+    assert!(!records[1].is_truncated());
+
+    assert!(records[2].is_truncated());
+    assert_eq!(records[2].fields().len(), TraceRecord::FIELDS_CAPACITY);
+    assert!(records[2].fields().starts_with("oversized payload=xxx"));
+    assert_ne!(records[1].callsite_id, records[2].callsite_id);
+
+    let lines = reader.read_text().unwrap();
+    assert!(lines[1].ends_with("order filled order_id=42 side=buy"));
+    assert!(lines[2].ends_with('…'));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_worker_lifecycle_is_traced_and_kept_out_of_trace_logs() {
+    let trace_path = "tracing_lifecycle_trace.log";
+    let orders_path = "tracing_lifecycle_orders.log";
+    let _ = fs::remove_file(trace_path);
+    let _ = fs::remove_file(orders_path);
+
+    let mut trace = LoggerConfig::new()
+        .path(trace_path)
+        .flush_interval(Duration::from_millis(1))
+        .build::<TraceRecord>()
+        .unwrap();
+    trace.start().unwrap();
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::registry()
+        .with(PicoLayer::new(trace))
+        .with(capture.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        let mut orders = LoggerConfig::new()
+            .path(orders_path)
+            .build::<u64>()
+            .unwrap()
+            .with_name("orders");
+        orders.start().unwrap();
+        for i in 0..1000 {
+            orders.log(i).unwrap();
+        }
+        orders.shutdown().unwrap();
+        tracing::info!("after shutdown");
+    });
+
+    let events = capture.events.lock().unwrap();
+    let lifecycle: Vec<&Captured> = events
+        .iter()
+        .filter(|event| event.target.starts_with("picologger"))
+        .collect();
+    let messages: Vec<&str> = lifecycle.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.first(), Some(&"logger started"));
+    assert!(messages.contains(&"page flushed"), "{messages:?}");
+    assert!(messages.contains(&"shutting down"), "{messages:?}");
+    assert_eq!(messages.last(), Some(&"shut down"));
+    for event in &lifecycle {
+        assert_eq!(event.logger.as_deref(), Some("orders"), "{event:?}");
+    }
+
+    let reader = Logger::<TraceRecord>::new().with_read_config(trace_path.to_string());
+    let records: Vec<TraceRecord> = reader.read().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].fields(), "after shutdown");
+
+    fs::remove_file(trace_path).unwrap();
+    fs::remove_file(orders_path).unwrap();
+}
@@ -0,0 +1,416 @@
+use picologger::{
+    Backend, BufferFullPolicy, Capabilities, Durability, Logger, LoggerConfig, PicoError,
+    SyncPolicy, UringOptions,
+};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_fixed_resources_round_trip() {
+    let path = "uring_fixed_resources_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(8192)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024)
+            .build::<TestData>()
+            .unwrap();
+        assert!(!logger.uses_registered_buffers());
+        assert!(!logger.uses_fixed_file());
+        logger.start().unwrap();
+
+        for i in 0..5000 {
+            logger.log(TestData {
+                id: i as u64,
+                val: (i * 3) as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 5000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+        assert_eq!(item.val, (i * 3) as u32);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_sqpoll_round_trip() {
+    let path = "uring_sqpoll_test.log";
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(8192)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_uring_options(UringOptions {
+                sqpoll: true,
+                sqpoll_idle_ms: 10,
+                ..UringOptions::default()
+            });
+        logger.start().unwrap();
+        // Without the privileges or kernel for SQPOLL, start() says so on the error channel.
+        let errors = logger.take_error_receiver().unwrap();
+        match errors.try_recv() {
+            Ok(PicoError::OptionsDowngraded { downgrades }) => {
+                assert!(!logger.uses_sqpoll());
+                assert!(!downgrades.is_empty());
+            }
+            Ok(other) => panic!("unexpected error: {other:?}"),
+            Err(_) => assert!(logger.uses_sqpoll()),
+        }
+
+        for i in 0..5000 {
+            logger.log(TestData {
+                id: i as u64,
+                val: (i * 3) as u32,
+            });
+            if i % 1000 == 0 {
+                // Let the SQ poll thread go idle so later submissions need a wakeup.
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 5000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_burst_spanning_many_pages() {
+    let path = "uring_burst_test.log";
+    cleanup(path);
+
+    let count = 30_000;
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(32_768)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024)
+            .build::<TestData>()
+            .unwrap();
+        logger.start().unwrap();
+
+        for i in 0..count {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), count);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+fn round_trip_with(path: &str, page_count: usize, ring_depth: Option<u32>) {
+    cleanup(path);
+
+    {
+        let mut logger = LoggerConfig::new()
+            .path(path)
+            .capacity(32_768)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(10 * 1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_pages(page_count);
+        if let Some(depth) = ring_depth {
+            logger = logger.with_ring_depth(depth);
+        }
+        logger.start().unwrap();
+
+        for i in 0..10_000 {
+            logger.log(TestData {
+                id: i as u64,
+                val: i as u32,
+            });
+        }
+    }
+
+    let logger = Logger::<TestData>::new().with_read_config(path.to_string());
+    let result = logger.read().unwrap();
+    assert_eq!(result.len(), 10_000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+
+    cleanup(path);
+}
+
+#[test]
+fn test_small_page_pool() {
+    round_trip_with("uring_small_pool_test.log", 2, None);
+}
+
+#[test]
+fn test_page_pool_beyond_256() {
+    round_trip_with("uring_large_pool_test.log", 1024, None);
+}
+
+#[test]
+fn test_page_count_validation() {
+    let mut logger = LoggerConfig::new()
+        .path("uring_invalid_pool_test.log")
+        .capacity(1024)
+        .flush_interval(Duration::from_millis(1))
+        .poll_interval(Duration::from_micros(100))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_pages(1);
+    let err = logger.start().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(logger.log(TestData::default()).is_none());
+}
+
+#[test]
+fn test_shallow_ring() {
+    round_trip_with("uring_shallow_ring_test.log", 256, Some(2));
+}
+
+#[test]
+fn test_ring_depth_validation() {
+    for depth in [0, 48, 65_536] {
+        let mut logger = LoggerConfig::new()
+            .path("uring_invalid_depth_test.log")
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+            .with_ring_depth(depth);
+        let err = logger.start().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_completed_writes_wake_the_worker() {
+    let path = "uring_completion_wakeup_test.log";
+    cleanup(path);
+
+    // With neither timer due, only the completion itself can tell the worker the write is
+    // done.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .poll_interval(Duration::from_secs(10))
+        .build::<TestData>()
+        .unwrap()
+        .with_backend(Backend::IoUring);
+    assert!(!logger.wakes_on_completion());
+    logger.start().unwrap();
+    assert!(logger.wakes_on_completion());
+
+    for round in 1..=5 {
+        logger.log(TestData { id: round, val: 0 }).unwrap();
+        let started = Instant::now();
+        logger.flush().unwrap();
+        while logger.stats().entries_flushed < round {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "write {round} completed unnoticed"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    drop(logger);
+    cleanup(path);
+}
+
+fn iopoll() -> UringOptions {
+    UringOptions {
+        iopoll: true,
+        ..UringOptions::default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_iopoll_on_tmpfs_fails_start() {
+    // tmpfs has no device to poll; a file it can't take direct I/O on fails the same way.
+    let path = std::path::Path::new("/dev/shm/picolog_uring_iopoll_unavailable.log");
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_uring_options(iopoll());
+    let err = logger.start().unwrap_err();
+    let _ = fs::remove_file(path);
+    match err {
+        PicoError::IopollUnavailable { path: at, errno } => {
+            assert_eq!(at, path);
+            assert_eq!(errno, libc::EOPNOTSUPP);
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+/// Writes to `PICOLOG_TEST_IOPOLL_DIR`, which must be on a device `start()` finds it can
+/// poll, such as an NVMe drive with poll queues (`nvme.poll_queues=N`):
+///
+/// ```text
+/// PICOLOG_TEST_IOPOLL_DIR=/mnt/nvme cargo test --test uring -- --ignored
+/// ```
+#[test]
+#[ignore = "needs PICOLOG_TEST_IOPOLL_DIR on a device with poll queues"]
+fn test_iopoll_round_trip() {
+    let dir = std::env::var_os("PICOLOG_TEST_IOPOLL_DIR")
+        .expect("PICOLOG_TEST_IOPOLL_DIR must name a directory on a pollable device");
+    let path = std::path::Path::new(&dir).join("uring_iopoll_test.log");
+    let path = path.to_str().unwrap();
+    cleanup(path);
+
+    // Long timers leave polling as the only way the worker learns a write is done, and the
+    // sync policy runs range and full syncs inline.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .poll_interval(Duration::from_secs(10))
+        .preallocate(1024 * 1024)
+        .build::<TestData>()
+        .unwrap()
+        .with_uring_options(iopoll())
+        .with_sync_policy(SyncPolicy::default())
+        .with_buffer_full_policy(BufferFullPolicy::Block);
+    logger.start().unwrap();
+    assert!(!logger.wakes_on_completion());
+
+    let started = Instant::now();
+    logger.log(TestData { id: 0, val: 0 }).unwrap();
+    logger.flush().unwrap();
+    while logger.stats().entries_flushed < 1 {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "polled write never completed"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    for id in 1..5000 {
+        logger.log(TestData { id, val: 3 }).unwrap();
+    }
+    logger.shutdown().unwrap();
+
+    let result = Logger::<TestData>::new()
+        .with_read_config(path)
+        .read()
+        .unwrap();
+    assert_eq!(result.len(), 5000);
+    for (i, item) in result.iter().enumerate() {
+        assert_eq!(item.id, i as u64);
+    }
+    cleanup(path);
+}
+
+#[test]
+fn test_iopoll_conflicts() {
+    let path = "uring_iopoll_conflict_test.log";
+    let configured = || {
+        LoggerConfig::new()
+            .path(path)
+            .capacity(1024)
+            .flush_interval(Duration::from_millis(1))
+            .poll_interval(Duration::from_micros(100))
+            .preallocate(1024 * 1024)
+            .build::<TestData>()
+            .unwrap()
+    };
+    let configs = [
+        (configured().with_backend(Backend::Sync), "Backend::Sync"),
+        (
+            configured().with_durability(Durability::Fdatasync),
+            "Durability::Fdatasync",
+        ),
+    ];
+    for (logger, expected) in configs {
+        let mut logger = logger.with_uring_options(iopoll());
+        match logger.start().unwrap_err() {
+            PicoError::IopollConflict { setting } => assert_eq!(setting, expected),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(!std::path::Path::new(path).exists());
+    }
+}
+
+#[test]
+fn test_capabilities_describe_the_ring() {
+    let path = "uring_capabilities_test.log";
+    cleanup(path);
+
+    let mut logger = LoggerConfig::new().path(path).build::<TestData>().unwrap();
+    assert_eq!(logger.capabilities(), Capabilities::default());
+    logger.start().unwrap();
+    let caps = logger.capabilities();
+    assert_eq!(caps.backend, logger.backend());
+    assert_eq!(caps.registered_buffers, logger.uses_registered_buffers());
+    assert_eq!(caps.fixed_files, logger.uses_fixed_file());
+    assert_eq!(caps.sqpoll, logger.uses_sqpoll());
+    assert_eq!(caps.completion_wakeups, logger.wakes_on_completion());
+    assert!(!caps.iopoll);
+    if caps.backend == Backend::IoUring {
+        // Falling back to pwrite is the only answer to a kernel that can't report writes.
+        assert!(caps.opcode_probe);
+    }
+    drop(logger);
+
+    // A polled ring runs everything but page writes inline, whatever the kernel supports.
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .build::<TestData>()
+        .unwrap()
+        .with_uring_options(iopoll());
+    if logger.start().is_ok() {
+        let caps = logger.capabilities();
+        assert!(caps.iopoll);
+        assert!(!caps.fallocate);
+        assert!(!caps.sync_file_range);
+        assert!(!caps.completion_wakeups);
+    }
+    drop(logger);
+    cleanup(path);
+}
@@ -0,0 +1,40 @@
+//! Needs a process of its own: the assertions are about the very first seq_id handed out.
+
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+#[test]
+fn test_first_entry_watermark() {
+    let path = "watermark_first_entry.log";
+    let _ = fs::remove_file(path);
+
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .capacity(1024)
+        .build::<TestData>()
+        .unwrap();
+    logger.start().unwrap();
+    assert_eq!(Logger::<TestData>::get_last_flushed_entry(), 0);
+
+    let seq_id = logger.log(TestData { id: 7, val: 7 }).unwrap();
+    assert_eq!(seq_id, 1);
+
+    logger.flush().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Logger::<TestData>::get_last_flushed_entry() < seq_id {
+        assert!(Instant::now() < deadline, "first entry never acknowledged");
+        std::thread::yield_now();
+    }
+    assert_eq!(Logger::<TestData>::get_last_flushed_entry(), 1);
+
+    drop(logger);
+    let _ = fs::remove_file(path);
+}
@@ -0,0 +1,85 @@
+use picologger::testing::FaultInjector;
+use picologger::{Logger, LoggerConfig};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+struct TestData {
+    id: u64,
+    val: u32,
+}
+
+fn cleanup(path: &str) {
+    if std::path::Path::new(path).exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Holds back the completion of the first page's write until released, so the pages after
+/// it complete first.
+#[derive(Default)]
+struct FirstPageLast {
+    released: AtomicBool,
+}
+
+impl FaultInjector for FirstPageLast {
+    fn on_write_complete(&self, _offset: u64, _attempt: u32, result: i32) -> i32 {
+        result
+    }
+
+    fn hold_completion(&self, offset: u64, _attempt: u32) -> bool {
+        offset == 4096 && !self.released.load(Ordering::Relaxed)
+    }
+}
+
+fn wait_until_flushed(logger: &Logger<TestData>, entries: u64) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while logger.stats().entries_flushed < entries {
+        assert!(Instant::now() < deadline, "entries were never flushed");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_ack_waits_for_earlier_pages() {
+    let path = "write_order_test.log";
+    cleanup(path);
+    let injector = Arc::new(FirstPageLast::default());
+    let mut logger = LoggerConfig::new()
+        .path(path)
+        .flush_interval(Duration::from_secs(60))
+        .build::<TestData>()
+        .unwrap()
+        .with_fault_injector(injector.clone());
+    logger.start().unwrap();
+    let watermarks = logger.subscribe();
+
+    // 101 entries fill a 4 KiB page: three pages, the last one written by the flush.
+    let mut seq_ids = Vec::new();
+    for i in 0..303 {
+        seq_ids.push(logger.log(TestData { id: i, val: 0 }).unwrap());
+    }
+    logger.flush().unwrap();
+    wait_until_flushed(&logger, 202);
+
+    // The second and third pages are on disk, but the first isn't, so none of it is acked.
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(Logger::<TestData>::get_last_flushed_entry() < seq_ids[0]);
+    assert!(
+        watermarks
+            .try_iter()
+            .all(|watermark| watermark < seq_ids[0])
+    );
+    assert!(logger.read().unwrap().is_empty());
+
+    injector.released.store(true, Ordering::Relaxed);
+    wait_until_flushed(&logger, 303);
+    assert!(Logger::<TestData>::get_last_flushed_entry() >= seq_ids[302]);
+    assert_eq!(logger.read().unwrap().len(), 303);
+    logger.shutdown().unwrap();
+
+    cleanup(path);
+}